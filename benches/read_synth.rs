@@ -0,0 +1,60 @@
+//! Compare the tree based deserialization with the deserialization from a reader.
+//!
+//! Run with `cargo bench --bench read_synth`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::io::{BufReader, Cursor, Read};
+
+const SYNT170: &str = include_str!("../src/data_tests/SYNTHS/SYNT170.XML");
+
+/// Creates a synth containing range_count copies of the first sample range of SYNT170.
+fn create_multisample_synth(range_count: usize) -> String {
+    const END_TAG: &str = "</sampleRange>";
+
+    let ranges_start = SYNT170.find("<sampleRange>").unwrap();
+    let ranges_end = SYNT170.rfind(END_TAG).unwrap() + END_TAG.len();
+    let first_range_end = ranges_start
+        + SYNT170[ranges_start..]
+            .find(END_TAG)
+            .unwrap()
+        + END_TAG.len();
+    let ranges = vec![&SYNT170[ranges_start..first_range_end]; range_count].join("\n");
+
+    format!("{}{}{}", &SYNT170[..ranges_start], ranges, &SYNT170[ranges_end..])
+}
+
+fn read_synth_string(xml: &[u8]) -> deluge::Synth {
+    let mut content = String::new();
+
+    BufReader::new(Cursor::new(xml))
+        .read_to_string(&mut content)
+        .unwrap();
+
+    deluge::deserialize_synth(&content).unwrap()
+}
+
+fn read_synth_reader(xml: &[u8]) -> deluge::Synth {
+    deluge::deserialize_synth_from_reader(BufReader::new(Cursor::new(xml))).unwrap()
+}
+
+fn bench_read_synth(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_synth");
+    let inputs = [
+        ("SYNT170", SYNT170.to_string()),
+        ("500 ranges", create_multisample_synth(500)),
+    ];
+
+    for (name, xml) in inputs.iter() {
+        group.bench_with_input(BenchmarkId::new("string", name), xml.as_bytes(), |b, xml| {
+            b.iter(|| read_synth_string(black_box(xml)))
+        });
+        group.bench_with_input(BenchmarkId::new("reader", name), xml.as_bytes(), |b, xml| {
+            b.iter(|| read_synth_reader(black_box(xml)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_read_synth);
+criterion_main!(benches);
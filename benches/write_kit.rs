@@ -0,0 +1,97 @@
+//! Compare the serialization into a String with the serialization into a reused writer.
+//!
+//! The allocations done by each serialization are counted before measuring the time.
+//!
+//! Run with `cargo bench --bench write_kit`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const KIT_COUNT: usize = 500;
+
+/// The system allocator counting the allocations
+struct CountingAllocator;
+
+static ALLOCATION_COUNT: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATION_COUNT.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn write_kits_string(kits: &[deluge::Kit]) -> usize {
+    kits.iter()
+        .map(|kit| {
+            deluge::serialize_kit(kit)
+                .unwrap()
+                .len()
+        })
+        .sum()
+}
+
+fn write_kits_writer(kits: &[deluge::Kit], buffer: &mut Vec<u8>) -> usize {
+    let mut length = 0;
+
+    for kit in kits {
+        buffer.clear();
+        deluge::serialize_kit_to_writer(kit, buffer).unwrap();
+        length += buffer.len();
+    }
+
+    length
+}
+
+/// Print the number of allocations and the allocated bytes per kit
+fn count_allocations(name: &str, write: impl FnOnce() -> usize) {
+    let count = ALLOCATION_COUNT.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+
+    black_box(write());
+
+    let count = ALLOCATION_COUNT.load(Ordering::Relaxed) - count;
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes;
+
+    println!(
+        "write_kit/{}: {} allocations and {} bytes per kit",
+        name,
+        count / KIT_COUNT,
+        bytes / KIT_COUNT
+    );
+}
+
+fn bench_write_kit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write_kit");
+    let kits = vec![deluge::Kit::default(); KIT_COUNT];
+    let mut buffer = Vec::new();
+
+    // The buffer is filled once so its growth is not counted
+    write_kits_writer(&kits, &mut buffer);
+
+    count_allocations("string", || write_kits_string(&kits));
+    count_allocations("writer", || write_kits_writer(&kits, &mut buffer));
+
+    group.bench_function("string", |b| b.iter(|| write_kits_string(black_box(&kits))));
+    group.bench_function("writer", |b| b.iter(|| write_kits_writer(black_box(&kits), &mut buffer)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_write_kit);
+criterion_main!(benches);
@@ -0,0 +1,263 @@
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use super::{
+    check_required_directories, filesystem::make_io_error, find_root_directory_names, Card, CardError, FileMetadata, FileSystem,
+    LocalFileSystem,
+};
+
+/// The async version of [FileSystem], used by [Card::open_async]
+///
+/// The methods have the same behavior as the methods of [FileSystem] with the same name.
+pub trait AsyncFileSystem {
+    /// This method gives the paths of the directories present in a given directory.
+    fn get_directory_entries(&self, path: &Path) -> impl Future<Output = Result<Vec<PathBuf>, CardError>> + Send;
+
+    /// This method creates all the missing directories.
+    fn create_directory(&self, path: &Path) -> impl Future<Output = Result<(), CardError>> + Send;
+
+    /// Check if a directory exists
+    fn directory_exists(&self, path: &Path) -> impl Future<Output = bool> + Send;
+
+    /// Check if a file exists
+    fn file_exists(&self, path: &Path) -> impl Future<Output = bool> + Send;
+
+    /// Check if a path points on a file
+    fn is_file(&self, path: &Path) -> impl Future<Output = Result<bool, CardError>> + Send;
+
+    /// Get the size of a file in bytes
+    fn file_size(&self, path: &Path) -> impl Future<Output = Result<u64, CardError>> + Send;
+
+    /// Read the content of a text file
+    fn read_file(&self, path: &Path) -> impl Future<Output = Result<String, CardError>> + Send;
+
+    /// Read the content of a file as bytes
+    fn read_file_bytes(&self, path: &Path) -> impl Future<Output = Result<Vec<u8>, CardError>> + Send;
+
+    /// Read at most `length` bytes of a file starting at `offset`
+    ///
+    /// Less bytes are returned if the end of the file is reached.
+    fn read_file_range(&self, path: &Path, offset: u64, length: usize)
+        -> impl Future<Output = Result<Vec<u8>, CardError>> + Send;
+
+    /// Write a text file, the file is created if it doesn't exist or truncated if it exists.
+    fn write_file(&self, path: &Path, content: &str) -> impl Future<Output = Result<(), CardError>> + Send;
+
+    /// Write a file from bytes, the file is created if it doesn't exist or truncated if it exists.
+    fn write_file_bytes(&self, path: &Path, content: &[u8]) -> impl Future<Output = Result<(), CardError>> + Send;
+
+    /// Copy a file, the destination is overwritten if it exists.
+    fn copy_file(&self, from: &Path, to: &Path) -> impl Future<Output = Result<(), CardError>> + Send;
+
+    /// Rename or move a file, the destination is overwritten if it exists.
+    fn rename(&self, from: &Path, to: &Path) -> impl Future<Output = Result<(), CardError>> + Send;
+}
+
+/// The local file system accessed with tokio.
+///
+/// It implements [AsyncFileSystem] using `tokio::fs`, and [FileSystem] like [LocalFileSystem] so a card opened
+/// with [Card::open_async] can also use the blocking methods of [Card].
+#[derive(Default)]
+pub struct TokioFileSystem;
+
+impl AsyncFileSystem for TokioFileSystem {
+    async fn get_directory_entries(&self, path: &Path) -> Result<Vec<PathBuf>, CardError> {
+        let mut entries = tokio::fs::read_dir(path)
+            .await
+            .map_err(make_io_error(path))?;
+        let mut results = Vec::new();
+
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(make_io_error(path))?
+        {
+            results.push(entry.path());
+        }
+
+        Ok(results)
+    }
+
+    async fn create_directory(&self, path: &Path) -> Result<(), CardError> {
+        tokio::fs::create_dir_all(path)
+            .await
+            .map_err(make_io_error(path))
+    }
+
+    async fn directory_exists(&self, path: &Path) -> bool {
+        tokio::fs::metadata(path)
+            .await
+            .map(|metadata| metadata.is_dir())
+            .unwrap_or(false)
+    }
+
+    async fn file_exists(&self, path: &Path) -> bool {
+        tokio::fs::metadata(path)
+            .await
+            .map(|metadata| metadata.is_file())
+            .unwrap_or(false)
+    }
+
+    async fn is_file(&self, path: &Path) -> Result<bool, CardError> {
+        Ok(tokio::fs::metadata(path)
+            .await
+            .map_err(make_io_error(path))?
+            .is_file())
+    }
+
+    async fn file_size(&self, path: &Path) -> Result<u64, CardError> {
+        Ok(tokio::fs::metadata(path)
+            .await
+            .map_err(make_io_error(path))?
+            .len())
+    }
+
+    async fn read_file(&self, path: &Path) -> Result<String, CardError> {
+        tokio::fs::read_to_string(path)
+            .await
+            .map_err(make_io_error(path))
+    }
+
+    async fn read_file_bytes(&self, path: &Path) -> Result<Vec<u8>, CardError> {
+        tokio::fs::read(path)
+            .await
+            .map_err(make_io_error(path))
+    }
+
+    async fn read_file_range(&self, path: &Path, offset: u64, length: usize) -> Result<Vec<u8>, CardError> {
+        let mut file = tokio::fs::File::open(path)
+            .await
+            .map_err(make_io_error(path))?;
+        let mut content = Vec::with_capacity(length);
+
+        file.seek(std::io::SeekFrom::Start(offset))
+            .await
+            .map_err(make_io_error(path))?;
+        file.take(length as u64)
+            .read_to_end(&mut content)
+            .await
+            .map_err(make_io_error(path))?;
+
+        Ok(content)
+    }
+
+    async fn write_file(&self, path: &Path, content: &str) -> Result<(), CardError> {
+        tokio::fs::write(path, content)
+            .await
+            .map_err(make_io_error(path))
+    }
+
+    async fn write_file_bytes(&self, path: &Path, content: &[u8]) -> Result<(), CardError> {
+        tokio::fs::write(path, content)
+            .await
+            .map_err(make_io_error(path))
+    }
+
+    async fn copy_file(&self, from: &Path, to: &Path) -> Result<(), CardError> {
+        tokio::fs::copy(from, to)
+            .await
+            .map_err(make_io_error(from))?;
+
+        Ok(())
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> Result<(), CardError> {
+        tokio::fs::rename(from, to)
+            .await
+            .map_err(make_io_error(from))
+    }
+}
+
+impl FileSystem for TokioFileSystem {
+    fn get_directory_entries(&self, path: &Path) -> Result<Vec<PathBuf>, CardError> {
+        LocalFileSystem.get_directory_entries(path)
+    }
+
+    fn create_directory(&self, path: &Path) -> Result<(), CardError> {
+        LocalFileSystem.create_directory(path)
+    }
+
+    fn directory_exists(&self, path: &Path) -> bool {
+        LocalFileSystem.directory_exists(path)
+    }
+
+    fn file_exists(&self, path: &Path) -> bool {
+        LocalFileSystem.file_exists(path)
+    }
+
+    fn is_file(&self, path: &Path) -> Result<bool, CardError> {
+        LocalFileSystem.is_file(path)
+    }
+
+    fn file_size(&self, path: &Path) -> Result<u64, CardError> {
+        LocalFileSystem.file_size(path)
+    }
+
+    fn file_metadata(&self, path: &Path) -> Result<FileMetadata, CardError> {
+        LocalFileSystem.file_metadata(path)
+    }
+
+    fn read_file(&self, path: &Path) -> Result<String, CardError> {
+        LocalFileSystem.read_file(path)
+    }
+
+    fn read_file_bytes(&self, path: &Path) -> Result<Vec<u8>, CardError> {
+        LocalFileSystem.read_file_bytes(path)
+    }
+
+    fn read_file_range(&self, path: &Path, offset: u64, length: usize) -> Result<Vec<u8>, CardError> {
+        LocalFileSystem.read_file_range(path, offset, length)
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> Result<(), CardError> {
+        LocalFileSystem.write_file(path, content)
+    }
+
+    fn write_file_bytes(&self, path: &Path, content: &[u8]) -> Result<(), CardError> {
+        LocalFileSystem.write_file_bytes(path, content)
+    }
+
+    fn copy_file(&self, from: &Path, to: &Path) -> Result<(), CardError> {
+        LocalFileSystem.copy_file(from, to)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), CardError> {
+        LocalFileSystem.rename(from, to)
+    }
+}
+
+impl<FS: FileSystem + AsyncFileSystem> Card<FS> {
+    /// Open a card directory without blocking, see [Card::open]
+    ///
+    /// The folder structure is checked with the methods of [AsyncFileSystem].
+    pub async fn open_async(file_system: FS, root_directory: &Path) -> Result<Self, CardError> {
+        let root_directory = root_directory.to_path_buf();
+
+        if !AsyncFileSystem::directory_exists(&file_system, &root_directory).await {
+            return Err(CardError::DirectoryDoesNotExists(root_directory));
+        }
+
+        let directory_names = AsyncFileSystem::get_directory_entries(&file_system, &root_directory)
+            .await?
+            .iter()
+            .filter_map(|path| {
+                path.file_name()
+                    .map(|file_name| file_name.to_string_lossy().to_string())
+            })
+            .collect();
+        let folder_names = find_root_directory_names(&directory_names);
+
+        check_required_directories(&folder_names)?;
+
+        Ok(Self {
+            file_system: Arc::new(file_system),
+            folder_names,
+            root_directory,
+        })
+    }
+}
@@ -1,18 +1,52 @@
-use strum::EnumIter;
+use strum::{EnumIter, IntoEnumIterator};
 
-#[derive(Debug, EnumIter)]
+#[derive(Debug, EnumIter, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum CardFolder {
     Kits,
     Samples,
     Synths,
+    Songs,
+    /// The recordings of the audio input, in SAMPLES
+    Record,
+    /// The recordings of the Deluge output, in SAMPLES
+    Resample,
+    /// The recordings of the audio clips, in SAMPLES
+    Clips,
 }
 
 impl CardFolder {
+    /// Get the name of the folder, without the name of its parent folder
     pub const fn directory_name(&self) -> &'static str {
         match self {
             CardFolder::Kits => "KITS",
             CardFolder::Samples => "SAMPLES",
             CardFolder::Synths => "SYNTHS",
+            CardFolder::Songs => "SONGS",
+            CardFolder::Record => "RECORD",
+            CardFolder::Resample => "RESAMPLE",
+            CardFolder::Clips => "CLIPS",
         }
     }
+
+    /// Get the folder containing this folder, None if the folder is at the root of the card
+    pub const fn parent(&self) -> Option<CardFolder> {
+        match self {
+            CardFolder::Record | CardFolder::Resample | CardFolder::Clips => Some(CardFolder::Samples),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the folder must exist on a card
+    ///
+    /// Only KITS, SAMPLES and SYNTHS are required.
+    /// The folder SONGS is created by the Deluge when the first song is saved and the folders
+    /// in SAMPLES are created when the first recording is saved.
+    pub const fn is_required(&self) -> bool {
+        matches!(self, CardFolder::Kits | CardFolder::Samples | CardFolder::Synths)
+    }
+
+    /// Get the folders that must exist on a card, see [CardFolder::is_required]
+    pub fn required() -> impl Iterator<Item = CardFolder> {
+        Self::iter().filter(Self::is_required)
+    }
 }
@@ -0,0 +1,145 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use super::{Card, CardError, CardOpenError, FileSystem, PatchEntry, PatchName};
+use crate::{deserialize_kit_with_version, deserialize_synth_with_version, PatchType, ReadError, SamplePath, VersionInfo};
+
+/// The information about a patch collected by [Card::scan]
+#[derive(Debug, PartialEq, Eq)]
+pub struct PatchMetadata {
+    /// The absolute path of the patch file
+    pub path: PathBuf,
+    pub name: PatchName,
+    pub version_info: VersionInfo,
+    /// The sample paths used by the patch, the empty paths are ignored.
+    pub sample_paths: BTreeSet<SamplePath>,
+    /// The count of rows, only available for the kits.
+    pub row_count: Option<usize>,
+}
+
+/// A patch that can't be read or parsed
+#[derive(thiserror::Error, Debug)]
+#[error("failed to scan '{path}': {error}")]
+pub struct ScanError {
+    /// The absolute path of the patch file
+    pub path: PathBuf,
+    pub patch_type: PatchType,
+    pub error: CardOpenError,
+}
+
+/// The index of the patches of a card, see [Card::scan]
+///
+/// The patches are indexed by their name, for example "KIT012A".
+#[derive(Debug, Default)]
+pub struct CardIndex {
+    pub kits: BTreeMap<String, PatchMetadata>,
+    pub synths: BTreeMap<String, PatchMetadata>,
+    /// The patches that failed to load, they are not present in kits or synths.
+    pub errors: Vec<ScanError>,
+}
+
+impl CardIndex {
+    fn from_results(results: impl IntoIterator<Item = (PatchType, Result<PatchMetadata, ScanError>)>) -> Self {
+        let mut index = CardIndex::default();
+
+        for (patch_type, result) in results {
+            match result {
+                Ok(metadata) => {
+                    let patches = match patch_type {
+                        PatchType::Kit => &mut index.kits,
+                        PatchType::Synth => &mut index.synths,
+                    };
+
+                    patches.insert(metadata.name.to_string(), metadata);
+                }
+                Err(error) => index.errors.push(error),
+            }
+        }
+
+        index
+    }
+}
+
+impl<FS: FileSystem> Card<FS> {
+    /// List the patches of the KITS and SYNTHS folders
+    fn list_patches_to_scan(&self) -> Result<Vec<(PatchType, PatchEntry)>, CardError> {
+        let mut entries = Vec::new();
+
+        for patch_type in [PatchType::Kit, PatchType::Synth] {
+            entries.extend(
+                self.list_patches(patch_type)?
+                    .into_iter()
+                    .map(|entry| (patch_type, entry)),
+            );
+        }
+
+        Ok(entries)
+    }
+
+    fn scan_patch(&self, patch_type: PatchType, entry: PatchEntry) -> Result<PatchMetadata, ScanError> {
+        let to_scan_error = |error: CardOpenError| ScanError {
+            path: entry.path.clone(),
+            patch_type,
+            error,
+        };
+        let xml = self
+            .file_system
+            .read_file(&entry.path)
+            .map_err(|e| to_scan_error(e.into()))?;
+        let (version_info, sample_paths, row_count) = match patch_type {
+            PatchType::Kit => {
+                let (kit, version_info) =
+                    deserialize_kit_with_version(&xml).map_err(|e| to_scan_error(ReadError::DeserializationError(e).into()))?;
+
+                (version_info, kit.get_sample_paths(), Some(kit.rows.len()))
+            }
+            PatchType::Synth => {
+                let (synth, version_info) =
+                    deserialize_synth_with_version(&xml).map_err(|e| to_scan_error(ReadError::DeserializationError(e).into()))?;
+
+                (version_info, synth.get_sample_paths(), None)
+            }
+        };
+
+        Ok(PatchMetadata {
+            path: entry.path,
+            name: entry.name,
+            version_info,
+            sample_paths,
+            row_count,
+        })
+    }
+}
+
+impl<FS: FileSystem> Card<FS> {
+    /// Read and parse all the patches of the KITS and SYNTHS folders
+    ///
+    /// A patch that can't be read or parsed does not stop the scan, it's reported in [CardIndex::errors].
+    /// Enable the feature `parallel` to parse the patches in parallel with [Card::scan_parallel].
+    pub fn scan(&self) -> Result<CardIndex, CardError> {
+        let results = self
+            .list_patches_to_scan()?
+            .into_iter()
+            .map(|(patch_type, entry)| (patch_type, self.scan_patch(patch_type, entry)));
+
+        Ok(CardIndex::from_results(results))
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<FS: FileSystem + Send + Sync> Card<FS> {
+    /// Read and parse all the patches of the KITS and SYNTHS folders
+    ///
+    /// Like [Card::scan] but the patches are parsed in parallel using rayon.
+    pub fn scan_parallel(&self) -> Result<CardIndex, CardError> {
+        use rayon::prelude::*;
+
+        let results = self
+            .list_patches_to_scan()?
+            .into_par_iter()
+            .map(|(patch_type, entry)| (patch_type, self.scan_patch(patch_type, entry)))
+            .collect::<Vec<_>>();
+
+        Ok(CardIndex::from_results(results))
+    }
+}
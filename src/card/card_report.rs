@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use super::CardFolder;
+
+/// The state of a standard folder of a card, see [CardReport]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FolderReport {
+    pub folder: CardFolder,
+    /// The name of the folder found on the card, it may differ by the case from the standard name.
+    /// None if the folder is missing.
+    pub directory_name: Option<String>,
+    /// The count of patches in the folder and its subfolders.
+    /// None if the folder is missing or if it doesn't store patches.
+    pub patch_count: Option<usize>,
+}
+
+impl FolderReport {
+    pub fn exists(&self) -> bool {
+        self.directory_name.is_some()
+    }
+}
+
+/// The state of the standard folders of a card, see [Card::check](crate::Card::check)
+///
+/// Unlike [Card::open](crate::Card::open), a missing folder is reported instead of being an error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CardReport {
+    pub root_directory: PathBuf,
+    /// One report per standard folder, in the order of [CardFolder]
+    pub folders: Vec<FolderReport>,
+}
+
+impl CardReport {
+    /// Get the report of a folder
+    pub fn folder(&self, folder: CardFolder) -> Option<&FolderReport> {
+        self.folders
+            .iter()
+            .find(|report| report.folder == folder)
+    }
+
+    /// Get the required folders that are missing
+    pub fn missing_required_folders(&self) -> Vec<CardFolder> {
+        self.folders
+            .iter()
+            .filter(|report| report.folder.is_required() && !report.exists())
+            .map(|report| report.folder)
+            .collect()
+    }
+
+    /// Returns true if the card can be opened with [Card::open](crate::Card::open)
+    pub fn can_be_opened(&self) -> bool {
+        self.missing_required_folders()
+            .is_empty()
+    }
+}
@@ -1,15 +1,32 @@
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
 use crate::CardError;
 
 #[cfg(test)]
 use mockall::{automock, predicate::*};
 
-fn make_io_error(error: std::io::Error) -> CardError {
-    CardError::IoError(error.to_string())
+/// Convert a [std::io::Error] raised when accessing a path, the kind of the error is kept
+pub(super) fn make_io_error(path: &Path) -> impl FnOnce(std::io::Error) -> CardError + '_ {
+    move |error| CardError::io(error.kind(), error.to_string(), path)
+}
+
+/// The metadata of a file, see [FileSystem::file_metadata]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// The size in bytes
+    pub size: u64,
+    /// The time of the last modification, None if the file system doesn't provide it.
+    pub modified: Option<SystemTime>,
 }
 
 /// This trait exists to make unit testing possible.
+///
+/// The methods with a default implementation were added after the others, they are
+/// implemented using the older methods, or return an unsupported error when that's not possible,
+/// so the existing implementations keep compiling.
 #[cfg_attr(test, automock)]
 pub trait FileSystem {
     /// This method gives the paths of the directories present in a given directory.
@@ -26,6 +43,61 @@ pub trait FileSystem {
 
     /// Check if a path points on a file
     fn is_file(&self, path: &Path) -> Result<bool, CardError>;
+
+    /// Get the size of a file in bytes
+    fn file_size(&self, path: &Path) -> Result<u64, CardError>;
+
+    /// Get the size and the modification time of a file
+    ///
+    /// The default implementation uses [FileSystem::file_size] and doesn't provide the modification time.
+    fn file_metadata(&self, path: &Path) -> Result<FileMetadata, CardError> {
+        Ok(FileMetadata {
+            size: self.file_size(path)?,
+            modified: None,
+        })
+    }
+
+    /// Read the content of a text file
+    fn read_file(&self, path: &Path) -> Result<String, CardError>;
+
+    /// Read the content of a file as bytes
+    ///
+    /// The default implementation uses [FileSystem::read_file] so it only supports UTF-8 files.
+    fn read_file_bytes(&self, path: &Path) -> Result<Vec<u8>, CardError> {
+        Ok(self.read_file(path)?.into_bytes())
+    }
+
+    /// Read at most `length` bytes of a file starting at `offset`
+    ///
+    /// Less bytes are returned if the end of the file is reached.
+    fn read_file_range(&self, path: &Path, offset: u64, length: usize) -> Result<Vec<u8>, CardError>;
+
+    /// Write a text file, the file is created if it doesn't exist or truncated if it exists.
+    fn write_file(&self, path: &Path, content: &str) -> Result<(), CardError>;
+
+    /// Write a file from bytes, the file is created if it doesn't exist or truncated if it exists.
+    ///
+    /// The default implementation uses [FileSystem::write_file] so it only supports UTF-8 content.
+    fn write_file_bytes(&self, path: &Path, content: &[u8]) -> Result<(), CardError> {
+        let content =
+            std::str::from_utf8(content).map_err(|e| CardError::io(std::io::ErrorKind::InvalidData, e.to_string(), path))?;
+
+        self.write_file(path, content)
+    }
+
+    /// Copy a file, the destination is overwritten if it exists.
+    fn copy_file(&self, from: &Path, to: &Path) -> Result<(), CardError>;
+
+    /// Rename or move a file, the destination is overwritten if it exists.
+    ///
+    /// The default implementation returns an [std::io::ErrorKind::Unsupported] error.
+    fn rename(&self, from: &Path, _to: &Path) -> Result<(), CardError> {
+        Err(CardError::io(
+            std::io::ErrorKind::Unsupported,
+            "rename is not supported",
+            from,
+        ))
+    }
 }
 
 /// The local filesystem.
@@ -38,8 +110,8 @@ impl FileSystem for LocalFileSystem {
     fn get_directory_entries(&self, path: &Path) -> Result<Vec<PathBuf>, CardError> {
         let mut results: Vec<PathBuf> = Vec::new();
 
-        for entry in std::fs::read_dir(path).map_err(make_io_error)? {
-            if let Ok(entry) = entry.map_err(make_io_error) {
+        for entry in std::fs::read_dir(path).map_err(make_io_error(path))? {
+            if let Ok(entry) = entry.map_err(make_io_error(path)) {
                 results.push(entry.path());
             }
         }
@@ -48,7 +120,7 @@ impl FileSystem for LocalFileSystem {
     }
 
     fn create_directory(&self, path: &Path) -> Result<(), CardError> {
-        std::fs::create_dir_all(path).map_err(make_io_error)?;
+        std::fs::create_dir_all(path).map_err(make_io_error(path))?;
 
         Ok(())
     }
@@ -64,7 +136,66 @@ impl FileSystem for LocalFileSystem {
     fn is_file(&self, path: &Path) -> Result<bool, CardError> {
         Ok(path
             .metadata()
-            .map_err(make_io_error)?
+            .map_err(make_io_error(path))?
             .is_file())
     }
+
+    fn file_size(&self, path: &Path) -> Result<u64, CardError> {
+        Ok(path
+            .metadata()
+            .map_err(make_io_error(path))?
+            .len())
+    }
+
+    fn file_metadata(&self, path: &Path) -> Result<FileMetadata, CardError> {
+        let metadata = path
+            .metadata()
+            .map_err(make_io_error(path))?;
+
+        Ok(FileMetadata {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    fn read_file(&self, path: &Path) -> Result<String, CardError> {
+        std::fs::read_to_string(path).map_err(make_io_error(path))
+    }
+
+    fn read_file_bytes(&self, path: &Path) -> Result<Vec<u8>, CardError> {
+        std::fs::read(path).map_err(make_io_error(path))
+    }
+
+    fn read_file_range(&self, path: &Path, offset: u64, length: usize) -> Result<Vec<u8>, CardError> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut file = std::fs::File::open(path).map_err(make_io_error(path))?;
+        let mut content = Vec::with_capacity(length);
+
+        file.seek(SeekFrom::Start(offset))
+            .map_err(make_io_error(path))?;
+        file.take(length as u64)
+            .read_to_end(&mut content)
+            .map_err(make_io_error(path))?;
+
+        Ok(content)
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> Result<(), CardError> {
+        std::fs::write(path, content).map_err(make_io_error(path))
+    }
+
+    fn write_file_bytes(&self, path: &Path, content: &[u8]) -> Result<(), CardError> {
+        std::fs::write(path, content).map_err(make_io_error(path))
+    }
+
+    fn copy_file(&self, from: &Path, to: &Path) -> Result<(), CardError> {
+        std::fs::copy(from, to).map_err(make_io_error(from))?;
+
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), CardError> {
+        std::fs::rename(from, to).map_err(make_io_error(from))
+    }
 }
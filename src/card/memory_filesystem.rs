@@ -0,0 +1,255 @@
+use std::{
+    collections::BTreeMap,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{CardError, FileSystem};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Entry {
+    Directory,
+    File(Vec<u8>),
+}
+
+#[derive(Default, Debug)]
+struct State {
+    entries: BTreeMap<PathBuf, Entry>,
+    writes: Vec<PathBuf>,
+}
+
+/// A file system stored in memory.
+///
+/// This is useful to unit test code using a [Card](crate::Card) without touching the disk.
+/// The clones share the same content so a clone can be kept to inspect the content after
+/// the file system has been moved into a card.
+/// ```
+/// # use std::path::Path;
+/// # use deluge::{Card, MemoryFileSystem};
+/// let file_system = MemoryFileSystem::new().with_dir("card");
+/// Card::create(file_system.clone(), Path::new("card"))?;
+///
+/// assert!(file_system.written_paths().contains(&Path::new("card/KITS").to_path_buf()));
+/// # Ok::<(), deluge::CardError>(())
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct MemoryFileSystem {
+    state: Arc<Mutex<State>>,
+}
+
+impl MemoryFileSystem {
+    /// Create an empty file system
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a directory and its parents
+    pub fn with_dir(self, path: impl AsRef<Path>) -> Self {
+        Self::insert_directories(&mut self.lock(), path.as_ref());
+
+        self
+    }
+
+    /// Add a file, the parent directories are created if needed
+    pub fn with_file(self, path: impl AsRef<Path>, content: impl Into<Vec<u8>>) -> Self {
+        {
+            let mut state = self.lock();
+            let path = path.as_ref();
+
+            if let Some(parent) = path.parent() {
+                Self::insert_directories(&mut state, parent);
+            }
+
+            state
+                .entries
+                .insert(path.to_path_buf(), Entry::File(content.into()));
+        }
+
+        self
+    }
+
+    /// Get the content of a file
+    pub fn file_content(&self, path: impl AsRef<Path>) -> Option<Vec<u8>> {
+        match self.lock().entries.get(path.as_ref()) {
+            Some(Entry::File(content)) => Some(content.clone()),
+            _ => None,
+        }
+    }
+
+    /// Get the paths of the directories created and the files written
+    /// through the [FileSystem] trait, in the order of the operations.
+    pub fn written_paths(&self) -> Vec<PathBuf> {
+        self.lock().writes.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn insert_directories(state: &mut State, path: &Path) -> Vec<PathBuf> {
+        let mut created = Vec::new();
+
+        for ancestor in path.ancestors() {
+            if ancestor.as_os_str().is_empty() || state.entries.contains_key(ancestor) {
+                continue;
+            }
+
+            created.push(ancestor.to_path_buf());
+        }
+
+        created.reverse();
+
+        for directory in &created {
+            state
+                .entries
+                .insert(directory.clone(), Entry::Directory);
+        }
+
+        created
+    }
+
+    fn write_bytes(state: &mut State, path: &Path, content: Vec<u8>) -> Result<(), CardError> {
+        match path.parent() {
+            Some(parent) if !Self::is_directory(state, parent) => return Err(make_not_found_error(parent)),
+            _ => {}
+        }
+
+        if let Some(Entry::Directory) = state.entries.get(path) {
+            return Err(CardError::io(ErrorKind::IsADirectory, "is a directory", path));
+        }
+
+        state
+            .entries
+            .insert(path.to_path_buf(), Entry::File(content));
+        state.writes.push(path.to_path_buf());
+
+        Ok(())
+    }
+
+    fn is_directory(state: &State, path: &Path) -> bool {
+        path.as_os_str().is_empty() || state.entries.get(path) == Some(&Entry::Directory)
+    }
+}
+
+fn make_not_found_error(path: &Path) -> CardError {
+    CardError::io(ErrorKind::NotFound, "not found", path)
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn get_directory_entries(&self, path: &Path) -> Result<Vec<PathBuf>, CardError> {
+        let state = self.lock();
+
+        if !Self::is_directory(&state, path) {
+            return Err(make_not_found_error(path));
+        }
+
+        Ok(state
+            .entries
+            .keys()
+            .filter(|entry| entry.parent() == Some(path))
+            .cloned()
+            .collect())
+    }
+
+    fn create_directory(&self, path: &Path) -> Result<(), CardError> {
+        let mut state = self.lock();
+
+        if let Some(Entry::File(_)) = state.entries.get(path) {
+            return Err(CardError::io(ErrorKind::AlreadyExists, "is a file", path));
+        }
+
+        let mut created = Self::insert_directories(&mut state, path);
+
+        state.writes.append(&mut created);
+
+        Ok(())
+    }
+
+    fn directory_exists(&self, path: &Path) -> bool {
+        Self::is_directory(&self.lock(), path)
+    }
+
+    fn file_exists(&self, path: &Path) -> bool {
+        matches!(self.lock().entries.get(path), Some(Entry::File(_)))
+    }
+
+    fn is_file(&self, path: &Path) -> Result<bool, CardError> {
+        let state = self.lock();
+
+        match state.entries.get(path) {
+            Some(entry) => Ok(matches!(entry, Entry::File(_))),
+            None if Self::is_directory(&state, path) => Ok(false),
+            None => Err(make_not_found_error(path)),
+        }
+    }
+
+    fn file_size(&self, path: &Path) -> Result<u64, CardError> {
+        match self.lock().entries.get(path) {
+            Some(Entry::File(content)) => Ok(content.len() as u64),
+            Some(Entry::Directory) => Ok(0),
+            None => Err(make_not_found_error(path)),
+        }
+    }
+
+    fn read_file(&self, path: &Path) -> Result<String, CardError> {
+        match self.lock().entries.get(path) {
+            Some(Entry::File(content)) => {
+                String::from_utf8(content.clone()).map_err(|e| CardError::io(ErrorKind::InvalidData, e.to_string(), path))
+            }
+            _ => Err(make_not_found_error(path)),
+        }
+    }
+
+    fn read_file_bytes(&self, path: &Path) -> Result<Vec<u8>, CardError> {
+        match self.lock().entries.get(path) {
+            Some(Entry::File(content)) => Ok(content.clone()),
+            _ => Err(make_not_found_error(path)),
+        }
+    }
+
+    fn read_file_range(&self, path: &Path, offset: u64, length: usize) -> Result<Vec<u8>, CardError> {
+        match self.lock().entries.get(path) {
+            Some(Entry::File(content)) => Ok(content
+                .iter()
+                .skip(offset as usize)
+                .take(length)
+                .copied()
+                .collect()),
+            _ => Err(make_not_found_error(path)),
+        }
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> Result<(), CardError> {
+        Self::write_bytes(&mut self.lock(), path, content.as_bytes().to_vec())
+    }
+
+    fn write_file_bytes(&self, path: &Path, content: &[u8]) -> Result<(), CardError> {
+        Self::write_bytes(&mut self.lock(), path, content.to_vec())
+    }
+
+    fn copy_file(&self, from: &Path, to: &Path) -> Result<(), CardError> {
+        let mut state = self.lock();
+        let content = match state.entries.get(from) {
+            Some(Entry::File(content)) => content.clone(),
+            _ => return Err(make_not_found_error(from)),
+        };
+
+        Self::write_bytes(&mut state, to, content)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), CardError> {
+        let mut state = self.lock();
+        let content = match state.entries.get(from) {
+            Some(Entry::File(content)) => content.clone(),
+            _ => return Err(make_not_found_error(from)),
+        };
+
+        Self::write_bytes(&mut state, to, content)?;
+        state.entries.remove(from);
+
+        Ok(())
+    }
+}
@@ -7,10 +7,22 @@
 //! ├── SAMPLES
 //! └── SYNTHS
 //! ```
+//!
+//! The Deluge creates more folders when needed, like SONGS or SAMPLES/RECORD, see [CardFolder].
 
+#[cfg(feature = "async")]
+mod async_filesystem;
 mod card_folder;
+mod card_index;
+mod card_report;
 mod filesystem;
+mod memory_filesystem;
+mod patch_entry;
 mod patch_name;
+mod patch_rename;
+mod read_only_filesystem;
+mod sample_gathering;
+mod sample_issue;
 
 #[cfg(test)]
 mod tests;
@@ -19,17 +31,30 @@ use core::fmt::Debug;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     path::{Path, PathBuf},
 };
 use strum::IntoEnumIterator;
 
+#[cfg(feature = "async")]
+pub use async_filesystem::{AsyncFileSystem, TokioFileSystem};
 pub use card_folder::CardFolder;
-pub use filesystem::{FileSystem, LocalFileSystem};
-pub use patch_name::PatchName;
+pub use card_index::{CardIndex, PatchMetadata, ScanError};
+pub use card_report::{CardReport, FolderReport};
+pub use filesystem::{FileMetadata, FileSystem, LocalFileSystem};
+pub use memory_filesystem::MemoryFileSystem;
+pub use patch_entry::PatchEntry;
+pub use patch_name::{PatchName, PatchNameError, PatchNameFix};
+pub use patch_rename::RenameReport;
+pub use read_only_filesystem::{FileOperation, OperationLog, ReadOnlyFileSystem};
+pub use sample_gathering::{CollisionStrategy, GatherReport};
+pub use sample_issue::SampleIssue;
 
 use crate::values::SamplePath;
-use crate::PatchType;
+use crate::{
+    deserialize_kit_bytes_with_version, deserialize_synth_bytes_with_version, serialize_kit, serialize_synth, Kit, PatchType,
+    ReadError, Synth, VersionInfo, WriteError,
+};
 
 #[derive(thiserror::Error, Debug, PartialEq, Eq, Clone)]
 pub enum CardError {
@@ -39,12 +64,11 @@ pub enum CardError {
     #[error("Directory '{0}' already exists")]
     DirectoryAlreadyExists(PathBuf),
 
-    #[error("Missing root directory '{0}'")]
-    MissingRootDirectory(String),
+    #[error("Missing root directories: {}", .0.join(", "))]
+    MissingRootDirectories(Vec<String>),
 
-    // Store a String instead of std::io::Error to be able to derive PartialEq.
     #[error("I/O error: {0}")]
-    IoError(String),
+    IoError(IoErrorInfo),
 
     #[error("The file '{0}' is not located on a Deluge card")]
     FileNotInCard(PathBuf),
@@ -57,6 +81,87 @@ pub enum CardError {
 
     #[error("No more postfix letter available")]
     NoMorePostfixLetter,
+
+    #[error("The patch name '{0}' is not a standard patch name")]
+    NotStandardPatchName(String),
+
+    #[error("The patch '{0}' does not exist")]
+    PatchNotFound(String),
+
+    #[error("The file '{0}' already exists")]
+    FileAlreadyExists(PathBuf),
+
+    #[error("Invalid patch name '{0}': {1}")]
+    InvalidPatchName(String, PatchNameError),
+}
+
+impl CardError {
+    /// Create an I/O error about a file or a directory
+    pub(crate) fn io(kind: std::io::ErrorKind, message: impl Into<String>, path: &Path) -> Self {
+        CardError::IoError(IoErrorInfo {
+            kind,
+            message: message.into(),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Get the kind of the I/O error, None if the error is not an I/O error
+    /// ```
+    /// # use deluge::{FileSystem, LocalFileSystem};
+    /// # use std::{io::ErrorKind, path::Path};
+    /// let error = LocalFileSystem.read_file(Path::new("missing card/KITS/KIT000.XML")).unwrap_err();
+    ///
+    /// assert_eq!(error.io_error_kind(), Some(ErrorKind::NotFound));
+    /// ```
+    pub fn io_error_kind(&self) -> Option<std::io::ErrorKind> {
+        match self {
+            CardError::IoError(info) => Some(info.kind),
+            _ => None,
+        }
+    }
+}
+
+/// The details of an I/O error, see [CardError::IoError]
+///
+/// The kind of the [std::io::Error] is kept to tell apart, for example, a permission denied from a card removed.
+/// Two errors are equal if their kinds and their paths are equal, the messages are ignored.
+#[derive(Debug, Clone)]
+pub struct IoErrorInfo {
+    pub kind: std::io::ErrorKind,
+    /// The message of the original error
+    pub message: String,
+    /// The path of the file or the directory accessed, if known
+    pub path: Option<PathBuf>,
+}
+
+impl PartialEq for IoErrorInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.path == other.path
+    }
+}
+
+impl Eq for IoErrorInfo {}
+
+impl std::fmt::Display for IoErrorInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "'{}': {}", path.display(), self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Error returned when reading or writing a patch on a card
+#[derive(thiserror::Error, Debug)]
+pub enum CardOpenError {
+    #[error("Card error: {0}")]
+    CardError(#[from] CardError),
+
+    #[error("{0}")]
+    ReadError(#[from] ReadError),
+
+    #[error("{0}")]
+    WriteError(#[from] WriteError),
 }
 
 /// A deluge card
@@ -79,6 +184,8 @@ pub enum CardError {
 ///
 pub struct Card<FS: FileSystem> {
     root_directory: PathBuf,
+    /// The names of the folders as found on the card, the case can be different than the standard names.
+    folder_names: BTreeMap<CardFolder, String>,
     file_system: Arc<FS>,
 }
 
@@ -86,6 +193,7 @@ impl<FS: FileSystem> Clone for Card<FS> {
     fn clone(&self) -> Self {
         Self {
             root_directory: self.root_directory.clone(),
+            folder_names: self.folder_names.clone(),
             file_system: self.file_system.clone(),
         }
     }
@@ -123,26 +231,79 @@ impl<FS: FileSystem> Card<FS> {
 
     /// Check the required directories exist, return an error if not.
     fn check_required_directories(file_system: &FS, root_directory: &Path) -> Result<(), CardError> {
-        let directory_names = file_system
-            .get_directory_entries(root_directory)?
+        Self::find_required_directories(file_system, root_directory).map(|_| ())
+    }
+
+    /// Find the names of the required directories, return an error listing all the missing ones.
+    ///
+    /// Like FAT, the names are compared ignoring the case.
+    fn find_required_directories(file_system: &FS, root_directory: &Path) -> Result<BTreeMap<CardFolder, String>, CardError> {
+        let folder_names = Self::find_existing_directories(file_system, root_directory)?;
+
+        check_required_directories(&folder_names)?;
+
+        Ok(folder_names)
+    }
+
+    /// Find the names of the standard directories at the root of the card that exist, including the optional ones.
+    fn find_existing_directories(file_system: &FS, root_directory: &Path) -> Result<BTreeMap<CardFolder, String>, CardError> {
+        Ok(find_root_directory_names(&Self::get_entry_names(
+            file_system,
+            root_directory,
+        )?))
+    }
+
+    /// Find the names of the standard directories that exist in the existing standard directories.
+    fn find_existing_subdirectories(
+        file_system: &FS,
+        root_directory: &Path,
+        folder_names: &mut BTreeMap<CardFolder, String>,
+    ) -> Result<(), CardError> {
+        for parent in CardFolder::iter().filter(|folder| folder.parent().is_none()) {
+            let parent_path = match folder_names.get(&parent) {
+                Some(name) => root_directory.join(name),
+                None => continue,
+            };
+
+            if !file_system.directory_exists(&parent_path) {
+                continue;
+            }
+
+            let directory_names = Self::get_entry_names(file_system, &parent_path)?;
+
+            for folder in CardFolder::iter().filter(|folder| folder.parent() == Some(parent)) {
+                if let Some(name) = find_directory_name(&directory_names, folder.directory_name()) {
+                    folder_names.insert(folder, name.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_entry_names(file_system: &FS, directory: &Path) -> Result<BTreeSet<String>, CardError> {
+        Ok(file_system
+            .get_directory_entries(directory)?
             .iter()
             .filter_map(|path| {
                 path.file_name()
                     .map(|file_name| file_name.to_string_lossy().to_string())
             })
-            .collect::<BTreeSet<String>>();
-
-        for required_directory in CardFolder::iter() {
-            if !directory_names.contains(required_directory.directory_name()) {
-                return Err(CardError::MissingRootDirectory(
-                    required_directory
-                        .directory_name()
-                        .to_owned(),
-                ));
-            }
-        }
+            .collect())
+    }
 
-        Ok(())
+    fn directory_path(root_directory: &Path, folder_names: &BTreeMap<CardFolder, String>, folder: CardFolder) -> PathBuf {
+        let parent_path = match folder.parent() {
+            Some(parent) => Self::directory_path(root_directory, folder_names, parent),
+            None => root_directory.to_path_buf(),
+        };
+
+        parent_path.join(
+            folder_names
+                .get(&folder)
+                .map(String::as_str)
+                .unwrap_or_else(|| folder.directory_name()),
+        )
     }
 
     /// Creates the card directory and the required folders.
@@ -151,27 +312,68 @@ impl<FS: FileSystem> Card<FS> {
     /// The other directories may or may not exist, they will be created as needed.
     /// Existing files or folder excepted the standard ones are simply ignored.
     pub fn create(file_system: FS, root_directory: &Path) -> Result<Self, CardError> {
+        Self::create_with_folders(file_system, root_directory, &[])
+    }
+
+    /// Creates the card directory, the required folders and the optional folders specified.
+    ///
+    /// The folders containing the optional folders are created too, like SAMPLES for RECORD.
+    /// See [Card::create].
+    /// ```
+    /// # use std::path::Path;
+    /// # use deluge::{Card, CardFolder, MemoryFileSystem};
+    /// let file_system = MemoryFileSystem::new().with_dir("card");
+    /// let card = Card::create_with_folders(file_system.clone(), Path::new("card"), &[CardFolder::Resample])?;
+    ///
+    /// assert_eq!(card.get_directory_path(CardFolder::Resample), Path::new("card/SAMPLES/RESAMPLE"));
+    /// assert!(file_system.written_paths().contains(&Path::new("card/SAMPLES/RESAMPLE").to_path_buf()));
+    /// # Ok::<(), deluge::CardError>(())
+    /// ```
+    pub fn create_with_folders(file_system: FS, root_directory: &Path, folders: &[CardFolder]) -> Result<Self, CardError> {
         let root_directory = root_directory.to_path_buf();
 
         if !file_system.directory_exists(&root_directory) {
             return Err(CardError::DirectoryDoesNotExists(root_directory));
         }
 
-        let card = Self {
-            file_system: Arc::new(file_system),
-            root_directory,
+        let mut folder_names = Self::find_existing_directories(&file_system, &root_directory)?;
+
+        // The subfolders are searched only if needed, creating a minimal card reads only the root directory.
+        if folders
+            .iter()
+            .any(|folder| folder.parent().is_some())
+        {
+            Self::find_existing_subdirectories(&file_system, &root_directory, &mut folder_names)?;
+        }
+
+        // The parents are before their subfolders in the order of CardFolder.
+        let is_requested = |folder: &CardFolder| {
+            folder.is_required()
+                || folders.contains(folder)
+                || folders
+                    .iter()
+                    .any(|requested| requested.parent() == Some(*folder))
         };
 
-        for required_directory in CardFolder::iter() {
-            let path = &card.get_directory_path(required_directory);
+        for folder in CardFolder::iter().filter(is_requested) {
+            if folder_names.contains_key(&folder) {
+                continue;
+            }
+
+            let path = Self::directory_path(&root_directory, &folder_names, folder);
 
-            if !card.file_system.directory_exists(path) {
-                card.file_system
-                    .create_directory(path)?;
+            if !file_system.directory_exists(&path) {
+                file_system.create_directory(&path)?;
             }
+
+            folder_names.insert(folder, folder.directory_name().to_owned());
         }
 
-        Ok(card)
+        Ok(Self {
+            file_system: Arc::new(file_system),
+            folder_names,
+            root_directory,
+        })
     }
 
     /// Open a card directory.
@@ -184,14 +386,90 @@ impl<FS: FileSystem> Card<FS> {
             return Err(CardError::DirectoryDoesNotExists(root_directory));
         }
 
-        Self::check_required_directories(&file_system, &root_directory)?;
+        let folder_names = Self::find_required_directories(&file_system, &root_directory)?;
 
         Ok(Self {
+            file_system: Arc::new(file_system),
+            folder_names,
+            root_directory,
+        })
+    }
+
+    /// Check the standard folders of a card without failing if some are missing
+    ///
+    /// The patches of the existing folders are counted, including the ones in subfolders.
+    /// An error is returned only if the root directory doesn't exist or can't be read.
+    /// ```
+    /// # use deluge::{Card, CardFolder, MemoryFileSystem};
+    /// # use std::path::Path;
+    /// let file_system = MemoryFileSystem::new()
+    ///     .with_file("card/KITS/KIT000.XML", "")
+    ///     .with_dir("card/SAMPLES");
+    /// let report = Card::check(file_system, Path::new("card")).unwrap();
+    ///
+    /// assert_eq!(report.folder(CardFolder::Kits).unwrap().patch_count, Some(1));
+    /// assert_eq!(report.missing_required_folders(), vec![CardFolder::Synths]);
+    /// assert!(!report.can_be_opened());
+    /// ```
+    pub fn check(file_system: FS, root_directory: &Path) -> Result<CardReport, CardError> {
+        let root_directory = root_directory.to_path_buf();
+
+        if !file_system.directory_exists(&root_directory) {
+            return Err(CardError::DirectoryDoesNotExists(root_directory));
+        }
+
+        let mut folder_names = Self::find_existing_directories(&file_system, &root_directory)?;
+
+        Self::find_existing_subdirectories(&file_system, &root_directory, &mut folder_names)?;
+
+        let card = Self {
+            folder_names,
             file_system: Arc::new(file_system),
             root_directory,
+        };
+        let mut folders = Vec::new();
+
+        for folder in CardFolder::iter() {
+            let directory_name = card.folder_names.get(&folder).cloned();
+            let patch_type = [PatchType::Kit, PatchType::Synth]
+                .into_iter()
+                .find(|patch_type| patch_type.get_card_folder() == folder);
+            let patch_count = match (&directory_name, patch_type) {
+                (Some(_), Some(patch_type)) => Some(
+                    card.list_patches_recursive(patch_type)?
+                        .len(),
+                ),
+                _ => None,
+            };
+
+            folders.push(FolderReport {
+                folder,
+                directory_name,
+                patch_count,
+            });
+        }
+
+        Ok(CardReport {
+            root_directory: card.root_directory,
+            folders,
         })
     }
 
+    /// Get a card reading the same file system but recording the modifications instead of performing them
+    ///
+    /// The log lists the operations the card would have performed, see [ReadOnlyFileSystem].
+    pub fn into_read_only(self) -> (Card<ReadOnlyFileSystem<FS>>, OperationLog) {
+        let file_system = ReadOnlyFileSystem::from_shared(self.file_system);
+        let log = file_system.log();
+        let card = Card {
+            root_directory: self.root_directory,
+            folder_names: self.folder_names,
+            file_system: Arc::new(file_system),
+        };
+
+        (card, log)
+    }
+
     /// Get the root directory
     pub fn root_directory(&self) -> &Path {
         self.root_directory.as_path()
@@ -217,16 +495,296 @@ impl<FS: FileSystem> Card<FS> {
             .join(path.to_path())
     }
 
+    /// Read the properties of a WAV file of the card
+    #[cfg(feature = "wav")]
+    pub fn wav_info(&self, path: &SamplePath) -> Result<crate::WavInfo, crate::WavError> {
+        crate::samples::read_wav_info(self.file_system.as_ref(), &self.absolute_path(path))
+    }
+
     /// Get one of the card's directory path
+    ///
+    /// The name of the directory is the one found on the card, it may differ by the case from the standard name.
+    /// The path of a folder stored in another folder, like RECORD, includes the path of its parent.
     pub fn get_directory_path(&self, folder: CardFolder) -> PathBuf {
-        self.root_directory
-            .join(folder.directory_name())
+        Self::directory_path(&self.root_directory, &self.folder_names, folder)
+    }
+
+    /// List the patches stored in the folder of the specified patch type
+    ///
+    /// Only the XML files are listed, the extension is not case sensitive.
+    /// The patches are sorted like the Deluge browser sorts them, see [PatchEntry::browser_order].
+    pub fn list_patches(&self, patch_type: PatchType) -> Result<Vec<PatchEntry>, CardError> {
+        Ok(self
+            .list_directory_patches(&self.get_directory_path(patch_type.get_card_folder()))?
+            .0)
+    }
+
+    /// List the patches stored in the folder of the specified patch type and in its subfolders
+    ///
+    /// The patches of a folder are sorted like [Card::list_patches] does and come before the patches of its subfolders.
+    /// The subfolders are visited in alphabetical order ignoring the case.
+    pub fn list_patches_recursive(&self, patch_type: PatchType) -> Result<Vec<PatchEntry>, CardError> {
+        let mut patches = Vec::new();
+        let mut directories = vec![self.get_directory_path(patch_type.get_card_folder())];
+
+        while let Some(directory) = directories.pop() {
+            let (mut directory_patches, mut subdirectories) = self.list_directory_patches(&directory)?;
+
+            patches.append(&mut directory_patches);
+            subdirectories.sort_by_key(|path| path.to_string_lossy().to_lowercase());
+            directories.extend(subdirectories.into_iter().rev());
+        }
+
+        Ok(patches)
+    }
+
+    /// List the patches of a directory, sorted, and its subdirectories.
+    fn list_directory_patches(&self, directory: &Path) -> Result<(Vec<PatchEntry>, Vec<PathBuf>), CardError> {
+        let mut patches = Vec::new();
+        let mut subdirectories = Vec::new();
+
+        for path in self
+            .file_system
+            .get_directory_entries(directory)?
+        {
+            if !self.file_system.is_file(&path)? {
+                subdirectories.push(path);
+                continue;
+            }
+
+            if !is_patch_file_name(&path) {
+                continue;
+            }
+
+            let file_stem = match path.file_stem() {
+                Some(file_stem) => file_stem.to_string_lossy().to_string(),
+                None => continue,
+            };
+
+            if let Ok(name) = PatchName::from_str(&file_stem) {
+                patches.push(PatchEntry {
+                    size: self.file_system.file_size(&path)?,
+                    path,
+                    name,
+                });
+            }
+        }
+
+        patches.sort_by(PatchEntry::browser_order);
+
+        Ok((patches, subdirectories))
+    }
+
+    /// Find the path of a patch file
+    ///
+    /// The extension is not case sensitive so "KIT000.XML" and "KIT000.xml" are both found.
+    pub fn find_patch_path(&self, patch_type: PatchType, name: &PatchName) -> Result<Option<PathBuf>, CardError> {
+        let file_stem = name.to_string();
+
+        for path in self
+            .file_system
+            .get_directory_entries(&self.get_directory_path(patch_type.get_card_folder()))?
+        {
+            let has_same_stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy() == file_stem)
+                .unwrap_or(false);
+
+            if has_same_stem && is_patch_file_name(&path) && self.file_system.is_file(&path)? {
+                return Ok(Some(path));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Read a kit stored on the card
+    pub fn read_kit(&self, name: &PatchName) -> Result<Kit, CardOpenError> {
+        Ok(self.read_kit_with_version(name)?.0)
+    }
+
+    /// Read a kit stored on the card and the version information
+    pub fn read_kit_with_version(&self, name: &PatchName) -> Result<(Kit, VersionInfo), CardOpenError> {
+        let path = self.get_existing_patch_path(PatchType::Kit, name)?;
+        let bytes = self
+            .file_system
+            .read_file_bytes(&path)?;
+
+        deserialize_kit_bytes_with_version(&bytes).map_err(|e| ReadError::DeserializationError(e).into())
+    }
+
+    /// Read a synth stored on the card
+    pub fn read_synth(&self, name: &PatchName) -> Result<Synth, CardOpenError> {
+        Ok(self.read_synth_with_version(name)?.0)
+    }
+
+    /// Read a synth stored on the card and the version information
+    pub fn read_synth_with_version(&self, name: &PatchName) -> Result<(Synth, VersionInfo), CardOpenError> {
+        let path = self.get_existing_patch_path(PatchType::Synth, name)?;
+        let bytes = self
+            .file_system
+            .read_file_bytes(&path)?;
+
+        deserialize_synth_bytes_with_version(&bytes).map_err(|e| ReadError::DeserializationError(e).into())
+    }
+
+    /// Write a kit on the card
+    ///
+    /// If a patch with the same name exists, it's overwritten only if `overwrite` is true
+    /// otherwise the error [CardError::FileAlreadyExists] is returned.
+    /// The error [CardError::InvalidPatchName] is returned if the name can't be used as a file name, see [PatchName::validate].
+    /// Returns the path of the file written.
+    pub fn write_kit(&self, kit: &Kit, name: &PatchName, overwrite: bool) -> Result<PathBuf, CardOpenError> {
+        let xml = serialize_kit(kit).map_err(WriteError::SerializationError)?;
+
+        self.write_patch(PatchType::Kit, name, &xml, overwrite)
+    }
+
+    /// Write a synth on the card
+    ///
+    /// If a patch with the same name exists, it's overwritten only if `overwrite` is true
+    /// otherwise the error [CardError::FileAlreadyExists] is returned.
+    /// The error [CardError::InvalidPatchName] is returned if the name can't be used as a file name, see [PatchName::validate].
+    /// Returns the path of the file written.
+    pub fn write_synth(&self, synth: &Synth, name: &PatchName, overwrite: bool) -> Result<PathBuf, CardOpenError> {
+        let xml = serialize_synth(synth).map_err(WriteError::SerializationError)?;
+
+        self.write_patch(PatchType::Synth, name, &xml, overwrite)
+    }
+
+    fn write_patch(&self, patch_type: PatchType, name: &PatchName, xml: &str, overwrite: bool) -> Result<PathBuf, CardOpenError> {
+        name.validate()
+            .map_err(|error| CardError::InvalidPatchName(name.to_string(), error))?;
+
+        let path = match self.find_patch_path(patch_type, name)? {
+            Some(existing_path) if overwrite => existing_path,
+            Some(existing_path) => return Err(CardError::FileAlreadyExists(existing_path).into()),
+            None => self.get_patch_path(patch_type, name),
+        };
+
+        self.file_system
+            .write_file_bytes(&path, xml.as_bytes())?;
+
+        Ok(path)
+    }
+
+    fn get_existing_patch_path(&self, patch_type: PatchType, name: &PatchName) -> Result<PathBuf, CardError> {
+        self.find_patch_path(patch_type, name)?
+            .ok_or_else(|| CardError::PatchNotFound(name.to_string()))
+    }
+
+    fn get_patch_path(&self, patch_type: PatchType, name: &PatchName) -> PathBuf {
+        let mut result = self.get_directory_path(patch_type.get_card_folder());
+
+        result.push(name.to_string());
+        result.set_extension("XML");
+
+        result
+    }
+
+    /// Find the sample paths that do not match any file on the card
+    ///
+    /// Like FAT, the case is ignored so a path with a different case is not considered missing.
+    pub fn find_missing_samples(&self, sample_paths: &BTreeSet<SamplePath>) -> Result<Vec<SamplePath>, CardError> {
+        let mut missing_samples = Vec::new();
+
+        for sample_path in sample_paths {
+            if self
+                .resolve_sample_path(sample_path)?
+                .is_none()
+            {
+                missing_samples.push(sample_path.clone());
+            }
+        }
+
+        Ok(missing_samples)
+    }
+
+    /// Check the samples referenced by a patch stored on the card
+    ///
+    /// Returns the samples missing and the ones found with a different case.
+    pub fn validate_patch_samples(&self, patch_type: PatchType, name: &PatchName) -> Result<Vec<SampleIssue>, CardOpenError> {
+        let sample_paths = match patch_type {
+            PatchType::Kit => self.read_kit(name)?.get_sample_paths(),
+            PatchType::Synth => self
+                .read_synth(name)?
+                .get_sample_paths(),
+        };
+        let mut issues = Vec::new();
+
+        for sample_path in sample_paths {
+            match self.resolve_sample_path(&sample_path)? {
+                None => issues.push(SampleIssue::Missing(sample_path)),
+                Some(actual_path) if actual_path != sample_path => issues.push(SampleIssue::CaseMismatch {
+                    path: sample_path,
+                    actual_path,
+                }),
+                Some(_) => {}
+            }
+        }
+
+        Ok(issues)
+    }
+
+    /// Find the file matching a sample path, ignoring the case like FAT does.
+    ///
+    /// An entry with exactly the same name is preferred over an entry with a different case.
+    fn resolve_sample_path(&self, sample_path: &SamplePath) -> Result<Option<SamplePath>, CardError> {
+        let mut current_path = self.root_directory.clone();
+
+        for component in sample_path.to_path().components() {
+            if !self
+                .file_system
+                .directory_exists(&current_path)
+            {
+                return Ok(None);
+            }
+
+            let component = component.as_os_str().to_string_lossy();
+            let entries = self
+                .file_system
+                .get_directory_entries(&current_path)?;
+            let file_name_of = |entry: &PathBuf| {
+                entry
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            };
+            let found = entries
+                .iter()
+                .find(|entry| file_name_of(entry) == component)
+                .or_else(|| {
+                    entries
+                        .iter()
+                        .find(|entry| file_name_of(entry).eq_ignore_ascii_case(&component))
+                });
+
+            match found {
+                Some(entry) => current_path = entry.clone(),
+                None => return Ok(None),
+            }
+        }
+
+        match self
+            .file_system
+            .is_file(&current_path)?
+        {
+            true => Ok(Some(self.sample_path(&current_path)?)),
+            false => Ok(None),
+        }
     }
 
     /// Get the next standard patch path with name and extension
     pub fn get_next_standard_patch_path(&self, patch_type: PatchType) -> Result<PathBuf, CardError> {
-        let base_name = self.get_next_standard_patch_name(patch_type)?;
-        let mut result = self.get_directory_path(patch_type.get_card_folder());
+        self.get_next_standard_patch_path_in(patch_type, None)
+    }
+
+    /// Get the next standard patch path with name and extension in a subfolder
+    ///
+    /// The subfolder is relative to the folder of the patch type, `None` means the folder itself.
+    pub fn get_next_standard_patch_path_in(&self, patch_type: PatchType, subdir: Option<&Path>) -> Result<PathBuf, CardError> {
+        let base_name = self.get_next_standard_patch_name_in(patch_type, subdir)?;
+        let mut result = self.get_patch_directory_path(patch_type, subdir)?;
 
         result.push(base_name);
         result.set_extension("XML");
@@ -234,6 +792,66 @@ impl<FS: FileSystem> Card<FS> {
         Ok(result)
     }
 
+    /// Gets the next variation of a standard patch name
+    ///
+    /// The Deluge saves the variations of a patch by adding a letter to its name, for example the variations of
+    /// "KIT001" are "KIT001A", "KIT001B" etc. This function returns the first letter not used, so if "KIT001A" and
+    /// "KIT001C" exist "KIT001B" is returned. The suffix of `base` is ignored.
+    /// The error [CardError::NoMorePostfixLetter] is returned if all the letters up to 'Z' are used.
+    pub fn get_next_patch_variation(&self, base: &PatchName) -> Result<PatchName, CardError> {
+        let (patch_type, number) = match base {
+            PatchName::Standard { patch_type, number, .. } => (*patch_type, *number),
+            PatchName::Custom { .. } => return Err(CardError::NotStandardPatchName(base.to_string())),
+        };
+        let mut used_suffixes = BTreeSet::new();
+
+        for path in &self
+            .file_system
+            .get_directory_entries(&self.get_directory_path(patch_type.get_card_folder()))?
+        {
+            if !is_patch_file_name(path) || !self.file_system.is_file(path)? {
+                continue;
+            }
+
+            if let Some(Ok(PatchName::Standard {
+                number: existing_number,
+                suffix: Some(suffix),
+                ..
+            })) = path
+                .file_stem()
+                .map(|file_stem| PatchName::from_str(&file_stem.to_string_lossy()))
+            {
+                if existing_number == number {
+                    used_suffixes.insert(suffix.to_ascii_uppercase());
+                }
+            }
+        }
+
+        match ('A'..='Z').find(|letter| !used_suffixes.contains(letter)) {
+            Some(suffix) => Ok(PatchName::Standard {
+                patch_type,
+                number,
+                suffix: Some(suffix),
+            }),
+            None => Err(CardError::NoMorePostfixLetter),
+        }
+    }
+
+    /// Get the path of the folder of a patch type or of one of its subfolders
+    fn get_patch_directory_path(&self, patch_type: PatchType, subdir: Option<&Path>) -> Result<PathBuf, CardError> {
+        let mut result = self.get_directory_path(patch_type.get_card_folder());
+
+        if let Some(subdir) = subdir {
+            if !subdir.is_relative() {
+                return Err(CardError::PathNotRelative(subdir.to_path_buf()));
+            }
+
+            result.push(subdir);
+        }
+
+        Ok(result)
+    }
+
     /// Gets the next standard patch name
     ///
     /// With Deluge, when you create a patch it gets a default name. For example with kits, the first default
@@ -242,14 +860,21 @@ impl<FS: FileSystem> Card<FS> {
     /// The other names not respecting this pattern I call them custom patch names.
     /// Those can also have a number but this is optional and they can't have a letter (I'm not sure of that).
     pub fn get_next_standard_patch_name(&self, patch_type: PatchType) -> Result<String, CardError> {
+        self.get_next_standard_patch_name_in(patch_type, None)
+    }
+
+    /// Gets the next standard patch name in a subfolder
+    ///
+    /// The Deluge numbers the patches per folder so only the files of the subfolder are considered.
+    /// The subfolder is relative to the folder of the patch type, `None` means the folder itself.
+    pub fn get_next_standard_patch_name_in(&self, patch_type: PatchType, subdir: Option<&Path>) -> Result<String, CardError> {
         //! I assume the maximum is 3 digits but actually Deluge has a 4 digits screen so I'm not sure.
         const MAX_STANDARD_PATCH_NUMBER: u16 = 999;
-        let folder = patch_type.get_card_folder();
         let mut max_number: Option<u16> = None;
 
         for path in &self
             .file_system
-            .get_directory_entries(&self.get_directory_path(folder))?
+            .get_directory_entries(&self.get_patch_directory_path(patch_type, subdir)?)?
         {
             if self.file_system.is_file(path)? {
                 if let Some(file_name) = path
@@ -284,3 +909,48 @@ impl<FS: FileSystem> Card<FS> {
         .to_string())
     }
 }
+
+fn is_patch_file_name(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.eq_ignore_ascii_case("xml"))
+        .unwrap_or(false)
+}
+
+/// Find the names of the standard root directories in the names of the entries of the root directory
+///
+/// A name matching exactly is preferred over a name with a different case.
+fn find_root_directory_names(directory_names: &BTreeSet<String>) -> BTreeMap<CardFolder, String> {
+    let mut folder_names = BTreeMap::new();
+
+    for folder in CardFolder::iter().filter(|folder| folder.parent().is_none()) {
+        if let Some(name) = find_directory_name(directory_names, folder.directory_name()) {
+            folder_names.insert(folder, name.clone());
+        }
+    }
+
+    folder_names
+}
+
+/// Check the standard root directories required are found, see [CardFolder::required]
+fn check_required_directories(folder_names: &BTreeMap<CardFolder, String>) -> Result<(), CardError> {
+    let missing_directories = CardFolder::required()
+        .filter(|folder| !folder_names.contains_key(folder))
+        .map(|folder| folder.directory_name().to_owned())
+        .collect::<Vec<_>>();
+
+    match missing_directories.is_empty() {
+        true => Ok(()),
+        false => Err(CardError::MissingRootDirectories(missing_directories)),
+    }
+}
+
+/// Find a directory name ignoring the case like FAT, a name matching exactly is preferred
+fn find_directory_name<'a>(directory_names: &'a BTreeSet<String>, expected_name: &str) -> Option<&'a String> {
+    directory_names
+        .get(expected_name)
+        .or_else(|| {
+            directory_names
+                .iter()
+                .find(|name| name.eq_ignore_ascii_case(expected_name))
+        })
+}
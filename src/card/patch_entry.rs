@@ -0,0 +1,56 @@
+use std::cmp::Ordering;
+use std::path::PathBuf;
+
+use crate::PatchName;
+
+/// A patch file found on a card
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatchEntry {
+    /// The absolute path of the patch file
+    pub path: PathBuf,
+    /// The name of the patch parsed from the file name without extension
+    pub name: PatchName,
+    /// The size of the file in bytes
+    pub size: u64,
+}
+
+impl PatchEntry {
+    /// Compare two entries like the Deluge browser does.
+    ///
+    /// The standard names come first sorted by number then by suffix,
+    /// the custom names follow sorted alphabetically ignoring the case.
+    pub fn browser_order(&self, other: &Self) -> Ordering {
+        match (&self.name, &other.name) {
+            (
+                PatchName::Standard {
+                    number: left_number,
+                    suffix: left_suffix,
+                    ..
+                },
+                PatchName::Standard {
+                    number: right_number,
+                    suffix: right_suffix,
+                    ..
+                },
+            ) => left_number
+                .cmp(right_number)
+                .then(left_suffix.cmp(right_suffix)),
+            (PatchName::Standard { .. }, PatchName::Custom { .. }) => Ordering::Less,
+            (PatchName::Custom { .. }, PatchName::Standard { .. }) => Ordering::Greater,
+            (
+                PatchName::Custom {
+                    name: left_name,
+                    number: left_number,
+                },
+                PatchName::Custom {
+                    name: right_name,
+                    number: right_number,
+                },
+            ) => left_name
+                .to_lowercase()
+                .cmp(&right_name.to_lowercase())
+                .then(left_number.cmp(right_number))
+                .then_with(|| left_name.cmp(right_name)),
+        }
+    }
+}
@@ -7,6 +7,34 @@ use crate::PatchType;
 
 pub type ParseError = nom::error::Error<String>;
 
+/// Error returned when a custom patch name can't be used as a file name on the card
+#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone)]
+pub enum PatchNameError {
+    #[error("The patch name is empty")]
+    Empty,
+
+    #[error("The patch name has {0} characters, the maximum is {max}", max = PatchName::MAX_LENGTH)]
+    TooLong(usize),
+
+    #[error("The patch name contains the forbidden character '{0}'")]
+    ForbiddenCharacter(char),
+}
+
+/// A change made by [PatchName::sanitize_with_report]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PatchNameFix {
+    /// The spaces at the start or at the end of the name were removed
+    Trimmed,
+    /// The character was removed from the name
+    RemovedCharacter(char),
+    /// The name was converted to uppercase
+    Uppercased,
+    /// The name was truncated, the length before the truncation is specified
+    Truncated(usize),
+    /// Nothing remained of the name so [PatchName::DEFAULT_CUSTOM_NAME] was used
+    ReplacedByDefault,
+}
+
 /// A parsed patch name
 ///
 /// There are 2 types of patch name, standard and custom.
@@ -27,7 +55,7 @@ pub type ParseError = nom::error::Error<String>;
 ///     PatchName::Standard{ patch_type: PatchType::Synth, number: 234, suffix: Some('R') },
 /// )
 /// ```
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PatchName {
     Standard {
         patch_type: PatchType,
@@ -41,6 +69,134 @@ pub enum PatchName {
 }
 
 impl PatchName {
+    /// The maximum count of characters of a custom patch name, including its number.
+    ///
+    /// FAT accepts longer names but the Deluge browser only shows the beginning of long names.
+    pub const MAX_LENGTH: usize = 32;
+
+    /// The name used by [PatchName::sanitize] when nothing remains of the input name
+    pub const DEFAULT_CUSTOM_NAME: &'static str = "UNTITLED";
+
+    /// The characters forbidden by FAT in a file name
+    const FORBIDDEN_CHARACTERS: &'static str = "\\/:*?\"<>|";
+
+    /// Create a custom patch name, the name is converted to uppercase
+    ///
+    /// The spaces at the start and the end are ignored. The name must not be longer than [PatchName::MAX_LENGTH]
+    /// and can contain only printable ASCII characters excepted the ones FAT forbids: `\ / : * ? " < > |`.
+    /// ```
+    /// # use deluge::{PatchName, PatchNameError};
+    /// assert_eq!(
+    ///     PatchName::custom("Acid bass 2"),
+    ///     Ok(PatchName::Custom { name: "ACID BASS".to_string(), number: Some(2) })
+    /// );
+    /// assert_eq!(PatchName::custom("AC/DC"), Err(PatchNameError::ForbiddenCharacter('/')));
+    /// ```
+    pub fn custom(name: &str) -> Result<PatchName, PatchNameError> {
+        let name = name.trim().to_ascii_uppercase();
+
+        Self::check_custom_name(&name)?;
+
+        Ok(Self::parse_custom(&name))
+    }
+
+    /// Create a custom patch name by fixing the input name, see [PatchName::sanitize_with_report]
+    pub fn sanitize(name: &str) -> PatchName {
+        Self::sanitize_with_report(name).0
+    }
+
+    /// Create a custom patch name by fixing the input name, returns the name and the list of changes made
+    ///
+    /// The forbidden characters are removed, the name is converted to uppercase and truncated
+    /// to [PatchName::MAX_LENGTH] characters.
+    /// ```
+    /// # use deluge::{PatchName, PatchNameFix};
+    /// let (name, fixes) = PatchName::sanitize_with_report("what?");
+    ///
+    /// assert_eq!(name.to_string(), "WHAT");
+    /// assert_eq!(fixes, vec![PatchNameFix::RemovedCharacter('?'), PatchNameFix::Uppercased]);
+    /// ```
+    pub fn sanitize_with_report(name: &str) -> (PatchName, Vec<PatchNameFix>) {
+        let mut fixes = Vec::new();
+        let mut sanitized = String::with_capacity(name.len());
+
+        if name.trim() != name {
+            fixes.push(PatchNameFix::Trimmed);
+        }
+
+        for c in name.trim().chars() {
+            match Self::is_allowed(c) {
+                true => sanitized.push(c),
+                false => fixes.push(PatchNameFix::RemovedCharacter(c)),
+            }
+        }
+
+        if sanitized
+            .chars()
+            .any(|c| c.is_ascii_lowercase())
+        {
+            sanitized.make_ascii_uppercase();
+            fixes.push(PatchNameFix::Uppercased);
+        }
+
+        if sanitized.len() > Self::MAX_LENGTH {
+            fixes.push(PatchNameFix::Truncated(sanitized.len()));
+            sanitized.truncate(Self::MAX_LENGTH);
+        }
+
+        // Removing characters can leave spaces at the start or at the end
+        let sanitized = sanitized.trim();
+
+        if sanitized.is_empty() {
+            fixes.push(PatchNameFix::ReplacedByDefault);
+
+            return (Self::parse_custom(Self::DEFAULT_CUSTOM_NAME), fixes);
+        }
+
+        (Self::parse_custom(sanitized), fixes)
+    }
+
+    /// Check the name can be used as a file name on the card
+    ///
+    /// Standard names are always valid, custom names follow the rules of [PatchName::custom]
+    /// excepted they can contain lowercase characters.
+    pub fn validate(&self) -> Result<(), PatchNameError> {
+        match self {
+            PatchName::Standard { .. } => Ok(()),
+            PatchName::Custom { .. } => Self::check_custom_name(&self.to_string()),
+        }
+    }
+
+    fn is_allowed(c: char) -> bool {
+        (c.is_ascii_graphic() || c == ' ') && !Self::FORBIDDEN_CHARACTERS.contains(c)
+    }
+
+    fn check_custom_name(name: &str) -> Result<(), PatchNameError> {
+        if name.trim().is_empty() {
+            return Err(PatchNameError::Empty);
+        }
+
+        if let Some(c) = name
+            .chars()
+            .find(|c| !Self::is_allowed(*c))
+        {
+            return Err(PatchNameError::ForbiddenCharacter(c));
+        }
+
+        if name.len() > Self::MAX_LENGTH {
+            return Err(PatchNameError::TooLong(name.len()));
+        }
+
+        Ok(())
+    }
+
+    fn parse_custom(name: &str) -> PatchName {
+        // The custom parser never fails
+        parser::parse_custom_patch_name(name)
+            .map(|(_, patch_name)| patch_name)
+            .unwrap()
+    }
+
     fn standard_to_string(patch_type: PatchType, number: u16, suffix: Option<char>) -> String {
         let mut buffer = String::with_capacity(7);
 
@@ -161,7 +317,7 @@ mod parser {
         })(input)
     }
 
-    fn parse_custom_patch_name(input: &str) -> IResult<&str, PatchName> {
+    pub(super) fn parse_custom_patch_name(input: &str) -> IResult<&str, PatchName> {
         match input.rfind(' ') {
             Some(index) => {
                 let potential_number = &input[index + 1..];
@@ -303,4 +459,35 @@ mod tests {
 
         assert_eq!(expected, PatchName::from_str(input).unwrap());
     }
+
+    #[test_case("  acid ", "ACID", None ; "trimmed and uppercased")]
+    #[test_case("Bass 12", "BASS", Some(12) ; "with number")]
+    fn custom_valid_test(input: &str, expected_name: &str, expected_number: Option<u16>) {
+        let expected = PatchName::Custom {
+            name: expected_name.to_string(),
+            number: expected_number,
+        };
+
+        assert_eq!(PatchName::custom(input), Ok(expected));
+    }
+
+    #[test_case("   ", PatchNameError::Empty ; "empty")]
+    #[test_case("A:B", PatchNameError::ForbiddenCharacter(':') ; "forbidden character")]
+    #[test_case("CAF\u{c9}", PatchNameError::ForbiddenCharacter('\u{c9}') ; "not ascii")]
+    #[test_case("ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456", PatchNameError::TooLong(33) ; "too long")]
+    fn custom_invalid_test(input: &str, expected: PatchNameError) {
+        assert_eq!(PatchName::custom(input), Err(expected));
+    }
+
+    #[test_case("what?", "WHAT", &[PatchNameFix::RemovedCharacter('?'), PatchNameFix::Uppercased] ; "removed character")]
+    #[test_case(" <> ", "UNTITLED", &[PatchNameFix::Trimmed, PatchNameFix::RemovedCharacter('<'), PatchNameFix::RemovedCharacter('>'), PatchNameFix::ReplacedByDefault] ; "empty")]
+    #[test_case("ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456", "ABCDEFGHIJKLMNOPQRSTUVWXYZ012345", &[PatchNameFix::Truncated(33)] ; "truncated")]
+    #[test_case("KICK 2", "KICK 2", &[] ; "unchanged")]
+    fn sanitize_test(input: &str, expected_name: &str, expected_fixes: &[PatchNameFix]) {
+        let (name, fixes) = PatchName::sanitize_with_report(input);
+
+        assert_eq!(name.to_string(), expected_name);
+        assert_eq!(fixes, expected_fixes);
+        assert_eq!(name.validate(), Ok(()));
+    }
 }
@@ -0,0 +1,183 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::PatchType;
+
+use super::{is_patch_file_name, Card, CardError, FileSystem, PatchName};
+
+/// The files moved by [Card::rename_patch]
+///
+/// The map associates the original path of a file to its new path.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenameReport {
+    /// The patch and its variations renamed
+    pub renamed: BTreeMap<PathBuf, PathBuf>,
+    /// The variations of the patch left with their original name
+    pub variations_left: BTreeSet<PathBuf>,
+}
+
+impl<FS: FileSystem> Card<FS> {
+    /// Rename a patch stored on the card
+    ///
+    /// The variations of a standard patch are the patches with the same name followed by a letter, like "KIT045A"
+    /// for "KIT045". If `rename_variations` is true they are renamed too: the letter is added to the new name,
+    /// separated by a space for a custom name. Otherwise, or if the new name already has a letter, they are
+    /// listed in the report.
+    ///
+    /// Nothing is renamed if a file already uses one of the new names, the error [CardError::FileAlreadyExists] is returned.
+    /// Like FAT, the case is ignored when looking for an existing file, so a patch can be renamed to a name only differing by
+    /// the case. If renaming a file fails, the files already renamed get their original name back before the error is returned.
+    /// ```
+    /// # use std::path::{Path, PathBuf};
+    /// # use deluge::{Card, MemoryFileSystem, PatchName, PatchType};
+    /// let file_system = MemoryFileSystem::new()
+    ///     .with_file("card/KITS/KIT045.XML", "")
+    ///     .with_file("card/KITS/KIT045A.XML", "")
+    ///     .with_dir("card/SAMPLES")
+    ///     .with_dir("card/SYNTHS");
+    /// let card = Card::open(file_system, Path::new("card"))?;
+    /// let report = card.rename_patch(PatchType::Kit, &"KIT045".parse().unwrap(), &PatchName::custom("my drums")?, true)?;
+    ///
+    /// assert_eq!(
+    ///     report.renamed.get(Path::new("card/KITS/KIT045A.XML")),
+    ///     Some(&PathBuf::from("card/KITS/MY DRUMS A.XML"))
+    /// );
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn rename_patch(
+        &self,
+        patch_type: PatchType,
+        from: &PatchName,
+        to: &PatchName,
+        rename_variations: bool,
+    ) -> Result<RenameReport, CardError> {
+        let mut report = RenameReport::default();
+        let mut renames = vec![(self.get_existing_patch_path(patch_type, from)?, to.clone())];
+
+        for (path, suffix) in self.find_patch_variations(patch_type, from)? {
+            match variation_name(to, suffix) {
+                Some(name) if rename_variations => renames.push((path, name)),
+                _ => {
+                    report.variations_left.insert(path);
+                }
+            }
+        }
+
+        let mut moves = Vec::with_capacity(renames.len());
+        let existing_paths = self
+            .file_system
+            .get_directory_entries(&self.get_directory_path(patch_type.get_card_folder()))?;
+
+        // All the new names are checked before renaming anything
+        for (path, name) in renames {
+            name.validate()
+                .map_err(|error| CardError::InvalidPatchName(name.to_string(), error))?;
+
+            let new_path = path.with_file_name(format!("{}.XML", name.to_string()));
+
+            // The file renamed is not a collision, the new name can differ only by the case
+            if let Some(existing_path) = existing_paths
+                .iter()
+                .find(|existing_path| **existing_path != path && is_same_name_ignoring_case(existing_path, &new_path))
+            {
+                return Err(CardError::FileAlreadyExists(existing_path.clone()));
+            }
+
+            moves.push((path, new_path));
+        }
+
+        for (path, new_path) in moves {
+            if let Err(error) = self
+                .file_system
+                .rename(&path, &new_path)
+            {
+                self.restore_renamed_files(&report.renamed);
+
+                return Err(error);
+            }
+
+            report.renamed.insert(path, new_path);
+        }
+
+        Ok(report)
+    }
+
+    /// Give back their original name to the files renamed before a failure
+    ///
+    /// This is best effort: a file that can't be renamed back is left with its new name.
+    fn restore_renamed_files(&self, renamed: &BTreeMap<PathBuf, PathBuf>) {
+        for (path, new_path) in renamed {
+            let _ = self.file_system.rename(new_path, path);
+        }
+    }
+
+    /// Find the variations of a standard patch without suffix and their suffix
+    fn find_patch_variations(&self, patch_type: PatchType, name: &PatchName) -> Result<Vec<(PathBuf, char)>, CardError> {
+        let number = match name {
+            PatchName::Standard {
+                number, suffix: None, ..
+            } => *number,
+            _ => return Ok(Vec::new()),
+        };
+        let mut variations = Vec::new();
+
+        for path in self
+            .file_system
+            .get_directory_entries(&self.get_directory_path(patch_type.get_card_folder()))?
+        {
+            if !is_patch_file_name(&path) || !self.file_system.is_file(&path)? {
+                continue;
+            }
+
+            if let Some(Ok(PatchName::Standard {
+                patch_type: existing_patch_type,
+                number: existing_number,
+                suffix: Some(suffix),
+            })) = path
+                .file_stem()
+                .map(|file_stem| PatchName::from_str(&file_stem.to_string_lossy()))
+            {
+                if existing_patch_type == patch_type && existing_number == number {
+                    variations.push((path, suffix));
+                }
+            }
+        }
+
+        variations.sort();
+
+        Ok(variations)
+    }
+}
+
+/// Check if two paths have the same file name, ignoring the case like FAT
+fn is_same_name_ignoring_case(path: &Path, other: &Path) -> bool {
+    match (path.file_name(), other.file_name()) {
+        (Some(name), Some(other_name)) => name
+            .to_string_lossy()
+            .eq_ignore_ascii_case(&other_name.to_string_lossy()),
+        _ => false,
+    }
+}
+
+/// Get the name of a variation of a patch, None if the patch name already has a suffix
+fn variation_name(name: &PatchName, suffix: char) -> Option<PatchName> {
+    match name {
+        PatchName::Standard {
+            patch_type,
+            number,
+            suffix: None,
+        } => Some(PatchName::Standard {
+            patch_type: *patch_type,
+            number: *number,
+            suffix: Some(suffix),
+        }),
+        PatchName::Standard { .. } => None,
+        PatchName::Custom { .. } => Some(PatchName::Custom {
+            name: format!("{} {}", name.to_string(), suffix),
+            number: None,
+        }),
+    }
+}
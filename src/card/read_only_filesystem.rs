@@ -0,0 +1,281 @@
+use std::{
+    collections::BTreeSet,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::{CardError, FileMetadata, FileSystem};
+
+/// An operation modifying a file system, recorded by a [ReadOnlyFileSystem] instead of being performed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileOperation {
+    CreateDirectory(PathBuf),
+    WriteFile { path: PathBuf, size: usize },
+    CopyFile { from: PathBuf, to: PathBuf },
+    Rename { from: PathBuf, to: PathBuf },
+}
+
+#[derive(Default, Debug)]
+struct State {
+    operations: Vec<FileOperation>,
+    /// The directories that would have been created
+    directories: BTreeSet<PathBuf>,
+    /// The files that would have been written
+    files: BTreeSet<PathBuf>,
+    /// The files that would have been renamed
+    removed_files: BTreeSet<PathBuf>,
+}
+
+/// The operations recorded by a [ReadOnlyFileSystem]
+///
+/// The clones share the same operations so the log can be kept after the file system has been moved into a card.
+#[derive(Clone, Default, Debug)]
+pub struct OperationLog {
+    state: Arc<Mutex<State>>,
+}
+
+impl OperationLog {
+    /// Get the operations, in the order they were requested
+    pub fn operations(&self) -> Vec<FileOperation> {
+        self.lock().operations.clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().operations.is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A file system forwarding the reads to another file system and recording the writes instead of performing them
+///
+/// This is a dry run mode: the operations are checked like the wrapped file system would do, so a card
+/// using this file system succeeds or fails like it would with the wrapped file system.
+/// The directories created and the files written are seen as existing by the following operations and the files
+/// renamed are seen as missing, but the content of a file written can't be read back.
+/// ```
+/// # use std::path::Path;
+/// # use deluge::{Card, FileOperation, MemoryFileSystem, ReadOnlyFileSystem};
+/// let file_system = MemoryFileSystem::new().with_dir("card");
+/// let read_only_file_system = ReadOnlyFileSystem::new(file_system.clone());
+/// let log = read_only_file_system.log();
+///
+/// Card::create(read_only_file_system, Path::new("card"))?;
+///
+/// assert!(file_system.written_paths().is_empty());
+/// assert_eq!(log.operations()[0], FileOperation::CreateDirectory(Path::new("card/KITS").to_path_buf()));
+/// # Ok::<(), deluge::CardError>(())
+/// ```
+pub struct ReadOnlyFileSystem<FS: FileSystem> {
+    file_system: Arc<FS>,
+    log: OperationLog,
+}
+
+impl<FS: FileSystem> ReadOnlyFileSystem<FS> {
+    pub fn new(file_system: FS) -> Self {
+        Self::from_shared(Arc::new(file_system))
+    }
+
+    pub(crate) fn from_shared(file_system: Arc<FS>) -> Self {
+        Self {
+            file_system,
+            log: OperationLog::default(),
+        }
+    }
+
+    /// Get the log of the operations not performed
+    pub fn log(&self) -> OperationLog {
+        self.log.clone()
+    }
+
+    fn is_directory(&self, path: &Path) -> bool {
+        path.as_os_str().is_empty()
+            || self
+                .log
+                .lock()
+                .directories
+                .contains(path)
+            || self.file_system.directory_exists(path)
+    }
+
+    /// Check a file can be written like the wrapped file system would do
+    fn check_file_writable(&self, path: &Path) -> Result<(), CardError> {
+        match path.parent() {
+            Some(parent) if !self.is_directory(parent) => Err(CardError::io(ErrorKind::NotFound, "not found", parent)),
+            _ if self.is_directory(path) => Err(CardError::io(ErrorKind::IsADirectory, "is a directory", path)),
+            _ => Ok(()),
+        }
+    }
+
+    fn record_file(&self, path: &Path, operation: FileOperation) {
+        let mut state = self.log.lock();
+
+        state.files.insert(path.to_path_buf());
+        state.removed_files.remove(path);
+        state.operations.push(operation);
+    }
+}
+
+impl<FS: FileSystem> FileSystem for ReadOnlyFileSystem<FS> {
+    fn get_directory_entries(&self, path: &Path) -> Result<Vec<PathBuf>, CardError> {
+        let state = self.log.lock();
+        let mut entries = match self
+            .file_system
+            .get_directory_entries(path)
+        {
+            Ok(entries) => entries,
+            Err(_) if state.directories.contains(path) => Vec::new(),
+            Err(error) => return Err(error),
+        };
+
+        entries.retain(|entry| !state.removed_files.contains(entry));
+
+        let recorded_entries = state
+            .directories
+            .iter()
+            .chain(state.files.iter())
+            .filter(|entry| entry.parent() == Some(path) && !entries.contains(entry))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        entries.extend(recorded_entries);
+
+        Ok(entries)
+    }
+
+    fn create_directory(&self, path: &Path) -> Result<(), CardError> {
+        if self.file_exists(path) {
+            return Err(CardError::io(ErrorKind::AlreadyExists, "is a file", path));
+        }
+
+        let mut state = self.log.lock();
+
+        for ancestor in path.ancestors() {
+            if !ancestor.as_os_str().is_empty() {
+                state
+                    .directories
+                    .insert(ancestor.to_path_buf());
+            }
+        }
+
+        state
+            .operations
+            .push(FileOperation::CreateDirectory(path.to_path_buf()));
+
+        Ok(())
+    }
+
+    fn directory_exists(&self, path: &Path) -> bool {
+        self.is_directory(path)
+    }
+
+    fn file_exists(&self, path: &Path) -> bool {
+        let state = self.log.lock();
+
+        state.files.contains(path) || (!state.removed_files.contains(path) && self.file_system.file_exists(path))
+    }
+
+    fn is_file(&self, path: &Path) -> Result<bool, CardError> {
+        {
+            let state = self.log.lock();
+
+            if state.files.contains(path) {
+                return Ok(true);
+            }
+
+            if state.directories.contains(path) {
+                return Ok(false);
+            }
+
+            if state.removed_files.contains(path) {
+                return Err(CardError::io(ErrorKind::NotFound, "not found", path));
+            }
+        }
+
+        self.file_system.is_file(path)
+    }
+
+    fn file_size(&self, path: &Path) -> Result<u64, CardError> {
+        self.file_system.file_size(path)
+    }
+
+    fn file_metadata(&self, path: &Path) -> Result<FileMetadata, CardError> {
+        self.file_system.file_metadata(path)
+    }
+
+    fn read_file(&self, path: &Path) -> Result<String, CardError> {
+        self.file_system.read_file(path)
+    }
+
+    fn read_file_bytes(&self, path: &Path) -> Result<Vec<u8>, CardError> {
+        self.file_system.read_file_bytes(path)
+    }
+
+    fn read_file_range(&self, path: &Path, offset: u64, length: usize) -> Result<Vec<u8>, CardError> {
+        self.file_system
+            .read_file_range(path, offset, length)
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> Result<(), CardError> {
+        self.write_file_bytes(path, content.as_bytes())
+    }
+
+    fn write_file_bytes(&self, path: &Path, content: &[u8]) -> Result<(), CardError> {
+        self.check_file_writable(path)?;
+        self.record_file(
+            path,
+            FileOperation::WriteFile {
+                path: path.to_path_buf(),
+                size: content.len(),
+            },
+        );
+
+        Ok(())
+    }
+
+    fn copy_file(&self, from: &Path, to: &Path) -> Result<(), CardError> {
+        if !self.file_exists(from) {
+            return Err(CardError::io(ErrorKind::NotFound, "not found", from));
+        }
+
+        self.check_file_writable(to)?;
+        self.record_file(
+            to,
+            FileOperation::CopyFile {
+                from: from.to_path_buf(),
+                to: to.to_path_buf(),
+            },
+        );
+
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), CardError> {
+        if !self.file_exists(from) {
+            return Err(CardError::io(ErrorKind::NotFound, "not found", from));
+        }
+
+        self.check_file_writable(to)?;
+        self.record_file(
+            to,
+            FileOperation::Rename {
+                from: from.to_path_buf(),
+                to: to.to_path_buf(),
+            },
+        );
+
+        let mut state = self.log.lock();
+
+        state.files.remove(from);
+        state
+            .removed_files
+            .insert(from.to_path_buf());
+
+        Ok(())
+    }
+}
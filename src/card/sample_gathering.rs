@@ -0,0 +1,188 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use super::{Card, CardError, FileSystem};
+use crate::{values::SamplePath, Kit};
+
+/// What to do when a file with the same name already exists in the destination folder, see [Card::gather_kit_samples]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionStrategy {
+    /// Replace the existing file, unless the kit uses it
+    Overwrite,
+    /// Copy the sample under a new name such as "kick_1.wav"
+    Rename,
+    /// Use the existing file if its content is identical to the sample, otherwise copy the sample under a new name
+    ReuseIdentical,
+}
+
+/// The changes made by [Card::gather_kit_samples]
+///
+/// The maps associate the original path of a sample to its new path.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GatherReport {
+    /// The samples copied in the destination folder with their original name
+    pub copied: BTreeMap<SamplePath, SamplePath>,
+    /// The samples copied under a new name because another file with the same name was present
+    pub renamed: BTreeMap<SamplePath, SamplePath>,
+    /// The samples not copied because an identical file was present
+    pub reused: BTreeMap<SamplePath, SamplePath>,
+    /// The samples left in place because they are missing or already in the destination folder
+    pub skipped: BTreeSet<SamplePath>,
+}
+
+impl GatherReport {
+    /// All the path replacements made in the kit
+    pub fn replacements(&self) -> BTreeMap<SamplePath, SamplePath> {
+        self.copied
+            .iter()
+            .chain(self.renamed.iter())
+            .chain(self.reused.iter())
+            .map(|(from, to)| (from.clone(), to.clone()))
+            .collect()
+    }
+}
+
+impl<FS: FileSystem> Card<FS> {
+    /// Copy all the samples used by a kit into a folder of the card and update the kit
+    ///
+    /// The destination is a folder of the card such as "card/SAMPLES/KIT000", it's created if needed.
+    /// The missing samples are left untouched. Two samples with the same file name are never copied on the same file,
+    /// and a sample of the kit already in the destination folder is never overwritten, even with [CollisionStrategy::Overwrite].
+    pub fn gather_kit_samples(
+        &self,
+        kit: &mut Kit,
+        destination: &Path,
+        strategy: CollisionStrategy,
+    ) -> Result<GatherReport, CardError> {
+        let destination = self.sample_path(destination)?;
+        let mut report = GatherReport::default();
+        // The files written by this operation and the samples of the kit already in the destination, they are never overwritten
+        let mut written = BTreeSet::new();
+        let mut sources = Vec::new();
+
+        for sample_path in kit.get_sample_paths() {
+            let source = self.resolve_sample_path(&sample_path)?;
+
+            if let Some(source) = &source {
+                if source.to_path().parent() == Some(destination.to_path()) {
+                    written.insert(source.clone());
+                }
+            }
+
+            sources.push((sample_path, source));
+        }
+
+        self.file_system
+            .create_directory(&self.absolute_path(&destination))?;
+
+        for (sample_path, source) in sources {
+            let source = match source {
+                Some(source) if source.to_path().parent() != Some(destination.to_path()) => source,
+                _ => {
+                    report.skipped.insert(sample_path);
+                    continue;
+                }
+            };
+            let file_name = match source.to_path().file_name() {
+                Some(file_name) => file_name.to_string_lossy().to_string(),
+                None => {
+                    report.skipped.insert(sample_path);
+                    continue;
+                }
+            };
+            let target = SamplePath::new(
+                destination
+                    .to_path()
+                    .join(&file_name)
+                    .to_string_lossy(),
+            )?;
+            let target_exists = self
+                .file_system
+                .file_exists(&self.absolute_path(&target));
+
+            if written.contains(&target) || (target_exists && strategy != CollisionStrategy::Overwrite) {
+                if strategy == CollisionStrategy::ReuseIdentical
+                    && !written.contains(&target)
+                    && self.are_identical(&source, &target)?
+                {
+                    report
+                        .reused
+                        .insert(sample_path, target);
+                    continue;
+                }
+
+                let renamed = self.find_free_sample_path(&destination, &file_name)?;
+
+                self.copy_sample(&source, &renamed)?;
+                written.insert(renamed.clone());
+                report
+                    .renamed
+                    .insert(sample_path, renamed);
+            } else {
+                self.copy_sample(&source, &target)?;
+                written.insert(target.clone());
+                report
+                    .copied
+                    .insert(sample_path, target);
+            }
+        }
+
+        kit.replace_sample_paths(&report.replacements());
+
+        Ok(report)
+    }
+
+    fn copy_sample(&self, from: &SamplePath, to: &SamplePath) -> Result<(), CardError> {
+        self.file_system
+            .copy_file(&self.absolute_path(from), &self.absolute_path(to))
+    }
+
+    fn are_identical(&self, left: &SamplePath, right: &SamplePath) -> Result<bool, CardError> {
+        let left = self.absolute_path(left);
+        let right = self.absolute_path(right);
+        let size = self.file_system.file_size(&left)?;
+
+        if size != self.file_system.file_size(&right)? {
+            return Ok(false);
+        }
+
+        Ok(self
+            .file_system
+            .read_file_range(&left, 0, size as usize)?
+            == self
+                .file_system
+                .read_file_range(&right, 0, size as usize)?)
+    }
+
+    /// Find a name like "kick_1.wav" that is not used in a folder
+    fn find_free_sample_path(&self, folder: &SamplePath, file_name: &str) -> Result<SamplePath, CardError> {
+        let file_name = PathBuf::from(file_name);
+        let stem = file_name
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let mut suffix = 1;
+
+        loop {
+            let candidate = match file_name.extension() {
+                Some(extension) => format!("{}_{}.{}", stem, suffix, extension.to_string_lossy()),
+                None => format!("{}_{}", stem, suffix),
+            };
+            let candidate = SamplePath::new(
+                folder
+                    .to_path()
+                    .join(candidate)
+                    .to_string_lossy(),
+            )?;
+
+            if !self
+                .file_system
+                .file_exists(&self.absolute_path(&candidate))
+            {
+                return Ok(candidate);
+            }
+
+            suffix += 1;
+        }
+    }
+}
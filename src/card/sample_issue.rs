@@ -0,0 +1,27 @@
+use crate::values::SamplePath;
+
+/// A problem found with a sample referenced by a patch
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SampleIssue {
+    /// No file matches the path, even ignoring the case.
+    Missing(SamplePath),
+    /// A file exists but the case of its path is different.
+    ///
+    /// The Deluge is able to load it because FAT is not case sensitive but other tools may not.
+    CaseMismatch {
+        /// The path referenced by the patch
+        path: SamplePath,
+        /// The path of the file found on the card
+        actual_path: SamplePath,
+    },
+}
+
+impl SampleIssue {
+    /// The path referenced by the patch
+    pub fn path(&self) -> &SamplePath {
+        match self {
+            SampleIssue::Missing(path) => path,
+            SampleIssue::CaseMismatch { path, .. } => path,
+        }
+    }
+}
@@ -1,10 +1,16 @@
 use mockall::predicate::eq;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use test_case::test_case;
 
-use crate::{values::SamplePath, PatchType};
+use crate::{values::SamplePath, Kit, PatchName, PatchNameError, PatchType};
 
-use super::{filesystem::MockFileSystem, Card, CardError};
+use super::{
+    filesystem::MockFileSystem, Card, CardError, CardFolder, CardOpenError, CollisionStrategy, FileMetadata, FileOperation,
+    FileSystem, FolderReport, GatherReport, IoErrorInfo, LocalFileSystem, MemoryFileSystem, ReadOnlyFileSystem, RenameReport,
+    SampleIssue,
+};
 
 #[test]
 fn test_check_root_directories_all_correct() {
@@ -40,7 +46,7 @@ fn test_check_root_directories_first_missing() {
         });
 
     assert_eq!(
-        Err(CardError::MissingRootDirectory("KITS".into())),
+        Err(CardError::MissingRootDirectories(vec!["KITS".into()])),
         Card::check_required_directories(fs, Path::new("big pullayo"))
     );
 }
@@ -61,11 +67,81 @@ fn test_check_root_directories_last_missing() {
         });
 
     assert_eq!(
-        Err(CardError::MissingRootDirectory("SYNTHS".into())),
+        Err(CardError::MissingRootDirectories(vec!["SYNTHS".into()])),
         Card::check_required_directories(fs, Path::new("big pullayo"))
     );
 }
 
+#[test]
+fn test_check_root_directories_all_missing_are_reported() {
+    let fs = MemoryFileSystem::new().with_dir("card/SAMPLES");
+    let error = Card::open(fs, Path::new("card")).unwrap_err();
+
+    assert_eq!(error, CardError::MissingRootDirectories(vec!["KITS".into(), "SYNTHS".into()]));
+    assert_eq!(error.to_string(), "Missing root directories: KITS, SYNTHS");
+}
+
+#[test]
+fn test_check_card() {
+    let fs = MemoryFileSystem::new()
+        .with_file("card/Kits/KIT000.XML", "")
+        .with_file("card/Kits/drums/KIT001.XML", "")
+        .with_file("card/SYNTHS/SYNT000.XML", "")
+        .with_dir("card/SONGS");
+    let report = Card::check(fs, Path::new("card")).unwrap();
+
+    assert_eq!(
+        report.folders,
+        vec![
+            FolderReport {
+                folder: CardFolder::Kits,
+                directory_name: Some("Kits".to_string()),
+                patch_count: Some(2),
+            },
+            FolderReport {
+                folder: CardFolder::Samples,
+                directory_name: None,
+                patch_count: None,
+            },
+            FolderReport {
+                folder: CardFolder::Synths,
+                directory_name: Some("SYNTHS".to_string()),
+                patch_count: Some(1),
+            },
+            FolderReport {
+                folder: CardFolder::Songs,
+                directory_name: Some("SONGS".to_string()),
+                patch_count: None,
+            },
+            FolderReport {
+                folder: CardFolder::Record,
+                directory_name: None,
+                patch_count: None,
+            },
+            FolderReport {
+                folder: CardFolder::Resample,
+                directory_name: None,
+                patch_count: None,
+            },
+            FolderReport {
+                folder: CardFolder::Clips,
+                directory_name: None,
+                patch_count: None,
+            },
+        ]
+    );
+    assert_eq!(report.missing_required_folders(), vec![CardFolder::Samples]);
+    assert!(!report.can_be_opened());
+}
+
+#[test]
+fn test_check_card_non_existing_directory() {
+    assert_eq!(
+        Card::check(MemoryFileSystem::new(), Path::new("card")),
+        Err(CardError::DirectoryDoesNotExists(PathBuf::from("card")))
+    );
+}
+
 #[test]
 fn test_open_card_non_existing_directory() {
     let mut fs = MockFileSystem::default();
@@ -118,6 +194,9 @@ fn test_create_card_root_directory_does_not_exists() {
     fs.expect_directory_exists()
         .return_const(false);
 
+    fs.expect_get_directory_entries()
+        .return_const(Ok(Vec::new()));
+
     fs.expect_create_directory()
         .return_const(Ok(()));
 
@@ -137,6 +216,11 @@ fn test_create_card_check_subdirectories() {
         .with(eq(root_directory))
         .return_const(true);
 
+    fs.expect_get_directory_entries()
+        .times(1)
+        .with(eq(root_directory))
+        .return_const(Ok(Vec::new()));
+
     fs.expect_directory_exists()
         .times(1)
         .with(eq(samples_directory))
@@ -298,3 +382,1229 @@ fn test_sample_path(input: &str, expected_result: Result<&str, CardError>) {
 
     assert_eq!(expected_result, result);
 }
+
+#[test]
+fn test_list_patches() {
+    let root_directory = Path::new("root_dir");
+    let mut fs = MockFileSystem::new();
+
+    fs.expect_get_directory_entries()
+        .with(eq(Path::new("root_dir/KITS")))
+        .returning(|path| {
+            Ok(vec![
+                path.join("drums.xml"),
+                path.join("KIT010.XML"),
+                path.join("Acid 2.XML"),
+                path.join("KIT002A.XML"),
+                path.join("notes.txt"),
+                path.join("KIT002.XML"),
+                path.join("FOLDER.XML"),
+                path.join("acid.XML"),
+            ])
+        });
+    fs.expect_is_file()
+        .returning(|path| Ok(path != Path::new("root_dir/KITS/FOLDER.XML")));
+    fs.expect_file_size()
+        .returning(|_| Ok(42));
+
+    let card = create_mocked_card(fs, root_directory);
+    let patches = card
+        .list_patches(PatchType::Kit)
+        .unwrap();
+    let file_names: Vec<String> = patches
+        .iter()
+        .map(|patch| {
+            patch
+                .path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+
+    assert_eq!(
+        file_names,
+        vec![
+            "KIT002.XML",
+            "KIT002A.XML",
+            "KIT010.XML",
+            "acid.XML",
+            "Acid 2.XML",
+            "drums.xml"
+        ]
+    );
+    assert_eq!(
+        patches[0].name,
+        PatchName::Standard {
+            patch_type: PatchType::Kit,
+            number: 2,
+            suffix: None
+        }
+    );
+    assert_eq!(patches[0].size, 42);
+}
+
+#[test]
+fn test_read_kit_lowercase_extension() {
+    let root_directory = Path::new("root_dir");
+    let mut fs = MockFileSystem::new();
+
+    fs.expect_get_directory_entries()
+        .with(eq(Path::new("root_dir/KITS")))
+        .returning(|path| Ok(vec![path.join("KIT001.XML"), path.join("KIT002.xml")]));
+    fs.expect_is_file()
+        .returning(|_| Ok(true));
+    fs.expect_read_file_bytes()
+        .with(eq(Path::new("root_dir/KITS/KIT002.xml")))
+        .returning(|_| Ok(include_bytes!("../data_tests/KITS/KIT002.XML").to_vec()));
+
+    let card = create_mocked_card(fs, root_directory);
+    let name: PatchName = "KIT002".parse().unwrap();
+
+    assert!(card.read_kit(&name).is_ok());
+}
+
+#[test]
+fn test_read_kit_not_found() {
+    let root_directory = Path::new("root_dir");
+    let mut fs = MockFileSystem::new();
+
+    fs.expect_get_directory_entries()
+        .with(eq(Path::new("root_dir/KITS")))
+        .returning(|path| Ok(vec![path.join("KIT001.XML")]));
+    fs.expect_is_file()
+        .returning(|_| Ok(true));
+
+    let card = create_mocked_card(fs, root_directory);
+    let name: PatchName = "KIT002".parse().unwrap();
+
+    assert!(matches!(
+        card.read_kit(&name),
+        Err(CardOpenError::CardError(CardError::PatchNotFound(name))) if name == "KIT002"
+    ));
+}
+
+#[test]
+fn test_write_kit_refuses_to_overwrite() {
+    let root_directory = Path::new("root_dir");
+    let mut fs = MockFileSystem::new();
+
+    fs.expect_get_directory_entries()
+        .with(eq(Path::new("root_dir/KITS")))
+        .returning(|path| Ok(vec![path.join("KIT002.xml")]));
+    fs.expect_is_file()
+        .returning(|_| Ok(true));
+    fs.expect_write_file_bytes().never();
+
+    let card = create_mocked_card(fs, root_directory);
+    let name: PatchName = "KIT002".parse().unwrap();
+
+    assert!(matches!(
+        card.write_kit(&Kit::default(), &name, false),
+        Err(CardOpenError::CardError(CardError::FileAlreadyExists(path))) if path == Path::new("root_dir/KITS/KIT002.xml")
+    ));
+}
+
+#[test_case(&[], "root_dir/KITS/KIT002.XML" ; "new file")]
+#[test_case(&["KIT002.xml"], "root_dir/KITS/KIT002.xml" ; "existing file")]
+fn test_write_kit_overwrite(existing_files: &'static [&'static str], expected_path: &'static str) {
+    let root_directory = Path::new("root_dir");
+    let mut fs = MockFileSystem::new();
+
+    fs.expect_get_directory_entries()
+        .with(eq(Path::new("root_dir/KITS")))
+        .returning(move |path| {
+            Ok(existing_files
+                .iter()
+                .map(|file_name| path.join(file_name))
+                .collect())
+        });
+    fs.expect_is_file()
+        .returning(|_| Ok(true));
+    fs.expect_write_file_bytes()
+        .withf(move |path, _| path == Path::new(expected_path))
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let card = create_mocked_card(fs, root_directory);
+    let name: PatchName = "KIT002".parse().unwrap();
+
+    assert_eq!(
+        card.write_kit(&Kit::default(), &name, true)
+            .unwrap(),
+        Path::new(expected_path)
+    );
+}
+
+fn create_mocked_card_with_samples(mut fs: MockFileSystem) -> Card<MockFileSystem> {
+    fs.expect_get_directory_entries()
+        .with(eq(Path::new("root_dir/SAMPLES")))
+        .returning(|path| Ok(vec![path.join("Kick.wav"), path.join("DRUMS")]));
+    fs.expect_get_directory_entries()
+        .with(eq(Path::new("root_dir/SAMPLES/DRUMS")))
+        .returning(|path| Ok(vec![path.join("HAT.WAV"), path.join("hat.wav")]));
+    fs.expect_get_directory_entries()
+        .with(eq(Path::new("root_dir/KITS")))
+        .returning(|path| Ok(vec![path.join("KIT000.XML")]));
+    fs.expect_is_file()
+        .returning(|path| Ok(path.extension().is_some()));
+
+    create_mocked_card(fs, Path::new("root_dir"))
+}
+
+#[test]
+fn test_find_missing_samples() {
+    let card = create_mocked_card_with_samples(MockFileSystem::new());
+    let sample_paths: BTreeSet<SamplePath> = [
+        "SAMPLES/Kick.wav",
+        "samples/KICK.WAV",
+        "SAMPLES/DRUMS/hat.wav",
+        "SAMPLES/DRUMS/snare.wav",
+        "SAMPLES/OTHER/Kick.wav",
+    ]
+    .iter()
+    .map(|path| SamplePath::new(path).unwrap())
+    .collect();
+
+    assert_eq!(
+        card.find_missing_samples(&sample_paths)
+            .unwrap(),
+        vec![
+            SamplePath::new("SAMPLES/DRUMS/snare.wav").unwrap(),
+            SamplePath::new("SAMPLES/OTHER/Kick.wav").unwrap(),
+        ]
+    );
+}
+
+#[test]
+fn test_validate_patch_samples() {
+    let xml = include_str!("../data_tests/KITS/KIT000.XML");
+    let mut kit = crate::deserialize_kit(xml).unwrap();
+
+    kit.replace_sample_paths(
+        &kit.get_sample_paths()
+            .into_iter()
+            .enumerate()
+            .map(|(index, path)| {
+                let new_path = match index {
+                    0 => "samples/kick.wav",
+                    1 => "SAMPLES/DRUMS/hat.wav",
+                    _ => "SAMPLES/missing.wav",
+                };
+
+                (path, SamplePath::new(new_path).unwrap())
+            })
+            .collect(),
+    );
+
+    let xml = crate::serialize_kit(&kit).unwrap();
+    let mut fs = MockFileSystem::new();
+
+    fs.expect_read_file_bytes()
+        .returning(move |_| Ok(xml.clone().into_bytes()));
+
+    let card = create_mocked_card_with_samples(fs);
+    let name: PatchName = "KIT000".parse().unwrap();
+
+    assert_eq!(
+        card.validate_patch_samples(PatchType::Kit, &name)
+            .unwrap(),
+        vec![
+            SampleIssue::Missing(SamplePath::new("SAMPLES/missing.wav").unwrap()),
+            SampleIssue::CaseMismatch {
+                path: SamplePath::new("samples/kick.wav").unwrap(),
+                actual_path: SamplePath::new("SAMPLES/Kick.wav").unwrap(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_memory_file_system_entries() {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/SAMPLES")
+        .with_file("card/KITS/KIT000.XML", "<kit/>");
+
+    assert!(fs.directory_exists(Path::new("card/KITS")));
+    assert!(fs.file_exists(Path::new("card/KITS/KIT000.XML")));
+    assert!(!fs.file_exists(Path::new("card/KITS")));
+    assert_eq!(fs.is_file(Path::new("card/KITS/KIT000.XML")), Ok(true));
+    assert_eq!(fs.file_size(Path::new("card/KITS/KIT000.XML")), Ok(6));
+    assert_eq!(
+        fs.get_directory_entries(Path::new("card")),
+        Ok(vec![PathBuf::from("card/KITS"), PathBuf::from("card/SAMPLES")])
+    );
+    assert!(fs
+        .get_directory_entries(Path::new("card/SYNTHS"))
+        .is_err());
+    assert!(fs.written_paths().is_empty());
+}
+
+#[test]
+fn test_memory_file_system_metadata() {
+    let fs = MemoryFileSystem::new().with_file("card/KITS/KIT000.XML", "<kit/>");
+
+    assert_eq!(
+        fs.file_metadata(Path::new("card/KITS/KIT000.XML")),
+        Ok(FileMetadata { size: 6, modified: None })
+    );
+    assert!(fs
+        .file_metadata(Path::new("card/KITS/KIT001.XML"))
+        .is_err());
+}
+
+#[test]
+fn test_local_file_system_bytes() {
+    let path = std::env::temp_dir().join(format!("deluge_test_local_file_system_bytes_{}.XML", std::process::id()));
+    let fs = LocalFileSystem;
+    let content = b"<kit name=\"caf\xE9\"/>";
+
+    fs.write_file_bytes(&path, content)
+        .unwrap();
+
+    let metadata = fs.file_metadata(&path).unwrap();
+    let read_content = fs.read_file_bytes(&path).unwrap();
+
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(read_content, content);
+    assert_eq!(metadata.size, content.len() as u64);
+    assert!(metadata.modified.is_some());
+}
+
+#[test]
+fn test_read_kit_is_not_limited_to_utf8() {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/SAMPLES")
+        .with_dir("card/SYNTHS")
+        .with_file(
+            "card/KITS/KIT057.XML",
+            include_bytes!("../data_tests/KITS/KIT057_LATIN1.XML").to_vec(),
+        );
+    let card = Card::open(fs, Path::new("card")).unwrap();
+
+    assert!(card
+        .read_kit(&"KIT057".parse().unwrap())
+        .is_ok());
+}
+
+#[test]
+fn test_memory_file_system_create_card() {
+    let fs = MemoryFileSystem::new().with_dir("card");
+    let card = Card::create(fs.clone(), Path::new("card")).unwrap();
+
+    assert_eq!(
+        fs.written_paths(),
+        vec![
+            PathBuf::from("card/KITS"),
+            PathBuf::from("card/SAMPLES"),
+            PathBuf::from("card/SYNTHS")
+        ]
+    );
+
+    let name: PatchName = "KIT000".parse().unwrap();
+    let path = card
+        .write_kit(&Kit::default(), &name, false)
+        .unwrap();
+
+    assert_eq!(path, Path::new("card/KITS/KIT000.XML"));
+    assert!(fs.file_content(&path).is_some());
+    assert_eq!(card.read_kit(&name).unwrap(), Kit::default());
+}
+
+#[test_case(MemoryFileSystem::new() ; "missing root directory")]
+#[test_case(MemoryFileSystem::new().with_dir("card") ; "empty root directory")]
+#[test_case(MemoryFileSystem::new().with_dir("card/Kits").with_dir("card/SAMPLES") ; "existing directories")]
+#[test_case(MemoryFileSystem::new().with_dir("card").with_file("card/SYNTHS", "") ; "file instead of a directory")]
+fn test_read_only_create_card(fs: MemoryFileSystem) {
+    let read_only_fs = ReadOnlyFileSystem::new(fs.clone());
+    let log = read_only_fs.log();
+    let result = Card::create(read_only_fs, Path::new("card")).map(|_| ());
+    let expected_result = Card::create(fs.clone(), Path::new("card")).map(|_| ());
+    let created_directories = log
+        .operations()
+        .into_iter()
+        .map(|operation| match operation {
+            FileOperation::CreateDirectory(path) => path,
+            operation => panic!("unexpected operation {:?}", operation),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(result, expected_result);
+    assert_eq!(created_directories, fs.written_paths());
+}
+
+#[test]
+fn test_read_only_create_card_performs_no_write() {
+    let fs = MemoryFileSystem::new().with_dir("card");
+    let read_only_fs = ReadOnlyFileSystem::new(fs.clone());
+    let log = read_only_fs.log();
+    let card = Card::create(read_only_fs, Path::new("card")).unwrap();
+
+    assert!(fs.written_paths().is_empty());
+    assert!(!fs.directory_exists(Path::new("card/KITS")));
+    assert!(card
+        .list_patches(PatchType::Kit)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        log.operations(),
+        vec![
+            FileOperation::CreateDirectory(PathBuf::from("card/KITS")),
+            FileOperation::CreateDirectory(PathBuf::from("card/SAMPLES")),
+            FileOperation::CreateDirectory(PathBuf::from("card/SYNTHS")),
+        ]
+    );
+}
+
+#[test]
+fn test_into_read_only() {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/KITS")
+        .with_dir("card/SAMPLES")
+        .with_dir("card/SYNTHS");
+    let card = Card::open(fs.clone(), Path::new("card")).unwrap();
+    let (card, log) = card.into_read_only();
+    let name: PatchName = "KIT000".parse().unwrap();
+    let path = card
+        .write_kit(&Kit::default(), &name, false)
+        .unwrap();
+    let file_system = ReadOnlyFileSystem::new(fs.clone());
+
+    assert_eq!(path, Path::new("card/KITS/KIT000.XML"));
+    assert!(fs.written_paths().is_empty());
+    assert!(matches!(
+        card.write_kit(&Kit::default(), &name, false),
+        Err(CardOpenError::CardError(CardError::FileAlreadyExists(_)))
+    ));
+    assert!(matches!(
+        &log.operations()[..],
+        [FileOperation::WriteFile { path, size }] if path == Path::new("card/KITS/KIT000.XML") && *size > 0
+    ));
+    assert_eq!(
+        file_system.copy_file(Path::new("card/SAMPLES/snare.wav"), Path::new("card/SAMPLES/copy.wav")),
+        Err(CardError::io(ErrorKind::NotFound, "", Path::new("card/SAMPLES/snare.wav")))
+    );
+    assert_eq!(
+        file_system.write_file(Path::new("card/MISSING/file.txt"), ""),
+        Err(CardError::io(ErrorKind::NotFound, "", Path::new("card/MISSING")))
+    );
+    assert!(file_system.log().is_empty());
+}
+
+#[test]
+fn test_local_file_system_missing_root_is_not_found() {
+    let root = Path::new("missing card");
+    let error = LocalFileSystem
+        .get_directory_entries(root)
+        .unwrap_err();
+
+    assert_eq!(error.io_error_kind(), Some(ErrorKind::NotFound));
+    assert!(matches!(
+        &error,
+        CardError::IoError(IoErrorInfo { kind: ErrorKind::NotFound, path: Some(path), message })
+            if path == root && !message.is_empty()
+    ));
+    assert_eq!(
+        MemoryFileSystem::new().is_file(root),
+        Err(CardError::io(ErrorKind::NotFound, "not found", root))
+    );
+}
+
+#[test]
+fn test_io_error_equality_ignores_message() {
+    let path = Path::new("card/KITS");
+
+    assert_eq!(
+        CardError::io(ErrorKind::PermissionDenied, "Permission denied (os error 13)", path),
+        CardError::io(ErrorKind::PermissionDenied, "access denied", path)
+    );
+    assert_ne!(
+        CardError::io(ErrorKind::PermissionDenied, "", path),
+        CardError::io(ErrorKind::NotFound, "", path)
+    );
+    assert_ne!(
+        CardError::io(ErrorKind::NotFound, "", path),
+        CardError::io(ErrorKind::NotFound, "", Path::new("card/SYNTHS"))
+    );
+}
+
+#[test]
+fn test_write_kit_invalid_name() {
+    let fs = MemoryFileSystem::new().with_dir("card");
+    let card = Card::create(fs.clone(), Path::new("card")).unwrap();
+    let name = PatchName::Custom {
+        name: "AC/DC".to_string(),
+        number: None,
+    };
+
+    assert!(matches!(
+        card.write_kit(&Kit::default(), &name, false),
+        Err(CardOpenError::CardError(CardError::InvalidPatchName(
+            _,
+            PatchNameError::ForbiddenCharacter('/')
+        )))
+    ));
+    assert_eq!(fs.written_paths().len(), 3);
+}
+
+#[test_case("KITS", "SYNTHS", "SAMPLES")]
+#[test_case("Kits", "Synths", "Samples")]
+#[test_case("kits", "synths", "samples")]
+fn test_open_card_case_insensitive(kits: &str, synths: &str, samples: &str) {
+    let fs = MemoryFileSystem::new()
+        .with_dir(Path::new("card").join(kits))
+        .with_dir(Path::new("card").join(synths))
+        .with_dir(Path::new("card").join(samples));
+    let card = Card::open(fs, Path::new("card")).unwrap();
+
+    assert_eq!(card.get_directory_path(CardFolder::Kits), Path::new("card").join(kits));
+    assert_eq!(card.get_directory_path(CardFolder::Synths), Path::new("card").join(synths));
+    assert_eq!(card.get_directory_path(CardFolder::Samples), Path::new("card").join(samples));
+}
+
+#[test]
+fn test_create_card_keeps_existing_case() {
+    let fs = MemoryFileSystem::new().with_dir("card/Kits");
+    let card = Card::create(fs.clone(), Path::new("card")).unwrap();
+
+    assert_eq!(
+        fs.written_paths(),
+        vec![PathBuf::from("card/SAMPLES"), PathBuf::from("card/SYNTHS")]
+    );
+    assert_eq!(card.get_directory_path(CardFolder::Kits), Path::new("card/Kits"));
+}
+
+#[test]
+fn test_songs_folder_is_optional() {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/KITS")
+        .with_dir("card/SAMPLES")
+        .with_dir("card/SYNTHS");
+    let card = Card::open(fs.clone(), Path::new("card")).unwrap();
+
+    assert_eq!(card.get_directory_path(CardFolder::Songs), Path::new("card/SONGS"));
+
+    let card = Card::open(fs.with_dir("card/Songs"), Path::new("card")).unwrap();
+
+    assert_eq!(card.get_directory_path(CardFolder::Songs), Path::new("card/Songs"));
+}
+
+#[test]
+fn test_required_folders() {
+    assert_eq!(
+        CardFolder::required().collect::<Vec<_>>(),
+        vec![CardFolder::Kits, CardFolder::Samples, CardFolder::Synths]
+    );
+}
+
+#[test]
+fn test_samples_subfolders_are_optional() {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/KITS")
+        .with_dir("card/Samples")
+        .with_dir("card/SYNTHS");
+    let card = Card::open(fs, Path::new("card")).unwrap();
+
+    assert_eq!(card.get_directory_path(CardFolder::Record), Path::new("card/Samples/RECORD"));
+    assert_eq!(
+        card.get_directory_path(CardFolder::Resample),
+        Path::new("card/Samples/RESAMPLE")
+    );
+    assert_eq!(card.get_directory_path(CardFolder::Clips), Path::new("card/Samples/CLIPS"));
+}
+
+#[test]
+fn test_create_card_with_folders() {
+    let fs = MemoryFileSystem::new().with_dir("card");
+    let card = Card::create_with_folders(
+        fs.clone(),
+        Path::new("card"),
+        &[CardFolder::Clips, CardFolder::Songs, CardFolder::Record],
+    )
+    .unwrap();
+
+    assert_eq!(
+        fs.written_paths(),
+        vec![
+            PathBuf::from("card/KITS"),
+            PathBuf::from("card/SAMPLES"),
+            PathBuf::from("card/SYNTHS"),
+            PathBuf::from("card/SONGS"),
+            PathBuf::from("card/SAMPLES/RECORD"),
+            PathBuf::from("card/SAMPLES/CLIPS"),
+        ]
+    );
+    assert_eq!(card.get_directory_path(CardFolder::Clips), Path::new("card/SAMPLES/CLIPS"));
+}
+
+#[test]
+fn test_create_card_with_folders_keeps_existing_case() {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/Samples/record")
+        .with_dir("card/KITS")
+        .with_dir("card/SYNTHS");
+    let card = Card::create_with_folders(fs.clone(), Path::new("card"), &[CardFolder::Record, CardFolder::Resample]).unwrap();
+
+    assert_eq!(fs.written_paths(), vec![PathBuf::from("card/Samples/RESAMPLE")]);
+    assert_eq!(card.get_directory_path(CardFolder::Record), Path::new("card/Samples/record"));
+    assert_eq!(
+        card.get_directory_path(CardFolder::Resample),
+        Path::new("card/Samples/RESAMPLE")
+    );
+}
+
+#[test]
+fn test_check_card_samples_subfolders() {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/KITS")
+        .with_dir("card/SYNTHS")
+        .with_dir("card/SAMPLES/Resample");
+    let report = Card::check(fs, Path::new("card")).unwrap();
+
+    assert!(report.can_be_opened());
+    assert_eq!(
+        report
+            .folder(CardFolder::Record)
+            .unwrap()
+            .directory_name,
+        None
+    );
+    assert_eq!(
+        report
+            .folder(CardFolder::Resample)
+            .unwrap()
+            .directory_name,
+        Some("Resample".to_string())
+    );
+}
+
+fn create_card_with_subfolders() -> Card<MemoryFileSystem> {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/SAMPLES")
+        .with_dir("card/SYNTHS")
+        .with_file("card/KITS/KIT000.XML", "")
+        .with_file("card/KITS/drums/KIT005.XML", "")
+        .with_file("card/KITS/drums/KIT002.XML", "")
+        .with_file("card/KITS/drums/808/KIT001.XML", "")
+        .with_file("card/KITS/Bass/KIT010.XML", "");
+
+    Card::open(fs, Path::new("card")).unwrap()
+}
+
+#[test_case(None, "KIT001", "card/KITS/KIT001.XML")]
+#[test_case(Some("drums"), "KIT006", "card/KITS/drums/KIT006.XML")]
+#[test_case(Some("drums/808"), "KIT002", "card/KITS/drums/808/KIT002.XML")]
+fn test_get_next_standard_patch_name_in(subdir: Option<&str>, expected_name: &str, expected_path: &str) {
+    let card = create_card_with_subfolders();
+    let subdir = subdir.map(Path::new);
+
+    assert_eq!(
+        card.get_next_standard_patch_name_in(PatchType::Kit, subdir),
+        Ok(expected_name.to_string())
+    );
+    assert_eq!(
+        card.get_next_standard_patch_path_in(PatchType::Kit, subdir),
+        Ok(PathBuf::from(expected_path))
+    );
+}
+
+#[test]
+fn test_get_next_standard_patch_name_in_absolute_subdir() {
+    let card = create_card_with_subfolders();
+
+    assert_eq!(
+        card.get_next_standard_patch_name_in(PatchType::Kit, Some(Path::new("/drums"))),
+        Err(CardError::PathNotRelative(PathBuf::from("/drums")))
+    );
+}
+
+#[test]
+fn test_list_patches_recursive() {
+    let card = create_card_with_subfolders();
+    let paths: Vec<PathBuf> = card
+        .list_patches_recursive(PatchType::Kit)
+        .unwrap()
+        .into_iter()
+        .map(|patch| patch.path)
+        .collect();
+
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("card/KITS/KIT000.XML"),
+            PathBuf::from("card/KITS/Bass/KIT010.XML"),
+            PathBuf::from("card/KITS/drums/KIT002.XML"),
+            PathBuf::from("card/KITS/drums/KIT005.XML"),
+            PathBuf::from("card/KITS/drums/808/KIT001.XML"),
+        ]
+    );
+    assert_eq!(
+        card.list_patches(PatchType::Kit)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test_case(PatchType::Kit, &["KIT001"], "KIT001", Ok("KIT001A") ; "first variation")]
+#[test_case(PatchType::Kit, &["KIT001", "KIT001A", "KIT001C", "KIT002B"], "KIT001", Ok("KIT001B") ; "gap")]
+#[test_case(PatchType::Kit, &["KIT001", "KIT001A", "KIT001B"], "KIT001A", Ok("KIT001C") ; "from variation")]
+#[test_case(PatchType::Synth, &["SYNT003", "SYNT003A"], "SYNT003", Ok("SYNT003B") ; "synth")]
+#[test_case(PatchType::Kit, &[], "Acid", Err(CardError::NotStandardPatchName("Acid".to_string())) ; "custom name")]
+fn test_get_next_patch_variation(
+    patch_type: PatchType,
+    existing_names: &[&str],
+    base: &str,
+    expected_result: Result<&str, CardError>,
+) {
+    let folder = patch_type
+        .get_card_folder()
+        .directory_name();
+    let fs = existing_names.iter().fold(
+        MemoryFileSystem::new()
+            .with_dir("card/KITS")
+            .with_dir("card/SYNTHS")
+            .with_dir("card/SAMPLES"),
+        |fs, name| fs.with_file(format!("card/{}/{}.XML", folder, name), ""),
+    );
+    let card = Card::open(fs, Path::new("card")).unwrap();
+
+    assert_eq!(
+        card.get_next_patch_variation(&base.parse().unwrap()),
+        expected_result.map(|name| name.parse().unwrap())
+    );
+}
+
+#[test]
+fn test_get_next_patch_variation_no_more_letter() {
+    let fs = ('A'..='Z').fold(
+        MemoryFileSystem::new()
+            .with_dir("card/SYNTHS")
+            .with_dir("card/SAMPLES")
+            .with_file("card/KITS/KIT001.XML", ""),
+        |fs, letter| fs.with_file(format!("card/KITS/KIT001{}.XML", letter), ""),
+    );
+    let card = Card::open(fs, Path::new("card")).unwrap();
+
+    assert_eq!(
+        card.get_next_patch_variation(&"KIT001".parse().unwrap()),
+        Err(CardError::NoMorePostfixLetter)
+    );
+}
+
+#[test]
+fn test_scan() {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/SAMPLES")
+        .with_file("card/KITS/KIT030.XML", include_str!("../data_tests/KITS/KIT030.XML"))
+        .with_file("card/KITS/KIT031.XML", "<kit><soundSources>")
+        .with_file("card/SYNTHS/SYNT168A.XML", include_str!("../data_tests/SYNTHS/SYNT168A.XML"))
+        .with_file("card/SYNTHS/README.TXT", "not a patch");
+    let card = Card::open(fs, Path::new("card")).unwrap();
+    let index = card.scan().unwrap();
+
+    assert_eq!(index.kits.keys().collect::<Vec<_>>(), vec!["KIT030"]);
+    assert_eq!(index.synths.keys().collect::<Vec<_>>(), vec!["SYNT168A"]);
+
+    let kit = &index.kits["KIT030"];
+
+    assert_eq!(kit.path, Path::new("card/KITS/KIT030.XML"));
+    assert_eq!(kit.row_count, Some(8));
+    assert_eq!(kit.sample_paths.len(), 8);
+
+    let synth = &index.synths["SYNT168A"];
+
+    assert_eq!(synth.row_count, None);
+    assert_eq!(
+        synth
+            .version_info
+            .firmware_version
+            .as_deref(),
+        Some("3.1.5")
+    );
+    assert!(!synth.sample_paths.is_empty());
+
+    assert_eq!(index.errors.len(), 1);
+    assert_eq!(index.errors[0].path, Path::new("card/KITS/KIT031.XML"));
+    assert_eq!(index.errors[0].patch_type, PatchType::Kit);
+    assert!(matches!(index.errors[0].error, CardOpenError::ReadError(_)));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_scan_parallel() {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/SAMPLES")
+        .with_file("card/KITS/KIT030.XML", include_str!("../data_tests/KITS/KIT030.XML"))
+        .with_file("card/KITS/KIT031.XML", "<kit><soundSources>")
+        .with_file("card/SYNTHS/SYNT168A.XML", include_str!("../data_tests/SYNTHS/SYNT168A.XML"));
+    let card = Card::open(fs, Path::new("card")).unwrap();
+    let index = card.scan_parallel().unwrap();
+    let sequential_index = card.scan().unwrap();
+
+    assert_eq!(index.kits, sequential_index.kits);
+    assert_eq!(index.synths, sequential_index.synths);
+    assert_eq!(index.errors.len(), 1);
+    assert_eq!(index.errors[0].path, Path::new("card/KITS/KIT031.XML"));
+}
+
+#[cfg(feature = "wav")]
+#[test]
+fn test_new_sample_from_file() {
+    use crate::{Sound, WavError};
+
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/KITS")
+        .with_dir("card/SYNTHS")
+        .with_file(
+            "card/SAMPLES/KICK.WAV",
+            include_bytes!("../data_tests/WAV/mono_16bit_44100.wav").as_slice(),
+        );
+    let card = Card::open(fs, Path::new("card")).unwrap();
+    let sound = Sound::new_sample_from_file(&card, Path::new("card/SAMPLES/KICK.WAV")).unwrap();
+
+    assert_eq!(
+        sound,
+        Sound::new_sample(SamplePath::new("SAMPLES/KICK.WAV").unwrap(), 0u64.into(), 100u64.into())
+    );
+
+    let outside = PathBuf::from("elsewhere/KICK.WAV");
+
+    assert_eq!(
+        Sound::new_sample_from_file(&card, &outside),
+        Err(WavError::CardError(CardError::FileNotInCard(outside)))
+    );
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_open_async() {
+    use super::{AsyncFileSystem, TokioFileSystem};
+
+    let root = std::env::temp_dir().join(format!("deluge_test_open_async_{}", std::process::id()));
+    let fs = TokioFileSystem;
+
+    for directory in ["KITS", "SYNTHS"] {
+        AsyncFileSystem::create_directory(&fs, &root.join(directory))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        Card::open_async(TokioFileSystem, &root)
+            .await
+            .err(),
+        Some(CardError::MissingRootDirectories(vec!["SAMPLES".to_string()]))
+    );
+
+    AsyncFileSystem::create_directory(&fs, &root.join("samples"))
+        .await
+        .unwrap();
+
+    let card = Card::open_async(TokioFileSystem, &root)
+        .await
+        .unwrap();
+
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(card.get_directory_path(CardFolder::Samples), root.join("samples"));
+    assert_eq!(card.get_directory_path(CardFolder::Kits), root.join("KITS"));
+}
+
+fn gather_kit_samples(strategy: CollisionStrategy, existing_snare: &str) -> (GatherReport, Kit, MemoryFileSystem) {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/KITS")
+        .with_dir("card/SYNTHS")
+        .with_file("card/SAMPLES/A/kick.wav", "kick A")
+        .with_file("card/SAMPLES/B/kick.wav", "kick B")
+        .with_file("card/SAMPLES/snare.wav", "snare")
+        .with_file("card/SAMPLES/KIT000/snare.wav", existing_snare);
+    let card = Card::open(fs.clone(), Path::new("card")).unwrap();
+    let mut kit = Kit::new(Vec::new());
+
+    for path in [
+        "SAMPLES/A/kick.wav",
+        "SAMPLES/B/kick.wav",
+        "SAMPLES/snare.wav",
+        "SAMPLES/missing.wav",
+    ] {
+        kit.add_sound_row(crate::Sound::new_sample(
+            SamplePath::new(path).unwrap(),
+            0u64.into(),
+            999u64.into(),
+        ));
+    }
+
+    let report = card
+        .gather_kit_samples(&mut kit, Path::new("card/SAMPLES/KIT000"), strategy)
+        .unwrap();
+
+    (report, kit, fs)
+}
+
+fn sample_paths<const N: usize>(paths: [(&str, &str); N]) -> BTreeMap<SamplePath, SamplePath> {
+    paths
+        .into_iter()
+        .map(|(from, to)| (SamplePath::new(from).unwrap(), SamplePath::new(to).unwrap()))
+        .collect()
+}
+
+#[test]
+fn test_gather_kit_samples_rename() {
+    let (report, kit, fs) = gather_kit_samples(CollisionStrategy::Rename, "snare");
+
+    assert_eq!(
+        report.copied,
+        sample_paths([("SAMPLES/A/kick.wav", "SAMPLES/KIT000/kick.wav")])
+    );
+    assert_eq!(
+        report.renamed,
+        sample_paths([
+            ("SAMPLES/B/kick.wav", "SAMPLES/KIT000/kick_1.wav"),
+            ("SAMPLES/snare.wav", "SAMPLES/KIT000/snare_1.wav")
+        ])
+    );
+    assert!(report.reused.is_empty());
+    assert_eq!(
+        report.skipped,
+        BTreeSet::from([SamplePath::new("SAMPLES/missing.wav").unwrap()])
+    );
+    assert_eq!(fs.file_content("card/SAMPLES/KIT000/kick_1.wav"), Some(b"kick B".to_vec()));
+    assert_eq!(
+        kit.get_sample_paths(),
+        BTreeSet::from_iter(
+            [
+                "SAMPLES/KIT000/kick.wav",
+                "SAMPLES/KIT000/kick_1.wav",
+                "SAMPLES/KIT000/snare_1.wav",
+                "SAMPLES/missing.wav"
+            ]
+            .map(|path| SamplePath::new(path).unwrap())
+        )
+    );
+}
+
+#[test]
+fn test_gather_kit_samples_overwrite() {
+    let (report, _, fs) = gather_kit_samples(CollisionStrategy::Overwrite, "old snare");
+
+    assert_eq!(
+        report.copied,
+        sample_paths([
+            ("SAMPLES/A/kick.wav", "SAMPLES/KIT000/kick.wav"),
+            ("SAMPLES/snare.wav", "SAMPLES/KIT000/snare.wav")
+        ])
+    );
+    // The kick of the folder A copied by the same operation is never overwritten
+    assert_eq!(
+        report.renamed,
+        sample_paths([("SAMPLES/B/kick.wav", "SAMPLES/KIT000/kick_1.wav")])
+    );
+    assert_eq!(fs.file_content("card/SAMPLES/KIT000/snare.wav"), Some(b"snare".to_vec()));
+    assert_eq!(fs.file_content("card/SAMPLES/KIT000/kick.wav"), Some(b"kick A".to_vec()));
+}
+
+#[test]
+fn test_gather_kit_samples_overwrite_keeps_the_samples_of_the_kit() {
+    let fs = MemoryFileSystem::new()
+        .with_dir("card/KITS")
+        .with_dir("card/SYNTHS")
+        .with_file("card/SAMPLES/KIT000/snare.wav", "snare KIT000")
+        .with_file("card/SAMPLES/DRUMS/snare.wav", "snare DRUMS");
+    let card = Card::open(fs.clone(), Path::new("card")).unwrap();
+    let mut kit = Kit::new(Vec::new());
+
+    for path in ["SAMPLES/KIT000/snare.wav", "SAMPLES/DRUMS/snare.wav"] {
+        kit.add_sound_row(crate::Sound::new_sample(
+            SamplePath::new(path).unwrap(),
+            0u64.into(),
+            999u64.into(),
+        ));
+    }
+
+    let report = card
+        .gather_kit_samples(&mut kit, Path::new("card/SAMPLES/KIT000"), CollisionStrategy::Overwrite)
+        .unwrap();
+
+    assert!(report.copied.is_empty());
+    assert_eq!(
+        report.renamed,
+        sample_paths([("SAMPLES/DRUMS/snare.wav", "SAMPLES/KIT000/snare_1.wav")])
+    );
+    assert_eq!(
+        report.skipped,
+        BTreeSet::from([SamplePath::new("SAMPLES/KIT000/snare.wav").unwrap()])
+    );
+    assert_eq!(
+        fs.file_content("card/SAMPLES/KIT000/snare.wav"),
+        Some(b"snare KIT000".to_vec())
+    );
+    assert_eq!(
+        fs.file_content("card/SAMPLES/KIT000/snare_1.wav"),
+        Some(b"snare DRUMS".to_vec())
+    );
+    assert_eq!(
+        kit.get_sample_paths(),
+        BTreeSet::from_iter(
+            ["SAMPLES/KIT000/snare.wav", "SAMPLES/KIT000/snare_1.wav"].map(|path| SamplePath::new(path).unwrap())
+        )
+    );
+}
+
+#[test_case("snare", true ; "identical")]
+#[test_case("old snare", false ; "different")]
+fn test_gather_kit_samples_reuse_identical(existing_snare: &str, reused: bool) {
+    let (report, _, fs) = gather_kit_samples(CollisionStrategy::ReuseIdentical, existing_snare);
+
+    assert_eq!(
+        report
+            .reused
+            .contains_key(&SamplePath::new("SAMPLES/snare.wav").unwrap()),
+        reused
+    );
+    assert_eq!(fs.file_exists(Path::new("card/SAMPLES/KIT000/snare_1.wav")), !reused);
+    assert_eq!(
+        fs.file_content("card/SAMPLES/KIT000/snare.wav"),
+        Some(existing_snare.as_bytes().to_vec())
+    );
+}
+
+fn create_card_with_variations() -> (Card<MemoryFileSystem>, MemoryFileSystem) {
+    let fs = MemoryFileSystem::new()
+        .with_file("card/KITS/KIT045.XML", "kit")
+        .with_file("card/KITS/KIT045A.XML", "a")
+        .with_file("card/KITS/KIT045B.xml", "b")
+        .with_file("card/KITS/KIT046A.XML", "other")
+        .with_dir("card/SAMPLES")
+        .with_dir("card/SYNTHS");
+
+    (Card::open(fs.clone(), Path::new("card")).unwrap(), fs)
+}
+
+#[test_case("MY DRUMS", &["card/KITS/MY DRUMS.XML", "card/KITS/MY DRUMS A.XML", "card/KITS/MY DRUMS B.XML"] ; "custom name")]
+#[test_case("KIT100", &["card/KITS/KIT100.XML", "card/KITS/KIT100A.XML", "card/KITS/KIT100B.XML"] ; "standard name")]
+fn test_rename_patch_with_variations(new_name: &str, expected_paths: &[&str]) {
+    let (card, fs) = create_card_with_variations();
+    let report = card
+        .rename_patch(PatchType::Kit, &"KIT045".parse().unwrap(), &new_name.parse().unwrap(), true)
+        .unwrap();
+    let original_paths = ["card/KITS/KIT045.XML", "card/KITS/KIT045A.XML", "card/KITS/KIT045B.xml"];
+
+    assert_eq!(
+        report,
+        RenameReport {
+            renamed: original_paths
+                .iter()
+                .zip(expected_paths)
+                .map(|(from, to)| (PathBuf::from(from), PathBuf::from(to)))
+                .collect(),
+            variations_left: BTreeSet::new(),
+        }
+    );
+    assert_eq!(fs.file_content(expected_paths[1]), Some(b"a".to_vec()));
+    assert!(original_paths
+        .iter()
+        .all(|path| !fs.file_exists(Path::new(path))));
+    assert!(fs.file_exists(Path::new("card/KITS/KIT046A.XML")));
+}
+
+#[test]
+fn test_rename_patch_without_variations() {
+    let (card, fs) = create_card_with_variations();
+    let report = card
+        .rename_patch(
+            PatchType::Kit,
+            &"KIT045".parse().unwrap(),
+            &"MY DRUMS".parse().unwrap(),
+            false,
+        )
+        .unwrap();
+
+    assert_eq!(
+        report.renamed,
+        BTreeMap::from([(PathBuf::from("card/KITS/KIT045.XML"), PathBuf::from("card/KITS/MY DRUMS.XML"))])
+    );
+    assert_eq!(
+        report.variations_left,
+        BTreeSet::from([PathBuf::from("card/KITS/KIT045A.XML"), PathBuf::from("card/KITS/KIT045B.xml")])
+    );
+    assert!(fs.file_exists(Path::new("card/KITS/KIT045A.XML")));
+}
+
+#[test]
+fn test_rename_patch_name_collision() {
+    let (card, fs) = create_card_with_variations();
+    let fs = fs.with_file("card/KITS/MY DRUMS B.XML", "");
+
+    assert_eq!(
+        card.rename_patch(PatchType::Kit, &"KIT045".parse().unwrap(), &"MY DRUMS".parse().unwrap(), true),
+        Err(CardError::FileAlreadyExists(PathBuf::from("card/KITS/MY DRUMS B.XML")))
+    );
+    assert!(fs.written_paths().is_empty());
+    assert!(fs.file_exists(Path::new("card/KITS/KIT045.XML")));
+}
+
+#[test]
+fn test_rename_patch_not_found() {
+    let (card, _) = create_card_with_variations();
+
+    assert_eq!(
+        card.rename_patch(PatchType::Kit, &"KIT047".parse().unwrap(), &"MY DRUMS".parse().unwrap(), true),
+        Err(CardError::PatchNotFound("KIT047".to_string()))
+    );
+}
+
+#[test]
+fn test_rename_patch_case_variant() {
+    let fs = MemoryFileSystem::new()
+        .with_file("card/KITS/My Drums.XML", "kit")
+        .with_dir("card/SAMPLES")
+        .with_dir("card/SYNTHS");
+    let card = Card::open(fs.clone(), Path::new("card")).unwrap();
+
+    card.rename_patch(
+        PatchType::Kit,
+        &"My Drums".parse().unwrap(),
+        &"MY DRUMS".parse().unwrap(),
+        true,
+    )
+    .unwrap();
+
+    assert_eq!(fs.file_content("card/KITS/MY DRUMS.XML"), Some(b"kit".to_vec()));
+    assert!(!fs.file_exists(Path::new("card/KITS/My Drums.XML")));
+}
+
+#[test]
+fn test_rename_patch_collision_ignores_case() {
+    let (card, fs) = create_card_with_variations();
+    let fs = fs.with_file("card/KITS/my drums a.xml", "");
+
+    assert_eq!(
+        card.rename_patch(PatchType::Kit, &"KIT045".parse().unwrap(), &"MY DRUMS".parse().unwrap(), true),
+        Err(CardError::FileAlreadyExists(PathBuf::from("card/KITS/my drums a.xml")))
+    );
+    assert!(fs.file_exists(Path::new("card/KITS/KIT045.XML")));
+}
+
+#[test]
+fn test_rename_patch_failure_restores_names() {
+    let root_directory = Path::new("root_dir");
+    let mut fs = MockFileSystem::new();
+
+    fs.expect_get_directory_entries()
+        .with(eq(Path::new("root_dir/KITS")))
+        .returning(|path| Ok(vec![path.join("KIT045.XML"), path.join("KIT045A.XML")]));
+    fs.expect_is_file()
+        .returning(|_| Ok(true));
+    fs.expect_rename()
+        .with(
+            eq(Path::new("root_dir/KITS/KIT045.XML")),
+            eq(Path::new("root_dir/KITS/KIT100.XML")),
+        )
+        .times(1)
+        .returning(|_, _| Ok(()));
+    fs.expect_rename()
+        .with(
+            eq(Path::new("root_dir/KITS/KIT045A.XML")),
+            eq(Path::new("root_dir/KITS/KIT100A.XML")),
+        )
+        .times(1)
+        .returning(|from, _| Err(CardError::io(ErrorKind::PermissionDenied, "denied", from)));
+    fs.expect_rename()
+        .with(
+            eq(Path::new("root_dir/KITS/KIT100.XML")),
+            eq(Path::new("root_dir/KITS/KIT045.XML")),
+        )
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let card = create_mocked_card(fs, root_directory);
+
+    assert_eq!(
+        card.rename_patch(PatchType::Kit, &"KIT045".parse().unwrap(), &"KIT100".parse().unwrap(), true)
+            .unwrap_err()
+            .io_error_kind(),
+        Some(ErrorKind::PermissionDenied)
+    );
+}
+
+/// A file system implementing only the required methods of [FileSystem]
+struct RequiredMethodsFileSystem(MemoryFileSystem);
+
+impl FileSystem for RequiredMethodsFileSystem {
+    fn get_directory_entries(&self, path: &Path) -> Result<Vec<PathBuf>, CardError> {
+        self.0.get_directory_entries(path)
+    }
+
+    fn create_directory(&self, path: &Path) -> Result<(), CardError> {
+        self.0.create_directory(path)
+    }
+
+    fn directory_exists(&self, path: &Path) -> bool {
+        self.0.directory_exists(path)
+    }
+
+    fn file_exists(&self, path: &Path) -> bool {
+        self.0.file_exists(path)
+    }
+
+    fn is_file(&self, path: &Path) -> Result<bool, CardError> {
+        self.0.is_file(path)
+    }
+
+    fn file_size(&self, path: &Path) -> Result<u64, CardError> {
+        self.0.file_size(path)
+    }
+
+    fn read_file(&self, path: &Path) -> Result<String, CardError> {
+        self.0.read_file(path)
+    }
+
+    fn read_file_range(&self, path: &Path, offset: u64, length: usize) -> Result<Vec<u8>, CardError> {
+        self.0
+            .read_file_range(path, offset, length)
+    }
+
+    fn write_file(&self, path: &Path, content: &str) -> Result<(), CardError> {
+        self.0.write_file(path, content)
+    }
+
+    fn copy_file(&self, from: &Path, to: &Path) -> Result<(), CardError> {
+        self.0.copy_file(from, to)
+    }
+}
+
+#[test]
+fn test_rename_patch_unsupported() {
+    let (_, fs) = create_card_with_variations();
+    let card = Card::open(RequiredMethodsFileSystem(fs.clone()), Path::new("card")).unwrap();
+
+    assert_eq!(
+        card.rename_patch(PatchType::Kit, &"KIT045".parse().unwrap(), &"KIT100".parse().unwrap(), true)
+            .unwrap_err()
+            .io_error_kind(),
+        Some(ErrorKind::Unsupported)
+    );
+    assert!(fs.file_exists(Path::new("card/KITS/KIT045.XML")));
+}
+
+#[test]
+fn test_rename_patch_read_only() {
+    let (card, fs) = create_card_with_variations();
+    let (card, log) = card.into_read_only();
+
+    card.rename_patch(PatchType::Kit, &"KIT045A".parse().unwrap(), &"KIT050".parse().unwrap(), true)
+        .unwrap();
+
+    assert_eq!(
+        log.operations(),
+        vec![FileOperation::Rename {
+            from: PathBuf::from("card/KITS/KIT045A.XML"),
+            to: PathBuf::from("card/KITS/KIT050.XML"),
+        }]
+    );
+    assert_eq!(card.find_patch_path(PatchType::Kit, &"KIT045A".parse().unwrap()), Ok(None));
+    assert!(fs.file_exists(Path::new("card/KITS/KIT045A.XML")));
+}
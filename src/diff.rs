@@ -0,0 +1,880 @@
+//! Compare patches field by field
+//!
+//! The differences are reported as a list of [FieldChange], each one identified by a dotted path
+//! such as "generator.osc1.sample.ranges[2].file_path" or "rows[3].sound.volume".
+//!
+//! The collections are compared this way:
+//! - the rows of a kit, the sample ranges and the mod knobs are compared index by index
+//! - the patch cables are matched by their pair (source, destination), the path looks like "cables[velocity->volume].amount"
+//!
+//! An entry that exists only in the old patch has no new value, an entry that exists only in the new patch has no old value.
+use crate::{
+    values::{Int8, Uint8},
+    Arpeggiator, Chorus, CvGateRow, Delay, Distorsion, Envelope, Equalizer, Flanger, FmCarrier, FmModulator, FmSynth, Hpf, Kit,
+    Lfo1, Lfo2, Lpf, Metadata, MidiRow, ModKnob, ModulationFx, PatchCable, Phaser, RingModSynth, RowKit, Sample, SampleOneZone,
+    SampleOscillator, SampleRange, SampleZone, Sidechain, Sound, SoundRow, SubtractiveOscillator, SubtractiveSynth, SynthEngine,
+    Unison, WaveformOscillator,
+};
+
+/// A field that differs between two patches
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    /// The dotted path of the field, for example "envelope1.attack"
+    pub path: String,
+    /// The value in the old patch, None if the entry was added
+    pub old: Option<String>,
+    /// The value in the new patch, None if the entry was removed
+    pub new: Option<String>,
+}
+
+impl FieldChange {
+    pub fn new(path: impl Into<String>, old: Option<String>, new: Option<String>) -> Self {
+        Self {
+            path: path.into(),
+            old,
+            new,
+        }
+    }
+
+    /// Returns true if the entry only exists in the new patch.
+    pub fn is_added(&self) -> bool {
+        self.old.is_none() && self.new.is_some()
+    }
+
+    /// Returns true if the entry only exists in the old patch.
+    pub fn is_removed(&self) -> bool {
+        self.old.is_some() && self.new.is_none()
+    }
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, "{}: {} -> {}", self.path, old, new),
+            (None, Some(new)) => write!(f, "{}: added {}", self.path, new),
+            (Some(old), None) => write!(f, "{}: removed {}", self.path, old),
+            (None, None) => write!(f, "{}", self.path),
+        }
+    }
+}
+
+/// Compare two sounds
+pub fn diff_sounds(old: &Sound, new: &Sound) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    old.diff(new, "", &mut changes);
+
+    changes
+}
+
+/// Compare two kits
+pub fn diff_kits(old: &Kit, new: &Kit) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    old.diff(new, "", &mut changes);
+
+    changes
+}
+
+/// Select the differences ignored by [Sound::sonically_equals_with] and [Kit::sonically_equals_with]
+///
+/// The differences that don't change the audible result are ignored by default.
+/// The firmware version is never compared because it's not stored in the patches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PatchCompareOptions {
+    /// Ignore the names of the rows of a kit
+    pub ignore_row_names: bool,
+    /// Ignore the row of a kit selected in the user interface
+    pub ignore_selected_row: bool,
+    /// Ignore the parameters assigned to the mod knobs
+    pub ignore_mod_knobs: bool,
+    /// Ignore the [Metadata] of a kit, the device doesn't use it
+    pub ignore_metadata: bool,
+}
+
+impl Default for PatchCompareOptions {
+    fn default() -> Self {
+        Self {
+            ignore_row_names: true,
+            ignore_selected_row: true,
+            ignore_mod_knobs: true,
+            ignore_metadata: true,
+        }
+    }
+}
+
+impl PatchCompareOptions {
+    /// Compare everything, like the operator ==
+    pub fn strict() -> Self {
+        Self {
+            ignore_row_names: false,
+            ignore_selected_row: false,
+            ignore_mod_knobs: false,
+            ignore_metadata: false,
+        }
+    }
+
+    fn is_ignored(&self, change: &FieldChange) -> bool {
+        let fields = change
+            .path
+            .split('.')
+            .collect::<Vec<&str>>();
+
+        match fields.as_slice() {
+            ["selected_row_index"] => self.ignore_selected_row,
+            ["metadata", ..] => self.ignore_metadata,
+            [row, "name"] if row.starts_with("rows[") => self.ignore_row_names,
+            _ => {
+                self.ignore_mod_knobs
+                    && fields
+                        .iter()
+                        .any(|field| field.starts_with("mod_knobs"))
+            }
+        }
+    }
+
+    /// Returns true if all the changes are ignored
+    pub(crate) fn ignores_all(&self, changes: &[FieldChange]) -> bool {
+        changes
+            .iter()
+            .all(|change| self.is_ignored(change))
+    }
+}
+
+trait Diff {
+    /// Push the differences between self and other in changes
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>);
+
+    /// The text used when the value is added or removed
+    fn label(&self) -> String;
+}
+
+fn join(path: &str, name: &str) -> String {
+    match path.is_empty() {
+        true => name.to_string(),
+        false => format!("{}.{}", path, name),
+    }
+}
+
+fn push_change<T: Diff>(old: &T, new: &T, path: &str, changes: &mut Vec<FieldChange>) {
+    changes.push(FieldChange::new(path, Some(old.label()), Some(new.label())));
+}
+
+/// Implements Diff for types that can be compared and displayed directly
+macro_rules! diff_display {
+    ($($name:ty),*) => {
+        $(
+            impl Diff for $name {
+                fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+                    if self != other {
+                        push_change(self, other, path, changes);
+                    }
+                }
+
+                fn label(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+/// Implements Diff for structures by comparing each field
+macro_rules! diff_struct {
+    ($name:ident { $($field:ident),* $(,)? }) => {
+        impl Diff for $name {
+            fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+                $(self.$field.diff(&other.$field, &join(path, stringify!($field)), changes);)*
+            }
+
+            fn label(&self) -> String {
+                stringify!($name).to_string()
+            }
+        }
+    };
+}
+
+diff_display!(
+    u8,
+    u32,
+    String,
+    crate::HexU50,
+    crate::DecU50,
+    crate::ClippingAmount,
+    crate::UnisonDetune,
+    crate::MidiNote,
+    crate::Pan,
+    crate::OnOff,
+    crate::RetrigPhase,
+    crate::SamplePath,
+    crate::SamplePosition,
+    crate::AttackSidechain,
+    crate::ReleaseSidechain,
+    crate::PatchSource,
+    crate::PatchDestination,
+    crate::Polyphony,
+    crate::VoicePriority,
+    crate::OscType,
+    crate::LfoShape,
+    crate::SamplePlayMode,
+    crate::PitchSpeed,
+    crate::SyncLevel,
+    crate::LpfMode,
+    crate::ArpeggiatorMode,
+    crate::FilterType
+);
+
+impl<const MIN: i8, const MAX: i8, const DEFAULT: i8> Diff for Int8<MIN, MAX, DEFAULT> {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        if self != other {
+            push_change(self, other, path, changes);
+        }
+    }
+
+    fn label(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl<const MIN: u8, const MAX: u8, const DEFAULT: u8> Diff for Uint8<MIN, MAX, DEFAULT> {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        if self != other {
+            push_change(self, other, path, changes);
+        }
+    }
+
+    fn label(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl<T: Diff> Diff for Option<T> {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        match (self, other) {
+            (Some(old), Some(new)) => old.diff(new, path, changes),
+            (None, None) => {}
+            _ => changes.push(FieldChange::new(
+                path,
+                self.as_ref().map(Diff::label),
+                other.as_ref().map(Diff::label),
+            )),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Some(value) => value.label(),
+            None => String::from("None"),
+        }
+    }
+}
+
+impl<T: Diff> Diff for Box<T> {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        self.as_ref()
+            .diff(other.as_ref(), path, changes)
+    }
+
+    fn label(&self) -> String {
+        self.as_ref().label()
+    }
+}
+
+/// Compare index by index, the extra entries are reported as added or removed.
+impl<T: Diff> Diff for Vec<T> {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        for index in 0..self.len().max(other.len()) {
+            let item_path = format!("{}[{}]", path, index);
+
+            match (self.get(index), other.get(index)) {
+                (Some(old), Some(new)) => old.diff(new, &item_path, changes),
+                (old, new) => changes.push(FieldChange::new(item_path, old.map(Diff::label), new.map(Diff::label))),
+            }
+        }
+    }
+
+    fn label(&self) -> String {
+        format!("{} entries", self.len())
+    }
+}
+
+impl Diff for Sound {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        self.generator
+            .diff(&other.generator, &join(path, "generator"), changes);
+        self.polyphonic
+            .diff(&other.polyphonic, &join(path, "polyphonic"), changes);
+        self.max_voices
+            .diff(&other.max_voices, &join(path, "max_voices"), changes);
+        self.voice_priority
+            .diff(&other.voice_priority, &join(path, "voice_priority"), changes);
+        self.volume
+            .diff(&other.volume, &join(path, "volume"), changes);
+        self.pan
+            .diff(&other.pan, &join(path, "pan"), changes);
+        self.portamento
+            .diff(&other.portamento, &join(path, "portamento"), changes);
+        self.reverb_amount
+            .diff(&other.reverb_amount, &join(path, "reverb_amount"), changes);
+        self.stutter_rate
+            .diff(&other.stutter_rate, &join(path, "stutter_rate"), changes);
+        self.sidechain_send
+            .diff(&other.sidechain_send, &join(path, "sidechain_send"), changes);
+        self.envelope1
+            .diff(&other.envelope1, &join(path, "envelope1"), changes);
+        self.envelope2
+            .diff(&other.envelope2, &join(path, "envelope2"), changes);
+        self.lfo1
+            .diff(&other.lfo1, &join(path, "lfo1"), changes);
+        self.lfo2
+            .diff(&other.lfo2, &join(path, "lfo2"), changes);
+        self.unison
+            .diff(&other.unison, &join(path, "unison"), changes);
+        self.arpeggiator
+            .diff(&other.arpeggiator, &join(path, "arpeggiator"), changes);
+        self.delay
+            .diff(&other.delay, &join(path, "delay"), changes);
+        self.distorsion
+            .diff(&other.distorsion, &join(path, "distorsion"), changes);
+        self.modulation_fx
+            .diff(&other.modulation_fx, &join(path, "modulation_fx"), changes);
+        self.equalizer
+            .diff(&other.equalizer, &join(path, "equalizer"), changes);
+        self.sidechain
+            .diff(&other.sidechain, &join(path, "sidechain"), changes);
+        diff_cables(&self.cables, &other.cables, &join(path, "cables"), changes);
+        self.mod_knobs
+            .diff(&other.mod_knobs, &join(path, "mod_knobs"), changes);
+    }
+
+    fn label(&self) -> String {
+        String::from("Sound")
+    }
+}
+
+/// Match the cables by their pair (source, destination)
+fn diff_cables(old: &[PatchCable], new: &[PatchCable], path: &str, changes: &mut Vec<FieldChange>) {
+    let cable_path = |cable: &PatchCable| format!("{}[{}->{}]", path, cable.source, cable.destination);
+    let mut matched = vec![false; new.len()];
+
+    for old_cable in old {
+        let new_index = new
+            .iter()
+            .enumerate()
+            .position(|(index, new_cable)| {
+                !matched[index] && new_cable.source == old_cable.source && new_cable.destination == old_cable.destination
+            });
+
+        match new_index {
+            Some(index) => {
+                matched[index] = true;
+                old_cable
+                    .amount
+                    .diff(&new[index].amount, &join(&cable_path(old_cable), "amount"), changes);
+            }
+            None => changes.push(FieldChange::new(cable_path(old_cable), Some(old_cable.label()), None)),
+        }
+    }
+
+    for (new_cable, _) in new
+        .iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+    {
+        changes.push(FieldChange::new(cable_path(new_cable), None, Some(new_cable.label())));
+    }
+}
+
+impl Diff for PatchCable {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        self.source
+            .diff(&other.source, &join(path, "source"), changes);
+        self.destination
+            .diff(&other.destination, &join(path, "destination"), changes);
+        self.amount
+            .diff(&other.amount, &join(path, "amount"), changes);
+    }
+
+    fn label(&self) -> String {
+        self.amount.to_string()
+    }
+}
+
+impl Diff for ModKnob {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        self.control_param
+            .diff(&other.control_param, &join(path, "control_param"), changes);
+        self.patch_amount_from_source.diff(
+            &other.patch_amount_from_source,
+            &join(path, "patch_amount_from_source"),
+            changes,
+        );
+    }
+
+    fn label(&self) -> String {
+        self.control_param.clone()
+    }
+}
+
+impl Diff for SynthEngine {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        match (self, other) {
+            (SynthEngine::Subtractive(old), SynthEngine::Subtractive(new)) => old.diff(new, path, changes),
+            (SynthEngine::RingMod(old), SynthEngine::RingMod(new)) => old.diff(new, path, changes),
+            (SynthEngine::Fm(old), SynthEngine::Fm(new)) => old.diff(new, path, changes),
+            _ => push_change(self, other, path, changes),
+        }
+    }
+
+    fn label(&self) -> String {
+        self.to_sound_type().to_string()
+    }
+}
+
+impl Diff for SubtractiveOscillator {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        match (self, other) {
+            (SubtractiveOscillator::Waveform(old), SubtractiveOscillator::Waveform(new)) => old.diff(new, path, changes),
+            (SubtractiveOscillator::Sample(old), SubtractiveOscillator::Sample(new)) => old.diff(new, path, changes),
+            _ => push_change(self, other, path, changes),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            SubtractiveOscillator::Waveform(oscillator) => oscillator.osc_type.to_string(),
+            SubtractiveOscillator::Sample(_) => String::from("Sample"),
+        }
+    }
+}
+
+impl Diff for Sample {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        match (self, other) {
+            (Sample::OneZone(old), Sample::OneZone(new)) => old.diff(new, path, changes),
+            (Sample::SampleRanges(old), Sample::SampleRanges(new)) => old.diff(new, &join(path, "ranges"), changes),
+            _ => push_change(self, other, path, changes),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Sample::OneZone(_) => String::from("One zone"),
+            Sample::SampleRanges(ranges) => format!("{} ranges", ranges.len()),
+        }
+    }
+}
+
+impl Diff for ModulationFx {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        match (self, other) {
+            (ModulationFx::Off, ModulationFx::Off) => {}
+            (ModulationFx::Flanger(old), ModulationFx::Flanger(new)) => old.diff(new, path, changes),
+            (ModulationFx::Chorus(old), ModulationFx::Chorus(new)) => old.diff(new, path, changes),
+            (ModulationFx::Phaser(old), ModulationFx::Phaser(new)) => old.diff(new, path, changes),
+            _ => push_change(self, other, path, changes),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            ModulationFx::Off => String::from("Off"),
+            ModulationFx::Flanger(_) => String::from("Flanger"),
+            ModulationFx::Chorus(_) => String::from("Chorus"),
+            ModulationFx::Phaser(_) => String::from("Phaser"),
+        }
+    }
+}
+
+impl Diff for RowKit {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        match (self, other) {
+            (RowKit::Sound(old), RowKit::Sound(new)) => old.diff(new, path, changes),
+            (RowKit::Midi(old), RowKit::Midi(new)) => old.diff(new, path, changes),
+            (RowKit::CvGate(old), RowKit::CvGate(new)) => old.diff(new, path, changes),
+            _ => push_change(self, other, path, changes),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            RowKit::Sound(row) => row.name.clone(),
+            RowKit::Midi(row) => format!("MIDI channel {} note {}", row.channel, row.note),
+            RowKit::CvGate(row) => format!("CV/Gate channel {}", row.channel),
+        }
+    }
+}
+
+diff_struct!(SubtractiveSynth {
+    osc1,
+    osc2,
+    osc2_sync,
+    osc1_volume,
+    osc2_volume,
+    noise,
+    lpf_mode,
+    lpf_frequency,
+    lpf_resonance,
+    hpf_frequency,
+    hpf_resonance,
+});
+diff_struct!(RingModSynth {
+    osc1,
+    osc2,
+    osc2_sync,
+    noise
+});
+diff_struct!(FmSynth {
+    osc1,
+    osc2,
+    modulator1,
+    modulator2,
+    osc1_volume,
+    osc2_volume,
+    modulator2_to_modulator1,
+});
+diff_struct!(FmCarrier {
+    transpose,
+    fine_transpose,
+    retrig_phase,
+    feedback
+});
+diff_struct!(FmModulator {
+    transpose,
+    fine_transpose,
+    retrig_phase,
+    amount,
+    feedback,
+});
+diff_struct!(WaveformOscillator {
+    osc_type,
+    transpose,
+    fine_transpose,
+    retrig_phase,
+    pulse_width,
+});
+diff_struct!(SampleOscillator {
+    transpose,
+    fine_transpose,
+    mode,
+    reversed,
+    pitch_speed,
+    time_stretch_amount,
+    linear_interpolation,
+    sample,
+});
+diff_struct!(SampleOneZone { file_path, zone });
+
+impl Diff for SampleRange {
+    fn diff(&self, other: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        self.range_top_note
+            .diff(&other.range_top_note, &join(path, "range_top_note"), changes);
+        self.transpose
+            .diff(&other.transpose, &join(path, "transpose"), changes);
+        self.fine_transpose
+            .diff(&other.fine_transpose, &join(path, "fine_transpose"), changes);
+        self.file_path
+            .diff(&other.file_path, &join(path, "file_path"), changes);
+        self.zone
+            .diff(&other.zone, &join(path, "zone"), changes);
+    }
+
+    fn label(&self) -> String {
+        self.file_path.to_string()
+    }
+}
+
+diff_struct!(SampleZone {
+    start,
+    end,
+    start_loop,
+    end_loop
+});
+diff_struct!(Envelope {
+    attack,
+    decay,
+    sustain,
+    release
+});
+diff_struct!(Lfo1 { shape, sync_level, rate });
+diff_struct!(Lfo2 { shape, rate });
+diff_struct!(Unison { voice_count, detune });
+diff_struct!(Arpeggiator {
+    mode,
+    gate,
+    rate,
+    sync_level,
+    octaves_count
+});
+diff_struct!(Delay {
+    ping_pong,
+    analog,
+    amount,
+    rate,
+    sync_level
+});
+diff_struct!(Distorsion {
+    bit_crush,
+    saturation,
+    decimation
+});
+diff_struct!(Equalizer {
+    bass_level,
+    bass_frequency,
+    treble_level,
+    treble_frequency,
+});
+diff_struct!(Flanger { rate, feedback });
+diff_struct!(Chorus { rate, depth, offset });
+diff_struct!(Phaser { rate, depth, feedback });
+diff_struct!(Sidechain {
+    attack,
+    release,
+    shape,
+    sync
+});
+diff_struct!(SoundRow { name, sound });
+diff_struct!(MidiRow { channel, note });
+diff_struct!(CvGateRow { channel });
+diff_struct!(Lpf { frequency, resonance });
+diff_struct!(Hpf { frequency, resonance });
+diff_struct!(Kit {
+    rows,
+    selected_row_index,
+    volume,
+    pan,
+    reverb_amount,
+    lpf_mode,
+    current_filter_type,
+    bit_crush,
+    decimation,
+    stutter_rate,
+    modulation_fx,
+    delay,
+    sidechain,
+    lpf,
+    hpf,
+    equalizer,
+    metadata,
+});
+diff_struct!(Metadata {
+    name,
+    author,
+    tags,
+    notes
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_kits, diff_sounds, FieldChange, PatchCompareOptions};
+    use crate::{deserialize_kit, deserialize_synth, Metadata, PatchCable, PatchDestination, PatchSource, RowKit};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_diff_same_patch_saved_with_different_versions() {
+        let synth_v2 = deserialize_synth(include_str!("data_tests/SYNTHS/SYNT168.XML")).unwrap();
+        let synth_v3 = deserialize_synth(include_str!("data_tests/SYNTHS/SYNT168A.XML")).unwrap();
+
+        assert_eq!(diff_sounds(&synth_v2.sound, &synth_v3.sound), Vec::new());
+    }
+
+    #[test]
+    fn test_diff_different_patches() {
+        let synth_168 = deserialize_synth(include_str!("data_tests/SYNTHS/SYNT168.XML")).unwrap();
+        let synth_061 = deserialize_synth(include_str!("data_tests/SYNTHS/SYNT061.XML")).unwrap();
+
+        assert!(!diff_sounds(&synth_168.sound, &synth_061.sound).is_empty());
+    }
+
+    #[test]
+    fn test_diff_values() {
+        let old = deserialize_synth(include_str!("data_tests/SYNTHS/SYNT061.XML")).unwrap();
+        let mut new = old.clone();
+
+        new.sound.volume = 12.into();
+        new.sound.envelope1.attack = 3.into();
+
+        assert_eq!(
+            diff_sounds(&old.sound, &new.sound),
+            vec![
+                FieldChange::new("volume", Some(old.sound.volume.to_string()), Some(String::from("12"))),
+                FieldChange::new(
+                    "envelope1.attack",
+                    Some(old.sound.envelope1.attack.to_string()),
+                    Some(String::from("3"))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_sample_ranges() {
+        let old = deserialize_synth(include_str!("data_tests/SYNTHS/SYNT168A.XML")).unwrap();
+        let mut new = old.clone();
+        let ranges = new
+            .sound
+            .generator
+            .as_subtractive_mut()
+            .unwrap()
+            .osc1
+            .as_sample_mut()
+            .unwrap()
+            .sample
+            .as_sample_ranges_mut()
+            .unwrap();
+        let removed = ranges.pop().unwrap();
+        let removed_index = ranges.len();
+
+        ranges[0].transpose = 7.into();
+
+        let changes = diff_sounds(&old.sound, &new.sound);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].path, "generator.osc1.sample.ranges[0].transpose");
+        assert_eq!(changes[0].new, Some(String::from("7")));
+        assert_eq!(changes[1].path, format!("generator.osc1.sample.ranges[{}]", removed_index));
+        assert_eq!(changes[1].old, Some(removed.file_path.to_string()));
+        assert!(changes[1].is_removed());
+    }
+
+    #[test]
+    fn test_diff_cables() {
+        let old = deserialize_synth(include_str!("data_tests/SYNTHS/SYNT061.XML")).unwrap();
+        let mut new = old.clone();
+
+        new.sound.cables.reverse();
+        new.sound
+            .cables
+            .push(PatchCable::new(PatchSource::Aftertouch, PatchDestination::Pitch, 10.into()));
+
+        assert_eq!(
+            diff_sounds(&old.sound, &new.sound),
+            vec![FieldChange::new("cables[aftertouch->pitch]", None, Some(String::from("10")))]
+        );
+
+        let cable = new.sound.cables.first_mut().unwrap();
+        let expected_path = format!("cables[{}->{}].amount", cable.source, cable.destination);
+        let expected_old = cable.amount.to_string();
+
+        cable.amount = 1.into();
+
+        let changes = diff_sounds(&old.sound, &new.sound);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes[0],
+            FieldChange::new(expected_path, Some(expected_old), Some(String::from("1")))
+        );
+    }
+
+    #[test]
+    fn test_diff_kits() {
+        let old = deserialize_kit(include_str!("data_tests/KITS/KIT057.XML")).unwrap();
+        let mut new = old.clone();
+
+        assert_eq!(diff_kits(&old, &new), Vec::new());
+
+        let removed = new.rows.pop().unwrap();
+        let removed_index = new.rows.len();
+
+        if let RowKit::Sound(row) = &mut new.rows[0] {
+            row.name = String::from("RENAMED");
+            row.sound.volume = 1.into();
+        }
+
+        let changes = diff_kits(&old, &new);
+        let paths = changes
+            .iter()
+            .map(|change| change.path.as_str())
+            .collect::<Vec<_>>();
+        let expected_removed_path = format!("rows[{}]", removed_index);
+
+        assert_eq!(
+            paths,
+            vec!["rows[0].name", "rows[0].sound.volume", expected_removed_path.as_str()]
+        );
+        assert_eq!(changes[2].old, Some(removed.as_sound().unwrap().name.clone()));
+        assert!(changes[2].is_removed());
+    }
+
+    #[test]
+    fn test_diff_kits_metadata() {
+        let old = deserialize_kit(include_str!("data_tests/KITS/KIT057.XML")).unwrap();
+        let mut new = old.clone();
+
+        new.metadata = Some(Metadata {
+            author: Some(String::from("Someone")),
+            ..Metadata::default()
+        });
+
+        assert_eq!(
+            diff_kits(&old, &new),
+            vec![FieldChange::new("metadata", None, Some(String::from("Metadata")))]
+        );
+        assert!(new.sonically_equals(&old));
+        assert!(!new.sonically_equals_with(&old, &PatchCompareOptions::strict()));
+
+        let old = new.clone();
+
+        new.metadata
+            .as_mut()
+            .unwrap()
+            .tags
+            .push(String::from("drums"));
+
+        assert_eq!(
+            diff_kits(&old, &new),
+            vec![FieldChange::new("metadata.tags[0]", None, Some(String::from("drums")))]
+        );
+    }
+
+    #[test]
+    fn test_sonically_equals_sounds() {
+        let old = deserialize_synth(include_str!("data_tests/SYNTHS/SYNT061.XML")).unwrap();
+        let mut new = old.clone();
+
+        new.sound.mod_knobs[0].control_param = String::from("pitch");
+        new.sound.cables.reverse();
+
+        assert!(new.sound.sonically_equals(&old.sound));
+        assert!(!new
+            .sound
+            .sonically_equals_with(&old.sound, &PatchCompareOptions::strict()));
+
+        new.sound.volume = 1.into();
+
+        assert!(!new.sound.sonically_equals(&old.sound));
+    }
+
+    #[test]
+    fn test_sonically_equals_kits() {
+        let old = deserialize_kit(include_str!("data_tests/KITS/KIT057.XML")).unwrap();
+        let mut new = old.clone();
+
+        if let RowKit::Sound(row) = &mut new.rows[0] {
+            row.name = String::from("RENAMED");
+        }
+
+        new.selected_row_index = Some(1);
+
+        assert!(new.sonically_equals(&old));
+        assert!(!new.sonically_equals_with(
+            &old,
+            &PatchCompareOptions {
+                ignore_row_names: false,
+                ..Default::default()
+            }
+        ));
+        assert!(!new.sonically_equals_with(
+            &old,
+            &PatchCompareOptions {
+                ignore_selected_row: false,
+                ..Default::default()
+            }
+        ));
+
+        new.rows.pop();
+
+        assert!(!new.sonically_equals(&old));
+    }
+}
@@ -0,0 +1,367 @@
+//! Write the patches on the disk without losing the previous file if the writing fails.
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::WriteError;
+
+/// Write all the bytes, returns the count of bytes written
+///
+/// Unlike [Write::write_all], a failure after some bytes were written is reported
+/// with [WriteError::Incomplete].
+pub(crate) fn write_counted<W: Write>(writable: &mut W, bytes: &[u8]) -> Result<usize, WriteError> {
+    let mut written = 0;
+
+    while written < bytes.len() {
+        let error = match writable.write(&bytes[written..]) {
+            Ok(0) => std::io::Error::new(ErrorKind::WriteZero, "failed to write the whole content"),
+            Ok(count) => {
+                written += count;
+                continue;
+            }
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => error,
+        };
+
+        return Err(write_failure(error, written, bytes.len()));
+    }
+
+    writable
+        .flush()
+        .map_err(WriteError::WriteError)?;
+
+    Ok(written)
+}
+
+/// The error of a writing failing after a count of bytes written
+fn write_failure(error: std::io::Error, written: usize, expected: usize) -> WriteError {
+    match written {
+        0 => WriteError::WriteError(error),
+        _ => WriteError::Incomplete {
+            written,
+            expected,
+            error,
+        },
+    }
+}
+
+/// Write a file by writing a temporary file in the same directory then renaming it
+///
+/// If the writing fails the original file is kept intact. If the file system can't rename
+/// the temporary file, the file is written directly.
+pub(crate) fn write_file_atomically(path: &Path, content: &str) -> Result<usize, WriteError> {
+    let temporary_path = get_temporary_path(path);
+    let written = write_file(&temporary_path, content, true).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temporary_path);
+    })?;
+
+    if std::fs::rename(&temporary_path, path).is_ok() {
+        return Ok(written);
+    }
+
+    let _ = std::fs::remove_file(&temporary_path);
+
+    write_file(path, content, false)
+}
+
+fn write_file(path: &Path, content: &str, sync: bool) -> Result<usize, WriteError> {
+    let file_error = |error| WriteError::WriteFileError(error, path.to_path_buf());
+    let mut file = File::create(path).map_err(file_error)?;
+    let written = write_counted(&mut file, content.as_bytes()).map_err(|error| WriteError::new_file_error(error, path))?;
+
+    if sync {
+        file.sync_all().map_err(file_error)?;
+    }
+
+    Ok(written)
+}
+
+/// The async version of [write_file_atomically], the files are written with `tokio::fs`
+#[cfg(feature = "async")]
+pub(crate) async fn write_file_atomically_async(path: &Path, content: &str) -> Result<usize, WriteError> {
+    let temporary_path = get_temporary_path(path);
+    let written = match write_file_async(&temporary_path, content, true).await {
+        Ok(written) => written,
+        Err(error) => {
+            let _ = tokio::fs::remove_file(&temporary_path).await;
+
+            return Err(error);
+        }
+    };
+
+    if tokio::fs::rename(&temporary_path, path)
+        .await
+        .is_ok()
+    {
+        return Ok(written);
+    }
+
+    let _ = tokio::fs::remove_file(&temporary_path).await;
+
+    write_file_async(path, content, false).await
+}
+
+/// The async version of [write_counted]
+#[cfg(feature = "async")]
+async fn write_counted_async<W: tokio::io::AsyncWrite + Unpin>(writable: &mut W, bytes: &[u8]) -> Result<usize, WriteError> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut written = 0;
+
+    while written < bytes.len() {
+        let error = match writable.write(&bytes[written..]).await {
+            Ok(0) => std::io::Error::new(ErrorKind::WriteZero, "failed to write the whole content"),
+            Ok(count) => {
+                written += count;
+                continue;
+            }
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => error,
+        };
+
+        return Err(write_failure(error, written, bytes.len()));
+    }
+
+    writable
+        .flush()
+        .await
+        .map_err(WriteError::WriteError)?;
+
+    Ok(written)
+}
+
+#[cfg(feature = "async")]
+async fn write_file_async(path: &Path, content: &str, sync: bool) -> Result<usize, WriteError> {
+    let file_error = |error| WriteError::WriteFileError(error, path.to_path_buf());
+    let mut file = tokio::fs::File::create(path)
+        .await
+        .map_err(file_error)?;
+    let written = write_counted_async(&mut file, content.as_bytes())
+        .await
+        .map_err(|error| WriteError::new_file_error(error, path))?;
+
+    if sync {
+        file.sync_all()
+            .await
+            .map_err(file_error)?;
+    }
+
+    Ok(written)
+}
+
+/// Get the path of the temporary file, for example "KITS/.KIT001.XML.tmp" for "KITS/KIT001.XML"
+fn get_temporary_path(path: &Path) -> PathBuf {
+    let mut file_name = OsString::from(".");
+
+    file_name.push(path.file_name().unwrap_or_default());
+    file_name.push(".tmp");
+
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_kit_from_file, read_synth_from_file, write_kit_to_file, Kit, ReadError};
+    use pretty_assertions::assert_eq;
+
+    /// A writer failing after a count of bytes
+    struct FailingWriter {
+        content: Vec<u8>,
+        capacity: usize,
+    }
+
+    impl Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let count = buf
+                .len()
+                .min(self.capacity - self.content.len())
+                .min(4);
+
+            if count == 0 {
+                return Err(std::io::Error::other("no space left"));
+            }
+
+            self.content
+                .extend_from_slice(&buf[..count]);
+
+            Ok(count)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl tokio::io::AsyncWrite for FailingWriter {
+        fn poll_write(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            std::task::Poll::Ready(self.get_mut().write(buf))
+        }
+
+        fn poll_flush(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    fn make_test_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("deluge_file_writing_{}_{}", name, std::process::id()));
+
+        std::fs::create_dir_all(&directory).unwrap();
+
+        directory
+    }
+
+    #[test]
+    fn test_write_counted() {
+        let mut writer = FailingWriter {
+            content: Vec::new(),
+            capacity: 100,
+        };
+
+        assert_eq!(write_counted(&mut writer, b"hello world").unwrap(), 11);
+        assert_eq!(writer.content, b"hello world");
+    }
+
+    #[test]
+    fn test_write_counted_incomplete() {
+        let mut writer = FailingWriter {
+            content: Vec::new(),
+            capacity: 6,
+        };
+
+        assert!(matches!(
+            write_counted(&mut writer, b"hello world"),
+            Err(WriteError::Incomplete {
+                written: 6,
+                expected: 11,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_write_counted_nothing_written() {
+        let mut writer = FailingWriter {
+            content: Vec::new(),
+            capacity: 0,
+        };
+
+        assert!(matches!(write_counted(&mut writer, b"hello"), Err(WriteError::WriteError(_))));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_write_counted_async_incomplete() {
+        let mut writer = FailingWriter {
+            content: Vec::new(),
+            capacity: 6,
+        };
+
+        assert!(matches!(
+            write_counted_async(&mut writer, b"hello world").await,
+            Err(WriteError::Incomplete {
+                written: 6,
+                expected: 11,
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_write_counted_async_nothing_written() {
+        let mut writer = FailingWriter {
+            content: Vec::new(),
+            capacity: 0,
+        };
+
+        assert!(matches!(
+            write_counted_async(&mut writer, b"hello").await,
+            Err(WriteError::WriteError(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_kit_to_file_replaces_the_file() {
+        let directory = make_test_directory("replace");
+        let path = directory.join("KIT000.XML");
+
+        std::fs::write(&path, "previous content").unwrap();
+
+        let written = write_kit_to_file(&Kit::default(), &path).unwrap();
+
+        assert_eq!(written as u64, std::fs::metadata(&path).unwrap().len());
+        assert_eq!(read_kit_from_file(&path).unwrap(), Kit::default());
+        assert!(!get_temporary_path(&path).exists());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_read_synth_from_file_error_keeps_path_and_location() {
+        let directory = make_test_directory("read_error");
+        let path = directory.join("SYNT184.XML");
+
+        std::fs::write(
+            &path,
+            include_str!("data_tests/SYNTHS/SYNT184.XML").replace("mode=\"subtractive\"", "mode=\"heu\""),
+        )
+        .unwrap();
+
+        match read_synth_from_file(&path) {
+            Err(ReadError::DeserializationFileError(error, error_path)) => {
+                assert_eq!(error_path, path);
+                assert_eq!(error.location(), Some((2, 1)));
+            }
+            result => panic!("unexpected result {:?}", result.map(|_| ())),
+        }
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_write_kit_to_file_async() {
+        use crate::{read_kit_from_file_async, write_kit_to_file_async};
+
+        let directory = make_test_directory("async");
+        let path = directory.join("KIT000.XML");
+
+        std::fs::write(&path, "previous content").unwrap();
+
+        let written = write_kit_to_file_async(&Kit::default(), &path)
+            .await
+            .unwrap();
+
+        assert_eq!(written as u64, std::fs::metadata(&path).unwrap().len());
+        assert_eq!(
+            read_kit_from_file_async(&path)
+                .await
+                .unwrap(),
+            Kit::default()
+        );
+        assert!(!get_temporary_path(&path).exists());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_get_temporary_path() {
+        assert_eq!(
+            get_temporary_path(Path::new("card/KITS/KIT001.XML")),
+            Path::new("card/KITS/.KIT001.XML.tmp")
+        );
+    }
+}
@@ -0,0 +1,14 @@
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum KitError {
+    #[error("row index {0} out of bounds, the kit has {1} rows")]
+    RowIndexOutOfBounds(usize, usize),
+
+    #[error("only the sound rows have a name")]
+    UnnamedRow,
+
+    #[error("the name of a row can't be empty")]
+    EmptyRowName,
+
+    #[error("another row is already named '{0}'")]
+    DuplicateRowName(String),
+}
@@ -0,0 +1,143 @@
+use crate::{Delay, Equalizer, Hpf, Kit, Lpf, ModulationFx, Sidechain, Sound, SynthEngine};
+
+/// The effects applied to a whole kit, see [Kit::global_fx]
+///
+/// The effects have the same types than the effects of a [Sound] excepted the filters,
+/// see [Lpf::from_sound_filter] and [Hpf::from_sound_filter].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlobalFx<'a> {
+    pub lpf: &'a Lpf,
+    pub hpf: &'a Hpf,
+    pub delay: &'a Delay,
+    pub sidechain: &'a Sidechain,
+    pub modulation_fx: &'a ModulationFx,
+    pub equalizer: &'a Equalizer,
+}
+
+/// The effects applied to a whole kit, see [Kit::global_fx_mut]
+#[derive(Debug, PartialEq, Eq)]
+pub struct GlobalFxMut<'a> {
+    pub lpf: &'a mut Lpf,
+    pub hpf: &'a mut Hpf,
+    pub delay: &'a mut Delay,
+    pub sidechain: &'a mut Sidechain,
+    pub modulation_fx: &'a mut ModulationFx,
+    pub equalizer: &'a mut Equalizer,
+}
+
+impl Kit {
+    /// Get the global effects of the kit
+    pub fn global_fx(&self) -> GlobalFx<'_> {
+        GlobalFx {
+            lpf: &self.lpf,
+            hpf: &self.hpf,
+            delay: &self.delay,
+            sidechain: &self.sidechain,
+            modulation_fx: &self.modulation_fx,
+            equalizer: &self.equalizer,
+        }
+    }
+
+    /// Get mutably the global effects of the kit
+    pub fn global_fx_mut(&mut self) -> GlobalFxMut<'_> {
+        GlobalFxMut {
+            lpf: &mut self.lpf,
+            hpf: &mut self.hpf,
+            delay: &mut self.delay,
+            sidechain: &mut self.sidechain,
+            modulation_fx: &mut self.modulation_fx,
+            equalizer: &mut self.equalizer,
+        }
+    }
+
+    /// Copy the effects of a sound to the global effects of the kit
+    ///
+    /// The delay, the sidechain, the modulation FX, the equalizer and the distorsion are copied.
+    /// The filters are copied only if the sound is subtractive because the other engines have no filter,
+    /// otherwise the filters of the kit are not modified.
+    /// ```
+    /// # use deluge::{Kit, Sound};
+    /// let mut kit = Kit::default();
+    /// let mut sound = Sound::default();
+    ///
+    /// sound.delay.amount = 20.into();
+    /// kit.apply_fx_from_sound(&sound);
+    ///
+    /// assert_eq!(kit.global_fx().delay, &sound.delay);
+    /// ```
+    pub fn apply_fx_from_sound(&mut self, sound: &Sound) {
+        if let SynthEngine::Subtractive(generator) = &sound.generator {
+            self.lpf_mode = generator.lpf_mode.clone();
+            self.lpf = Lpf::from_sound_filter(generator.lpf_frequency, generator.lpf_resonance);
+            self.hpf = Hpf::from_sound_filter(generator.hpf_frequency, generator.hpf_resonance);
+        }
+
+        self.delay = sound.delay.clone();
+        self.sidechain = sound.sidechain.clone();
+        self.modulation_fx = sound.modulation_fx.clone();
+        self.equalizer = sound.equalizer.clone();
+        self.bit_crush = sound.distorsion.bit_crush;
+        self.decimation = sound.distorsion.decimation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{deserialize_kit, deserialize_synth, values::HexU50, FmSynth, Hpf, Kit, Lpf, Sound, SynthEngine};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_apply_fx_from_sound() {
+        let synth = deserialize_synth(include_str!("../data_tests/SYNTHS/SYNT168A.XML")).unwrap();
+        let generator = synth
+            .sound
+            .generator
+            .as_subtractive()
+            .unwrap();
+        let mut kit = Kit::default();
+
+        kit.apply_fx_from_sound(&synth.sound);
+
+        let global_fx = kit.global_fx();
+
+        assert_eq!(
+            global_fx.lpf,
+            &Lpf::from_sound_filter(generator.lpf_frequency, generator.lpf_resonance)
+        );
+        assert_eq!(
+            global_fx.hpf,
+            &Hpf::from_sound_filter(generator.hpf_frequency, generator.hpf_resonance)
+        );
+        assert_eq!(global_fx.delay, &synth.sound.delay);
+        assert_eq!(global_fx.sidechain, &synth.sound.sidechain);
+        assert_eq!(global_fx.modulation_fx, &synth.sound.modulation_fx);
+        assert_eq!(global_fx.equalizer, &synth.sound.equalizer);
+        assert_eq!(kit.lpf_mode, generator.lpf_mode);
+    }
+
+    #[test]
+    fn test_apply_fx_from_fm_sound_keeps_the_filters() {
+        let mut kit = deserialize_kit(include_str!("../data_tests/KITS/KIT030.XML")).unwrap();
+        let (lpf, hpf) = (kit.lpf.clone(), kit.hpf.clone());
+        let mut sound = Sound {
+            generator: SynthEngine::from(FmSynth::default()),
+            ..Default::default()
+        };
+
+        sound.delay.rate = 12.into();
+        kit.apply_fx_from_sound(&sound);
+
+        assert_eq!(kit.lpf, lpf);
+        assert_eq!(kit.hpf, hpf);
+        assert_eq!(kit.delay, sound.delay);
+    }
+
+    #[test]
+    fn test_global_fx_mut() {
+        let mut kit = Kit::default();
+
+        kit.global_fx_mut().lpf.frequency = 20.into();
+
+        assert_eq!(kit.lpf.frequency, HexU50::new(20));
+    }
+}
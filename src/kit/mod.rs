@@ -1,11 +1,28 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use crate::{
-    values::{CvGateChannel, FilterType, HexU50, LpfMode, MidiChannel, Pan, Polyphony, SamplePath},
-    Delay, Equalizer, Flanger, ModulationFx, Sample, SampleOneZone, SampleZone, Sidechain, Sound, SubtractiveOscillator,
+    diff::{diff_kits, PatchCompareOptions},
+    range_check::{check_ranges, CheckRange},
+    values::{
+        note_name_to_number, CvGateChannel, FilterType, HexU50, LpfMode, MidiChannel, MidiNote, NoteParseError, OctaveConvention,
+        Pan, SamplePath,
+    },
+    Delay, Equalizer, Flanger, IssueCode, Metadata, ModulationFx, PatchIssue, Sidechain, Sound,
 };
 
+mod error;
+mod global_fx;
 mod row;
+mod row_conflict;
+mod row_pruning;
+mod row_volumes;
 
+pub use error::KitError;
+pub use global_fx::{GlobalFx, GlobalFxMut};
 pub use row::{CvGateRow, MidiRow, RowKit, SoundRow};
+pub use row_conflict::RowConflict;
+pub use row_pruning::PruneRowsOptions;
+pub use row_volumes::ClampedVolume;
 
 /// Store a kit patch
 ///
@@ -24,15 +41,16 @@ pub use row::{CvGateRow, MidiRow, RowKit, SoundRow};
 ///         WaveformOscillatorBuilder::default().osc_type(OscType::Square).build().unwrap().into(),
 ///         WaveformOscillatorBuilder::default().build().unwrap().into(),
 ///     ), "SQR1")
-///     .add_midi_row(1.into(), 60)
+///     .add_midi_row(1.into(), 60.into())
 ///     .build()
 ///     .unwrap()
 ///     ;
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::validate"))]
 pub struct Kit {
-    #[builder(setter(each(name = "add_row")))]
+    #[builder(setter(custom), field(type = "KitBuilderRows", build = "self.rows.build()"))]
     pub rows: Vec<RowKit>,
 
     pub selected_row_index: Option<u32>,
@@ -66,8 +84,33 @@ pub struct Kit {
 
     /// The global equalizer
     pub equalizer: Equalizer,
+
+    /// The librarian information, see [Metadata]
+    pub metadata: Option<Metadata>,
 }
 
+check_ranges!(
+    Kit,
+    KitBuilder {
+        rows,
+        selected_row_index,
+        volume,
+        pan,
+        reverb_amount,
+        lpf_mode,
+        current_filter_type,
+        bit_crush,
+        decimation,
+        stutter_rate,
+        modulation_fx,
+        delay,
+        sidechain,
+        lpf,
+        hpf,
+        equalizer
+    }
+);
+
 impl Kit {
     pub fn new(rows: Vec<RowKit>) -> Self {
         let has_rows = rows.is_empty();
@@ -92,17 +135,357 @@ impl Kit {
             lpf: Lpf::default(),
             hpf: Hpf::default(),
             equalizer: Equalizer::default(),
+            metadata: None,
         }
     }
 
+    /// Get the selected row
+    /// Returns None if no row is selected or if the selected row index is out of bounds.
     pub fn current_row(&self) -> Option<&RowKit> {
         self.selected_row_index
-            .map(|index| &self.rows[index as usize])
+            .and_then(|index| self.rows.get(index as usize))
+    }
+
+    /// Get the selected row index clamped to the last row, None if the kit has no row
+    pub(crate) fn clamped_selected_row_index(&self) -> Option<u32> {
+        let last = self.rows.len().checked_sub(1)? as u32;
+
+        self.selected_row_index
+            .map(|index| index.min(last))
     }
 
+    /// Get mutably the selected row
+    /// Returns None if no row is selected or if the selected row index is out of bounds.
     pub fn current_row_mut(&mut self) -> Option<&mut RowKit> {
         self.selected_row_index
-            .map(|index| &mut self.rows[index as usize])
+            .and_then(|index| self.rows.get_mut(index as usize))
+    }
+
+    /// Select a row, returns an error if the index is out of bounds
+    /// ```
+    /// use deluge::Kit;
+    ///
+    /// let mut kit = Kit::default();
+    ///
+    /// assert!(kit.set_selected_row(1).is_err());
+    /// kit.add_midi_row(1.into(), 60.into());
+    /// kit.set_selected_row(1).unwrap();
+    ///
+    /// assert!(kit.current_row().unwrap().is_midi());
+    /// ```
+    pub fn set_selected_row(&mut self, index: usize) -> Result<(), KitError> {
+        self.check_row_index(index)?;
+        self.selected_row_index = Some(index as u32);
+
+        Ok(())
+    }
+
+    /// Check the kit and the sounds of its rows
+    ///
+    /// The paths of the issues found in a sound are prefixed by the row, for example "rows[2].sound.volume".
+    pub fn validate(&self) -> Vec<PatchIssue> {
+        let mut issues = Vec::new();
+
+        if let Some(selected_row_index) = self.selected_row_index {
+            if selected_row_index as usize >= self.rows.len() {
+                issues.push(PatchIssue::error(
+                    IssueCode::SelectedRowOutOfBounds,
+                    "selected_row_index",
+                    format!(
+                        "the row {} is selected but the kit has {} rows",
+                        selected_row_index,
+                        self.rows.len()
+                    ),
+                ));
+            }
+        }
+
+        let mut names = BTreeSet::new();
+
+        for (index, row) in self.rows.iter().enumerate() {
+            if let RowKit::Sound(sound_row) = row {
+                let prefix = format!("rows[{}]", index);
+
+                if !names.insert(sound_row.name.as_str()) {
+                    issues.push(PatchIssue::warning(
+                        IssueCode::DuplicateRowName,
+                        format!("{}.name", prefix),
+                        format!("another row is named '{}'", sound_row.name),
+                    ));
+                }
+
+                issues.extend(
+                    sound_row
+                        .sound
+                        .validate()
+                        .into_iter()
+                        .map(|issue| issue.with_path_prefix(&format!("{}.sound", prefix))),
+                );
+            }
+        }
+
+        issues
+    }
+
+    /// Compare two kits ignoring the differences that are not audible
+    ///
+    /// The names of the rows, the selected row, the mod knobs assignments and the metadata are ignored,
+    /// see [PatchCompareOptions].
+    pub fn sonically_equals(&self, other: &Kit) -> bool {
+        self.sonically_equals_with(other, &PatchCompareOptions::default())
+    }
+
+    /// Compare two kits ignoring the differences selected by the options
+    pub fn sonically_equals_with(&self, other: &Kit, options: &PatchCompareOptions) -> bool {
+        options.ignores_all(&diff_kits(self, other))
+    }
+
+    /// Iterate over the sound rows, the MIDI and CV gate rows are skipped.
+    pub fn sound_rows(&self) -> impl Iterator<Item = &SoundRow> {
+        self.rows
+            .iter()
+            .filter_map(RowKit::as_sound)
+    }
+
+    /// Iterate mutably over the sound rows, the MIDI and CV gate rows are skipped.
+    pub fn sound_rows_mut(&mut self) -> impl Iterator<Item = &mut SoundRow> {
+        self.rows
+            .iter_mut()
+            .filter_map(RowKit::as_sound_mut)
+    }
+
+    /// Iterate over the sounds of the sound rows
+    pub fn sounds(&self) -> impl Iterator<Item = &Sound> {
+        self.sound_rows()
+            .map(|row| row.sound.as_ref())
+    }
+
+    /// Iterate mutably over the sounds of the sound rows
+    /// ```
+    /// use deluge::{HexU50, Kit};
+    ///
+    /// let mut kit = Kit::with_default_rows(8);
+    ///
+    /// for sound in kit.sounds_mut() {
+    ///     sound.reverb_amount = 20.into();
+    /// }
+    ///
+    /// assert!(kit.sounds().all(|sound| sound.reverb_amount == HexU50::new(20)));
+    /// ```
+    pub fn sounds_mut(&mut self) -> impl Iterator<Item = &mut Sound> {
+        self.sound_rows_mut()
+            .map(|row| row.sound.as_mut())
+    }
+
+    /// Get the count of sound rows
+    /// ```
+    /// use deluge::Kit;
+    ///
+    /// let mut kit = Kit::with_default_rows(2);
+    /// kit.add_midi_row(1.into(), 60.into());
+    ///
+    /// assert_eq!(kit.rows.len(), 3);
+    /// assert_eq!(kit.sound_count(), 2);
+    /// ```
+    pub fn sound_count(&self) -> usize {
+        self.sound_rows().count()
+    }
+
+    /// Find the first sound row with the specified name
+    ///
+    /// The comparison is case sensitive, see [Kit::find_sound_row_ignore_case] for a case insensitive search.
+    /// ```
+    /// use deluge::{Kit, Sound};
+    ///
+    /// let mut kit = Kit::default();
+    /// kit.add_named_sound(Sound::default(), "Kick");
+    ///
+    /// assert!(kit.find_sound_row("Kick").is_some());
+    /// assert!(kit.find_sound_row("KICK").is_none());
+    /// ```
+    pub fn find_sound_row(&self, name: &str) -> Option<&SoundRow> {
+        self.sound_rows()
+            .find(|row| row.name == name)
+    }
+
+    /// Find mutably the first sound row with the specified name
+    ///
+    /// The comparison is case sensitive.
+    pub fn find_sound_row_mut(&mut self, name: &str) -> Option<&mut SoundRow> {
+        self.sound_rows_mut()
+            .find(|row| row.name == name)
+    }
+
+    /// Find the first sound row with the specified name ignoring the ASCII case
+    ///
+    /// The Deluge stores the patches on a FAT filesystem which is case insensitive.
+    /// ```
+    /// use deluge::{Kit, Sound};
+    ///
+    /// let mut kit = Kit::default();
+    /// kit.add_named_sound(Sound::default(), "Kick");
+    ///
+    /// assert!(kit.find_sound_row_ignore_case("KICK").is_some());
+    /// ```
+    pub fn find_sound_row_ignore_case(&self, name: &str) -> Option<&SoundRow> {
+        self.sound_rows()
+            .find(|row| row.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Find mutably the first sound row with the specified name ignoring the ASCII case
+    pub fn find_sound_row_ignore_case_mut(&mut self, name: &str) -> Option<&mut SoundRow> {
+        self.sound_rows_mut()
+            .find(|row| row.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Gets all the sample paths used by the sound rows of this kit.
+    pub fn get_sample_paths(&self) -> BTreeSet<SamplePath> {
+        self.sound_rows()
+            .flat_map(|row| row.sound.get_sample_paths())
+            .collect()
+    }
+
+    /// Replace the sample paths of each sound row using a map where the keys are the paths to replace
+    ///
+    /// Returns the count of references replaced.
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use deluge::{Kit, Sound, SamplePath};
+    ///
+    /// let mut kit = Kit::new(Vec::new());
+    /// let old_path = SamplePath::new("SAMPLES/kick.wav").unwrap();
+    /// let new_path = SamplePath::new("SAMPLES/KIT000/kick.wav").unwrap();
+    ///
+    /// kit.add_sound_row(Sound::new_sample(old_path.clone(), 0u64.into(), 999u64.into()));
+    ///
+    /// assert_eq!(kit.replace_sample_paths(&BTreeMap::from([(old_path, new_path.clone())])), 1);
+    /// assert!(kit.get_sample_paths().contains(&new_path));
+    /// ```
+    pub fn replace_sample_paths(&mut self, replacements: &BTreeMap<SamplePath, SamplePath>) -> usize {
+        self.sound_rows_mut()
+            .map(|row| {
+                row.sound
+                    .replace_sample_paths(replacements)
+            })
+            .sum()
+    }
+
+    /// Remove a row
+    ///
+    /// The selected row index is updated to keep pointing to the same row.
+    /// If the selected row is removed, the selection is cleared.
+    pub fn remove_row(&mut self, index: usize) -> Result<RowKit, KitError> {
+        self.check_row_index(index)?;
+
+        let row = self.rows.remove(index);
+
+        self.selected_row_index = match self.selected_row_index {
+            Some(selected) if selected as usize == index => None,
+            Some(selected) if selected as usize > index => Some(selected - 1),
+            selected => selected,
+        };
+
+        Ok(row)
+    }
+
+    /// Move a row from an index to another
+    ///
+    /// The selected row index is updated to keep pointing to the same row.
+    /// ```
+    /// use deluge::Kit;
+    ///
+    /// let mut kit = Kit::default();
+    /// kit.add_midi_row(1.into(), 60.into());
+    /// kit.move_row(1, 0).unwrap();
+    ///
+    /// assert!(kit.rows[0].is_midi());
+    /// assert_eq!(kit.selected_row_index, Some(1));
+    /// ```
+    pub fn move_row(&mut self, from: usize, to: usize) -> Result<(), KitError> {
+        self.check_row_index(from)?;
+        self.check_row_index(to)?;
+
+        let row = self.rows.remove(from);
+
+        self.rows.insert(to, row);
+        self.selected_row_index = self.selected_row_index.map(|selected| {
+            let selected_index = selected as usize;
+
+            if selected_index == from {
+                to as u32
+            } else if from < selected_index && selected_index <= to {
+                selected - 1
+            } else if to <= selected_index && selected_index < from {
+                selected + 1
+            } else {
+                selected
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Swap two rows
+    ///
+    /// The selected row index is updated to keep pointing to the same row.
+    pub fn swap_rows(&mut self, a: usize, b: usize) -> Result<(), KitError> {
+        self.check_row_index(a)?;
+        self.check_row_index(b)?;
+
+        self.rows.swap(a, b);
+        self.selected_row_index = self
+            .selected_row_index
+            .map(|selected| match selected as usize {
+                index if index == a => b as u32,
+                index if index == b => a as u32,
+                _ => selected,
+            });
+
+        Ok(())
+    }
+
+    /// Rename a row
+    ///
+    /// Returns an error if the name is empty or if the row is not a sound row.
+    /// If `unique` is true, the name must not be used by another row, the comparison ignores the ASCII case.
+    /// ```
+    /// use deluge::{Kit, KitError, Sound};
+    ///
+    /// let mut kit = Kit::default();
+    /// kit.add_named_sound(Sound::default(), "Snare");
+    ///
+    /// kit.rename_row(0, "Kick", true).unwrap();
+    ///
+    /// assert_eq!(kit.rows[0].name(), Some("Kick"));
+    /// assert_eq!(kit.rename_row(0, "SNARE", true), Err(KitError::DuplicateRowName("SNARE".to_string())));
+    /// ```
+    pub fn rename_row(&mut self, index: usize, new_name: &str, unique: bool) -> Result<(), KitError> {
+        self.check_row_index(index)?;
+
+        if new_name.is_empty() {
+            return Err(KitError::EmptyRowName);
+        }
+
+        let is_duplicate = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(row_index, _)| *row_index != index)
+            .filter_map(|(_, row)| row.name())
+            .any(|name| name.eq_ignore_ascii_case(new_name));
+
+        if unique && is_duplicate {
+            return Err(KitError::DuplicateRowName(new_name.to_string()));
+        }
+
+        self.rows[index].set_name(new_name)
+    }
+
+    fn check_row_index(&self, index: usize) -> Result<(), KitError> {
+        match index < self.rows.len() {
+            true => Ok(()),
+            false => Err(KitError::RowIndexOutOfBounds(index, self.rows.len())),
+        }
     }
 
     fn add_row(&mut self, row: RowKit) -> &mut RowKit {
@@ -111,6 +494,35 @@ impl Kit {
         self.rows.last_mut().unwrap()
     }
 
+    /// Create a kit with a count of rows playing the sound of the rows of a new kit of the Deluge
+    ///
+    /// Each row plays [Sound::default_kit_row] and is named like the firmware does, from "U1" to "Un".
+    /// Only a new kit with one row is compared with a file saved by the device.
+    /// ```
+    /// use deluge::Kit;
+    ///
+    /// let kit = Kit::with_default_rows(16);
+    ///
+    /// assert_eq!(kit.rows.len(), 16);
+    /// assert_eq!(kit.rows[15].name(), Some("U16"));
+    /// ```
+    pub fn with_default_rows(count: usize) -> Self {
+        let rows = (1..=count)
+            .map(|number| RowKit::new_sound(Sound::default_kit_row(), &format!("U{}", number)))
+            .collect();
+
+        Self::new(rows)
+    }
+
+    /// Add a count of sound rows playing a copy of the template
+    ///
+    /// The rows are named after their position like [Kit::add_sound_row], for example "U5" for the fifth row.
+    pub fn fill_rows_from_template(&mut self, template: &Sound, count: usize) {
+        for _ in 0..count {
+            self.add_sound_row(template.clone());
+        }
+    }
+
     pub fn add_sound_row(&mut self, sound: Sound) -> &mut Sound {
         self.add_named_sound(sound, &format!("U{}", self.rows.len() + 1))
     }
@@ -135,12 +547,32 @@ impl Kit {
     /// use deluge::Kit;
     ///
     /// let mut kit = Kit::default();
-    /// kit.add_midi_row(1.into(), 60);
+    /// kit.add_midi_row(1.into(), 60.into());
     /// ```
-    pub fn add_midi_row(&mut self, channel: MidiChannel, note: u8) {
+    pub fn add_midi_row(&mut self, channel: MidiChannel, note: MidiNote) {
         self.add_row(RowKit::new_midi(channel, note));
     }
 
+    /// Add a MIDI row playing a note name like "C#3"
+    ///
+    /// The name uses the octave numbering of the Deluge where the note 60 is C3,
+    /// see [note_name_to_number](crate::note_name_to_number) to convert a name using the MIDI convention.
+    /// ```
+    /// use deluge::Kit;
+    ///
+    /// let mut kit = Kit::default();
+    /// kit.add_midi_row_named(1.into(), "C#3").unwrap();
+    ///
+    /// assert_eq!(kit.rows[1].as_midi().unwrap().note, 61.into());
+    /// ```
+    pub fn add_midi_row_named(&mut self, channel: MidiChannel, note_name: &str) -> Result<(), NoteParseError> {
+        let note = note_name_to_number(note_name, OctaveConvention::Deluge)?;
+
+        self.add_midi_row(channel, note.into());
+
+        Ok(())
+    }
+
     /// Add a CV gate row
     /// ```
     /// use deluge::Kit;
@@ -158,31 +590,88 @@ impl Kit {
 /// This implementation returns a Kit exactly like the Deluge would create it without any user changes.
 impl Default for Kit {
     fn default() -> Self {
-        let osc1 = SubtractiveOscillator::new_sample(Sample::OneZone(SampleOneZone {
-            file_path: SamplePath::default(),
-            zone: Some(SampleZone {
-                start: 0u64.into(),
-                end: 9999999u64.into(),
-                start_loop: None,
-                end_loop: None,
-            }),
-        }));
-        let osc2 = SubtractiveOscillator::new_sample(Sample::OneZone(SampleOneZone {
-            file_path: SamplePath::default(),
-            zone: None,
-        }));
+        Self::with_default_rows(1)
+    }
+}
 
-        let mut default_sound = Sound::new_subtractive(osc1, osc2);
+/// The rows of a [KitBuilder] and whether their conflicts are checked when the kit is built
+#[derive(Clone, Debug, Default)]
+struct KitBuilderRows {
+    rows: Option<Vec<RowKit>>,
+    validate: bool,
+}
 
-        default_sound.polyphonic = Polyphony::Auto;
-        default_sound.mod_knobs[12].control_param = "pitch".to_string();
+impl KitBuilderRows {
+    fn build(&self) -> Vec<RowKit> {
+        self.rows
+            .clone()
+            .unwrap_or_else(|| Kit::default().rows)
+    }
+}
 
-        Self::new(vec![RowKit::Sound(SoundRow::new(default_sound, "U1"))])
+impl CheckRange for KitBuilderRows {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        self.rows.check_range(path)
     }
 }
 
 // KitBuilder is generated by derive_builder::Builder.
 impl KitBuilder {
+    pub fn rows(&mut self, rows: Vec<RowKit>) -> &mut Self {
+        self.rows.rows = Some(rows);
+
+        self
+    }
+
+    pub fn add_row(&mut self, row: RowKit) -> &mut Self {
+        self.rows
+            .rows
+            .get_or_insert_with(Vec::new)
+            .push(row);
+
+        self
+    }
+
+    /// Check the rows conflicts when the kit is built, see [Kit::check_row_conflicts]
+    /// ```
+    /// use deluge::KitBuilder;
+    ///
+    /// let result = KitBuilder::default()
+    ///     .add_gate_row(1.into())
+    ///     .add_gate_row(1.into())
+    ///     .validate_rows(true)
+    ///     .build();
+    ///
+    /// assert!(result.is_err());
+    /// ```
+    pub fn validate_rows(&mut self, validate: bool) -> &mut Self {
+        self.rows.validate = validate;
+
+        self
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.check_ranges()?;
+
+        if !self.rows.validate {
+            return Ok(());
+        }
+
+        match self
+            .rows
+            .rows
+            .as_deref()
+            .map(row_conflict::find_row_conflicts)
+        {
+            Some(conflicts) if !conflicts.is_empty() => Err(conflicts
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")),
+            _ => Ok(()),
+        }
+    }
+
     /// Add a sound row with a custom name
     /// ```
     /// use deluge::{Kit, Sound, KitBuilder, SamplePath};
@@ -199,6 +688,7 @@ impl KitBuilder {
             &format!(
                 "U{}",
                 self.rows
+                    .rows
                     .as_ref()
                     .map(|rows| rows.len())
                     .unwrap_or_default()
@@ -213,7 +703,7 @@ impl KitBuilder {
         self.add_row(RowKit::new_sound(sound, name))
     }
 
-    pub fn add_midi_row(&mut self, channel: MidiChannel, note: u8) -> &mut Self {
+    pub fn add_midi_row(&mut self, channel: MidiChannel, note: MidiNote) -> &mut Self {
         self.add_row(RowKit::new_midi(channel, note))
     }
 
@@ -223,12 +713,22 @@ impl KitBuilder {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct Lpf {
     pub frequency: HexU50,
     pub resonance: HexU50,
 }
 
+check_ranges!(Lpf, LpfBuilder { frequency, resonance });
+
+impl Lpf {
+    /// Create a filter from the low pass filter parameters of a [SubtractiveSynth](crate::SubtractiveSynth)
+    pub fn from_sound_filter(frequency: HexU50, resonance: HexU50) -> Self {
+        Self { frequency, resonance }
+    }
+}
+
 impl Default for Lpf {
     fn default() -> Self {
         Self {
@@ -239,12 +739,22 @@ impl Default for Lpf {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct Hpf {
     pub frequency: HexU50,
     pub resonance: HexU50,
 }
 
+check_ranges!(Hpf, HpfBuilder { frequency, resonance });
+
+impl Hpf {
+    /// Create a filter from the high pass filter parameters of a [SubtractiveSynth](crate::SubtractiveSynth)
+    pub fn from_sound_filter(frequency: HexU50, resonance: HexU50) -> Self {
+        Self { frequency, resonance }
+    }
+}
+
 impl Default for Hpf {
     fn default() -> Self {
         Self {
@@ -256,8 +766,245 @@ impl Default for Hpf {
 
 #[cfg(test)]
 mod tests {
-    use crate::{deserialize_kit, serialize_kit, Kit};
+    use crate::{
+        deserialize_kit, serialize_kit, values::HexU50, FmCarrier, Kit, KitError, NoteParseError, RowKit, SamplePath, Sound,
+    };
     use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    fn make_kit_with_midi_rows(notes: &[u8], selected_row_index: Option<u32>) -> Kit {
+        let mut kit = Kit::new(
+            notes
+                .iter()
+                .map(|note| RowKit::new_midi(1.into(), (*note).into()))
+                .collect(),
+        );
+
+        kit.selected_row_index = selected_row_index;
+        kit
+    }
+
+    fn get_notes(kit: &Kit) -> Vec<u8> {
+        kit.rows
+            .iter()
+            .map(|row| row.as_midi().unwrap().note.as_u8())
+            .collect()
+    }
+
+    #[test]
+    fn test_add_midi_row_named() {
+        let mut kit = Kit::new(Vec::new());
+
+        kit.add_midi_row_named(1.into(), "C3")
+            .unwrap();
+        kit.add_midi_row_named(1.into(), "C4")
+            .unwrap();
+        kit.add_midi_row_named(1.into(), "Bb2")
+            .unwrap();
+
+        assert_eq!(get_notes(&kit), vec![60, 72, 58]);
+        assert_eq!(kit.add_midi_row_named(1.into(), "G#8"), Err(NoteParseError::OutOfRange(128)));
+        assert_eq!(kit.rows.len(), 3);
+    }
+
+    #[test]
+    fn test_remove_row() {
+        let mut kit = make_kit_with_midi_rows(&[0, 1, 2], Some(2));
+
+        assert_eq!(
+            kit.remove_row(0)
+                .unwrap()
+                .as_midi()
+                .unwrap()
+                .note,
+            0.into()
+        );
+        assert_eq!(get_notes(&kit), vec![1, 2]);
+        assert_eq!(kit.selected_row_index, Some(1));
+        assert_eq!(
+            kit.remove_row(1)
+                .unwrap()
+                .as_midi()
+                .unwrap()
+                .note,
+            2.into()
+        );
+        assert_eq!(kit.selected_row_index, None);
+        assert_eq!(kit.remove_row(1), Err(KitError::RowIndexOutOfBounds(1, 1)));
+    }
+
+    #[test]
+    fn test_move_row() {
+        let mut kit = make_kit_with_midi_rows(&[0, 1, 2, 3], Some(1));
+
+        kit.move_row(0, 3).unwrap();
+        assert_eq!(get_notes(&kit), vec![1, 2, 3, 0]);
+        assert_eq!(kit.selected_row_index, Some(0));
+
+        kit.move_row(3, 0).unwrap();
+        assert_eq!(get_notes(&kit), vec![0, 1, 2, 3]);
+        assert_eq!(kit.selected_row_index, Some(1));
+
+        kit.move_row(1, 2).unwrap();
+        assert_eq!(get_notes(&kit), vec![0, 2, 1, 3]);
+        assert_eq!(kit.selected_row_index, Some(2));
+
+        assert_eq!(kit.move_row(0, 4), Err(KitError::RowIndexOutOfBounds(4, 4)));
+    }
+
+    #[test]
+    fn test_swap_rows() {
+        let mut kit = make_kit_with_midi_rows(&[0, 1, 2], Some(0));
+
+        kit.swap_rows(0, 2).unwrap();
+        assert_eq!(get_notes(&kit), vec![2, 1, 0]);
+        assert_eq!(kit.selected_row_index, Some(2));
+
+        kit.swap_rows(0, 1).unwrap();
+        assert_eq!(kit.selected_row_index, Some(2));
+
+        assert_eq!(kit.swap_rows(3, 0), Err(KitError::RowIndexOutOfBounds(3, 3)));
+    }
+
+    #[test]
+    fn test_find_sound_row() {
+        let mut kit = deserialize_kit(include_str!("../data_tests/KITS/KIT_TEST_SOUNDS_MIDI_GATE.XML")).unwrap();
+
+        assert_eq!(kit.sound_rows().count(), kit.rows.len() - 2);
+        assert_eq!(
+            kit.find_sound_row("halftime_goodie3")
+                .unwrap()
+                .name,
+            "halftime_goodie3"
+        );
+        assert!(kit
+            .find_sound_row("HALFTIME_GOODIE3")
+            .is_none());
+        assert!(kit
+            .find_sound_row_ignore_case("HALFTIME_GOODIE3")
+            .is_some());
+        assert!(kit.find_sound_row("heu").is_none());
+
+        kit.find_sound_row_mut("halftime_goodie3")
+            .unwrap()
+            .sound
+            .envelope1
+            .attack = 12.into();
+
+        assert_eq!(
+            kit.find_sound_row("halftime_goodie3")
+                .unwrap()
+                .sound
+                .envelope1
+                .attack,
+            HexU50::new(12)
+        );
+    }
+
+    #[test]
+    fn test_get_sample_paths_kit30() {
+        let kit = deserialize_kit(include_str!("../data_tests/KITS/KIT030.XML")).unwrap();
+        let paths = kit.get_sample_paths();
+
+        assert_eq!(paths.len(), 8);
+        assert!(paths.contains(&SamplePath::new("SAMPLES/ARTISTS/CHAZ/CB4-Cassette808_BD02.wav").unwrap()));
+        assert!(paths.contains(&SamplePath::new("SAMPLES/ARTISTS/CHAZ/CB8-yo.wav").unwrap()));
+    }
+
+    #[test]
+    fn test_replace_sample_paths_kit30() {
+        let mut kit = deserialize_kit(include_str!("../data_tests/KITS/KIT030.XML")).unwrap();
+        let bd02 = SamplePath::new("SAMPLES/ARTISTS/CHAZ/CB4-Cassette808_BD02.wav").unwrap();
+        let yo = SamplePath::new("SAMPLES/ARTISTS/CHAZ/CB8-yo.wav").unwrap();
+        let new_bd02 = SamplePath::new("SAMPLES/KIT030/CB4-Cassette808_BD02.wav").unwrap();
+        let new_yo = SamplePath::new("SAMPLES/KIT030/CB8-yo.wav").unwrap();
+        let replacements = BTreeMap::from([(bd02.clone(), new_bd02.clone()), (yo.clone(), new_yo.clone())]);
+
+        assert_eq!(kit.replace_sample_paths(&replacements), 2);
+
+        let paths = kit.get_sample_paths();
+
+        assert_eq!(paths.len(), 8);
+        assert!(paths.contains(&new_bd02));
+        assert!(paths.contains(&new_yo));
+        assert!(!paths.contains(&bd02));
+        assert!(!paths.contains(&yo));
+        assert_eq!(kit.replace_sample_paths(&replacements), 0);
+    }
+
+    #[test]
+    fn test_get_sample_paths_default_kit() {
+        assert!(Kit::default()
+            .get_sample_paths()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_row_name() {
+        let mut kit = make_kit_with_midi_rows(&[0], None);
+
+        kit.add_named_sound(Sound::default(), "Kick");
+        kit.add_gate_row(1.into());
+
+        assert_eq!(kit.rows[0].name(), None);
+        assert_eq!(kit.rows[1].name(), Some("Kick"));
+        assert_eq!(kit.rows[2].set_name("Gate"), Err(KitError::UnnamedRow));
+    }
+
+    #[test]
+    fn test_rename_row() {
+        let mut kit = make_kit_with_midi_rows(&[0], None);
+
+        kit.add_named_sound(Sound::default(), "Kick");
+        kit.add_named_sound(Sound::default(), "Snare");
+
+        assert_eq!(kit.rename_row(1, "", false), Err(KitError::EmptyRowName));
+        assert_eq!(kit.rename_row(0, "Midi", false), Err(KitError::UnnamedRow));
+        assert_eq!(kit.rename_row(3, "Hat", false), Err(KitError::RowIndexOutOfBounds(3, 3)));
+        assert_eq!(
+            kit.rename_row(1, "snare", true),
+            Err(KitError::DuplicateRowName("snare".to_string()))
+        );
+
+        kit.rename_row(1, "Kick", true).unwrap();
+        kit.rename_row(1, "Snare", false)
+            .unwrap();
+
+        assert_eq!(kit.rows[1].name(), Some("Snare"));
+        assert_eq!(kit.rows[2].name(), Some("Snare"));
+    }
+
+    #[test]
+    fn test_empty_row_name_is_not_written() {
+        let mut kit = Kit::default();
+
+        kit.rows[0].set_name("").unwrap();
+
+        let xml = serialize_kit(&kit).unwrap();
+        let reloaded_kit = deserialize_kit(&xml).unwrap();
+
+        assert!(!xml.contains("name=\"\""));
+        assert_eq!(reloaded_kit.rows[0].name(), Some(""));
+    }
+
+    #[test]
+    fn test_current_row_out_of_bounds() {
+        let mut kit = make_kit_with_midi_rows(&[0], Some(3));
+
+        assert_eq!(kit.current_row(), None);
+        assert_eq!(kit.current_row_mut(), None);
+    }
+
+    #[test]
+    fn test_set_selected_row() {
+        let mut kit = make_kit_with_midi_rows(&[0, 1], None);
+
+        kit.set_selected_row(1).unwrap();
+        assert_eq!(kit.selected_row_index, Some(1));
+
+        assert_eq!(kit.set_selected_row(2), Err(KitError::RowIndexOutOfBounds(2, 2)));
+        assert_eq!(kit.selected_row_index, Some(1));
+    }
 
     #[test]
     fn default_kit_test() {
@@ -267,6 +1014,83 @@ mod tests {
         assert_eq!(expected_default_kit, default_kit)
     }
 
+    #[test]
+    fn test_default_kit_row() {
+        let kit = deserialize_kit(include_str!("../data_tests/default/KIT Default Test.XML")).unwrap();
+
+        assert_eq!(
+            kit.rows[0]
+                .as_sound()
+                .unwrap()
+                .sound
+                .as_ref(),
+            &Sound::default_kit_row()
+        );
+    }
+
+    #[test]
+    fn test_with_default_rows() {
+        // There is no 16 rows kit saved by the device in data_tests, the expected kit repeats the row of the captured
+        // default kit. The names of the rows are checked with "Test Pan.XML".
+        let mut expected_kit = deserialize_kit(include_str!("../data_tests/default/KIT Default Test.XML")).unwrap();
+        let default_row = expected_kit.rows[0].clone();
+
+        for number in 2..=16 {
+            let mut row = default_row.clone();
+
+            row.set_name(&format!("U{}", number))
+                .unwrap();
+            expected_kit.rows.push(row);
+        }
+
+        assert_eq!(Kit::with_default_rows(16), expected_kit);
+        assert_eq!(Kit::with_default_rows(1), Kit::default());
+    }
+
+    #[test]
+    fn test_with_default_rows_names() {
+        let kit = deserialize_kit(include_str!("../data_tests/KITS/Test Pan.XML")).unwrap();
+        let kit_with_default_rows = Kit::with_default_rows(kit.rows.len());
+
+        assert_eq!(
+            kit_with_default_rows
+                .rows
+                .iter()
+                .map(|row| row.name())
+                .collect::<Vec<_>>(),
+            kit.rows
+                .iter()
+                .map(|row| row.name())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_fill_rows_from_template() {
+        let mut kit = Kit::default();
+        let template = Sound::new_fm(FmCarrier::default(), FmCarrier::default());
+
+        kit.fill_rows_from_template(&template, 3);
+
+        assert_eq!(kit.rows.len(), 4);
+        assert_eq!(
+            kit.rows
+                .iter()
+                .map(|row| row.name().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["U1", "U2", "U3", "U4"]
+        );
+        assert_eq!(
+            kit.rows[3]
+                .as_sound()
+                .unwrap()
+                .sound
+                .as_ref(),
+            &template
+        );
+        assert_eq!(kit.selected_row_index, Some(0));
+    }
+
     #[test]
     fn test_load_write_load_kit_community_patches_synth_hats() {
         let kit = deserialize_kit(include_str!("../data_tests/KITS/Synth Hats.XML")).unwrap();
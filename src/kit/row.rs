@@ -1,8 +1,12 @@
 use crate::{
-    values::{CvGateChannel, MidiChannel},
-    Sound,
+    range_check::check_ranges,
+    values::{CvGateChannel, MidiChannel, MidiNote, Polyphony},
+    KitError, Sound, Synth,
 };
 
+/// The index of the mod knob controlling the portamento in a synth and the pitch in a kit row, see [Sound::default_kit_row]
+const PITCH_MOD_KNOB_INDEX: usize = 12;
+
 /// A row in a kit
 ///
 /// There are 3 different types of row for the Deluge:
@@ -11,6 +15,8 @@ use crate::{
 ///  - CV gate
 /// Each row in a Kit is an output and can be any of the 3 types.
 #[derive(Clone, Debug, PartialEq, Eq, enum_as_inner::EnumAsInner)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
 pub enum RowKit {
     Sound(SoundRow),
     Midi(MidiRow),
@@ -22,17 +28,69 @@ impl RowKit {
         RowKit::Sound(SoundRow::new(sound, name))
     }
 
-    pub fn new_midi(channel: MidiChannel, note: u8) -> Self {
+    pub fn new_midi(channel: MidiChannel, note: MidiNote) -> Self {
         RowKit::Midi(MidiRow { channel, note })
     }
 
     pub fn new_cv_gate(channel: CvGateChannel) -> Self {
         RowKit::CvGate(CvGateRow { channel })
     }
+
+    /// Create a sound row playing the sound of a synth patch
+    ///
+    /// The sound is adapted like the Deluge does for the rows of a new kit: the polyphony is set to auto and the
+    /// mod knob controlling the portamento in a default synth controls the pitch instead.
+    /// ```
+    /// # use deluge::{RowKit, Synth};
+    /// let row = RowKit::from_synth(&Synth::default(), "snare");
+    /// let sound_row = row.as_sound().unwrap();
+    ///
+    /// assert_eq!(sound_row.name, "snare");
+    /// assert_eq!(sound_row.sound.mod_knobs[12].control_param, "pitch");
+    /// ```
+    pub fn from_synth(synth: &Synth, name: &str) -> Self {
+        let mut sound = synth.sound.clone();
+
+        sound.polyphonic = Polyphony::Auto;
+
+        if let Some(mod_knob) = sound
+            .mod_knobs
+            .get_mut(PITCH_MOD_KNOB_INDEX)
+        {
+            if mod_knob.control_param == "portamento" {
+                mod_knob.control_param = "pitch".to_string();
+            }
+        }
+
+        RowKit::new_sound(sound, name)
+    }
+
+    /// Get the name of the row, only the sound rows have a name
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            RowKit::Sound(sound_row) => Some(&sound_row.name),
+            RowKit::Midi(_) | RowKit::CvGate(_) => None,
+        }
+    }
+
+    /// Set the name of the row, returns an error if the row is a MIDI or a CV gate row
+    ///
+    /// An empty name is not written in the patch, see [Kit::rename_row](crate::Kit::rename_row) to reject empty names.
+    pub fn set_name(&mut self, name: &str) -> Result<(), KitError> {
+        match self {
+            RowKit::Sound(sound_row) => {
+                sound_row.name = name.to_string();
+
+                Ok(())
+            }
+            RowKit::Midi(_) | RowKit::CvGate(_) => Err(KitError::UnnamedRow),
+        }
+    }
 }
 
 /// Audio output is a regular synth patch with a name.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoundRow {
     /// Sound is 320 bytes so I'm boxing it to reduce the size of AudioOutput on the stack.
     /// Box allocates his memory on the heap.
@@ -41,6 +99,8 @@ pub struct SoundRow {
     pub name: String,
 }
 
+check_ranges!(SoundRow { sound, name });
+
 impl SoundRow {
     pub fn new(sound: Sound, name: &str) -> Self {
         Self {
@@ -52,19 +112,80 @@ impl SoundRow {
 
 /// The MIDI output is a MIDI channel and a MIDI note.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MidiRow {
     pub channel: MidiChannel,
-    pub note: u8,
+    pub note: MidiNote,
 }
 
+check_ranges!(MidiRow { channel, note });
+
 /// The CV Gate output is the CV Gate channel only
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CvGateRow {
     pub channel: CvGateChannel,
 }
 
+check_ranges!(CvGateRow { channel });
+
 impl CvGateRow {
     pub fn new(channel: CvGateChannel) -> Self {
         Self { channel }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deserialize_kit, deserialize_synth, serialize_kit, Kit};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_from_synth() {
+        let synth = deserialize_synth(include_str!("../data_tests/SYNTHS/SYNT061.XML")).unwrap();
+        let row = RowKit::from_synth(&synth, "SYNT061");
+        let sound = &row.as_sound().unwrap().sound;
+
+        assert_eq!(sound.polyphonic, Polyphony::Auto);
+        assert_eq!(sound.mod_knobs[PITCH_MOD_KNOB_INDEX].control_param, "pitch");
+        assert_eq!(
+            Sound {
+                polyphonic: synth.sound.polyphonic.clone(),
+                mod_knobs: synth.sound.mod_knobs.clone(),
+                ..sound.as_ref().clone()
+            },
+            synth.sound
+        );
+    }
+
+    #[test]
+    fn test_from_synth_default_is_like_default_kit_row() {
+        let expected_sound = Sound::default_kit_row();
+        let synth = Synth::new(Sound {
+            generator: expected_sound.generator.clone(),
+            ..Sound::default()
+        });
+
+        assert_eq!(RowKit::from_synth(&synth, "U1"), RowKit::new_sound(expected_sound, "U1"));
+    }
+
+    #[test]
+    fn test_save_load_kit_with_row_from_synth() {
+        let synth = deserialize_synth(include_str!("../data_tests/SYNTHS/SYNT061.XML")).unwrap();
+        let mut kit = Kit::default();
+
+        kit.rows
+            .push(RowKit::from_synth(&synth, "SYNT061"));
+
+        let reloaded_kit = deserialize_kit(&serialize_kit(&kit).unwrap()).unwrap();
+        let reloaded_row = reloaded_kit.rows[1].as_sound().unwrap();
+
+        assert_eq!(reloaded_row.name, "SYNT061");
+        assert_eq!(reloaded_kit.rows[1], kit.rows[1]);
+        assert_eq!(
+            Synth::from_sound_row(reloaded_row).sound,
+            *kit.rows[1].as_sound().unwrap().sound
+        );
+    }
+}
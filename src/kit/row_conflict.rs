@@ -0,0 +1,167 @@
+use crate::{
+    values::{CvGateChannel, MidiChannel, MidiNote},
+    Kit, RowKit,
+};
+
+/// Several rows of a kit sending to the same output, see [Kit::check_row_conflicts]
+///
+/// The Deluge accepts such kits but it's usually a mistake of the code generating the kit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RowConflict {
+    /// Several CV gate rows use the same channel
+    CvGateChannel { channel: CvGateChannel, indexes: Vec<usize> },
+    /// Several MIDI rows send the same note on the same channel
+    MidiNote {
+        channel: MidiChannel,
+        note: MidiNote,
+        indexes: Vec<usize>,
+    },
+}
+
+impl RowConflict {
+    /// Get the indexes of the conflicting rows
+    pub fn indexes(&self) -> &[usize] {
+        match self {
+            RowConflict::CvGateChannel { indexes, .. } => indexes,
+            RowConflict::MidiNote { indexes, .. } => indexes,
+        }
+    }
+
+    fn indexes_mut(&mut self) -> &mut Vec<usize> {
+        match self {
+            RowConflict::CvGateChannel { indexes, .. } => indexes,
+            RowConflict::MidiNote { indexes, .. } => indexes,
+        }
+    }
+}
+
+impl std::fmt::Display for RowConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RowConflict::CvGateChannel { channel, indexes } => {
+                write!(f, "rows {:?} use the CV gate channel {}", indexes, channel.as_u8())
+            }
+            RowConflict::MidiNote { channel, note, indexes } => {
+                write!(
+                    f,
+                    "rows {:?} use the MIDI channel {} and the note {}",
+                    indexes,
+                    channel.as_u8(),
+                    note
+                )
+            }
+        }
+    }
+}
+
+/// Find the rows sharing the same output, the conflicts are ordered by the index of their first row
+pub(crate) fn find_row_conflicts(rows: &[RowKit]) -> Vec<RowConflict> {
+    let mut conflicts: Vec<RowConflict> = Vec::new();
+
+    for (index, row) in rows.iter().enumerate() {
+        let existing = conflicts
+            .iter_mut()
+            .find(|conflict| match (conflict, row) {
+                (RowConflict::CvGateChannel { channel, .. }, RowKit::CvGate(row)) => *channel == row.channel,
+                (RowConflict::MidiNote { channel, note, .. }, RowKit::Midi(row)) => *channel == row.channel && *note == row.note,
+                _ => false,
+            });
+
+        if let Some(conflict) = existing {
+            conflict.indexes_mut().push(index);
+            continue;
+        }
+
+        match row {
+            RowKit::CvGate(row) => conflicts.push(RowConflict::CvGateChannel {
+                channel: row.channel,
+                indexes: vec![index],
+            }),
+            RowKit::Midi(row) => conflicts.push(RowConflict::MidiNote {
+                channel: row.channel,
+                note: row.note,
+                indexes: vec![index],
+            }),
+            RowKit::Sound(_) => {}
+        }
+    }
+
+    conflicts.retain(|conflict| conflict.indexes().len() > 1);
+    conflicts
+}
+
+impl Kit {
+    /// Find the CV gate rows using the same channel and the MIDI rows sending the same note on the same channel
+    /// ```
+    /// # use deluge::{Kit, RowConflict};
+    /// let mut kit = Kit::default();
+    ///
+    /// kit.add_gate_row(1.into());
+    /// kit.add_midi_row(1.into(), 60.into());
+    /// kit.add_gate_row(1.into());
+    ///
+    /// assert_eq!(
+    ///     kit.check_row_conflicts(),
+    ///     vec![RowConflict::CvGateChannel { channel: 1.into(), indexes: vec![1, 3] }]
+    /// );
+    /// ```
+    pub fn check_row_conflicts(&self) -> Vec<RowConflict> {
+        find_row_conflicts(&self.rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Kit, KitBuilder, RowConflict, RowKit};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_check_row_conflicts_midi() {
+        let kit = Kit::new(vec![
+            RowKit::new_midi(1.into(), 60.into()),
+            RowKit::new_midi(2.into(), 60.into()),
+            RowKit::new_midi(1.into(), 61.into()),
+            RowKit::new_midi(1.into(), 60.into()),
+            RowKit::new_midi(1.into(), 60.into()),
+        ]);
+
+        assert_eq!(
+            kit.check_row_conflicts(),
+            vec![RowConflict::MidiNote {
+                channel: 1.into(),
+                note: 60.into(),
+                indexes: vec![0, 3, 4]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_row_conflicts_without_conflict() {
+        let kit = Kit::new(vec![
+            RowKit::new_cv_gate(1.into()),
+            RowKit::new_cv_gate(2.into()),
+            RowKit::new_midi(1.into(), 60.into()),
+        ]);
+
+        assert!(kit.check_row_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_builder_validate_rows() {
+        let mut builder = KitBuilder::default();
+
+        builder
+            .add_gate_row(2.into())
+            .add_gate_row(2.into());
+
+        assert!(builder.build().is_ok());
+        assert!(builder
+            .validate_rows(true)
+            .build()
+            .is_err());
+
+        builder.rows(vec![RowKit::new_cv_gate(2.into())]);
+
+        assert_eq!(builder.build().unwrap().rows.len(), 1);
+    }
+}
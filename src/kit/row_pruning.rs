@@ -0,0 +1,170 @@
+use crate::{Kit, RowKit, Sound};
+
+/// Select the rows removed by [Kit::prune_empty_rows_with]
+///
+/// The sound rows left as created by the Deluge are always removed, see [Kit::prune_empty_rows].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PruneRowsOptions {
+    /// Remove the MIDI rows too
+    pub remove_midi_rows: bool,
+    /// Remove the CV gate rows too
+    pub remove_cv_gate_rows: bool,
+}
+
+impl Kit {
+    /// Remove the sound rows left as created by the Deluge, returns the rows removed in their order in the kit
+    ///
+    /// A row is empty when its sound is [Sound::default_kit_row]: the oscillators play an empty sample and
+    /// the parameters were not changed. The names of the rows and the mod knobs are ignored.
+    /// The selected row index is updated to keep pointing to the same row, it's cleared if the selected row is removed.
+    /// ```
+    /// # use deluge::Kit;
+    /// let mut kit = Kit::with_default_rows(3);
+    ///
+    /// kit.rows[1].as_sound_mut().unwrap().sound.volume = 20.into();
+    ///
+    /// assert_eq!(kit.prune_empty_rows().len(), 2);
+    /// assert_eq!(kit.rows.len(), 1);
+    /// assert_eq!(kit.rows[0].name(), Some("U2"));
+    /// ```
+    pub fn prune_empty_rows(&mut self) -> Vec<RowKit> {
+        self.prune_empty_rows_with(&PruneRowsOptions::default())
+    }
+
+    /// Remove the empty sound rows and the MIDI or CV gate rows selected by the options
+    ///
+    /// See [Kit::prune_empty_rows].
+    pub fn prune_empty_rows_with(&mut self, options: &PruneRowsOptions) -> Vec<RowKit> {
+        let default_sound = Sound::default_kit_row();
+        let indices = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| match row {
+                RowKit::Sound(sound_row) => sound_row
+                    .sound
+                    .sonically_equals(&default_sound),
+                RowKit::Midi(_) => options.remove_midi_rows,
+                RowKit::CvGate(_) => options.remove_cv_gate_rows,
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<usize>>();
+
+        // Remove from the last row so the indices of the rows remaining to remove don't change.
+        let mut removed_rows = indices
+            .into_iter()
+            .rev()
+            .map(|index| self.remove_row(index).unwrap())
+            .collect::<Vec<RowKit>>();
+
+        removed_rows.reverse();
+
+        removed_rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{values::SamplePath, Kit, PruneRowsOptions, RowKit, Sound};
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    /// A kit with the rows: empty, sample, MIDI, empty, CV gate, empty
+    fn mixed_kit() -> Kit {
+        let mut kit = Kit::new(Vec::new());
+
+        kit.add_sound_row(Sound::default_kit_row());
+        kit.add_sound_row(Sound::new_sample(
+            SamplePath::new("SAMPLES/KICK.WAV").unwrap(),
+            0u64.into(),
+            100u64.into(),
+        ));
+        kit.add_midi_row(1.into(), 60.into());
+        kit.add_sound_row(Sound::default_kit_row());
+        kit.add_gate_row(1.into());
+        kit.add_sound_row(Sound::default_kit_row());
+
+        kit
+    }
+
+    fn row_names(kit: &Kit) -> Vec<Option<&str>> {
+        kit.rows
+            .iter()
+            .map(RowKit::name)
+            .collect()
+    }
+
+    #[test]
+    fn test_prune_empty_rows() {
+        let mut kit = mixed_kit();
+        let removed_rows = kit.prune_empty_rows();
+
+        assert_eq!(row_names(&kit), vec![Some("U2"), None, None]);
+        assert!(kit.rows[1].is_midi());
+        assert!(kit.rows[2].is_cv_gate());
+        assert_eq!(
+            removed_rows
+                .iter()
+                .map(RowKit::name)
+                .collect::<Vec<_>>(),
+            vec![Some("U1"), Some("U4"), Some("U6")]
+        );
+    }
+
+    #[test]
+    fn test_prune_empty_rows_keeps_changed_parameters() {
+        let mut kit = Kit::with_default_rows(2);
+
+        kit.rows[0]
+            .as_sound_mut()
+            .unwrap()
+            .sound
+            .reverb_amount = 20.into();
+        kit.rows[1]
+            .as_sound_mut()
+            .unwrap()
+            .sound
+            .mod_knobs[0]
+            .control_param = "volume".to_string();
+
+        assert_eq!(
+            kit.prune_empty_rows()
+                .iter()
+                .map(RowKit::name)
+                .collect::<Vec<_>>(),
+            vec![Some("U2")]
+        );
+        assert_eq!(row_names(&kit), vec![Some("U1")]);
+    }
+
+    #[test_case(false, false, 3 ; "sound rows only")]
+    #[test_case(true, false, 2 ; "midi rows")]
+    #[test_case(false, true, 2 ; "cv gate rows")]
+    #[test_case(true, true, 1 ; "all rows")]
+    fn test_prune_empty_rows_with_options(remove_midi_rows: bool, remove_cv_gate_rows: bool, expected_count: usize) {
+        let mut kit = mixed_kit();
+        let options = PruneRowsOptions {
+            remove_midi_rows,
+            remove_cv_gate_rows,
+        };
+
+        kit.prune_empty_rows_with(&options);
+
+        assert_eq!(kit.rows.len(), expected_count);
+        assert_eq!(kit.rows[0].name(), Some("U2"));
+    }
+
+    #[test_case(Some(1), Some(0) ; "selected sound row is shifted")]
+    #[test_case(Some(2), Some(1) ; "selected midi row is shifted")]
+    #[test_case(Some(4), Some(2) ; "selected cv gate row is shifted")]
+    #[test_case(Some(3), None ; "selected row removed")]
+    #[test_case(None, None ; "no selected row")]
+    fn test_prune_empty_rows_selected_row_index(selected_row_index: Option<u32>, expected: Option<u32>) {
+        let mut kit = mixed_kit();
+
+        kit.selected_row_index = selected_row_index;
+        kit.prune_empty_rows();
+
+        assert_eq!(kit.selected_row_index, expected);
+    }
+}
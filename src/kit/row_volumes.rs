@@ -0,0 +1,159 @@
+use crate::{values::HexU50, Kit};
+
+/// The highest volume of a row
+const MAX_VOLUME: i16 = 50;
+
+/// A sound row whose volume was clamped in the range [0; 50], see [Kit::scale_row_volumes]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClampedVolume {
+    /// The index of the row in the kit
+    pub index: usize,
+    /// The volume before the change
+    pub original: HexU50,
+    /// The volume set, the closest to the requested volume in the range [0; 50]
+    pub volume: HexU50,
+}
+
+impl Kit {
+    /// Add a delta to the volume of each sound row, the volumes are saturated in the range [0; 50]
+    ///
+    /// Returns the rows whose volume was clamped, ordered by index. The MIDI and CV gate rows are ignored.
+    /// ```
+    /// # use deluge::{ClampedVolume, Kit};
+    /// let mut kit = Kit::with_default_rows(2);
+    ///
+    /// kit.rows[1].as_sound_mut().unwrap().sound.volume = 4.into();
+    ///
+    /// // Lower the rows and raise the kit to compensate
+    /// let clamped_rows = kit.scale_row_volumes(-6);
+    /// kit.volume = kit.volume + 6;
+    ///
+    /// assert_eq!(
+    ///     clamped_rows,
+    ///     vec![ClampedVolume { index: 1, original: 4.into(), volume: 0.into() }]
+    /// );
+    /// ```
+    pub fn scale_row_volumes(&mut self, delta: i8) -> Vec<ClampedVolume> {
+        self.update_row_volumes(|volume| i16::from(volume.as_u8()) + i16::from(delta))
+    }
+
+    /// Scale the volumes of the sound rows so the loudest row has the target volume
+    ///
+    /// The other rows keep the same proportion to the loudest row, the volumes are rounded to the nearest step and
+    /// saturated in the range [0; 50]. Nothing changes if all the rows are silent.
+    /// Returns the rows whose volume was clamped, ordered by index. The MIDI and CV gate rows are ignored.
+    pub fn normalize_row_volumes(&mut self, target: HexU50) -> Vec<ClampedVolume> {
+        let loudest = match self
+            .sounds()
+            .map(|sound| sound.volume.as_u8())
+            .max()
+        {
+            Some(loudest) if loudest > 0 => f32::from(loudest),
+            _ => return Vec::new(),
+        };
+        let ratio = f32::from(target.as_u8()) / loudest;
+
+        self.update_row_volumes(|volume| (f32::from(volume.as_u8()) * ratio).round() as i16)
+    }
+
+    fn update_row_volumes(&mut self, new_volume: impl Fn(HexU50) -> i16) -> Vec<ClampedVolume> {
+        let mut clamped_volumes = Vec::new();
+
+        for (index, row) in self.rows.iter_mut().enumerate() {
+            let sound = match row.as_sound_mut() {
+                Some(row) => &mut row.sound,
+                None => continue,
+            };
+            let requested = new_volume(sound.volume);
+            let volume = HexU50::new(requested.clamp(0, MAX_VOLUME) as u8);
+
+            if i16::from(volume.as_u8()) != requested {
+                clamped_volumes.push(ClampedVolume {
+                    index,
+                    original: sound.volume,
+                    volume,
+                });
+            }
+
+            sound.volume = volume;
+        }
+
+        clamped_volumes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{values::HexU50, ClampedVolume, Kit, RowKit, Sound};
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    fn kit_with_volumes(volumes: &[u8]) -> Kit {
+        let mut kit = Kit::new(Vec::new());
+
+        for (index, volume) in volumes.iter().enumerate() {
+            let sound = Sound {
+                volume: (*volume).into(),
+                ..Sound::default()
+            };
+
+            kit.rows
+                .push(RowKit::new_sound(sound, &format!("ROW{}", index)));
+        }
+
+        kit.add_midi_row(1.into(), 60.into());
+        kit
+    }
+
+    fn row_volumes(kit: &Kit) -> Vec<u8> {
+        kit.sounds()
+            .map(|sound| sound.volume.as_u8())
+            .collect()
+    }
+
+    fn clamped(index: usize, original: u8, volume: u8) -> ClampedVolume {
+        ClampedVolume {
+            index,
+            original: original.into(),
+            volume: volume.into(),
+        }
+    }
+
+    #[test_case(&[40, 25, 10], -6, &[34, 19, 4], vec![] ; "lower")]
+    #[test_case(&[40, 25, 10], 6, &[46, 31, 16], vec![] ; "raise")]
+    #[test_case(&[40, 25, 4, 0], -6, &[34, 19, 0, 0], vec![clamped(2, 4, 0), clamped(3, 0, 0)] ; "clamp min")]
+    #[test_case(&[48, 25, 50], 6, &[50, 31, 50], vec![clamped(0, 48, 50), clamped(2, 50, 50)] ; "clamp max")]
+    fn test_scale_row_volumes(volumes: &[u8], delta: i8, expected_volumes: &[u8], expected_clamped: Vec<ClampedVolume>) {
+        let mut kit = kit_with_volumes(volumes);
+
+        assert_eq!(kit.scale_row_volumes(delta), expected_clamped);
+        assert_eq!(row_volumes(&kit), expected_volumes);
+    }
+
+    #[test_case(&[40, 20, 10], 50, &[50, 25, 13] ; "louder")]
+    #[test_case(&[40, 20, 10], 20, &[20, 10, 5] ; "quieter")]
+    #[test_case(&[30, 0, 15], 30, &[30, 0, 15] ; "same")]
+    #[test_case(&[0, 0], 40, &[0, 0] ; "silent rows")]
+    fn test_normalize_row_volumes(volumes: &[u8], target: u8, expected_volumes: &[u8]) {
+        let mut kit = kit_with_volumes(volumes);
+
+        assert_eq!(kit.normalize_row_volumes(target.into()), vec![]);
+        assert_eq!(row_volumes(&kit), expected_volumes);
+    }
+
+    #[test]
+    fn test_normalize_row_volumes_out_of_range_target() {
+        let mut kit = kit_with_volumes(&[40, 10]);
+
+        assert_eq!(kit.normalize_row_volumes(HexU50::new(60)), vec![clamped(0, 40, 50)]);
+        assert_eq!(row_volumes(&kit), [50, 15]);
+    }
+
+    #[test]
+    fn test_row_volumes_without_sound_rows() {
+        let mut kit = Kit::new(vec![RowKit::new_cv_gate(1.into())]);
+
+        assert_eq!(kit.scale_row_volumes(-6), vec![]);
+        assert_eq!(kit.normalize_row_volumes(50.into()), vec![]);
+    }
+}
@@ -40,40 +40,95 @@
 //! to avoid overflows.
 //!
 //! Each structures of this crate can be created using the builder pattern.
+//!
+//! #### Serde
+//! When the feature `serde` is enabled, [Synth], [Kit], [Sound] and all the structures they contain implement
+//! `serde::Serialize` and `serde::Deserialize`. This allows to export a patch to another format such as JSON.
+//! The engines ([SynthEngine]) and the rows ([RowKit]) are internally tagged using the field `type`.
+//!
+//! #### WAV
+//! When the feature `wav` is enabled, `wav_info` reads the length of a WAV file from its header and
+//! `Sound::new_sample_from_file` creates a sound playing a whole sample of a card.
+//!
+//! #### Async
+//! When the feature `async` is enabled, the patches can be read and written without blocking using tokio, for example
+//! with `read_kit_from_file_async` and `write_kit_to_file_async`. A card can be opened with `Card::open_async` using
+//! a `TokioFileSystem`, an implementation of the trait `AsyncFileSystem`.
 
 mod card;
+mod diff;
+mod file_writing;
 mod kit;
+mod metadata;
+mod params;
+mod patch;
+mod range_check;
 mod samples;
 mod serialization;
+mod song;
 mod sound;
+mod summary;
 mod synth;
 mod values;
 
-pub use card::{Card, CardError, CardFolder, FileSystem, LocalFileSystem, PatchName};
-pub use kit::{CvGateRow, Hpf, HpfBuilder, Kit, KitBuilder, KitBuilderError, Lpf, LpfBuilder, MidiRow, RowKit, SoundRow};
+#[cfg(feature = "async")]
+pub use card::{AsyncFileSystem, TokioFileSystem};
+pub use card::{
+    Card, CardError, CardFolder, CardIndex, CardOpenError, CardReport, CollisionStrategy, FileMetadata, FileOperation,
+    FileSystem, FolderReport, GatherReport, IoErrorInfo, LocalFileSystem, MemoryFileSystem, OperationLog, PatchEntry,
+    PatchMetadata, PatchName, PatchNameError, PatchNameFix, ReadOnlyFileSystem, RenameReport, SampleIssue, ScanError,
+};
+pub use diff::{diff_kits, diff_sounds, FieldChange, PatchCompareOptions};
+pub use kit::{
+    ClampedVolume, CvGateRow, GlobalFx, GlobalFxMut, Hpf, HpfBuilder, Kit, KitBuilder, KitBuilderError, KitError, Lpf,
+    LpfBuilder, MidiRow, PruneRowsOptions, RowConflict, RowKit, SoundRow,
+};
+pub use metadata::Metadata;
+pub use params::{ParamRef, ParamValue};
+pub use patch::Patch;
+pub use samples::{
+    build_usage_index, read_sample_paths, read_sample_references, try_read_sample_paths, OscillatorSlot, SamplePathReplacer,
+    SampleReference, SampleScanError, SampleUsage, SampleUsageIndex,
+};
+#[cfg(feature = "wav")]
+pub use samples::{wav_info, WavError, WavInfo};
 pub use serialization::{
-    deserialize_kit, deserialize_kit_with_version, deserialize_synth, deserialize_synth_with_version, serialize_kit,
-    serialize_synth, PatchType, SerializationError, VersionInfo,
+    deserialize_kit, deserialize_kit_bytes, deserialize_kit_bytes_with_version, deserialize_kit_from_reader,
+    deserialize_kit_from_reader_with_version, deserialize_kit_lenient, deserialize_kit_with_clamped_values,
+    deserialize_kit_with_options, deserialize_kit_with_report, deserialize_kit_with_version, deserialize_patch,
+    deserialize_patch_bytes, deserialize_patch_from_reader, deserialize_sound, deserialize_sound_with_name, deserialize_synth,
+    deserialize_synth_bytes, deserialize_synth_bytes_with_version, deserialize_synth_from_reader,
+    deserialize_synth_from_reader_with_version, deserialize_synth_with_clamped_values, deserialize_synth_with_report,
+    deserialize_synth_with_version, peek_patch_info, peek_patch_info_from_reader, serialize_kit, serialize_kit_canonical,
+    serialize_kit_to_writer, serialize_kit_to_writer_with_options, serialize_kit_with_options, serialize_kit_with_version,
+    serialize_sound, serialize_sound_with_name, serialize_synth, serialize_synth_canonical, serialize_synth_to_writer,
+    serialize_synth_to_writer_with_options, serialize_synth_with_options, serialize_synth_with_version, ClampedValue,
+    FirmwareVersion, FormatVersion, MigratedField, MigrationKind, MigrationReport, Newline, OutOfRange, PatchInfo, PatchType,
+    RowError, SerializationError, SerializationOptions, VersionInfo, XmlFormat, LATEST_SUPPORTED_FIRMWARE_VERSION,
 };
+pub use song::extract_sounds;
 pub use sound::{
-    Arpeggiator, ArpeggiatorBuilder, Chorus, ChorusBuilder, Delay, DelayBuilder, Distorsion, DistorsionBuilder, Envelope,
-    EnvelopeBuilder, Equalizer, EqualizerBuilder, Flanger, FlangerBuilder, FmCarrier, FmCarrierBuilder, FmModulator,
-    FmModulatorBuilder, FmSynth, FmSynthBuilder, Lfo1, Lfo1Builder, Lfo2, Lfo2Builder, ModKnob, ModKnobBuilder, ModulationFx,
-    PatchCable, PatchCableBuilder, Phaser, PhaserBuilder, RingModSynth, Sample, SampleOneZone, SampleOscillator,
-    SampleOscillatorBuilder, SampleRange, SampleZone, Sidechain, Sound, SoundBuilder, SoundBuilderError, SubtractiveOscillator,
-    SubtractiveSynth, SubtractiveSynthBuilder, SynthEngine, Unison, UnisonBuilder, WaveformOscillator, WaveformOscillatorBuilder,
+    Arpeggiator, ArpeggiatorBuilder, Chorus, ChorusBuilder, ConversionError, Delay, DelayBuilder, Distorsion, DistorsionBuilder,
+    EngineError, Envelope, EnvelopeBuilder, Equalizer, EqualizerBuilder, Flanger, FlangerBuilder, FmCarrier, FmCarrierBuilder,
+    FmModulator, FmModulatorBuilder, FmSynth, FmSynthBuilder, IssueCode, IssueSeverity, Lfo1, Lfo1Builder, Lfo2, Lfo2Builder,
+    ModKnob, ModKnobBuilder, ModulationFx, MultisampleBuilder, MultisampleError, PatchCable, PatchCableBuilder, PatchIssue,
+    Phaser, PhaserBuilder, RangeIssue, RatioError, RingModSynth, Sample, SampleOneZone, SampleOscillator,
+    SampleOscillatorBuilder, SampleRange, SampleZone, SampleZoneError, Sidechain, Sound, SoundBuilder, SoundBuilderError,
+    Stretch, SubtractiveOscillator, SubtractiveSynth, SubtractiveSynthBuilder, SynthEngine, TransposeError, Unison,
+    UnisonBuilder, WaveformOscillator, WaveformOscillatorBuilder,
 };
-pub use synth::Synth;
+pub use synth::{Synth, SynthBuilder, SynthBuilderError};
 pub use values::{
-    ArpeggiatorMode, AttackSidechain, ClippingAmount, CvGateChannel, DecU50, FilterType, FineTranspose, HexU50, LfoShape,
-    LpfMode, MidiChannel, ModulationFxType, OctavesCount, OnOff, OscType, Pan, PitchSpeed, Polyphony, ReleaseSidechain,
+    note_name_to_number, note_number_to_name, ArpeggiatorMode, AttackSidechain, ClippingAmount, CvGateChannel, DecU50,
+    FilterType, FineTranspose, HexU50, LfoShape, LpfMode, MidiChannel, MidiNote, ModulationFxType, NoteParseError,
+    OctaveConvention, OctavesCount, OnOff, OscType, Pan, PatchDestination, PatchSource, PitchSpeed, Polyphony, ReleaseSidechain,
     RetrigPhase, SamplePath, SamplePlayMode, SamplePosition, SyncLevel, SynthMode, TableIndex, TimeStretchAmount, Transpose,
     UnisonDetune, UnisonVoiceCount, VoicePriority,
 };
-pub use samples::{SamplePathReplacer, read_sample_paths};
 
 use std::{
-    io::{Read, Write},
+    fs::File,
+    io::{BufRead, BufReader, Read, Write},
     path::{Path, PathBuf},
 };
 
@@ -87,14 +142,18 @@ pub enum ReadError {
 
     #[error("Error while reading '{1}': {0}")]
     ReadFileError(std::io::Error, PathBuf),
+
+    #[error("Deserialization error in '{1}': {0}")]
+    DeserializationFileError(SerializationError, PathBuf),
 }
 
 impl ReadError {
     pub fn new_file_error<P: AsRef<Path>>(error: ReadError, path: P) -> ReadError {
         match error {
-            ReadError::DeserializationError(e) => ReadError::DeserializationError(e),
+            ReadError::DeserializationError(e) => ReadError::DeserializationFileError(e, path.as_ref().to_path_buf()),
             ReadError::ReadError(e) => ReadError::ReadFileError(e, path.as_ref().to_path_buf()),
             ReadError::ReadFileError(e, path) => ReadError::ReadFileError(e, path),
+            ReadError::DeserializationFileError(e, path) => ReadError::DeserializationFileError(e, path),
         }
     }
 }
@@ -109,6 +168,13 @@ pub enum WriteError {
 
     #[error("Error while writing '{1}': {0}")]
     WriteFileError(std::io::Error, PathBuf),
+
+    #[error("Incomplete writing, {written} bytes written on {expected}: {error}")]
+    Incomplete {
+        written: usize,
+        expected: usize,
+        error: std::io::Error,
+    },
 }
 
 impl WriteError {
@@ -117,115 +183,251 @@ impl WriteError {
             WriteError::SerializationError(e) => WriteError::SerializationError(e),
             WriteError::WriteError(e) => WriteError::WriteFileError(e, path.as_ref().to_path_buf()),
             WriteError::WriteFileError(e, path) => WriteError::WriteFileError(e, path),
+            error @ WriteError::Incomplete { .. } => error,
         }
     }
 }
 
 pub fn detect_patch_type<R: Read>(read: &mut R) -> Option<PatchType> {
-    let mut xml_content = String::new();
+    serialization::detect_patch_type(&read_xml(read).ok()?)
+}
+
+/// Read all the content and transcode it to UTF-8, see [deserialize_synth_bytes]
+fn read_xml<R: Read>(read: &mut R) -> Result<String, ReadError> {
+    let mut bytes = Vec::new();
 
-    read.read_to_string(&mut xml_content)
-        .map_err(ReadError::ReadError)
-        .ok()?;
+    read.read_to_end(&mut bytes)
+        .map_err(ReadError::ReadError)?;
 
-    serialization::detect_patch_type(&xml_content)
+    Ok(serialization::decode_xml(&bytes)
+        .map_err(ReadError::DeserializationError)?
+        .into_owned())
 }
 
-pub fn read_synth<R: Read>(read: &mut R) -> Result<Synth, ReadError> {
-    let mut xml_content = String::new();
+/// Deserialize a file, a file encoded in UTF-8 without BOM is parsed while it's read.
+/// The files using another encoding are loaded entirely then transcoded.
+///
+/// The errors keep the path of the file. The parser of a reader doesn't keep the content read, so the line and
+/// the column of an error are found by reading the file again.
+fn read_file<P: AsRef<Path>, T>(
+    path: P,
+    from_reader: fn(BufReader<File>) -> Result<T, SerializationError>,
+    from_bytes: fn(&[u8]) -> Result<T, SerializationError>,
+) -> Result<T, ReadError> {
+    let path = path.as_ref();
+    let file_error = |e| ReadError::ReadFileError(e, path.to_path_buf());
+    let deserialization_error = |e| ReadError::DeserializationFileError(e, path.to_path_buf());
+    let mut reader = BufReader::new(File::open(path).map_err(file_error)?);
+
+    if serialization::is_utf8_without_bom(reader.fill_buf().map_err(file_error)?) {
+        return from_reader(reader).map_err(|error| {
+            deserialization_error(match std::fs::read_to_string(path) {
+                Ok(xml) => error.with_location(&xml),
+                Err(_) => error,
+            })
+        });
+    }
 
-    read.read_to_string(&mut xml_content)
-        .map_err(ReadError::ReadError)?;
+    let mut bytes = Vec::new();
+
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(file_error)?;
+
+    from_bytes(&bytes).map_err(deserialization_error)
+}
+
+pub fn read_synth<R: Read>(read: &mut R) -> Result<Synth, ReadError> {
+    let xml_content = read_xml(read)?;
 
     deserialize_synth(&xml_content).map_err(ReadError::DeserializationError)
 }
 
 pub fn read_synth_with_version<R: Read>(read: &mut R) -> Result<(Synth, VersionInfo), ReadError> {
-    let mut xml_content = String::new();
-
-    read.read_to_string(&mut xml_content)
-        .map_err(ReadError::ReadError)?;
+    let xml_content = read_xml(read)?;
 
     deserialize_synth_with_version(&xml_content).map_err(ReadError::DeserializationError)
 }
 
+/// Read a synth from a file
+///
+/// A file encoded in UTF-8 is parsed while it's read, see [deserialize_synth_from_reader].
+/// The encoding is detected and the files encoded in UTF-16 or latin-1 are transcoded, see [deserialize_synth_bytes].
 pub fn read_synth_from_file<P: AsRef<Path>>(path: P) -> Result<Synth, ReadError> {
-    let mut file = std::fs::File::open(&path).map_err(|e| ReadError::ReadFileError(e, path.as_ref().to_path_buf()))?;
-
-    read_synth(&mut file).map_err(|e| ReadError::new_file_error(e, path.as_ref()))
+    read_file(path, deserialize_synth_from_reader, deserialize_synth_bytes)
 }
 
 pub fn read_synth_from_file_with_version<P: AsRef<Path>>(path: P) -> Result<(Synth, VersionInfo), ReadError> {
-    let mut file = std::fs::File::open(&path).map_err(|e| ReadError::ReadFileError(e, path.as_ref().to_path_buf()))?;
+    read_file(
+        path,
+        deserialize_synth_from_reader_with_version,
+        deserialize_synth_bytes_with_version,
+    )
+}
+
+/// Read a kit or a synth, see [deserialize_patch]
+pub fn read_patch<R: Read>(read: &mut R) -> Result<(Patch, VersionInfo), ReadError> {
+    let xml_content = read_xml(read)?;
 
-    read_synth_with_version(&mut file).map_err(|e| ReadError::new_file_error(e, path.as_ref()))
+    deserialize_patch(&xml_content).map_err(ReadError::DeserializationError)
 }
 
-pub fn read_kit<R: Read>(read: &mut R) -> Result<Kit, ReadError> {
-    let mut xml_content = String::new();
+/// Read a kit or a synth from a file, the type of the patch is given by the name of the root element
+///
+/// The encoding is handled like [read_kit_from_file] and [read_synth_from_file].
+/// ```
+/// # use deluge::{read_patch_from_file, PatchType};
+/// let (patch, _) = read_patch_from_file("src/data_tests/KITS/KIT057.XML").unwrap();
+///
+/// assert_eq!(patch.patch_type(), PatchType::Kit);
+/// ```
+pub fn read_patch_from_file<P: AsRef<Path>>(path: P) -> Result<(Patch, VersionInfo), ReadError> {
+    read_file(path, deserialize_patch_from_reader, deserialize_patch_bytes)
+}
 
-    read.read_to_string(&mut xml_content)
-        .map_err(ReadError::ReadError)?;
+pub fn read_kit<R: Read>(read: &mut R) -> Result<Kit, ReadError> {
+    let xml_content = read_xml(read)?;
 
     deserialize_kit(&xml_content).map_err(ReadError::DeserializationError)
 }
 
 pub fn read_kit_with_version<R: Read>(read: &mut R) -> Result<(Kit, VersionInfo), ReadError> {
-    let mut xml_content = String::new();
-
-    read.read_to_string(&mut xml_content)
-        .map_err(ReadError::ReadError)?;
+    let xml_content = read_xml(read)?;
 
     deserialize_kit_with_version(&xml_content).map_err(ReadError::DeserializationError)
 }
 
+/// Read a kit from a file
+///
+/// A file encoded in UTF-8 is parsed while it's read, see [deserialize_kit_from_reader].
+/// The encoding is detected and the files encoded in UTF-16 or latin-1 are transcoded, see [deserialize_kit_bytes].
 pub fn read_kit_from_file<P: AsRef<Path>>(path: P) -> Result<Kit, ReadError> {
-    let mut file = std::fs::File::open(&path).map_err(|e| ReadError::ReadFileError(e, path.as_ref().to_path_buf()))?;
-
-    read_kit(&mut file).map_err(|e| ReadError::new_file_error(e, path.as_ref()))
+    read_file(path, deserialize_kit_from_reader, deserialize_kit_bytes)
 }
 
 pub fn read_kit_from_file_with_version<P: AsRef<Path>>(path: P) -> Result<(Kit, VersionInfo), ReadError> {
-    let mut file = std::fs::File::open(&path).map_err(|e| ReadError::ReadFileError(e, path.as_ref().to_path_buf()))?;
+    read_file(
+        path,
+        deserialize_kit_from_reader_with_version,
+        deserialize_kit_bytes_with_version,
+    )
+}
+
+/// Options used when reading a kit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReadKitOptions {
+    /// Skip the rows that can't be loaded instead of failing.
+    /// By default the reading fails at the first row that can't be loaded.
+    pub lenient: bool,
+}
+
+/// Read a kit using options, returns the kit and the rows skipped
+///
+/// The rows are skipped only if [ReadKitOptions::lenient] is true, otherwise the list of rows skipped is always empty.
+pub fn read_kit_with_options<R: Read>(read: &mut R, options: &ReadKitOptions) -> Result<(Kit, Vec<RowError>), ReadError> {
+    let xml_content = read_xml(read)?;
 
-    read_kit_with_version(&mut file).map_err(|e| ReadError::new_file_error(e, path.as_ref()))
+    match options.lenient {
+        true => deserialize_kit_lenient(&xml_content),
+        false => deserialize_kit(&xml_content).map(|kit| (kit, Vec::new())),
+    }
+    .map_err(ReadError::DeserializationError)
 }
 
-pub fn write_synth<W: Write>(synth: &Synth, writable: &mut W) -> Result<(), WriteError> {
+/// Read a kit from a file using options, see [read_kit_with_options]
+pub fn read_kit_from_file_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &ReadKitOptions,
+) -> Result<(Kit, Vec<RowError>), ReadError> {
+    let mut file = File::open(&path).map_err(|e| ReadError::ReadFileError(e, path.as_ref().to_path_buf()))?;
+
+    read_kit_with_options(&mut file, options).map_err(|e| ReadError::new_file_error(e, path.as_ref()))
+}
+
+/// Write a synth, returns the count of bytes written
+///
+/// If the writing fails after some bytes were written the error [WriteError::Incomplete] is returned.
+pub fn write_synth<W: Write>(synth: &Synth, writable: &mut W) -> Result<usize, WriteError> {
     let xml_content = serialize_synth(synth).map_err(WriteError::SerializationError)?;
 
-    writable
-        .write_all(xml_content.as_bytes())
-        .map_err(WriteError::WriteError)
+    file_writing::write_counted(writable, xml_content.as_bytes())
 }
 
-pub fn write_synth_to_file<P: AsRef<Path>>(synth: &Synth, path: P) -> Result<(), WriteError> {
-    let mut file = std::fs::File::create(&path).map_err(|e| WriteError::WriteFileError(e, path.as_ref().to_path_buf()))?;
+/// Write a synth in a file, returns the count of bytes written
+///
+/// The synth is written in a temporary file renamed once complete, so the existing file is not lost if the
+/// writing fails. If the file system can't rename the file, the file is written directly.
+pub fn write_synth_to_file<P: AsRef<Path>>(synth: &Synth, path: P) -> Result<usize, WriteError> {
+    let xml_content = serialize_synth(synth).map_err(WriteError::SerializationError)?;
 
-    write_synth(synth, &mut file).map_err(|e| WriteError::new_file_error(e, path))
+    file_writing::write_file_atomically(path.as_ref(), &xml_content)
 }
 
-pub fn write_kit<W: Write>(kit: &Kit, writable: &mut W) -> Result<(), WriteError> {
+/// Write a kit, returns the count of bytes written
+///
+/// If the writing fails after some bytes were written the error [WriteError::Incomplete] is returned.
+pub fn write_kit<W: Write>(kit: &Kit, writable: &mut W) -> Result<usize, WriteError> {
     let xml_content = serialize_kit(kit).map_err(WriteError::SerializationError)?;
 
-    writable
-        .write_all(xml_content.as_bytes())
-        .map_err(WriteError::WriteError)
+    file_writing::write_counted(writable, xml_content.as_bytes())
 }
 
-pub fn write_kit_to_file<P: AsRef<Path>>(kit: &Kit, path: P) -> Result<(), WriteError> {
-    let mut file = std::fs::File::create(&path).map_err(|e| WriteError::WriteFileError(e, path.as_ref().to_path_buf()))?;
+/// Write a kit in a file, returns the count of bytes written
+///
+/// The kit is written in a temporary file renamed once complete, so the existing file is not lost if the
+/// writing fails. If the file system can't rename the file, the file is written directly.
+pub fn write_kit_to_file<P: AsRef<Path>>(kit: &Kit, path: P) -> Result<usize, WriteError> {
+    let xml_content = serialize_kit(kit).map_err(WriteError::SerializationError)?;
 
-    write_kit(kit, &mut file).map_err(|e| WriteError::new_file_error(e, path))
+    file_writing::write_file_atomically(path.as_ref(), &xml_content)
 }
 
-pub fn detect_file_patch_type<P: AsRef<Path>>(path: P) -> Option<PatchType> {
-    let mut file = std::fs::File::open(path.as_ref()).ok()?;
-    let mut xml_content = String::new();
+/// Read a kit from a file without blocking, see [read_kit_from_file]
+#[cfg(feature = "async")]
+pub async fn read_kit_from_file_async<P: AsRef<Path>>(path: P) -> Result<Kit, ReadError> {
+    deserialize_kit_bytes(&read_file_async(path.as_ref()).await?)
+        .map_err(|e| ReadError::DeserializationFileError(e, path.as_ref().to_path_buf()))
+}
 
-    file.read_to_string(&mut xml_content)
-        .map_err(ReadError::ReadError)
-        .ok()?;
+/// Read a synth from a file without blocking, see [read_synth_from_file]
+#[cfg(feature = "async")]
+pub async fn read_synth_from_file_async<P: AsRef<Path>>(path: P) -> Result<Synth, ReadError> {
+    deserialize_synth_bytes(&read_file_async(path.as_ref()).await?)
+        .map_err(|e| ReadError::DeserializationFileError(e, path.as_ref().to_path_buf()))
+}
+
+/// Read a kit or a synth from a file without blocking, see [read_patch_from_file]
+#[cfg(feature = "async")]
+pub async fn read_patch_from_file_async<P: AsRef<Path>>(path: P) -> Result<(Patch, VersionInfo), ReadError> {
+    deserialize_patch_bytes(&read_file_async(path.as_ref()).await?)
+        .map_err(|e| ReadError::DeserializationFileError(e, path.as_ref().to_path_buf()))
+}
+
+/// Write a synth in a file without blocking, see [write_synth_to_file]
+#[cfg(feature = "async")]
+pub async fn write_synth_to_file_async<P: AsRef<Path>>(synth: &Synth, path: P) -> Result<usize, WriteError> {
+    let xml_content = serialize_synth(synth).map_err(WriteError::SerializationError)?;
+
+    file_writing::write_file_atomically_async(path.as_ref(), &xml_content).await
+}
+
+/// Write a kit in a file without blocking, see [write_kit_to_file]
+#[cfg(feature = "async")]
+pub async fn write_kit_to_file_async<P: AsRef<Path>>(kit: &Kit, path: P) -> Result<usize, WriteError> {
+    let xml_content = serialize_kit(kit).map_err(WriteError::SerializationError)?;
+
+    file_writing::write_file_atomically_async(path.as_ref(), &xml_content).await
+}
+
+#[cfg(feature = "async")]
+async fn read_file_async(path: &Path) -> Result<Vec<u8>, ReadError> {
+    tokio::fs::read(path)
+        .await
+        .map_err(|e| ReadError::ReadFileError(e, path.to_path_buf()))
+}
+
+pub fn detect_file_patch_type<P: AsRef<Path>>(path: P) -> Option<PatchType> {
+    let mut file = File::open(path.as_ref()).ok()?;
 
-    serialization::detect_patch_type(&xml_content)
+    detect_patch_type(&mut file)
 }
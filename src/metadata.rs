@@ -0,0 +1,37 @@
+/// Librarian information attached to a [Synth](crate::Synth) or a [Kit](crate::Kit)
+///
+/// This is an extension of this crate, not a feature of the firmware: the metadata is stored in an XML comment
+/// placed at the beginning of the patch. Loading a patch with this comment on a device is not yet verified, and the
+/// metadata is expected to be lost if the patch is saved on the device.
+/// The metadata is not audible, [Kit::sonically_equals](crate::Kit::sonically_equals) ignores it by default.
+/// ```
+/// # use deluge::{deserialize_synth, serialize_synth, Metadata, Synth};
+/// let synth = Synth {
+///     metadata: Some(Metadata {
+///         author: Some("Someone".to_string()),
+///         tags: vec!["pad".to_string(), "warm".to_string()],
+///         ..Metadata::default()
+///     }),
+///     ..Synth::default()
+/// };
+/// let xml = serialize_synth(&synth).unwrap();
+///
+/// assert!(xml.contains("deluge-rs metadata"));
+/// assert_eq!(deserialize_synth(&xml).unwrap(), synth);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metadata {
+    /// The name of the patch in the librarian, it can differ from the file name
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+}
+
+impl Metadata {
+    /// Returns true if no information is set
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
@@ -0,0 +1,356 @@
+//! Visit the parameters of a sound
+//!
+//! Each parameter is identified by the same dotted path as [FieldChange](crate::FieldChange),
+//! for example "envelope1.attack" or "cables[velocity->volume].amount".
+//! The parameters are the values edited with the knobs of the device: the values in the range [0; 50],
+//! the pans and the transpositions. The modes, the shapes and the sample zones are not parameters.
+use crate::{
+    range_check::join,
+    values::{FineTranspose, Transpose},
+    Arpeggiator, Chorus, DecU50, Delay, Distorsion, Envelope, Equalizer, Flanger, FmCarrier, FmModulator, FmSynth, HexU50, Lfo1,
+    Lfo2, ModulationFx, Pan, PatchCable, Phaser, RingModSynth, Sample, SampleOscillator, SampleRange, Sidechain, Sound,
+    SubtractiveOscillator, SubtractiveSynth, SynthEngine, WaveformOscillator,
+};
+
+/// A mutable reference to a parameter, see [Sound::for_each_param_mut]
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParamRef<'a> {
+    /// The dotted path of the parameter, for example "envelope1.attack"
+    pub name: String,
+    pub value: ParamValue<'a>,
+}
+
+/// The value of a parameter
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParamValue<'a> {
+    HexU50(&'a mut HexU50),
+    DecU50(&'a mut DecU50),
+    Pan(&'a mut Pan),
+    Transpose(&'a mut Transpose),
+    FineTranspose(&'a mut FineTranspose),
+}
+
+impl ParamRef<'_> {
+    /// Get the value if the parameter is in the range [0; 50]
+    pub fn as_hexu50_mut(&mut self) -> Option<&mut HexU50> {
+        match &mut self.value {
+            ParamValue::HexU50(value) => Some(&mut **value),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) trait VisitParams {
+    /// Call the function for each parameter, the path is the one of self
+    fn visit_params(&mut self, path: &str, f: &mut dyn FnMut(ParamRef<'_>));
+}
+
+/// Implements VisitParams for a type stored in a variant of ParamValue
+macro_rules! visit_value {
+    ($($name:ty => $variant:ident),*) => {
+        $(
+            impl VisitParams for $name {
+                fn visit_params(&mut self, path: &str, f: &mut dyn FnMut(ParamRef<'_>)) {
+                    f(ParamRef {
+                        name: path.to_string(),
+                        value: ParamValue::$variant(self),
+                    });
+                }
+            }
+        )*
+    };
+}
+
+/// Implements VisitParams for structures by visiting the listed fields
+macro_rules! visit_struct {
+    ($name:ident { $($field:ident),* $(,)? }) => {
+        impl VisitParams for $name {
+            fn visit_params(&mut self, path: &str, f: &mut dyn FnMut(ParamRef<'_>)) {
+                $(self.$field.visit_params(&join(path, stringify!($field)), f);)*
+            }
+        }
+    };
+}
+
+visit_value!(
+    HexU50 => HexU50,
+    DecU50 => DecU50,
+    Pan => Pan,
+    Transpose => Transpose,
+    FineTranspose => FineTranspose
+);
+
+impl<T: VisitParams> VisitParams for Option<T> {
+    fn visit_params(&mut self, path: &str, f: &mut dyn FnMut(ParamRef<'_>)) {
+        if let Some(value) = self {
+            value.visit_params(path, f);
+        }
+    }
+}
+
+impl<T: VisitParams> VisitParams for Vec<T> {
+    fn visit_params(&mut self, path: &str, f: &mut dyn FnMut(ParamRef<'_>)) {
+        for (index, item) in self.iter_mut().enumerate() {
+            item.visit_params(&format!("{}[{}]", path, index), f);
+        }
+    }
+}
+
+impl VisitParams for Sound {
+    fn visit_params(&mut self, path: &str, f: &mut dyn FnMut(ParamRef<'_>)) {
+        self.generator
+            .visit_params(&join(path, "generator"), f);
+        self.volume
+            .visit_params(&join(path, "volume"), f);
+        self.pan
+            .visit_params(&join(path, "pan"), f);
+        self.portamento
+            .visit_params(&join(path, "portamento"), f);
+        self.reverb_amount
+            .visit_params(&join(path, "reverb_amount"), f);
+        self.stutter_rate
+            .visit_params(&join(path, "stutter_rate"), f);
+        self.sidechain_send
+            .visit_params(&join(path, "sidechain_send"), f);
+        self.envelope1
+            .visit_params(&join(path, "envelope1"), f);
+        self.envelope2
+            .visit_params(&join(path, "envelope2"), f);
+        self.lfo1
+            .visit_params(&join(path, "lfo1"), f);
+        self.lfo2
+            .visit_params(&join(path, "lfo2"), f);
+        self.arpeggiator
+            .visit_params(&join(path, "arpeggiator"), f);
+        self.delay
+            .visit_params(&join(path, "delay"), f);
+        self.distorsion
+            .visit_params(&join(path, "distorsion"), f);
+        self.modulation_fx
+            .visit_params(&join(path, "modulation_fx"), f);
+        self.equalizer
+            .visit_params(&join(path, "equalizer"), f);
+        self.sidechain
+            .visit_params(&join(path, "sidechain"), f);
+
+        // Like the diff, the cables are identified by their pair (source, destination)
+        for cable in self.cables.iter_mut() {
+            let cable_path = format!("{}[{}->{}]", join(path, "cables"), cable.source, cable.destination);
+
+            cable.visit_params(&cable_path, f);
+        }
+    }
+}
+
+impl VisitParams for SynthEngine {
+    fn visit_params(&mut self, path: &str, f: &mut dyn FnMut(ParamRef<'_>)) {
+        match self {
+            SynthEngine::Subtractive(generator) => generator.visit_params(path, f),
+            SynthEngine::RingMod(generator) => generator.visit_params(path, f),
+            SynthEngine::Fm(generator) => generator.visit_params(path, f),
+        }
+    }
+}
+
+impl VisitParams for SubtractiveOscillator {
+    fn visit_params(&mut self, path: &str, f: &mut dyn FnMut(ParamRef<'_>)) {
+        match self {
+            SubtractiveOscillator::Waveform(oscillator) => oscillator.visit_params(path, f),
+            SubtractiveOscillator::Sample(oscillator) => oscillator.visit_params(path, f),
+        }
+    }
+}
+
+impl VisitParams for Sample {
+    fn visit_params(&mut self, path: &str, f: &mut dyn FnMut(ParamRef<'_>)) {
+        match self {
+            Sample::OneZone(_) => {}
+            Sample::SampleRanges(ranges) => ranges.visit_params(&join(path, "ranges"), f),
+        }
+    }
+}
+
+impl VisitParams for ModulationFx {
+    fn visit_params(&mut self, path: &str, f: &mut dyn FnMut(ParamRef<'_>)) {
+        match self {
+            ModulationFx::Off => {}
+            ModulationFx::Flanger(flanger) => flanger.visit_params(path, f),
+            ModulationFx::Chorus(chorus) => chorus.visit_params(path, f),
+            ModulationFx::Phaser(phaser) => phaser.visit_params(path, f),
+        }
+    }
+}
+
+visit_struct!(SubtractiveSynth {
+    osc1,
+    osc2,
+    osc1_volume,
+    osc2_volume,
+    noise,
+    lpf_frequency,
+    lpf_resonance,
+    hpf_frequency,
+    hpf_resonance,
+});
+visit_struct!(RingModSynth { osc1, osc2, noise });
+visit_struct!(FmSynth {
+    osc1,
+    osc2,
+    modulator1,
+    modulator2,
+    osc1_volume,
+    osc2_volume,
+});
+visit_struct!(FmCarrier {
+    transpose,
+    fine_transpose,
+    feedback
+});
+visit_struct!(FmModulator {
+    transpose,
+    fine_transpose,
+    amount,
+    feedback,
+});
+visit_struct!(WaveformOscillator {
+    transpose,
+    fine_transpose,
+    pulse_width,
+});
+visit_struct!(SampleOscillator {
+    transpose,
+    fine_transpose,
+    sample,
+});
+visit_struct!(SampleRange {
+    transpose,
+    fine_transpose
+});
+visit_struct!(Envelope {
+    attack,
+    decay,
+    sustain,
+    release
+});
+visit_struct!(Lfo1 { rate });
+visit_struct!(Lfo2 { rate });
+visit_struct!(Arpeggiator { gate, rate });
+visit_struct!(Delay { amount, rate });
+visit_struct!(Distorsion { bit_crush, decimation });
+visit_struct!(Equalizer {
+    bass_level,
+    bass_frequency,
+    treble_level,
+    treble_frequency,
+});
+visit_struct!(Flanger { rate, feedback });
+visit_struct!(Chorus { rate, depth, offset });
+visit_struct!(Phaser { rate, depth, feedback });
+visit_struct!(Sidechain { shape });
+visit_struct!(PatchCable { amount });
+
+impl Sound {
+    /// Call a function with a mutable reference to each parameter of the sound
+    ///
+    /// The parameters of the generator, the envelopes, the LFOs, the effects and the patch cables are visited.
+    /// ```
+    /// # use deluge::{HexU50, Sound};
+    /// let mut sound = Sound::default();
+    ///
+    /// // Reduce all the parameters in the range [0; 50] by 10%
+    /// sound.for_each_param_mut(|mut param| {
+    ///     if let Some(value) = param.as_hexu50_mut() {
+    ///         *value = HexU50::from_percent(value.as_percent() * 0.9);
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(sound.volume, HexU50::new(36));
+    /// ```
+    pub fn for_each_param_mut(&mut self, mut f: impl FnMut(ParamRef<'_>)) {
+        self.visit_params("", &mut f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deserialize_synth;
+    use pretty_assertions::assert_eq;
+
+    fn param_names(sound: &mut Sound) -> Vec<String> {
+        let mut names = Vec::new();
+
+        sound.for_each_param_mut(|param| names.push(param.name));
+
+        names
+    }
+
+    #[test]
+    fn test_default_sound_params() {
+        let expected = vec![
+            "generator.osc1.transpose",
+            "generator.osc1.fine_transpose",
+            "generator.osc1.pulse_width",
+            "generator.osc2.transpose",
+            "generator.osc2.fine_transpose",
+            "generator.osc2.pulse_width",
+            "generator.osc1_volume",
+            "generator.osc2_volume",
+            "generator.noise",
+            "generator.lpf_frequency",
+            "generator.lpf_resonance",
+            "generator.hpf_frequency",
+            "generator.hpf_resonance",
+            "volume",
+            "pan",
+            "portamento",
+            "reverb_amount",
+            "stutter_rate",
+            "envelope1.attack",
+            "envelope1.decay",
+            "envelope1.sustain",
+            "envelope1.release",
+            "envelope2.attack",
+            "envelope2.decay",
+            "envelope2.sustain",
+            "envelope2.release",
+            "lfo1.rate",
+            "lfo2.rate",
+            "arpeggiator.gate",
+            "arpeggiator.rate",
+            "delay.amount",
+            "delay.rate",
+            "distorsion.bit_crush",
+            "distorsion.decimation",
+            "equalizer.bass_level",
+            "equalizer.bass_frequency",
+            "equalizer.treble_level",
+            "equalizer.treble_frequency",
+            "sidechain.shape",
+            "cables[velocity->volume].amount",
+        ];
+
+        assert_eq!(param_names(&mut Sound::default()), expected);
+    }
+
+    #[test]
+    fn test_sample_ranges_params() {
+        let mut synth = deserialize_synth(include_str!("data_tests/SYNTHS/SYNT168A.XML")).unwrap();
+        let names = param_names(&mut synth.sound);
+
+        assert!(names.contains(&"generator.osc1.sample.ranges[1].transpose".to_string()));
+    }
+
+    #[test]
+    fn test_for_each_param_mut_edits_the_sound() {
+        let mut sound = Sound::default();
+
+        sound.for_each_param_mut(|mut param| {
+            if param.name.ends_with("reverb_amount") {
+                *param.as_hexu50_mut().unwrap() = 40.into();
+            }
+        });
+
+        assert_eq!(sound.reverb_amount, HexU50::new(40));
+    }
+}
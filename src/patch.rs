@@ -0,0 +1,42 @@
+use enum_as_inner::EnumAsInner;
+
+use crate::{Kit, PatchType, Synth};
+
+/// A kit or a synth, used when the type of a patch is known only once it's loaded
+///
+/// See [deserialize_patch](crate::deserialize_patch) and [read_patch_from_file](crate::read_patch_from_file).
+/// ```
+/// # use deluge::{deserialize_patch, Patch, PatchType};
+/// let (patch, _) = deserialize_patch(include_str!("data_tests/SYNTHS/SYNT184.XML")).unwrap();
+///
+/// assert_eq!(patch.patch_type(), PatchType::Synth);
+/// assert!(patch.as_synth().is_some());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, EnumAsInner)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum Patch {
+    Kit(Kit),
+    Synth(Box<Synth>),
+}
+
+impl Patch {
+    pub fn patch_type(&self) -> PatchType {
+        match self {
+            Patch::Kit(_) => PatchType::Kit,
+            Patch::Synth(_) => PatchType::Synth,
+        }
+    }
+}
+
+impl From<Kit> for Patch {
+    fn from(kit: Kit) -> Self {
+        Patch::Kit(kit)
+    }
+}
+
+impl From<Synth> for Patch {
+    fn from(synth: Synth) -> Self {
+        Patch::Synth(Box::new(synth))
+    }
+}
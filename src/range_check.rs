@@ -0,0 +1,322 @@
+//! Check the values of a patch are in their range
+//!
+//! The values like [HexU50] can be created without validation, a value out of its range
+//! is only detected when the device loads the patch. The builders use [CheckRange] to report
+//! the first field out of range when `build()` is called, the field is identified by a dotted path
+//! such as "envelope1.attack" or "rows[3].sound.volume".
+use crate::{
+    values::{Int8, Uint8},
+    DecU50, HexU50, MidiNote, ModulationFx, RowKit, Sample, SerializationError, SubtractiveOscillator, SynthEngine,
+};
+
+pub(crate) trait CheckRange {
+    /// Returns an error describing the first value out of range
+    fn check_range(&self, path: &str) -> Result<(), String>;
+}
+
+pub(crate) fn join(path: &str, name: &str) -> String {
+    match path.is_empty() {
+        true => name.to_string(),
+        false => format!("{}.{}", path, name),
+    }
+}
+
+fn out_of_range(path: &str, error: SerializationError) -> String {
+    format!("'{}' is out of range: {}", path, error)
+}
+
+/// Implements [CheckRange] for a structure by checking each field, and the function `check_ranges`
+/// of its builder, to be used with `#[builder(build_fn(validate = "Self::check_ranges"))]`.
+macro_rules! check_ranges {
+    ($name:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::range_check::CheckRange for $name {
+            fn check_range(&self, path: &str) -> Result<(), String> {
+                $($crate::range_check::CheckRange::check_range(
+                    &self.$field,
+                    &$crate::range_check::join(path, stringify!($field)),
+                )?;)*
+
+                Ok(())
+            }
+        }
+    };
+    ($name:ident, $builder:ident { $($field:ident),* $(,)? }) => {
+        $crate::range_check::check_ranges!($name { $($field),* });
+
+        impl $builder {
+            fn check_ranges(&self) -> Result<(), String> {
+                $($crate::range_check::CheckRange::check_range(&self.$field, stringify!($field))?;)*
+
+                Ok(())
+            }
+        }
+    };
+}
+
+pub(crate) use check_ranges;
+
+/// Implements [CheckRange] for the types that can't be out of range
+macro_rules! always_in_range {
+    ($($name:ty),*) => {
+        $(
+            impl CheckRange for $name {
+                fn check_range(&self, _path: &str) -> Result<(), String> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+always_in_range!(
+    u8,
+    u32,
+    String,
+    crate::Pan,
+    crate::OnOff,
+    crate::SamplePath,
+    crate::SamplePosition,
+    crate::AttackSidechain,
+    crate::ReleaseSidechain,
+    crate::PatchSource,
+    crate::PatchDestination,
+    crate::Polyphony,
+    crate::VoicePriority,
+    crate::OscType,
+    crate::LfoShape,
+    crate::SamplePlayMode,
+    crate::PitchSpeed,
+    crate::SyncLevel,
+    crate::LpfMode,
+    crate::ArpeggiatorMode,
+    crate::FilterType
+);
+
+impl CheckRange for HexU50 {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        HexU50::try_new(self.as_u8())
+            .map(|_| ())
+            .map_err(|error| out_of_range(path, error))
+    }
+}
+
+impl CheckRange for DecU50 {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        DecU50::try_new(self.as_u8())
+            .map(|_| ())
+            .map_err(|error| out_of_range(path, error))
+    }
+}
+
+impl CheckRange for crate::RetrigPhase {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        match self.degrees() {
+            Some(degrees) => crate::RetrigPhase::try_new(degrees)
+                .map(|_| ())
+                .map_err(|error| out_of_range(path, error)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl CheckRange for crate::ClippingAmount {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        crate::ClippingAmount::try_new(self.as_u8())
+            .map(|_| ())
+            .map_err(|error| out_of_range(path, error))
+    }
+}
+
+impl CheckRange for crate::UnisonDetune {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        crate::UnisonDetune::try_new(self.as_u8())
+            .map(|_| ())
+            .map_err(|error| out_of_range(path, error))
+    }
+}
+
+impl CheckRange for MidiNote {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        MidiNote::try_new(self.as_u8())
+            .map(|_| ())
+            .map_err(|error| out_of_range(path, error))
+    }
+}
+
+impl<const MIN: u8, const MAX: u8, const DEFAULT: u8> CheckRange for Uint8<MIN, MAX, DEFAULT> {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        Self::try_new(self.as_u8())
+            .map(|_| ())
+            .map_err(|error| out_of_range(path, error))
+    }
+}
+
+impl<const MIN: i8, const MAX: i8, const DEFAULT: i8> CheckRange for Int8<MIN, MAX, DEFAULT> {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        Self::try_new(self.as_i8())
+            .map(|_| ())
+            .map_err(|error| out_of_range(path, error))
+    }
+}
+
+impl<T: CheckRange> CheckRange for Option<T> {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        match self {
+            Some(value) => value.check_range(path),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: CheckRange> CheckRange for Box<T> {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        self.as_ref().check_range(path)
+    }
+}
+
+impl<T: CheckRange> CheckRange for Vec<T> {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        for (index, item) in self.iter().enumerate() {
+            item.check_range(&format!("{}[{}]", path, index))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CheckRange for SynthEngine {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        match self {
+            SynthEngine::Subtractive(generator) => generator.check_range(path),
+            SynthEngine::RingMod(generator) => generator.check_range(path),
+            SynthEngine::Fm(generator) => generator.check_range(path),
+        }
+    }
+}
+
+impl CheckRange for SubtractiveOscillator {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        match self {
+            SubtractiveOscillator::Waveform(oscillator) => oscillator.check_range(path),
+            SubtractiveOscillator::Sample(oscillator) => oscillator.check_range(path),
+        }
+    }
+}
+
+impl CheckRange for Sample {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        match self {
+            Sample::OneZone(one_zone) => one_zone.check_range(path),
+            Sample::SampleRanges(ranges) => ranges.check_range(&join(path, "ranges")),
+        }
+    }
+}
+
+impl CheckRange for ModulationFx {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        match self {
+            ModulationFx::Off => Ok(()),
+            ModulationFx::Flanger(flanger) => flanger.check_range(path),
+            ModulationFx::Chorus(chorus) => chorus.check_range(path),
+            ModulationFx::Phaser(phaser) => phaser.check_range(path),
+        }
+    }
+}
+
+impl CheckRange for RowKit {
+    fn check_range(&self, path: &str) -> Result<(), String> {
+        match self {
+            RowKit::Sound(row) => row.check_range(path),
+            RowKit::Midi(row) => row.check_range(path),
+            RowKit::CvGate(row) => row.check_range(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        deserialize_synth_with_clamped_values, Envelope, HexU50, KitBuilder, SerializationOptions, Sound, SoundBuilder,
+        SoundBuilderError, UnisonBuilder,
+    };
+    use pretty_assertions::assert_eq;
+
+    /// Load SYNT184 edited with a value out of range, the values out of range are only kept when loading a patch.
+    fn sound_out_of_range(from: &str, to: &str) -> Sound {
+        let xml = include_str!("data_tests/SYNTHS/SYNT184.XML").replace(from, to);
+
+        deserialize_synth_with_clamped_values(&xml, &SerializationOptions::default())
+            .unwrap()
+            .0
+            .sound
+    }
+
+    #[test]
+    fn test_build_sound_out_of_range() {
+        let error = SoundBuilder::default()
+            .volume(HexU50::new(200))
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(error, SoundBuilderError::ValidationError(_)));
+        assert_eq!(error.to_string(), "'volume' is out of range: overflow: 200 > 50");
+    }
+
+    #[test]
+    fn test_build_sound_nested_out_of_range() {
+        let envelope = Envelope {
+            attack: HexU50::new(51),
+            decay: 0.into(),
+            sustain: 50.into(),
+            release: 0.into(),
+        };
+        let error = SoundBuilder::default()
+            .envelope1(envelope)
+            .build()
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "'envelope1.attack' is out of range: overflow: 51 > 50");
+    }
+
+    #[test]
+    fn test_build_unison_out_of_range() {
+        let error = UnisonBuilder::default()
+            .voice_count(
+                sound_out_of_range("<unison num=\"1\"", "<unison num=\"0\"")
+                    .unison
+                    .voice_count,
+            )
+            .build()
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "'voice_count' is out of range: underflow: 0 < 1");
+    }
+
+    #[test]
+    fn test_build_kit_out_of_range() {
+        let transposed = sound_out_of_range(
+            "<osc1\n\t\ttype=\"square\"\n\t\ttranspose=\"0\"",
+            "<osc1\n\t\ttype=\"square\"\n\t\ttranspose=\"100\"",
+        );
+
+        let error = KitBuilder::default()
+            .add_sound_row(Sound::default())
+            .add_named_sound_row(transposed, "TRANSPOSED")
+            .build()
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "'rows[1].sound.generator.osc1.transpose' is out of range: overflow: 100 > 96"
+        );
+    }
+
+    #[test]
+    fn test_build_default_values() {
+        assert!(SoundBuilder::default().build().is_ok());
+        assert!(KitBuilder::default()
+            .add_sound_row(Sound::default())
+            .build()
+            .is_ok());
+    }
+}
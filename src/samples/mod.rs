@@ -1,22 +1,165 @@
 mod sample_path_replacer;
+mod usage_index;
+#[cfg(feature = "wav")]
+mod wav;
 
 pub use sample_path_replacer::SamplePathReplacer;
+pub use usage_index::{build_usage_index, SampleUsage, SampleUsageIndex};
+#[cfg(feature = "wav")]
+pub(crate) use wav::read_wav_info;
+#[cfg(feature = "wav")]
+pub use wav::{wav_info, WavError, WavInfo};
 
 use crate::SamplePath;
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::reader::Reader;
 use std::io::BufRead;
 
 /// Get the sample paths found in a patch.
 /// This function does not check the XML really contains a Deluge patch.
+///
+/// The errors are ignored, see [try_read_sample_paths] to detect them.
 pub fn read_sample_paths<'l>(reader: impl BufRead + 'l) -> impl Iterator<Item = SamplePath> + 'l {
-    SamplesReader::new(reader)
+    read_sample_references(reader).map(|reference| reference.path)
+}
+
+/// Get the sample paths found in a patch, reporting the errors.
+///
+/// An invalid file name is reported and the reading continues, the iteration stops after an
+/// invalid XML. A truncated file is reported as an error.
+/// ```
+/// # use deluge::{try_read_sample_paths, SampleScanError};
+/// let xml = "<sound><osc1><fileName>SAMPLES/kick.wav</fileName></osc1><osc2>";
+/// let results: Vec<_> = try_read_sample_paths(xml.as_bytes()).collect();
+///
+/// assert!(results[0].is_ok());
+/// assert_eq!(results[1], Err(SampleScanError::UnexpectedEof(63, 2)));
+/// ```
+pub fn try_read_sample_paths<'l>(reader: impl BufRead + 'l) -> impl Iterator<Item = Result<SamplePath, SampleScanError>> + 'l {
+    SamplesReader::new(reader).map(|result| result.map(|reference| reference.path))
+}
+
+/// Get the sample paths found in a patch with the place where they are used.
+/// This function does not check the XML really contains a Deluge patch.
+/// ```
+/// # use deluge::{read_sample_references, OscillatorSlot};
+/// let xml = "<kit><soundSources><sound><osc1><fileName>SAMPLES/kick.wav</fileName></osc1></sound></soundSources></kit>";
+/// let reference = read_sample_references(xml.as_bytes()).next().unwrap();
+///
+/// assert_eq!(reference.row_index, Some(0));
+/// assert_eq!(reference.oscillator, Some(OscillatorSlot::Osc1));
+/// assert!(!reference.in_sample_range);
+/// ```
+pub fn read_sample_references<'l>(reader: impl BufRead + 'l) -> impl Iterator<Item = SampleReference> + 'l {
+    SamplesReader::new(reader).filter_map(Result::ok)
+}
+
+/// An error found by [try_read_sample_paths], the first value is the byte offset in the XML
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum SampleScanError {
+    // Store a String instead of quick_xml::Error to be able to derive PartialEq.
+    #[error("invalid XML at byte {0}: {1}")]
+    Xml(usize, String),
+
+    #[error("unexpected end of file at byte {0}, {1} elements are not closed")]
+    UnexpectedEof(usize, usize),
+
+    #[error("the file name at byte {0} is not valid UTF-8")]
+    InvalidUtf8(usize),
+
+    #[error("the file name '{1}' at byte {0} is not a valid sample path")]
+    InvalidPath(usize, String),
+}
+
+/// The oscillator using a sample
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OscillatorSlot {
+    Osc1,
+    Osc2,
+}
+
+/// A sample path found by [read_sample_references]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SampleReference {
+    pub path: SamplePath,
+    /// The index of the row in the kit, None for a synth.
+    ///
+    /// All the rows are counted, including the MIDI and CV gate rows, so the index matches [Kit::rows](crate::Kit::rows).
+    pub row_index: Option<usize>,
+    /// The oscillator using the sample, None if the sample is not found in an oscillator.
+    pub oscillator: Option<OscillatorSlot>,
+    /// True if the sample is one of the ranges of a multisample
+    pub in_sample_range: bool,
+}
+
+/// The elements enclosing the current XML element
+#[derive(Default)]
+struct Context {
+    /// The count of opened elements
+    depth: usize,
+    /// The depth of the soundSources element
+    sound_sources_depth: Option<usize>,
+    row_index: Option<usize>,
+    /// The depth of the oscillator element and its slot
+    oscillator: Option<(usize, OscillatorSlot)>,
+    /// The depth of the sampleRange element
+    sample_range_depth: Option<usize>,
+}
+
+impl Context {
+    fn enter(&mut self, name: &[u8]) {
+        if self.sound_sources_depth == Some(self.depth) {
+            self.row_index = Some(
+                self.row_index
+                    .map_or(0, |index| index + 1),
+            );
+        }
+
+        self.depth += 1;
+
+        match name {
+            SOUND_SOURCES_TAG => self.sound_sources_depth = Some(self.depth),
+            OSC1_TAG => self.oscillator = Some((self.depth, OscillatorSlot::Osc1)),
+            OSC2_TAG => self.oscillator = Some((self.depth, OscillatorSlot::Osc2)),
+            SAMPLE_RANGE_TAG => self.sample_range_depth = Some(self.depth),
+            _ => (),
+        }
+    }
+
+    fn leave(&mut self) {
+        if self.sound_sources_depth == Some(self.depth) {
+            self.sound_sources_depth = None;
+            self.row_index = None;
+        }
+
+        if matches!(self.oscillator, Some((depth, _)) if depth == self.depth) {
+            self.oscillator = None;
+        }
+
+        if self.sample_range_depth == Some(self.depth) {
+            self.sample_range_depth = None;
+        }
+
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    fn reference(&self, path: SamplePath) -> SampleReference {
+        SampleReference {
+            path,
+            row_index: self.row_index,
+            oscillator: self.oscillator.map(|(_, slot)| slot),
+            in_sample_range: self.sample_range_depth.is_some(),
+        }
+    }
 }
 
 struct SamplesReader<R: BufRead> {
     reader: Reader<R>,
     is_in_filename_tag: bool,
+    context: Context,
     buffer: Vec<u8>,
+    /// True after the end of the file or an invalid XML
+    is_finished: bool,
 }
 
 impl<R: BufRead> SamplesReader<R> {
@@ -28,46 +171,118 @@ impl<R: BufRead> SamplesReader<R> {
         Self {
             reader,
             is_in_filename_tag: false,
+            context: Context::default(),
             buffer: Vec::with_capacity(128),
+            is_finished: false,
         }
     }
 }
 
 const FILENAME_TAG: &[u8; 8] = b"fileName";
+const SOUND_SOURCES_TAG: &[u8] = b"soundSources";
+const OSC1_TAG: &[u8] = b"osc1";
+const OSC2_TAG: &[u8] = b"osc2";
+const SAMPLE_RANGE_TAG: &[u8] = b"sampleRange";
+
+/// Read the attribute fileName used since the format version 3
+fn read_file_name_attribute(tag_bytes: &BytesStart, position: usize) -> Option<Result<SamplePath, SampleScanError>> {
+    let attribute = match tag_bytes.try_get_attribute(FILENAME_TAG) {
+        Ok(attribute) => attribute?,
+        Err(error) => return Some(Err(SampleScanError::Xml(position, error.to_string()))),
+    };
+
+    Some(
+        attribute
+            .unescape_value()
+            .map_err(|error| SampleScanError::Xml(position, error.to_string()))
+            .and_then(|value| make_sample_path(&value, position)),
+    )
+}
+
+fn make_sample_path(text: &str, position: usize) -> Result<SamplePath, SampleScanError> {
+    SamplePath::new(text).map_err(|_| SampleScanError::InvalidPath(position, text.to_string()))
+}
 
 impl<R: BufRead> Iterator for SamplesReader<R> {
-    type Item = SamplePath;
+    type Item = Result<SampleReference, SampleScanError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Ok(event) = self
-            .reader
-            .read_event_into(&mut self.buffer)
-        {
+        if self.is_finished {
+            return None;
+        }
+
+        loop {
+            self.buffer.clear();
+
+            let event = match self
+                .reader
+                .read_event_into(&mut self.buffer)
+            {
+                Ok(event) => event,
+                Err(error) => {
+                    self.is_finished = true;
+
+                    return Some(Err(SampleScanError::Xml(self.reader.buffer_position(), error.to_string())));
+                }
+            };
+            let position = self.reader.buffer_position();
+
             match event {
-                Event::Start(tag_bytes) if tag_bytes.name().as_ref() == FILENAME_TAG => {
-                    self.is_in_filename_tag = true;
+                Event::Start(tag_bytes) => {
+                    self.context
+                        .enter(tag_bytes.name().as_ref());
+
+                    if tag_bytes.name().as_ref() == FILENAME_TAG {
+                        self.is_in_filename_tag = true;
+                    } else if let Some(result) = read_file_name_attribute(&tag_bytes, position) {
+                        return Some(result.map(|path| self.context.reference(path)));
+                    }
                 }
-                Event::End(tag_bytes) if tag_bytes.name().as_ref() == FILENAME_TAG => {
-                    self.is_in_filename_tag = false;
+                Event::Empty(tag_bytes) => {
+                    self.context
+                        .enter(tag_bytes.name().as_ref());
+
+                    let result = read_file_name_attribute(&tag_bytes, position)
+                        .map(|result| result.map(|path| self.context.reference(path)));
+
+                    self.context.leave();
+
+                    if result.is_some() {
+                        return result;
+                    }
                 }
-                Event::Text(text_bytes) if self.is_in_filename_tag => {
-                    if let Ok(text_utf8) = String::from_utf8(text_bytes.to_vec()) {
-                        return SamplePath::new(text_utf8).ok();
+                Event::End(tag_bytes) => {
+                    if tag_bytes.name().as_ref() == FILENAME_TAG {
+                        self.is_in_filename_tag = false;
                     }
+
+                    self.context.leave();
+                }
+                Event::Text(text_bytes) if self.is_in_filename_tag => {
+                    let result = match std::str::from_utf8(&text_bytes) {
+                        Ok(text) => make_sample_path(text, position).map(|path| self.context.reference(path)),
+                        Err(_) => Err(SampleScanError::InvalidUtf8(position)),
+                    };
+
+                    return Some(result);
+                }
+                Event::Eof => {
+                    self.is_finished = true;
+
+                    return match self.context.depth {
+                        0 => None,
+                        depth => Some(Err(SampleScanError::UnexpectedEof(position, depth))),
+                    };
                 }
-                Event::Eof => break,
                 _ => (),
             }
-
-            self.buffer.clear();
         }
-
-        None
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{OscillatorSlot, SampleReference, SampleScanError};
     use crate::SamplePath;
 
     #[test]
@@ -98,5 +313,121 @@ mod tests {
         );
         assert_eq!(SamplePath::new("SAMPLES/ARTISTS/CHAZ/CB3-BELL.WAV").unwrap(), paths[6]);
         assert_eq!(SamplePath::new("SAMPLES/ARTISTS/CHAZ/CB8-yo.wav").unwrap(), paths[7]);
+
+        let references: Vec<SampleReference> =
+            super::read_sample_references(Cursor::new(include_str!("../data_tests/KITS/KIT030.XML"))).collect();
+
+        assert_eq!(
+            references
+                .iter()
+                .map(|reference| reference.row_index)
+                .collect::<Vec<_>>(),
+            (0..8).map(Some).collect::<Vec<_>>()
+        );
+        assert!(references
+            .iter()
+            .all(|reference| reference.oscillator == Some(OscillatorSlot::Osc1) && !reference.in_sample_range));
+    }
+
+    #[test]
+    fn test_read_sample_references() {
+        let xml = "<kit>
+            <soundSources>
+                <sound><osc1 fileName=\"SAMPLES/kick.wav\" /></sound>
+                <midiOutput channel=\"1\" note=\"60\" />
+                <sound>
+                    <osc1 type=\"sample\" />
+                    <osc2>
+                        <sampleRanges>
+                            <sampleRange rangeTopNote=\"60\"><fileName>SAMPLES/C4.wav</fileName></sampleRange>
+                            <sampleRange fileName=\"SAMPLES/C5.wav\" />
+                        </sampleRanges>
+                    </osc2>
+                </sound>
+            </soundSources>
+            <fileName>SAMPLES/outside.wav</fileName>
+        </kit>";
+        let reference =
+            |path: &str, row_index: Option<usize>, oscillator: Option<OscillatorSlot>, in_sample_range: bool| SampleReference {
+                path: SamplePath::new(path).unwrap(),
+                row_index,
+                oscillator,
+                in_sample_range,
+            };
+
+        assert_eq!(
+            super::read_sample_references(xml.as_bytes()).collect::<Vec<_>>(),
+            vec![
+                reference("SAMPLES/kick.wav", Some(0), Some(OscillatorSlot::Osc1), false),
+                reference("SAMPLES/C4.wav", Some(2), Some(OscillatorSlot::Osc2), true),
+                reference("SAMPLES/C5.wav", Some(2), Some(OscillatorSlot::Osc2), true),
+                reference("SAMPLES/outside.wav", None, None, false),
+            ]
+        );
+
+        let synth_references: Vec<SampleReference> =
+            super::read_sample_references(include_str!("../data_tests/SYNTHS/SYNT168A.XML").as_bytes()).collect();
+
+        assert_eq!(synth_references.len(), 2);
+        assert!(synth_references
+            .iter()
+            .all(|reference| reference.row_index.is_none()
+                && reference.oscillator == Some(OscillatorSlot::Osc1)
+                && reference.in_sample_range));
+    }
+
+    #[test]
+    fn test_try_read_sample_paths_truncated() {
+        let file_content = include_bytes!("../data_tests/KITS/KIT030.XML");
+        let results: Vec<Result<SamplePath, SampleScanError>> =
+            super::try_read_sample_paths(&file_content[..file_content.len() / 2]).collect();
+        let (last, paths) = results.split_last().unwrap();
+
+        assert!(!paths.is_empty() && paths.len() < 8);
+        assert!(paths.iter().all(Result::is_ok));
+        assert!(matches!(
+            last,
+            Err(SampleScanError::UnexpectedEof(_, _) | SampleScanError::Xml(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_try_read_sample_paths_errors() {
+        let invalid_utf8 =
+            b"<sound><osc1><fileName>SAMPLES/\xFF.wav</fileName></osc1><osc2><fileName>SAMPLES/b.wav</fileName></osc2></sound>";
+
+        assert_eq!(
+            super::try_read_sample_paths(invalid_utf8.as_slice()).collect::<Vec<_>>(),
+            vec![
+                Err(SampleScanError::InvalidUtf8(36)),
+                Ok(SamplePath::new("SAMPLES/b.wav").unwrap())
+            ]
+        );
+        assert_eq!(
+            super::read_sample_paths(invalid_utf8.as_slice()).collect::<Vec<_>>(),
+            vec![SamplePath::new("SAMPLES/b.wav").unwrap()]
+        );
+
+        let mismatch = b"<sound><osc1></osc2><fileName>SAMPLES/b.wav</fileName></sound>";
+        let results: Vec<Result<SamplePath, SampleScanError>> = super::try_read_sample_paths(mismatch.as_slice()).collect();
+
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], Err(SampleScanError::Xml(_, _))));
+    }
+
+    #[test]
+    fn test_read_file_name_attributes() {
+        use std::io::Cursor;
+
+        let file_content = Cursor::new(include_str!("../data_tests/SYNTHS/SYNT168A.XML"));
+        let paths: Vec<SamplePath> = super::read_sample_paths(file_content).collect();
+
+        assert_eq!(
+            paths,
+            vec![
+                SamplePath::new("SAMPLES/Artists/Leonard Ludvigsen/Hangdrum/1.wav").unwrap(),
+                SamplePath::new("SAMPLES/Artists/Leonard Ludvigsen/Hangdrum/2.wav").unwrap(),
+            ]
+        );
     }
 }
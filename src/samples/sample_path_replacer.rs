@@ -1,14 +1,22 @@
 use crate::SamplePath;
-use quick_xml::events::{BytesText, Event};
+use quick_xml::events::{BytesStart, BytesText, Event};
 use quick_xml::{Reader, Writer};
 use std::collections::BTreeMap;
 use std::io::{BufRead, Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Replace the sample paths of a patch without parsing it
+///
+/// The replacements are applied in this order:
+///  - the exact replacements set by [SamplePathReplacer::set_replacement]
+///  - the exact replacements ignoring the case, if enabled by [SamplePathReplacer::set_case_insensitive]
+///  - the longest prefix set by [SamplePathReplacer::set_prefix_replacement]
 #[derive(Default)]
 pub struct SamplePathReplacer {
     paths_to_replace: BTreeMap<SamplePath, SamplePath>,
+    prefixes_to_replace: BTreeMap<SamplePath, SamplePath>,
+    case_insensitive: bool,
 }
 
 impl SamplePathReplacer {
@@ -24,8 +32,75 @@ impl SamplePathReplacer {
             .insert(original, replacement);
     }
 
+    /// Set or reset a replacement of all the paths starting by a prefix.
+    ///
+    /// The prefix is compared component by component, "SAMPLES/Kick" does not match "SAMPLES/Kicks/1.wav".
+    ///
+    /// # Arguments
+    ///
+    /// * `old_prefix`: The prefix to replace, for example "SAMPLES/Artists/Old Name"
+    /// * `new_prefix`: The replacement, for example "SAMPLES/Artists/New Name"
+    ///
+    pub fn set_prefix_replacement(&mut self, old_prefix: SamplePath, new_prefix: SamplePath) {
+        self.prefixes_to_replace
+            .insert(old_prefix, new_prefix);
+    }
+
+    /// Enable or disable the case insensitive matching, disabled by default.
+    ///
+    /// Like FAT, only the ASCII characters are compared ignoring the case.
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) {
+        self.case_insensitive = case_insensitive;
+    }
+
+    /// Get the replacement of a path, None if the path is not replaced.
+    pub fn replacement(&self, path: &SamplePath) -> Option<SamplePath> {
+        if let Some(replacement) = self.paths_to_replace.get(path) {
+            return Some(replacement.clone());
+        }
+
+        if self.case_insensitive {
+            let exact_match = self
+                .paths_to_replace
+                .iter()
+                .find(|(original, _)| strip_prefix(path.to_path(), original.to_path(), true) == Some(PathBuf::new()));
+
+            if let Some((_, replacement)) = exact_match {
+                return Some(replacement.clone());
+            }
+        }
+
+        self.prefixes_to_replace
+            .iter()
+            .filter_map(|(old_prefix, new_prefix)| {
+                strip_prefix(path.to_path(), old_prefix.to_path(), self.case_insensitive).map(|rest| {
+                    (
+                        old_prefix
+                            .to_path()
+                            .components()
+                            .count(),
+                        new_prefix,
+                        rest,
+                    )
+                })
+            })
+            .max_by_key(|(length, _, _)| *length)
+            .and_then(|(_, new_prefix, rest)| {
+                SamplePath::new(
+                    new_prefix
+                        .to_path()
+                        .join(rest)
+                        .to_string_lossy(),
+                )
+                .ok()
+            })
+    }
+
     /// Rewrite a XML document following the replacements.
     ///
+    /// The paths are replaced in the elements "fileName" written by the older firmwares, and in the attributes
+    /// "fileName" written since the format version 3.
+    ///
     /// # Arguments
     ///
     /// * `reader`: The object that implement `Read`, used to read the XML.
@@ -66,16 +141,23 @@ impl SamplePathReplacer {
                 Event::Start(tag_bytes) if tag_bytes.name().as_ref() == super::FILENAME_TAG => {
                     is_in_filename_tag = true;
                 }
+                Event::Start(tag_bytes) => {
+                    if let Some(tag) = self.rewrite_file_name_attribute(tag_bytes) {
+                        event = Event::Start(tag);
+                    }
+                }
+                Event::Empty(tag_bytes) => {
+                    if let Some(tag) = self.rewrite_file_name_attribute(tag_bytes) {
+                        event = Event::Empty(tag);
+                    }
+                }
                 Event::End(tag_bytes) if tag_bytes.name().as_ref() == super::FILENAME_TAG => {
                     is_in_filename_tag = false;
                 }
                 Event::Text(text_bytes) if is_in_filename_tag => {
                     if let Ok(text_utf8) = String::from_utf8(text_bytes.to_vec()) {
                         if let Ok(original_path) = SamplePath::new(text_utf8) {
-                            if let Some(replacement_path) = self
-                                .paths_to_replace
-                                .get(&original_path)
-                            {
+                            if let Some(replacement_path) = self.replacement(&original_path) {
                                 event = Event::Text(BytesText::new(&replacement_path.to_string_lossy()).into_owned());
                             }
                         }
@@ -92,6 +174,33 @@ impl SamplePathReplacer {
         Ok(())
     }
 
+    /// Replace the path of the attribute "fileName" of a tag, returns None if the tag is not changed.
+    ///
+    /// The other attributes are copied as they were read.
+    fn rewrite_file_name_attribute(&self, tag_bytes: &BytesStart) -> Option<BytesStart<'static>> {
+        let attributes = tag_bytes
+            .attributes()
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        let replacement_path = attributes
+            .iter()
+            .find(|attribute| attribute.key.as_ref() == super::FILENAME_TAG)
+            .and_then(|attribute| attribute.unescape_value().ok())
+            .and_then(|value| SamplePath::new(value.as_ref()).ok())
+            .and_then(|original_path| self.replacement(&original_path))?;
+        let replacement_path = replacement_path.to_string_lossy();
+        let mut tag = BytesStart::new(String::from_utf8_lossy(tag_bytes.name().as_ref()).into_owned());
+
+        for attribute in attributes {
+            match attribute.key.as_ref() == super::FILENAME_TAG {
+                true => tag.push_attribute(("fileName", replacement_path.as_str())),
+                false => tag.push_attribute(attribute),
+            }
+        }
+
+        Some(tag)
+    }
+
     pub fn rewrite_file(&self, file_path: impl AsRef<Path>) -> Result<(), quick_xml::Error>
     {
         fn make_err(e: std::io::Error) -> quick_xml::Error { quick_xml::Error::Io(Arc::new(e)) }
@@ -106,6 +215,32 @@ impl SamplePathReplacer {
     }
 }
 
+/// Remove a prefix from a path, comparing the components
+fn strip_prefix(path: &Path, prefix: &Path, case_insensitive: bool) -> Option<PathBuf> {
+    let mut components = path.components();
+
+    for prefix_component in prefix.components() {
+        let component = components.next()?;
+        let is_equal = match case_insensitive {
+            true => component
+                .as_os_str()
+                .to_string_lossy()
+                .eq_ignore_ascii_case(
+                    &prefix_component
+                        .as_os_str()
+                        .to_string_lossy(),
+                ),
+            false => component == prefix_component,
+        };
+
+        if !is_equal {
+            return None;
+        }
+    }
+
+    Some(components.as_path().to_path_buf())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::samples::sample_path_replacer::SamplePathReplacer;
@@ -203,4 +338,149 @@ mod tests {
         assert_eq!(SamplePath::new("SAMPLES/ARTISTS/CHAZ/CB3-BELL_YO.WAV").unwrap(), paths[6]);
         assert_eq!(SamplePath::new("SAMPLES/ARTISTS/CHAZ/CB8-yo_YO.wav").unwrap(), paths[7]);
     }
+
+    #[test]
+    fn test_replace_file_name_attributes() {
+        use crate::{deserialize_synth, samples::read_sample_paths};
+        use pretty_assertions::assert_eq;
+        use std::{collections::BTreeMap, io::Cursor};
+
+        let file_content = include_str!("../data_tests/SYNTHS/SYNT168A.XML");
+        let mut replacer = SamplePathReplacer::default();
+        let mut buffer = Vec::new();
+
+        replacer.set_prefix_replacement(path("SAMPLES/Artists/Leonard Ludvigsen/Hangdrum"), path("SAMPLES/Hangdrum"));
+        replacer
+            .rewrite(file_content.as_bytes(), &mut buffer)
+            .unwrap();
+
+        let rewritten = String::from_utf8(buffer).unwrap();
+
+        assert_eq!(
+            read_sample_paths(Cursor::new(rewritten.as_bytes())).collect::<Vec<_>>(),
+            read_sample_paths(Cursor::new(file_content.as_bytes()))
+                .map(|sample_path| replacer
+                    .replacement(&sample_path)
+                    .unwrap_or(sample_path))
+                .collect::<Vec<_>>()
+        );
+        assert!(rewritten.contains("fileName=\"SAMPLES/Hangdrum/1.wav\""));
+        assert!(!rewritten.contains("Leonard Ludvigsen/Hangdrum"));
+
+        let mut expected_synth = deserialize_synth(file_content).unwrap();
+
+        expected_synth
+            .sound
+            .replace_sample_paths(&BTreeMap::from([
+                (
+                    path("SAMPLES/Artists/Leonard Ludvigsen/Hangdrum/1.wav"),
+                    path("SAMPLES/Hangdrum/1.wav"),
+                ),
+                (
+                    path("SAMPLES/Artists/Leonard Ludvigsen/Hangdrum/2.wav"),
+                    path("SAMPLES/Hangdrum/2.wav"),
+                ),
+            ]));
+
+        assert_eq!(deserialize_synth(&rewritten).unwrap(), expected_synth);
+    }
+
+    const OSCILLATORS_XML: &str = "<sound>
+\t<osc1 type=\"sample\">
+\t\t<fileName>SAMPLES/Artists/Old Name/kick.wav</fileName>
+\t</osc1>
+\t<osc2 type=\"sample\">
+\t\t<sampleRanges>
+\t\t\t<sampleRange rangeTopNote=\"60\">
+\t\t\t\t<fileName>samples/artists/old name/Piano/C4.wav</fileName>
+\t\t\t</sampleRange>
+\t\t\t<sampleRange>
+\t\t\t\t<fileName>SAMPLES/Artists/Old Name/Piano/C5.wav</fileName>
+\t\t\t</sampleRange>
+\t\t</sampleRanges>
+\t</osc2>
+</sound>";
+
+    fn rewrite(replacer: &SamplePathReplacer) -> String {
+        let mut buffer = Vec::new();
+
+        replacer
+            .rewrite(OSCILLATORS_XML.as_bytes(), &mut buffer)
+            .unwrap();
+
+        String::from_utf8(buffer).unwrap()
+    }
+
+    fn path(path: &str) -> SamplePath {
+        SamplePath::new(path).unwrap()
+    }
+
+    #[test]
+    fn test_prefix_replacement() {
+        use pretty_assertions::assert_eq;
+
+        let mut replacer = SamplePathReplacer::default();
+
+        replacer.set_prefix_replacement(path("SAMPLES/Artists/Old Name"), path("SAMPLES/Artists/New Name"));
+
+        assert_eq!(
+            rewrite(&replacer),
+            OSCILLATORS_XML
+                .replace("SAMPLES/Artists/Old Name/kick.wav", "SAMPLES/Artists/New Name/kick.wav")
+                .replace(
+                    "SAMPLES/Artists/Old Name/Piano/C5.wav",
+                    "SAMPLES/Artists/New Name/Piano/C5.wav"
+                )
+        );
+
+        replacer.set_case_insensitive(true);
+
+        assert_eq!(
+            rewrite(&replacer),
+            OSCILLATORS_XML
+                .replace("/Old Name/", "/New Name/")
+                .replace(
+                    "samples/artists/old name/Piano/C4.wav",
+                    "SAMPLES/Artists/New Name/Piano/C4.wav"
+                )
+        );
+    }
+
+    #[test]
+    fn test_replacement_priority() {
+        use pretty_assertions::assert_eq;
+
+        let mut replacer = SamplePathReplacer::default();
+
+        replacer.set_prefix_replacement(path("SAMPLES"), path("BACKUP"));
+        replacer.set_prefix_replacement(path("SAMPLES/Artists/Old Name/Piano"), path("SAMPLES/Piano"));
+        replacer.set_replacement(path("SAMPLES/Artists/Old Name/Piano/C5.wav"), path("SAMPLES/C5.wav"));
+        replacer.set_replacement(path("SAMPLES/ARTISTS/OLD NAME/PIANO/C4.WAV"), path("SAMPLES/C4.wav"));
+
+        assert_eq!(
+            replacer.replacement(&path("SAMPLES/Artists/Old Name/Piano/C5.wav")),
+            Some(path("SAMPLES/C5.wav"))
+        );
+        assert_eq!(
+            replacer.replacement(&path("SAMPLES/Artists/Old Name/Piano/C3.wav")),
+            Some(path("SAMPLES/Piano/C3.wav"))
+        );
+        assert_eq!(
+            replacer.replacement(&path("SAMPLES/Artists/Old Name/kick.wav")),
+            Some(path("BACKUP/Artists/Old Name/kick.wav"))
+        );
+        assert_eq!(replacer.replacement(&path("samples/artists/old name/Piano/C4.wav")), None);
+        assert_eq!(replacer.replacement(&path("SAMPLESS/kick.wav")), None);
+
+        replacer.set_case_insensitive(true);
+
+        assert_eq!(
+            replacer.replacement(&path("samples/artists/old name/Piano/C4.wav")),
+            Some(path("SAMPLES/C4.wav"))
+        );
+        assert_eq!(
+            replacer.replacement(&path("samples/artists/old name/piano/C3.wav")),
+            Some(path("SAMPLES/Piano/C3.wav"))
+        );
+    }
 }
@@ -0,0 +1,201 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use super::read_sample_paths;
+use crate::SamplePath;
+
+/// The patches using a sample, see [SampleUsageIndex]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SampleUsage {
+    /// The path as written by the first patch referencing the sample
+    pub path: SamplePath,
+    /// The different ways the patches write the path, for example with another case.
+    pub spellings: BTreeSet<SamplePath>,
+    /// The patches referencing the sample, in the order they were indexed
+    pub patches: Vec<PathBuf>,
+}
+
+/// The patches using each sample, see [build_usage_index]
+///
+/// Like FAT, the paths are compared ignoring the case. The separators '\' written by some Windows tools are
+/// considered equal to '/'.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SampleUsageIndex {
+    usages: BTreeMap<String, SampleUsage>,
+}
+
+/// Index the samples used by patches
+///
+/// Each item is the path of a patch and its XML content. The empty paths are ignored.
+/// ```
+/// # use std::path::PathBuf;
+/// # use deluge::{build_usage_index, SamplePath};
+/// let index = build_usage_index(
+///     [
+///         (PathBuf::from("KITS/KIT000.XML"), "<kit><fileName>SAMPLES/kick.wav</fileName></kit>".to_string()),
+///         (PathBuf::from("KITS/KIT001.XML"), "<kit><fileName>samples\\KICK.WAV</fileName></kit>".to_string()),
+///     ]
+///     .into_iter(),
+/// );
+/// let usage = index.get(&SamplePath::new("SAMPLES/kick.wav").unwrap()).unwrap();
+///
+/// assert_eq!(usage.patches.len(), 2);
+/// assert_eq!(usage.spellings.len(), 2);
+/// ```
+pub fn build_usage_index(patches: impl Iterator<Item = (PathBuf, String)>) -> SampleUsageIndex {
+    let mut index = SampleUsageIndex::default();
+
+    for (patch_path, xml) in patches {
+        for sample_path in read_sample_paths(xml.as_bytes()) {
+            index.insert(sample_path, &patch_path);
+        }
+    }
+
+    index
+}
+
+fn normalize(path: &SamplePath) -> String {
+    path.to_string_lossy()
+        .replace('\\', "/")
+        .to_ascii_lowercase()
+}
+
+impl SampleUsageIndex {
+    fn insert(&mut self, sample_path: SamplePath, patch_path: &Path) {
+        if sample_path.is_empty() {
+            return;
+        }
+
+        let usage = self
+            .usages
+            .entry(normalize(&sample_path))
+            .or_insert_with(|| SampleUsage {
+                path: sample_path.clone(),
+                spellings: BTreeSet::new(),
+                patches: Vec::new(),
+            });
+
+        usage.spellings.insert(sample_path);
+
+        if !usage
+            .patches
+            .iter()
+            .any(|path| path == patch_path)
+        {
+            usage
+                .patches
+                .push(patch_path.to_path_buf());
+        }
+    }
+
+    /// Get the usage of a sample, the path can be written with any case or separator
+    pub fn get(&self, path: &SamplePath) -> Option<&SampleUsage> {
+        self.usages.get(&normalize(path))
+    }
+
+    /// Get the usages of all the samples
+    pub fn iter(&self) -> impl Iterator<Item = &SampleUsage> {
+        self.usages.values()
+    }
+
+    /// The count of samples referenced
+    pub fn len(&self) -> usize {
+        self.usages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.usages.is_empty()
+    }
+
+    /// The samples referenced with different spellings of their path
+    pub fn inconsistent_spellings(&self) -> impl Iterator<Item = &SampleUsage> {
+        self.iter()
+            .filter(|usage| usage.spellings.len() > 1)
+    }
+
+    /// Find the samples not referenced by any patch
+    ///
+    /// For example, to find the orphan files of the SAMPLES folder.
+    pub fn unused(&self, samples_on_disk: &BTreeSet<SamplePath>) -> Vec<SamplePath> {
+        samples_on_disk
+            .iter()
+            .filter(|path| self.get(path).is_none())
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn make_index() -> SampleUsageIndex {
+        build_usage_index(
+            [
+                ("KITS/KIT030.XML", include_str!("../data_tests/KITS/KIT030.XML").to_string()),
+                (
+                    "SYNTHS/SYNT168A.XML",
+                    include_str!("../data_tests/SYNTHS/SYNT168A.XML").to_string(),
+                ),
+                (
+                    "SYNTHS/SYNT000.XML",
+                    "<sound><osc1><fileName>samples\\artists\\chaz\\cb3-bell.wav</fileName></osc1></sound>".to_string(),
+                ),
+            ]
+            .into_iter()
+            .map(|(path, xml)| (PathBuf::from(path), xml)),
+        )
+    }
+
+    #[test]
+    fn test_build_usage_index() {
+        let index = make_index();
+        let bell = index
+            .get(&SamplePath::new("SAMPLES/ARTISTS/CHAZ/CB3-BELL.WAV").unwrap())
+            .unwrap();
+
+        assert_eq!(index.len(), 10);
+        assert_eq!(bell.path, SamplePath::new("SAMPLES/ARTISTS/CHAZ/CB3-BELL.WAV").unwrap());
+        assert_eq!(
+            bell.patches,
+            vec![PathBuf::from("KITS/KIT030.XML"), PathBuf::from("SYNTHS/SYNT000.XML")]
+        );
+        assert_eq!(
+            index
+                .inconsistent_spellings()
+                .map(|usage| &usage.path)
+                .collect::<Vec<_>>(),
+            vec![&bell.path]
+        );
+        assert_eq!(
+            index
+                .get(&SamplePath::new("SAMPLES/Artists/Leonard Ludvigsen/Hangdrum/2.wav").unwrap())
+                .unwrap()
+                .patches,
+            vec![PathBuf::from("SYNTHS/SYNT168A.XML")]
+        );
+    }
+
+    #[test]
+    fn test_unused_samples() {
+        let index = make_index();
+        let samples_on_disk = BTreeSet::from_iter(
+            [
+                "SAMPLES/ARTISTS/CHAZ/cb8-YO.wav",
+                "SAMPLES/ARTISTS/CHAZ/orphan.wav",
+                "SAMPLES/Artists/Leonard Ludvigsen/Hangdrum/1.wav",
+                "SAMPLES/Artists/Leonard Ludvigsen/Hangdrum/3.wav",
+            ]
+            .map(|path| SamplePath::new(path).unwrap()),
+        );
+
+        assert_eq!(
+            index.unused(&samples_on_disk),
+            vec![
+                SamplePath::new("SAMPLES/ARTISTS/CHAZ/orphan.wav").unwrap(),
+                SamplePath::new("SAMPLES/Artists/Leonard Ludvigsen/Hangdrum/3.wav").unwrap(),
+            ]
+        );
+    }
+}
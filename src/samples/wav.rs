@@ -0,0 +1,138 @@
+use std::path::Path;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::{CardError, FileSystem, LocalFileSystem};
+
+/// The properties of a WAV file, see [wav_info]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WavInfo {
+    /// The count of frames, a frame contains one sample per channel
+    pub frame_count: u64,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bits_per_sample: u16,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum WavError {
+    #[error(transparent)]
+    CardError(#[from] CardError),
+
+    #[error("not a RIFF WAVE file")]
+    NotWave,
+
+    #[error("the chunk '{0}' is missing")]
+    MissingChunk(&'static str),
+
+    #[error("the format chunk is invalid")]
+    InvalidFormat,
+}
+
+const RIFF_HEADER_SIZE: u64 = 12;
+const CHUNK_HEADER_SIZE: usize = 8;
+const FORMAT_CHUNK_MIN_SIZE: usize = 16;
+
+/// Read the properties of a WAV file
+///
+/// Only the RIFF header and the chunk headers are read, not the audio data.
+pub fn wav_info(path: &Path) -> Result<WavInfo, WavError> {
+    read_wav_info(&LocalFileSystem, path)
+}
+
+/// Read the properties of a WAV file using a [FileSystem]
+pub(crate) fn read_wav_info<FS: FileSystem>(file_system: &FS, path: &Path) -> Result<WavInfo, WavError> {
+    let header = file_system.read_file_range(path, 0, RIFF_HEADER_SIZE as usize)?;
+
+    if header.len() < RIFF_HEADER_SIZE as usize || &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err(WavError::NotWave);
+    }
+
+    let mut offset = RIFF_HEADER_SIZE;
+    // The sample rate, the channels, the bits per sample and the bytes per frame
+    let mut format: Option<(u32, u16, u16, u16)> = None;
+
+    loop {
+        let chunk_header = file_system.read_file_range(path, offset, CHUNK_HEADER_SIZE)?;
+
+        if chunk_header.len() < CHUNK_HEADER_SIZE {
+            return Err(match format {
+                Some(_) => WavError::MissingChunk("data"),
+                None => WavError::MissingChunk("fmt "),
+            });
+        }
+
+        let chunk_size = LittleEndian::read_u32(&chunk_header[4..8]) as u64;
+
+        match &chunk_header[0..4] {
+            b"fmt " => {
+                let chunk = file_system.read_file_range(path, offset + CHUNK_HEADER_SIZE as u64, FORMAT_CHUNK_MIN_SIZE)?;
+
+                if chunk.len() < FORMAT_CHUNK_MIN_SIZE {
+                    return Err(WavError::InvalidFormat);
+                }
+
+                let channels = LittleEndian::read_u16(&chunk[2..4]);
+                let sample_rate = LittleEndian::read_u32(&chunk[4..8]);
+                let block_align = LittleEndian::read_u16(&chunk[12..14]);
+                let bits_per_sample = LittleEndian::read_u16(&chunk[14..16]);
+
+                if channels == 0 || block_align == 0 {
+                    return Err(WavError::InvalidFormat);
+                }
+
+                format = Some((sample_rate, channels, bits_per_sample, block_align));
+            }
+            b"data" => {
+                let (sample_rate, channels, bits_per_sample, block_align) = format.ok_or(WavError::MissingChunk("fmt "))?;
+
+                return Ok(WavInfo {
+                    frame_count: chunk_size / block_align as u64,
+                    sample_rate,
+                    channels,
+                    bits_per_sample,
+                });
+            }
+            _ => {}
+        }
+
+        // The chunks are aligned on 2 bytes
+        offset += CHUNK_HEADER_SIZE as u64 + chunk_size + chunk_size % 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryFileSystem;
+    use test_case::test_case;
+
+    const MONO: &[u8] = include_bytes!("../data_tests/WAV/mono_16bit_44100.wav");
+    const STEREO: &[u8] = include_bytes!("../data_tests/WAV/stereo_24bit_48000.wav");
+
+    #[test_case(MONO, WavInfo { frame_count: 100, sample_rate: 44100, channels: 1, bits_per_sample: 16 } ; "mono")]
+    #[test_case(STEREO, WavInfo { frame_count: 25, sample_rate: 48000, channels: 2, bits_per_sample: 24 } ; "stereo with list chunk")]
+    fn test_read_wav_info(content: &[u8], expected: WavInfo) {
+        let file_system = MemoryFileSystem::new().with_file("sample.wav", content);
+
+        assert_eq!(read_wav_info(&file_system, Path::new("sample.wav")), Ok(expected));
+    }
+
+    #[test]
+    fn test_wav_info_local_file() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/data_tests/WAV/mono_16bit_44100.wav");
+
+        assert_eq!(wav_info(&path).unwrap().frame_count, 100);
+    }
+
+    #[test_case(b"", WavError::NotWave ; "empty")]
+    #[test_case(b"RIFF\x04\x00\x00\x00AIFF", WavError::NotWave ; "not wave")]
+    #[test_case(b"RIFF\x04\x00\x00\x00WAVE", WavError::MissingChunk("fmt ") ; "no chunk")]
+    #[test_case(&MONO[..36], WavError::MissingChunk("data") ; "no data chunk")]
+    #[test_case(&MONO[..30], WavError::InvalidFormat ; "truncated format")]
+    fn test_read_wav_info_errors(content: &[u8], expected: WavError) {
+        let file_system = MemoryFileSystem::new().with_file("sample.wav", content);
+
+        assert_eq!(read_wav_info(&file_system, Path::new("sample.wav")), Err(expected));
+    }
+}
@@ -0,0 +1,163 @@
+//! Detect the encoding of a patch file and transcode it to UTF-8
+//!
+//! The Deluge writes UTF-8 files but the patches edited on a computer can be saved
+//! in UTF-16 with a BOM or in latin-1.
+use std::{borrow::Cow, ops::Range};
+
+use crate::SerializationError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+/// Returns true if the content can be parsed without transcoding
+///
+/// Only the beginning of the content is required, the BOM and the XML declaration are enough.
+pub(crate) fn is_utf8_without_bom(prefix: &[u8]) -> bool {
+    matches!(detect_encoding(prefix), Ok((Encoding::Utf8, 0)))
+}
+
+/// Decode the XML content, the XML declaration of a transcoded content declares UTF-8
+pub(crate) fn decode_xml(bytes: &[u8]) -> Result<Cow<'_, str>, SerializationError> {
+    let (encoding, bom_length) = detect_encoding(bytes)?;
+    let content = &bytes[bom_length..];
+    let xml = match encoding {
+        Encoding::Utf8 => {
+            return std::str::from_utf8(content)
+                .map(Cow::Borrowed)
+                .map_err(|_| SerializationError::InvalidEncoding("UTF-8".to_string()))
+        }
+        Encoding::Utf16Le => decode_utf16(content, u16::from_le_bytes)?,
+        Encoding::Utf16Be => decode_utf16(content, u16::from_be_bytes)?,
+        Encoding::Latin1 => content
+            .iter()
+            .map(|byte| char::from(*byte))
+            .collect(),
+    };
+
+    Ok(Cow::Owned(declare_utf8(xml)))
+}
+
+/// Detect the encoding using the BOM or the XML declaration, returns the encoding and the length of the BOM
+fn detect_encoding(bytes: &[u8]) -> Result<(Encoding, usize), SerializationError> {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => return Ok((Encoding::Utf8, 3)),
+        [0xFF, 0xFE, ..] => return Ok((Encoding::Utf16Le, 2)),
+        [0xFE, 0xFF, ..] => return Ok((Encoding::Utf16Be, 2)),
+        // Without BOM the first char of the declaration is enough, see appendix F of the XML specification
+        [b'<', 0, b'?', 0, ..] => return Ok((Encoding::Utf16Le, 0)),
+        [0, b'<', 0, b'?', ..] => return Ok((Encoding::Utf16Be, 0)),
+        _ => {}
+    }
+
+    let encoding = match get_declared_encoding(bytes) {
+        None => return Ok((Encoding::Utf8, 0)),
+        Some(encoding) => encoding,
+    };
+
+    match encoding.to_ascii_lowercase().as_str() {
+        "utf-8" | "utf8" | "us-ascii" | "ascii" => Ok((Encoding::Utf8, 0)),
+        "latin-1" | "latin1" | "iso-8859-1" | "iso8859-1" | "iso_8859-1" => Ok((Encoding::Latin1, 0)),
+        _ => Err(SerializationError::UnsupportedEncoding(encoding)),
+    }
+}
+
+/// Get the value of the attribute "encoding" of the XML declaration
+///
+/// The declaration contains only ASCII chars so it can be read before knowing the encoding.
+fn get_declared_encoding(bytes: &[u8]) -> Option<String> {
+    let end = bytes
+        .windows(2)
+        .position(|window| window == b"?>")?;
+    let declaration = std::str::from_utf8(&bytes[..end + 2]).ok()?;
+
+    find_declared_encoding(declaration).map(|range| declaration[range].to_string())
+}
+
+/// Find the range of the value of the attribute "encoding" of the XML declaration
+fn find_declared_encoding(xml: &str) -> Option<Range<usize>> {
+    let declaration = &xml[..xml.find("?>")?];
+
+    if !declaration.starts_with("<?xml") {
+        return None;
+    }
+
+    let value = declaration[declaration.find("encoding")? + "encoding".len()..]
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start();
+    let quote = value
+        .chars()
+        .next()
+        .filter(|c| *c == '"' || *c == '\'')?;
+    let value_start = declaration.len() - value.len() + 1;
+    let value_length = declaration[value_start..].find(quote)?;
+
+    Some(value_start..value_start + value_length)
+}
+
+fn decode_utf16(content: &[u8], to_u16: fn([u8; 2]) -> u16) -> Result<String, SerializationError> {
+    let invalid_encoding = || SerializationError::InvalidEncoding("UTF-16".to_string());
+
+    if !content.len().is_multiple_of(2) {
+        return Err(invalid_encoding());
+    }
+
+    let units = content
+        .chunks_exact(2)
+        .map(|pair| to_u16([pair[0], pair[1]]))
+        .collect::<Vec<_>>();
+
+    String::from_utf16(&units).map_err(|_| invalid_encoding())
+}
+
+/// Replace the encoding of the XML declaration by UTF-8, the XML parser would use the declared encoding otherwise
+fn declare_utf8(mut xml: String) -> String {
+    if let Some(range) = find_declared_encoding(&xml) {
+        xml.replace_range(range, "UTF-8");
+    }
+
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    #[test_case(b"<?xml version=\"1.0\"?><sound/>", Encoding::Utf8 ; "no encoding")]
+    #[test_case(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>", Encoding::Utf8 ; "utf-8")]
+    #[test_case(b"<?xml version='1.0' encoding='latin-1'?>", Encoding::Latin1 ; "latin-1")]
+    #[test_case(b"<?xml version=\"1.0\" encoding = \"ISO-8859-1\"?>", Encoding::Latin1 ; "iso-8859-1")]
+    #[test_case(b"\xFF\xFE<\0?\0", Encoding::Utf16Le ; "utf-16 le bom")]
+    #[test_case(b"\0<\0?\0x", Encoding::Utf16Be ; "utf-16 be without bom")]
+    #[test_case(b"<sound/>", Encoding::Utf8 ; "no declaration")]
+    fn test_detect_encoding(bytes: &[u8], expected: Encoding) {
+        assert_eq!(detect_encoding(bytes).unwrap().0, expected);
+    }
+
+    #[test]
+    fn test_unsupported_encoding() {
+        assert!(matches!(
+            detect_encoding(b"<?xml version=\"1.0\" encoding=\"EBCDIC\"?>"),
+            Err(SerializationError::UnsupportedEncoding(encoding)) if encoding == "EBCDIC"
+        ));
+    }
+
+    #[test]
+    fn test_decode_latin1() {
+        let xml = decode_xml(b"<?xml version=\"1.0\" encoding=\"latin-1\"?><a name=\"caf\xE9\"/>").unwrap();
+
+        assert_eq!(xml, "<?xml version=\"1.0\" encoding=\"UTF-8\"?><a name=\"café\"/>");
+    }
+
+    #[test]
+    fn test_decode_utf8_is_borrowed() {
+        assert!(matches!(decode_xml(b"<a/>").unwrap(), Cow::Borrowed("<a/>")));
+    }
+}
@@ -1,9 +1,15 @@
 use std::{num::ParseIntError, sync::Arc};
 
+use super::{FirmwareVersion, FormatVersion};
+use crate::SampleZoneError;
+
 #[derive(thiserror::Error, Debug, Clone)]
 pub enum SerializationError {
-    #[error("parsing XML failed: {0}")]
-    XmlParsingFailed(#[from] Arc<xmltree::ParseError>),
+    #[error("reading XML failed: {0}")]
+    XmlReadingFailed(Arc<quick_xml::Error>),
+
+    #[error("writing XML failed: {0}")]
+    XmlWritingFailed(Arc<std::io::Error>),
 
     #[error("parsing integer failed: {0}")]
     ParseIntError(#[from] ParseIntError),
@@ -23,12 +29,21 @@ pub enum SerializationError {
     #[error("unsupported sound source '{0}'")]
     UnsupportedSoundSource(String),
 
-    #[error("unsupported sound type")]
-    UnsupportedSoundType,
+    #[error("unsupported sound type '{0}'")]
+    UnsupportedSoundType(String),
 
     #[error("invalid version format")]
     InvalidVersionFormat,
 
+    #[error("unsupported format version: {0:?}")]
+    UnsupportedVersion(FormatVersion),
+
+    #[error("earliest compatible firmware {1} is more recent than the firmware version {0}")]
+    IncompatibleFirmwareVersions(FirmwareVersion, FirmwareVersion),
+
+    #[error("earliest compatible firmware {0} does not match the format version {1:?}")]
+    IncompatibleFormatVersion(FirmwareVersion, FormatVersion),
+
     #[error("overflow: {0} > {1}")]
     Overflow(String, String),
 
@@ -49,6 +64,128 @@ pub enum SerializationError {
 
     #[error("value not found in table: {0}")]
     ValueNotFoundInTable(u32),
+
+    #[error("invalid MIDI note '{0}'")]
+    InvalidMidiNote(String),
+
+    #[error("unsupported encoding '{0}'")]
+    UnsupportedEncoding(String),
+
+    #[error("the content is not valid {0}")]
+    InvalidEncoding(String),
+
+    #[error("unknown value '{value}' for '{field}'")]
+    UnknownEnumVariant { field: String, value: String },
+
+    #[error("the root element is neither a kit nor a sound")]
+    NoPatchRoot,
+
+    #[error("root element '{0}' is neither a kit nor a synth")]
+    UnsupportedPatchRoot(String),
+
+    #[error("the selected row {0} is out of bounds, the kit has {1} rows")]
+    SelectedRowOutOfBounds(u32, usize),
+
+    #[error("the sound has {0} mod knobs instead of 16")]
+    InvalidModKnobCount(usize),
+
+    #[error("the sound of the row {index} has {count} mod knobs instead of 16")]
+    InvalidRowModKnobCount { index: usize, count: usize },
+
+    #[error("the value '{value}' of '{field}' is out of range")]
+    ValueOutOfRange { field: String, value: String },
+
+    #[error("invalid sample zone: {0}")]
+    InvalidSampleZone(#[from] SampleZoneError),
+
+    #[error("{error} (in '{path}'{})", format_location(.location))]
+    Located {
+        /// The path of the XML node where the error occurred, for example "kit/soundSources/sound[3]".
+        path: String,
+        /// The line and the column of the XML node, both starting at 1.
+        location: Option<(usize, usize)>,
+        error: Box<SerializationError>,
+    },
+}
+
+/// An error that occurred while loading a row of a kit
+#[derive(thiserror::Error, Debug, Clone)]
+#[error("failed to load row {index}: {error}")]
+pub struct RowError {
+    /// The index of the row in the kit
+    pub index: usize,
+    /// The name of the row if it was found
+    pub name: Option<String>,
+    pub error: SerializationError,
+}
+
+impl From<std::io::Error> for SerializationError {
+    fn from(error: std::io::Error) -> Self {
+        SerializationError::XmlWritingFailed(Arc::new(error))
+    }
+}
+
+fn format_location(location: &Option<(usize, usize)>) -> String {
+    match location {
+        Some((line, column)) => format!(" at line {}, column {}", line, column),
+        None => String::new(),
+    }
+}
+
+impl SerializationError {
+    /// Get the path of the XML node where the error occurred
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            SerializationError::Located { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Get the line and the column of the XML node where the error occurred
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            SerializationError::Located { location, .. } => *location,
+            _ => None,
+        }
+    }
+
+    /// Get the error without its path and its location
+    pub fn inner(&self) -> &SerializationError {
+        match self {
+            SerializationError::Located { error, .. } => error.inner(),
+            error => error,
+        }
+    }
+
+    /// Annotate the error with the path of the XML node where it occurred.
+    /// An error already annotated keeps its path.
+    pub(crate) fn with_path(self, path: impl Into<String>) -> Self {
+        match self {
+            SerializationError::Located { .. } => self,
+            error => SerializationError::Located {
+                path: path.into(),
+                location: None,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Find the location of the node in the XML source.
+    /// The error must have been annotated with a path before.
+    pub(crate) fn with_location(self, xml: &str) -> Self {
+        match self {
+            SerializationError::Located {
+                path,
+                location: None,
+                error,
+            } => SerializationError::Located {
+                location: super::xml::find_location(xml, &path),
+                path,
+                error,
+            },
+            error => error,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -61,4 +198,13 @@ mod tests {
     fn test_error_is_sync() {
         check_sync::<super::SerializationError>();
     }
+
+    #[test]
+    fn test_located_error_message() {
+        let error = super::SerializationError::MissingElement("delay".to_string()).with_path("kit/soundSources/sound[3]");
+
+        assert_eq!(error.path(), Some("kit/soundSources/sound[3]"));
+        assert_eq!(error.location(), None);
+        assert_eq!(error.to_string(), "missing element 'delay' (in 'kit/soundSources/sound[3]')");
+    }
 }
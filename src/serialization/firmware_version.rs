@@ -0,0 +1,191 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use serde::{Serialize, Serializer};
+
+use super::SerializationError;
+
+/// The latest firmware version supported by this crate
+///
+/// The patches are written with this version. A patch saved by a more recent firmware
+/// might contain data this crate does not know.
+pub const LATEST_SUPPORTED_FIRMWARE_VERSION: FirmwareVersion = FirmwareVersion::new(3, 1, 5);
+
+/// A parsed firmware version such as "3.1.5" or "3.1.0-beta"
+///
+/// The versions are ordered like semantic versions: a version with a suffix comes before the
+/// same version without suffix, so "3.1.0-beta" < "3.1.0".
+/// ```
+/// # use deluge::{FirmwareVersion, LATEST_SUPPORTED_FIRMWARE_VERSION};
+/// let version: FirmwareVersion = "3.1.0-beta".parse().unwrap();
+///
+/// assert_eq!(version.suffix.as_deref(), Some("beta"));
+/// assert!(version < LATEST_SUPPORTED_FIRMWARE_VERSION);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FirmwareVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+    /// The suffix without the dash, for example "beta"
+    pub suffix: Option<String>,
+}
+
+impl FirmwareVersion {
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            suffix: None,
+        }
+    }
+
+    pub fn with_suffix(self, suffix: impl Into<String>) -> Self {
+        Self {
+            suffix: Some(suffix.into()),
+            ..self
+        }
+    }
+
+    /// Returns true if this version is more recent than [LATEST_SUPPORTED_FIRMWARE_VERSION].
+    pub fn is_newer_than_supported(&self) -> bool {
+        self > &LATEST_SUPPORTED_FIRMWARE_VERSION
+    }
+}
+
+impl FromStr for FirmwareVersion {
+    type Err = SerializationError;
+
+    /// Parse a version like "3.1.5" or "3.1.0-beta"
+    ///
+    /// The minor and patch numbers are optional, "3" is parsed as "3.0.0".
+    /// The versions of the community firmware like "c1.0.1" are not supported.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (numbers, suffix) = match text.split_once('-') {
+            Some((numbers, suffix)) if !suffix.is_empty() => (numbers, Some(suffix.to_string())),
+            Some(_) => return Err(SerializationError::InvalidVersionFormat),
+            None => (text, None),
+        };
+        let numbers = numbers
+            .split('.')
+            .map(|number| {
+                number
+                    .parse::<u16>()
+                    .map_err(|_| SerializationError::InvalidVersionFormat)
+            })
+            .collect::<Result<Vec<u16>, SerializationError>>()?;
+
+        let version = match numbers.as_slice() {
+            [major] => FirmwareVersion::new(*major, 0, 0),
+            [major, minor] => FirmwareVersion::new(*major, *minor, 0),
+            [major, minor, patch] => FirmwareVersion::new(*major, *minor, *patch),
+            _ => return Err(SerializationError::InvalidVersionFormat),
+        };
+
+        Ok(FirmwareVersion { suffix, ..version })
+    }
+}
+
+impl std::fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+
+        if let Some(suffix) = &self.suffix {
+            write!(f, "-{}", suffix)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Ord for FirmwareVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.major
+            .cmp(&other.major)
+            .then(self.minor.cmp(&other.minor))
+            .then(self.patch.cmp(&other.patch))
+            .then_with(|| match (&self.suffix, &other.suffix) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(left), Some(right)) => left.cmp(right),
+            })
+    }
+}
+
+impl PartialOrd for FirmwareVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Serialize for FirmwareVersion {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("3.1.5", FirmwareVersion::new(3, 1, 5) ; "release")]
+    #[test_case("3.1.0-beta", FirmwareVersion::new(3, 1, 0).with_suffix("beta") ; "beta")]
+    #[test_case("4.1.0-alpha", FirmwareVersion::new(4, 1, 0).with_suffix("alpha") ; "alpha")]
+    #[test_case("2", FirmwareVersion::new(2, 0, 0) ; "major only")]
+    #[test_case("1.2", FirmwareVersion::new(1, 2, 0) ; "major and minor")]
+    fn test_parse(text: &str, expected: FirmwareVersion) {
+        assert_eq!(text.parse::<FirmwareVersion>().unwrap(), expected);
+    }
+
+    #[test_case("" ; "empty")]
+    #[test_case("c1.0.1" ; "community")]
+    #[test_case("3.1.0-" ; "empty suffix")]
+    #[test_case("1.2.3.4" ; "too many numbers")]
+    #[test_case("3..1" ; "missing number")]
+    #[test_case("HEU!" ; "not a version")]
+    fn test_parse_invalid(text: &str) {
+        assert!(text.parse::<FirmwareVersion>().is_err());
+    }
+
+    #[test_case("3.1.5")]
+    #[test_case("3.1.0-beta")]
+    fn test_display(text: &str) {
+        assert_eq!(
+            text.parse::<FirmwareVersion>()
+                .unwrap()
+                .to_string(),
+            text
+        );
+    }
+
+    #[test_case("3.1.0-beta", "3.1.0")]
+    #[test_case("3.1.0-alpha", "3.1.0-beta")]
+    #[test_case("3.1.0", "3.1.5")]
+    #[test_case("3.1.5", "3.2.0")]
+    #[test_case("2.9.9", "3.0.0")]
+    fn test_order(lesser: &str, greater: &str) {
+        let lesser = lesser
+            .parse::<FirmwareVersion>()
+            .unwrap();
+        let greater = greater
+            .parse::<FirmwareVersion>()
+            .unwrap();
+
+        assert!(lesser < greater);
+        assert!(greater > lesser);
+    }
+
+    #[test]
+    fn test_is_newer_than_supported() {
+        assert!(!LATEST_SUPPORTED_FIRMWARE_VERSION.is_newer_than_supported());
+        assert!(!FirmwareVersion::new(3, 1, 0)
+            .with_suffix("beta")
+            .is_newer_than_supported());
+        assert!(FirmwareVersion::new(4, 0, 0).is_newer_than_supported());
+    }
+}
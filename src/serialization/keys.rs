@@ -63,6 +63,7 @@ pub const LPF: &str = "lpf";
 pub const LPF_FREQUENCY: &str = "lpfFrequency";
 pub const LPF_MODE: &str = "lpfMode";
 pub const LPF_RESONANCE: &str = "lpfResonance";
+pub const MAX_VOICES: &str = "maxVoices";
 pub const MIDI_OUTPUT: &str = "midiOutput";
 pub const MODE: &str = "mode";
 pub const MODULATION_FX_CHORUS: &str = "chorus";
@@ -94,6 +95,7 @@ pub const PATCH_CABLE_SOURCE: &str = "source";
 pub const PING_PONG: &str = "pingPong";
 pub const POLYPHONIC: &str = "polyphonic";
 pub const PORTAMENTO: &str = "portamento";
+pub const PRESET_NAME: &str = "presetName";
 pub const PULSE_WIDTH_OSC_A: &str = "oscAPulseWidth";
 pub const PULSE_WIDTH_OSC_B: &str = "oscBPulseWidth";
 pub const RATE: &str = "rate";
@@ -101,12 +103,14 @@ pub const RESONANCE: &str = "resonance";
 pub const RETRIG_PHASE: &str = "retrigPhase";
 pub const REVERB_AMOUNT: &str = "reverbAmount";
 pub const REVERSED: &str = "reversed";
+pub const SAMPLE_OSC_TYPE: &str = "sample";
 pub const SAMPLE_RANGE: &str = "sampleRange";
 pub const SAMPLE_RANGES: &str = "sampleRanges";
 pub const SAMPLE_RANGE_TOP_NOTE: &str = "rangeTopNote";
 pub const SELECTED_DRUM_INDEX: &str = "selectedDrumIndex";
 pub const SIDECHAIN_COMPRESSOR_SHAPE: &str = "sidechainCompressorShape";
 pub const SIDECHAIN_SEND: &str = "sideChainSend";
+pub const SONG: &str = "song";
 pub const SOUND: &str = "sound";
 pub const SOUND_SOURCES: &str = "soundSources";
 pub const START_LOOP_SAMPLES_POS: &str = "startLoopPos";
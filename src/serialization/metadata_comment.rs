@@ -0,0 +1,196 @@
+//! Store the [Metadata] of a patch in an XML comment
+//!
+//! The comment starts with a header then each field is written on its own line as "key: value":
+//! ```text
+//! <!--deluge-rs metadata
+//! name: Warm pad
+//! tag: pad
+//! tag: warm
+//! -->
+//! ```
+//! The values are escaped so they can be written on a single line and never contain "--", which is forbidden in a comment.
+use xmltree::{Element, XMLNode};
+
+use crate::Metadata;
+
+const HEADER: &str = "deluge-rs metadata";
+const NAME: &str = "name";
+const AUTHOR: &str = "author";
+const TAG: &str = "tag";
+const NOTES: &str = "notes";
+
+/// Load the metadata from the first comment of the patch element having the header
+pub(crate) fn load_metadata(patch_node: &Element) -> Option<Metadata> {
+    patch_node
+        .children
+        .iter()
+        .filter_map(|node| match node {
+            XMLNode::Comment(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .find_map(parse_metadata)
+}
+
+/// Write the metadata as the first child of the patch element, nothing is written if there is no metadata.
+///
+/// An empty metadata is written as the header alone so it's loaded back as an empty metadata.
+pub(crate) fn write_metadata(patch_node: &mut Element, metadata: &Option<Metadata>) {
+    if let Some(metadata) = metadata {
+        patch_node
+            .children
+            .insert(0, XMLNode::Comment(format_metadata(metadata)));
+    }
+}
+
+fn format_metadata(metadata: &Metadata) -> String {
+    let mut text = format!("{}\n", HEADER);
+    let mut push_line = |key: &str, value: &str| text.push_str(&format!("{}: {}\n", key, escape(value)));
+
+    if let Some(name) = &metadata.name {
+        push_line(NAME, name);
+    }
+
+    if let Some(author) = &metadata.author {
+        push_line(AUTHOR, author);
+    }
+
+    for tag in &metadata.tags {
+        push_line(TAG, tag);
+    }
+
+    if let Some(notes) = &metadata.notes {
+        push_line(NOTES, notes);
+    }
+
+    text
+}
+
+/// Parse the content of a comment, returns None if it doesn't start with the header
+///
+/// The unknown keys are ignored so a comment written by a later version can be read.
+fn parse_metadata(text: &str) -> Option<Metadata> {
+    let mut lines = text.trim_start().lines();
+    let mut metadata = Metadata::default();
+
+    if lines.next()?.trim_end() != HEADER {
+        return None;
+    }
+
+    for line in lines {
+        let (key, value) = match line
+            .trim_end_matches('\r')
+            .split_once(':')
+        {
+            Some((key, value)) => (key.trim(), unescape(value.strip_prefix(' ').unwrap_or(value))),
+            None => continue,
+        };
+
+        match key {
+            NAME => metadata.name = Some(value),
+            AUTHOR => metadata.author = Some(value),
+            TAG => metadata.tags.push(value),
+            NOTES => metadata.notes = Some(value),
+            _ => (),
+        }
+    }
+
+    Some(metadata)
+}
+
+fn escape(value: &str) -> String {
+    let chars = value.chars().collect::<Vec<_>>();
+    let mut escaped = String::with_capacity(value.len());
+
+    for (index, c) in chars.iter().enumerate() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            // Only the dashes next to another dash are escaped, a comment can't contain "--"
+            '-' if chars.get(index + 1) == Some(&'-') || (index > 0 && chars[index - 1] == '-') => escaped.push_str("\\-"),
+            c => escaped.push(*c),
+        }
+    }
+
+    escaped
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next() {
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some(escaped) => escaped,
+                None => '\\',
+            },
+            c => c,
+        });
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    #[test_case("simple" ; "simple")]
+    #[test_case("Jean-Luc" ; "single dash")]
+    #[test_case("a--b---c-" ; "dashes")]
+    #[test_case("line 1\nline 2\r\n\\n" ; "new lines and backslashes")]
+    fn test_escape(value: &str) {
+        let escaped = escape(value);
+
+        assert!(!escaped.contains("--"));
+        assert!(!escaped.contains('\n'));
+        assert_eq!(unescape(&escaped), value);
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        let metadata =
+            parse_metadata("deluge-rs metadata\r\nname: Warm pad\r\ntag: pad\r\nunknown: value\r\ntag: warm\r\nnotes:\r\n")
+                .unwrap();
+
+        assert_eq!(
+            metadata,
+            Metadata {
+                name: Some("Warm pad".to_string()),
+                author: None,
+                tags: vec!["pad".to_string(), "warm".to_string()],
+                notes: Some(String::new()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_other_comment() {
+        assert_eq!(parse_metadata(" a comment written by someone "), None);
+    }
+
+    #[test]
+    fn test_write_no_metadata() {
+        let mut element = Element::new("sound");
+
+        write_metadata(&mut element, &None);
+
+        assert!(element.children.is_empty());
+        assert_eq!(load_metadata(&element), None);
+    }
+
+    #[test]
+    fn test_write_empty_metadata() {
+        let mut element = Element::new("sound");
+
+        write_metadata(&mut element, &Some(Metadata::default()));
+
+        assert_eq!(element.children.len(), 1);
+        assert_eq!(load_metadata(&element), Some(Metadata::default()));
+    }
+}
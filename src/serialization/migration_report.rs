@@ -0,0 +1,204 @@
+//! The [MigrationReport] recorded by the loaders of the formats 1 and 2.
+//!
+//! The loaders record each field they fill from a default or compute from another value while a patch is loaded,
+//! see [record_migrations]. The path of a field is the path of the model, the loaders enter the fields
+//! of the structures loaded with [in_field].
+use std::cell::RefCell;
+
+use crate::range_check::join;
+
+/// How a field was loaded from an older format, see [MigrationReport]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MigrationKind {
+    /// The format does not store the field, a default value was used.
+    Default,
+    /// The field was computed from another value, the name of this value is specified.
+    Reinterpreted(String),
+}
+
+/// A field not loaded as is from the file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigratedField {
+    /// The path of the field, for example "sound.arpeggiator" or "rows[2].sound.sidechain"
+    pub path: String,
+    pub kind: MigrationKind,
+}
+
+/// The fields filled from a default or reinterpreted while loading a patch saved with an older format
+///
+/// A patch converted from an older format may not sound identical, the report allows to warn the users.
+/// The report of a patch saved with the version 3 or by the community firmware is always empty.
+/// ```
+/// # use deluge::deserialize_synth_with_report;
+/// let (_, _, report) = deserialize_synth_with_report(include_str!("../data_tests/SYNTHS/SYNT184.XML")).unwrap();
+///
+/// assert!(report.is_empty());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub fields: Vec<MigratedField>,
+}
+
+impl MigrationReport {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Get how a field was migrated, None if the field was loaded as is
+    pub fn get(&self, path: &str) -> Option<&MigrationKind> {
+        self.fields
+            .iter()
+            .find(|field| field.path == path)
+            .map(|field| &field.kind)
+    }
+}
+
+thread_local! {
+    static CURRENT_REPORT: RefCell<Option<Recording>> = const { RefCell::new(None) };
+}
+
+/// The report of the patch being loaded on the current thread
+struct Recording {
+    /// The fields entered by the loaders, for example ["rows[2]", "sound"]
+    path: Vec<String>,
+    report: MigrationReport,
+}
+
+/// Restore the recording of the caller, even if the loading panics
+struct RecordingGuard(Option<Recording>);
+
+impl Drop for RecordingGuard {
+    fn drop(&mut self) {
+        CURRENT_REPORT.set(self.0.take());
+    }
+}
+
+/// Load a patch and return the migrations recorded by the loaders
+pub(crate) fn record_migrations<T, E>(load: impl FnOnce() -> Result<T, E>) -> Result<(T, MigrationReport), E> {
+    let recording = Recording {
+        path: Vec::new(),
+        report: MigrationReport::default(),
+    };
+    let guard = RecordingGuard(CURRENT_REPORT.replace(Some(recording)));
+    let result = load();
+    let recording = CURRENT_REPORT
+        .take()
+        .expect("the recording is only removed by its guard");
+
+    drop(guard);
+
+    result.map(|patch| (patch, recording.report))
+}
+
+/// Load a field, the migrations recorded while loading it are relative to this field.
+///
+/// If the loading fails the migrations recorded for this field are discarded, for example when a row of a kit is skipped.
+pub(crate) fn in_field<T, E>(name: &str, load: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+    let recorded_count = CURRENT_REPORT.with_borrow_mut(|recording| {
+        recording.as_mut().map(|recording| {
+            recording.path.push(name.to_string());
+            recording.report.fields.len()
+        })
+    });
+    let result = load();
+
+    if let Some(recorded_count) = recorded_count {
+        CURRENT_REPORT.with_borrow_mut(|recording| {
+            if let Some(recording) = recording.as_mut() {
+                recording.path.pop();
+
+                if result.is_err() {
+                    recording
+                        .report
+                        .fields
+                        .truncate(recorded_count);
+                }
+            }
+        });
+    }
+
+    result
+}
+
+/// Record a field the format does not store, the loader used a default value
+pub(crate) fn add_default(name: &str) {
+    add(name, MigrationKind::Default);
+}
+
+/// Record a field computed by the loader from another value of the file
+pub(crate) fn add_reinterpreted(name: &str, from: &str) {
+    add(name, MigrationKind::Reinterpreted(from.to_string()));
+}
+
+fn add(name: &str, kind: MigrationKind) {
+    CURRENT_REPORT.with_borrow_mut(|recording| {
+        if let Some(recording) = recording.as_mut() {
+            let path = recording
+                .path
+                .iter()
+                .fold(String::new(), |path, field| join(&path, field));
+
+            recording
+                .report
+                .fields
+                .push(MigratedField {
+                    path: join(&path, name),
+                    kind,
+                });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_record_in_fields() {
+        let (_, report) = record_migrations(|| {
+            add_default("sidechain");
+            in_field("rows[0]", || {
+                in_field("sound", || {
+                    add_reinterpreted("generator.osc1.retrig_phase", "oscillatorReset");
+                    Ok::<(), ()>(())
+                })
+            })
+        })
+        .unwrap();
+
+        assert_eq!(report.get("sidechain"), Some(&MigrationKind::Default));
+        assert_eq!(
+            report.get("rows[0].sound.generator.osc1.retrig_phase"),
+            Some(&MigrationKind::Reinterpreted("oscillatorReset".to_string()))
+        );
+        assert_eq!(report.fields.len(), 2);
+    }
+
+    #[test]
+    fn test_field_not_loaded_is_discarded() {
+        let (_, report) = record_migrations(|| {
+            let _ = in_field("rows[0]", || {
+                add_default("sound.arpeggiator");
+                Err::<(), ()>(())
+            });
+            in_field("rows[0]", || {
+                add_default("sound.sidechain");
+                Ok::<(), ()>(())
+            })
+        })
+        .unwrap();
+
+        assert_eq!(report.get("rows[0].sound.arpeggiator"), None);
+        assert_eq!(report.get("rows[0].sound.sidechain"), Some(&MigrationKind::Default));
+    }
+
+    #[test]
+    fn test_nothing_recorded_outside_a_loading() {
+        add_default("sidechain");
+
+        let (_, report) = record_migrations(|| Ok::<(), ()>(())).unwrap();
+
+        assert!(report.is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+use std::{io::BufRead, sync::Arc};
+
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use super::{keys, version_info::make_version_info, FormatVersion, PatchType, SerializationError};
+
+/// The type and the version of a patch, see [peek_patch_info]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatchInfo {
+    pub patch_type: PatchType,
+    pub format_version: FormatVersion,
+    pub firmware_version: Option<String>,
+    pub earliest_compatible_firmware: Option<String>,
+}
+
+/// Get the type and the version of a patch without deserializing it
+///
+/// Only the beginning of the XML is parsed, until the root element is found.
+/// ```
+/// # use deluge::{peek_patch_info, FormatVersion, PatchType};
+/// let info = peek_patch_info(include_str!("../data_tests/KITS/KIT026.XML")).unwrap();
+///
+/// assert_eq!(info.patch_type, PatchType::Kit);
+/// assert_eq!(info.format_version, FormatVersion::Version2);
+/// assert_eq!(info.firmware_version.as_deref(), Some("2.1.0"));
+/// ```
+pub fn peek_patch_info(xml: &str) -> Result<PatchInfo, SerializationError> {
+    peek_patch_info_from_reader(xml.as_bytes())
+}
+
+/// Get the type and the version of a patch without deserializing it, see [peek_patch_info]
+///
+/// The reading stops after the start of the root element.
+pub fn peek_patch_info_from_reader<R: BufRead>(reader: R) -> Result<PatchInfo, SerializationError> {
+    let to_error = |e: quick_xml::Error| SerializationError::XmlReadingFailed(Arc::new(e));
+    let mut reader = Reader::from_reader(reader);
+    let mut buffer = Vec::new();
+    let mut firmware_version = None;
+    let mut earliest_compatible_firmware = None;
+    // The version 2 stores the versions in elements placed before the root element
+    let mut version_element = None;
+
+    reader.trim_text(true);
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buffer)
+            .map_err(to_error)?;
+
+        match event {
+            Event::Start(tag) if get_version_key(tag.name().as_ref()).is_some() => {
+                version_element = get_version_key(tag.name().as_ref())
+            }
+            Event::Start(tag) | Event::Empty(tag) => {
+                let patch_type = get_patch_type(tag.name().as_ref()).ok_or(SerializationError::NoPatchRoot)?;
+
+                if let Some(version) = get_attribute(&tag, keys::FIRMWARE_VERSION)? {
+                    firmware_version = Some(version);
+                }
+
+                if let Some(version) = get_attribute(&tag, keys::EARLIEST_COMPATIBLE_FIRMWARE)? {
+                    earliest_compatible_firmware = Some(version);
+                }
+
+                let version_info = make_version_info(firmware_version, earliest_compatible_firmware);
+
+                return Ok(PatchInfo {
+                    patch_type,
+                    format_version: version_info.format_version,
+                    firmware_version: version_info.firmware_version,
+                    earliest_compatible_firmware: version_info.earliest_compatible_firmware,
+                });
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(to_error)?;
+
+                match version_element {
+                    Some(keys::FIRMWARE_VERSION) => firmware_version = Some(text.into_owned()),
+                    Some(_) => earliest_compatible_firmware = Some(text.into_owned()),
+                    None => (),
+                }
+            }
+            Event::End(_) => version_element = None,
+            Event::Eof => return Err(SerializationError::NoPatchRoot),
+            _ => (),
+        }
+
+        buffer.clear();
+    }
+}
+
+/// Get the key of the elements storing the versions in the format version 2
+pub(crate) fn get_version_key(name: &[u8]) -> Option<&'static str> {
+    [keys::FIRMWARE_VERSION, keys::EARLIEST_COMPATIBLE_FIRMWARE]
+        .into_iter()
+        .find(|key| key.as_bytes() == name)
+}
+
+pub(crate) fn get_patch_type(name: &[u8]) -> Option<PatchType> {
+    [PatchType::Kit, PatchType::Synth]
+        .into_iter()
+        .find(|patch_type| patch_type.get_key().as_bytes() == name)
+}
+
+fn get_attribute(tag: &BytesStart, key: &str) -> Result<Option<String>, SerializationError> {
+    let to_error = |e: quick_xml::Error| SerializationError::XmlReadingFailed(Arc::new(e));
+
+    match tag
+        .try_get_attribute(key)
+        .map_err(to_error)?
+    {
+        Some(attribute) => Ok(Some(
+            attribute
+                .unescape_value()
+                .map_err(to_error)?
+                .into_owned(),
+        )),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialization::community_synth_xml;
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    #[test_case(include_str!("../data_tests/KITS/KIT000.XML"), PatchType::Kit, FormatVersion::Version1, None ; "kit version 1")]
+    #[test_case(include_str!("../data_tests/KITS/KIT026.XML"), PatchType::Kit, FormatVersion::Version2, Some("2.1.0") ; "kit version 2")]
+    #[test_case(include_str!("../data_tests/KITS/KIT057.XML"), PatchType::Kit, FormatVersion::Version3, Some("3.1.5") ; "kit version 3")]
+    #[test_case(include_str!("../data_tests/SYNTHS/SYNT028.XML"), PatchType::Synth, FormatVersion::Version1, None ; "synth version 1")]
+    #[test_case(include_str!("../data_tests/SYNTHS/SYNT184.XML"), PatchType::Synth, FormatVersion::Version3, Some("3.1.5") ; "synth version 3")]
+    #[test_case(&community_synth_xml(), PatchType::Synth, FormatVersion::Community, Some("c1.0.1") ; "synth community")]
+    fn test_peek_patch_info(xml: &str, patch_type: PatchType, format_version: FormatVersion, firmware_version: Option<&str>) {
+        let info = peek_patch_info(xml).unwrap();
+
+        assert_eq!(info.patch_type, patch_type);
+        assert_eq!(info.format_version, format_version);
+        assert_eq!(info.firmware_version.as_deref(), firmware_version);
+    }
+
+    #[test]
+    fn test_peek_patch_info_empty_root() {
+        assert_eq!(
+            peek_patch_info("<sound firmwareVersion=\"3.1.5\" earliestCompatibleFirmware=\"3.1.0\"/>").unwrap(),
+            PatchInfo {
+                patch_type: PatchType::Synth,
+                format_version: FormatVersion::Version3,
+                firmware_version: Some("3.1.5".to_string()),
+                earliest_compatible_firmware: Some("3.1.0".to_string()),
+            }
+        );
+    }
+
+    #[test_case("<song></song>" ; "unknown root")]
+    #[test_case("<?xml version=\"1.0\"?>" ; "no root")]
+    fn test_peek_patch_info_not_a_patch(xml: &str) {
+        assert!(matches!(peek_patch_info(xml), Err(SerializationError::NoPatchRoot)));
+    }
+}
@@ -1,7 +1,85 @@
-pub const LATEST_SUPPORTED_FIRMWARE_VERSION: &str = "3.1.5";
+use std::collections::HashMap;
 
-const DELUGE_SAMPLE_FREQUECY_RATE: u64 = 44100u64;
+use xmltree::Element;
 
-pub fn convert_milliseconds_to_samples(milliseconds: u64) -> u64 {
-    milliseconds / DELUGE_SAMPLE_FREQUECY_RATE / 1000u64
+use crate::{Kit, RowError, RowKit, SerializationError};
+
+use super::{keys, migration_report, xml, FirmwareVersion};
+
+pub const VERSION_2_FIRMWARE_VERSION: FirmwareVersion = FirmwareVersion::new(2, 1, 0);
+
+/// Load each row of a kit using the specified function.
+///
+/// The rows that can't be loaded are skipped and reported as [RowError].
+/// The errors are annotated with the path of the node of the row, for example "kit/soundSources/sound[3]".
+/// The index in the path is the position of the node among the nodes with the same name.
+/// The migrations of a row are recorded in the field "rows[index]", the index of the row in the kit loaded.
+pub fn load_kit_rows(
+    sound_sources_node: &Element,
+    load_sound_source: impl Fn(&Element) -> Result<RowKit, SerializationError>,
+) -> (Vec<RowKit>, Vec<RowError>) {
+    let mut indices: HashMap<&str, usize> = HashMap::new();
+    let mut rows = Vec::new();
+    let mut row_errors = Vec::new();
+
+    for (row_index, row_node) in sound_sources_node
+        .children
+        .iter()
+        .filter_map(xml::keep_element_only)
+        .enumerate()
+    {
+        let index = indices
+            .entry(row_node.name.as_str())
+            .or_default();
+        let path = format!("{}/{}/{}[{}]", keys::KIT, keys::SOUND_SOURCES, row_node.name, index);
+
+        *index += 1;
+
+        match migration_report::in_field(&format!("rows[{}]", rows.len()), || load_sound_source(row_node)) {
+            Ok(row) => rows.push(row),
+            Err(error) => row_errors.push(RowError {
+                index: row_index,
+                name: load_row_name(row_node),
+                error: error.with_path(path),
+            }),
+        }
+    }
+
+    (rows, row_errors)
+}
+
+/// The name is stored as attribute since the version 3 and as child before.
+fn load_row_name(row_node: &Element) -> Option<String> {
+    xml::get_opt_attribute(row_node, keys::NAME)
+        .cloned()
+        .or_else(|| xml::get_opt_children_element(row_node, keys::NAME).map(xml::get_text))
+}
+
+/// Remap the index of the selected row to the rows loaded
+///
+/// The index is decreased by the count of rows skipped before the selected one, no row is selected if it was skipped.
+pub fn remap_selected_row_index(selected_row_index: Option<u32>, row_errors: &[RowError]) -> Option<u32> {
+    let selected_row_index = selected_row_index?;
+    let mut skipped_before = 0;
+
+    for row_error in row_errors {
+        match (row_error.index as u32).cmp(&selected_row_index) {
+            std::cmp::Ordering::Less => skipped_before += 1,
+            std::cmp::Ordering::Equal => return None,
+            std::cmp::Ordering::Greater => {}
+        }
+    }
+
+    Some(selected_row_index - skipped_before)
+}
+
+/// Turn the result of a lenient kit loading into a strict one.
+/// The first row error is returned if any.
+pub fn into_strict_kit(result: Result<(Kit, Vec<RowError>), SerializationError>) -> Result<Kit, SerializationError> {
+    let (kit, row_errors) = result?;
+
+    match row_errors.into_iter().next() {
+        Some(row_error) => Err(row_error.error),
+        None => Ok(kit),
+    }
 }
@@ -0,0 +1,38 @@
+//! Community firmware format
+//!
+//! The Deluge Community Firmware keeps the attribute based layout introduced by the format version 3
+//! and adds new parameters on top of it. These additional parameters are not supported yet and are
+//! ignored while loading.
+use crate::{Kit, RowError, SerializationError, Synth};
+use xmltree::Element;
+
+use super::serialization_v3;
+
+/// Load a synth saved with the community firmware
+pub fn load_synth_nodes(root_nodes: &[Element]) -> Result<Synth, SerializationError> {
+    serialization_v3::load_synth_nodes(root_nodes)
+}
+
+/// Load a kit saved with the community firmware
+pub fn load_kit_nodes(root_nodes: &[Element]) -> Result<Kit, SerializationError> {
+    serialization_v3::load_kit_nodes(root_nodes)
+}
+
+/// Load a kit saved with the community firmware, skipping the rows that can't be loaded
+pub fn load_kit_nodes_lenient(root_nodes: &[Element]) -> Result<(Kit, Vec<RowError>), SerializationError> {
+    serialization_v3::load_kit_nodes_lenient(root_nodes)
+}
+
+/// SYNT184.XML with the firmware versions written by the community firmware 1.0.1
+///
+/// There is no patch saved by the community firmware in the data tests, the header of a patch saved by the firmware 3.1.5
+/// is edited instead.
+#[cfg(test)]
+pub(crate) fn community_synth_xml() -> String {
+    include_str!("../data_tests/SYNTHS/SYNT184.XML")
+        .replace("firmwareVersion=\"3.1.5\"", "firmwareVersion=\"c1.0.1\"")
+        .replace(
+            "earliestCompatibleFirmware=\"3.1.0-beta\"",
+            "earliestCompatibleFirmware=\"4.1.0-alpha\"",
+        )
+}
@@ -0,0 +1,206 @@
+use super::{
+    firmware_version::LATEST_SUPPORTED_FIRMWARE_VERSION, serialization_common::VERSION_2_FIRMWARE_VERSION, FirmwareVersion,
+    FormatVersion, OutOfRange, SerializationError, XmlFormat,
+};
+
+/// The options used to serialize or deserialize a patch
+///
+/// By default the patch is written using the latest format version and the firmware versions
+/// matching this format. The firmware versions can be overridden, for example to write a patch
+/// loadable by an older firmware:
+/// ```
+/// # use deluge::{serialize_synth_with_options, SerializationOptions, Synth};
+/// let options = SerializationOptions::default()
+///     .with_firmware_version("3.1.5")
+///     .unwrap()
+///     .with_earliest_compatible_firmware("3.0.0")
+///     .unwrap();
+/// let xml = serialize_synth_with_options(&Synth::default(), &options).unwrap();
+///
+/// assert!(xml.contains("earliestCompatibleFirmware=\"3.0.0\""));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializationOptions {
+    /// Only the versions 2 and 3 are supported.
+    pub format_version: FormatVersion,
+    /// The version written in `firmwareVersion`, the default version of the format if none.
+    pub firmware_version: Option<FirmwareVersion>,
+    /// The version written in `earliestCompatibleFirmware`, the default version of the format if none.
+    pub earliest_compatible_firmware: Option<FirmwareVersion>,
+    /// When loading a kit, fail if the selected row is out of bounds instead of selecting the last row.
+    pub strict_selected_row: bool,
+    /// The formatting of the XML written, the default formatting of xmltree if none.
+    pub xml_format: Option<XmlFormat>,
+    /// When writing a synth or the sound rows of a kit, pad or truncate the mod knobs to 16 instead of failing, see [Sound::normalize_mod_knobs](crate::Sound::normalize_mod_knobs).
+    pub fix_mod_knobs: bool,
+    /// When loading a patch, what to do with an integer value out of its range, see [OutOfRange].
+    pub out_of_range: OutOfRange,
+}
+
+impl Default for SerializationOptions {
+    fn default() -> Self {
+        Self {
+            format_version: FormatVersion::Version3,
+            firmware_version: None,
+            earliest_compatible_firmware: None,
+            strict_selected_row: false,
+            xml_format: None,
+            fix_mod_knobs: false,
+            out_of_range: OutOfRange::Keep,
+        }
+    }
+}
+
+impl SerializationOptions {
+    pub fn with_format_version(self, format_version: FormatVersion) -> Self {
+        Self { format_version, ..self }
+    }
+
+    /// Set the firmware version, returns an error if the version is not valid.
+    pub fn with_firmware_version(self, version: &str) -> Result<Self, SerializationError> {
+        Ok(Self {
+            firmware_version: Some(version.parse()?),
+            ..self
+        })
+    }
+
+    /// Set the earliest compatible firmware, returns an error if the version is not valid.
+    pub fn with_earliest_compatible_firmware(self, version: &str) -> Result<Self, SerializationError> {
+        Ok(Self {
+            earliest_compatible_firmware: Some(version.parse()?),
+            ..self
+        })
+    }
+
+    /// Fail to load a kit when its selected row is out of bounds, see [deserialize_kit_with_options](crate::deserialize_kit_with_options)
+    pub fn with_strict_selected_row(self, strict_selected_row: bool) -> Self {
+        Self {
+            strict_selected_row,
+            ..self
+        }
+    }
+
+    /// Format the XML written, for example like the Deluge with [XmlFormat::deluge]
+    pub fn with_xml_format(self, xml_format: XmlFormat) -> Self {
+        Self {
+            xml_format: Some(xml_format),
+            ..self
+        }
+    }
+
+    /// Write a synth with the wrong count of mod knobs after fixing it, see [serialize_synth_with_options](crate::serialize_synth_with_options)
+    pub fn with_fix_mod_knobs(self, fix_mod_knobs: bool) -> Self {
+        Self { fix_mod_knobs, ..self }
+    }
+
+    /// Choose what to do with the integer values out of range when loading a patch, see [deserialize_synth_with_clamped_values](crate::deserialize_synth_with_clamped_values)
+    pub fn with_out_of_range(self, out_of_range: OutOfRange) -> Self {
+        Self { out_of_range, ..self }
+    }
+
+    /// Get the firmware version and the earliest compatible firmware to write
+    ///
+    /// The format version is detected using the major number of the earliest compatible firmware
+    /// so it must match the format version, otherwise the patch could not be read back.
+    /// Returns an error if the earliest compatible firmware is more recent than the firmware version.
+    pub(crate) fn firmware_versions(&self) -> Result<(FirmwareVersion, FirmwareVersion), SerializationError> {
+        let default_version = match self.format_version {
+            FormatVersion::Version3 => LATEST_SUPPORTED_FIRMWARE_VERSION,
+            FormatVersion::Version2 => VERSION_2_FIRMWARE_VERSION,
+            _ => return Err(SerializationError::UnsupportedVersion(self.format_version)),
+        };
+        let firmware_version = self
+            .firmware_version
+            .clone()
+            .unwrap_or_else(|| default_version.clone());
+        let earliest_compatible_firmware = self
+            .earliest_compatible_firmware
+            .clone()
+            .unwrap_or_else(|| default_version.clone());
+
+        if earliest_compatible_firmware.major != default_version.major {
+            return Err(SerializationError::IncompatibleFormatVersion(
+                earliest_compatible_firmware,
+                self.format_version,
+            ));
+        }
+
+        if earliest_compatible_firmware > firmware_version {
+            return Err(SerializationError::IncompatibleFirmwareVersions(
+                firmware_version,
+                earliest_compatible_firmware,
+            ));
+        }
+
+        Ok((firmware_version, earliest_compatible_firmware))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_default_firmware_versions() {
+        assert_eq!(
+            SerializationOptions::default()
+                .firmware_versions()
+                .unwrap(),
+            (LATEST_SUPPORTED_FIRMWARE_VERSION, LATEST_SUPPORTED_FIRMWARE_VERSION)
+        );
+        assert_eq!(
+            SerializationOptions::default()
+                .with_format_version(FormatVersion::Version2)
+                .firmware_versions()
+                .unwrap(),
+            (VERSION_2_FIRMWARE_VERSION, VERSION_2_FIRMWARE_VERSION)
+        );
+    }
+
+    #[test]
+    fn test_invalid_firmware_version() {
+        assert!(SerializationOptions::default()
+            .with_firmware_version("c1.0.1")
+            .is_err());
+        assert!(SerializationOptions::default()
+            .with_earliest_compatible_firmware("HEU!")
+            .is_err());
+    }
+
+    #[test]
+    fn test_earliest_compatible_firmware_more_recent() {
+        let options = SerializationOptions::default()
+            .with_firmware_version("3.0.0")
+            .unwrap()
+            .with_earliest_compatible_firmware("3.1.0")
+            .unwrap();
+
+        assert!(matches!(
+            options.firmware_versions(),
+            Err(SerializationError::IncompatibleFirmwareVersions(..))
+        ));
+    }
+
+    #[test]
+    fn test_earliest_compatible_firmware_format_mismatch() {
+        let options = SerializationOptions::default()
+            .with_earliest_compatible_firmware("2.1.0")
+            .unwrap();
+
+        assert!(matches!(
+            options.firmware_versions(),
+            Err(SerializationError::IncompatibleFormatVersion(_, FormatVersion::Version3))
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_format_version() {
+        assert!(matches!(
+            SerializationOptions::default()
+                .with_format_version(FormatVersion::Version1)
+                .firmware_versions(),
+            Err(SerializationError::UnsupportedVersion(FormatVersion::Version1))
+        ));
+    }
+}
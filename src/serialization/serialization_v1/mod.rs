@@ -1,10 +1,11 @@
 use crate::{
+    range_check::join,
     values::{
-        ArpeggiatorMode, AttackSidechain, HexU50, MidiChannel, ModulationFxType, OnOff, OscType, Pan, ReleaseSidechain,
+        ArpeggiatorMode, AttackSidechain, HexU50, MidiChannel, MidiNote, ModulationFxType, OnOff, OscType, Pan, ReleaseSidechain,
         RetrigPhase, SamplePosition, SyncLevel, SynthMode,
     },
     Arpeggiator, Chorus, CvGateRow, Delay, Distorsion, Envelope, Equalizer, Flanger, FmCarrier, FmModulator, FmSynth, Hpf, Kit,
-    Lfo1, Lfo2, Lpf, MidiRow, ModKnob, ModulationFx, PatchCable, Phaser, RingModSynth, RowKit, Sample, SampleOneZone,
+    Lfo1, Lfo2, Lpf, MidiRow, ModKnob, ModulationFx, PatchCable, Phaser, RingModSynth, RowError, RowKit, Sample, SampleOneZone,
     SampleOscillator, SampleRange, SampleZone, SerializationError, Sidechain, Sound, SoundRow, SubtractiveOscillator,
     SubtractiveSynth, Synth, SynthEngine, Unison, WaveformOscillator,
 };
@@ -12,8 +13,9 @@ use xmltree::Element;
 
 use super::{
     default_params::{DefaultParams, TwinSelector},
-    keys,
-    serialization_common::convert_milliseconds_to_samples,
+    keys, load_metadata,
+    migration_report::{add_default, add_reinterpreted, in_field},
+    serialization_common::{into_strict_kit, load_kit_rows, remap_selected_row_index},
     xml,
 };
 
@@ -22,64 +24,61 @@ pub fn load_synth_nodes(root_nodes: &[Element]) -> Result<Synth, SerializationEr
     let sound_node = xml::get_element(root_nodes, keys::SOUND)?;
 
     Ok(Synth {
-        sound: load_sound(sound_node)?,
+        sound: in_field("sound", || load_sound(sound_node))?,
+        metadata: load_metadata(sound_node),
     })
 }
 
 pub fn load_kit_nodes(roots: &[Element]) -> Result<Kit, SerializationError> {
+    into_strict_kit(load_kit_nodes_lenient(roots))
+}
+
+/// Load a deluge kit XML file, skipping the rows that can't be loaded
+pub fn load_kit_nodes_lenient(roots: &[Element]) -> Result<(Kit, Vec<RowError>), SerializationError> {
     let kit_node = xml::get_element(roots, keys::KIT)?;
     let sound_sources_node = xml::get_children_element(kit_node, keys::SOUND_SOURCES)?;
-    let sources: Vec<Result<RowKit, SerializationError>> = sound_sources_node
-        .children
-        .iter()
-        .filter_map(xml::keep_element_only)
-        .map(load_sound_source)
-        .collect();
-
-    if let Some(result_with_error) = sources.iter().find(|s| s.is_err()) {
-        return Err(result_with_error
-            .as_ref()
-            .unwrap_err()
-            .clone());
-    }
-
-    return Ok(Kit {
-        rows: sources
-            .iter()
-            .flatten()
-            .cloned()
-            .collect::<Vec<RowKit>>(),
-        lpf_mode: xml::parse_children_element_content(kit_node, keys::LPF_MODE)?,
+    let (rows, row_errors) = load_kit_rows(sound_sources_node, load_sound_source);
+    let kit = Kit {
+        rows,
+        lpf_mode: xml::parse_enum_children_element_content(kit_node, keys::LPF_MODE)?,
         modulation_fx: load_modulation_fx(kit_node)?,
         current_filter_type: xml::parse_children_element_content(kit_node, keys::CURRENT_FILTER_TYPE)?,
-        selected_row_index: xml::parse_children_element_content(kit_node, keys::SELECTED_DRUM_INDEX)?,
-        volume: load_global_hexu(kit_node, keys::VOLUME)?,
-        reverb_amount: load_global_hexu(kit_node, keys::REVERB_AMOUNT)?,
+        selected_row_index: remap_selected_row_index(
+            xml::parse_opt_children_element_content(kit_node, keys::SELECTED_DRUM_INDEX)?,
+            &row_errors,
+        ),
+        volume: load_global_hexu(kit_node, keys::VOLUME, "volume")?,
+        reverb_amount: load_global_hexu(kit_node, keys::REVERB_AMOUNT, "reverb_amount")?,
         pan: load_global_pan(kit_node)?,
-        bit_crush: load_global_hexu(kit_node, keys::BIT_CRUSH)?,
-        decimation: load_global_hexu(kit_node, keys::DECIMATION)?,
-        stutter_rate: load_global_hexu(kit_node, keys::STUTTER_RATE)?,
+        bit_crush: load_global_hexu(kit_node, keys::BIT_CRUSH, "bit_crush")?,
+        decimation: load_global_hexu(kit_node, keys::DECIMATION, "decimation")?,
+        stutter_rate: load_global_hexu(kit_node, keys::STUTTER_RATE, "stutter_rate")?,
         delay: load_global_delay(kit_node)?,
-        sidechain: Sidechain::default(),
+        sidechain: load_global_sidechain(),
         lpf: load_global_lpf(kit_node)?,
         hpf: load_global_hpf(kit_node)?,
         equalizer: load_global_equalizer(kit_node)?,
-    });
+        metadata: load_metadata(kit_node),
+    };
+
+    Ok((kit, row_errors))
 }
 
 fn load_sound(root: &Element) -> Result<Sound, SerializationError> {
-    let sound_type = xml::parse_opt_children_element_content::<SynthMode>(root, keys::MODE)?.unwrap_or(SynthMode::Subtractive);
+    // The version 1 doesn't always store the mode, the sound is subtractive in this case
+    let mode = xml::get_opt_children_element(root, keys::MODE).map_or_else(|| "subtractive".to_string(), xml::get_text);
     let default_params_node = xml::get_children_element(root, keys::DEFAULT_PARAMS)?;
 
-    let generator = match sound_type {
-        SynthMode::Subtractive => load_subtractive_sound(root)?,
-        SynthMode::Fm => load_fm_sound(root)?,
-        SynthMode::RingMod => load_ringmode_sound(root)?,
-        _ => return Err(SerializationError::UnsupportedSoundType),
+    let generator = match serde_plain::from_str::<SynthMode>(&mode) {
+        Ok(SynthMode::Subtractive) => load_subtractive_sound(root)?,
+        Ok(SynthMode::Fm) => load_fm_sound(root)?,
+        Ok(SynthMode::RingMod) => load_ringmode_sound(root)?,
+        _ => return Err(SerializationError::UnsupportedSoundType(mode)),
     };
 
     Ok(Sound {
         polyphonic: xml::parse_children_element_content(root, keys::POLYPHONIC)?,
+        max_voices: xml::parse_opt_children_element_content(root, keys::MAX_VOICES)?,
         voice_priority: xml::parse_children_element_content(root, keys::VOICE_PRIORITY)?,
         volume: xml::parse_children_element_content(default_params_node, keys::VOLUME)?,
         reverb_amount: xml::parse_children_element_content(default_params_node, keys::REVERB_AMOUNT)?,
@@ -93,18 +92,21 @@ fn load_sound(root: &Element) -> Result<Sound, SerializationError> {
         lfo1: load_lfo1(xml::get_children_element(root, keys::LFO1)?, default_params_node)?,
         lfo2: load_lfo2(xml::get_children_element(root, keys::LFO2)?, default_params_node)?,
         unison: load_unison(xml::get_children_element(root, keys::UNISON)?)?,
-        arpeggiator: create_default_arpeggiator(),
+        arpeggiator: load_arpeggiator(),
         delay: load_delay(xml::get_children_element(root, keys::DELAY)?, default_params_node)?,
         distorsion: load_distorsion(root, default_params_node)?,
         equalizer: load_equalizer(xml::get_children_element(default_params_node, keys::EQUALIZER)?)?,
         modulation_fx: load_modulation_fx(root)?,
-        sidechain: create_default_sidechain(),
+        sidechain: load_sidechain(),
         cables: load_patch_cables(xml::get_children_element(default_params_node, keys::PATCH_CABLES)?)?,
         mod_knobs: load_mod_knobs(xml::get_children_element(root, keys::MOD_KNOBS)?)?,
     })
 }
 
-fn create_default_arpeggiator() -> Arpeggiator {
+/// The version 1 doesn't store the arpeggiator
+fn load_arpeggiator() -> Arpeggiator {
+    add_default("arpeggiator");
+
     Arpeggiator {
         mode: ArpeggiatorMode::Off,
         sync_level: SyncLevel::Sixteenth,
@@ -114,7 +116,10 @@ fn create_default_arpeggiator() -> Arpeggiator {
     }
 }
 
-fn create_default_sidechain() -> Sidechain {
+/// The version 1 doesn't store the sidechain of the sounds
+fn load_sidechain() -> Sidechain {
+    add_default("sidechain");
+
     Sidechain {
         attack: AttackSidechain::try_from(327244).unwrap(),
         release: ReleaseSidechain::try_from(936).unwrap(),
@@ -123,12 +128,23 @@ fn create_default_sidechain() -> Sidechain {
     }
 }
 
+/// The version 1 doesn't store the sidechain of the kits
+fn load_global_sidechain() -> Sidechain {
+    add_default("sidechain");
+
+    Sidechain::default()
+}
+
 fn load_subtractive_sound(root: &Element) -> Result<SynthEngine, SerializationError> {
     let osc1_node = xml::get_children_element(root, keys::OSC1)?;
     let osc2_node = xml::get_children_element(root, keys::OSC2)?;
     let default_params_node = xml::get_children_element(root, keys::DEFAULT_PARAMS)?;
-    let mut osc1 = load_oscillator(osc1_node, &DefaultParams::new(TwinSelector::A, default_params_node))?;
-    let mut osc2 = load_oscillator(osc2_node, &DefaultParams::new(TwinSelector::B, default_params_node))?;
+    let mut osc1 = in_field("generator.osc1", || {
+        load_oscillator(osc1_node, &DefaultParams::new(TwinSelector::A, default_params_node))
+    })?;
+    let mut osc2 = in_field("generator.osc2", || {
+        load_oscillator(osc2_node, &DefaultParams::new(TwinSelector::B, default_params_node))
+    })?;
 
     load_oscillator_reset_osc(root, &mut osc1, &mut osc2)?;
 
@@ -137,7 +153,7 @@ fn load_subtractive_sound(root: &Element) -> Result<SynthEngine, SerializationEr
         osc2,
         osc2_sync: xml::parse_opt_children_element_content(osc2_node, keys::OSCILLATOR_SYNC)?.unwrap_or(OnOff::Off),
         noise: xml::parse_children_element_content(default_params_node, keys::NOISE_VOLUME)?,
-        lpf_mode: xml::parse_children_element_content(root, keys::LPF_MODE)?,
+        lpf_mode: xml::parse_enum_children_element_content(root, keys::LPF_MODE)?,
         lpf_frequency: xml::parse_children_element_content(default_params_node, keys::LPF_FREQUENCY)?,
         lpf_resonance: xml::parse_children_element_content(default_params_node, keys::LPF_RESONANCE)?,
         hpf_frequency: xml::parse_children_element_content(default_params_node, keys::HPF_FREQUENCY)?,
@@ -147,9 +163,11 @@ fn load_subtractive_sound(root: &Element) -> Result<SynthEngine, SerializationEr
     }))
 }
 
-fn assign_retrig_phase(mut osc: &mut SubtractiveOscillator, retrig_phase: RetrigPhase) {
+/// Assign the retrig phase computed from the oscillator reset, the sample oscillators don't have a retrig phase
+fn assign_retrig_phase(mut osc: &mut SubtractiveOscillator, name: &str, retrig_phase: RetrigPhase) {
     if let SubtractiveOscillator::Waveform(osc) = &mut osc {
         osc.retrig_phase = retrig_phase;
+        add_reinterpreted(&join(name, "retrig_phase"), keys::OSCILLATOR_RESET);
     }
 }
 
@@ -188,8 +206,8 @@ fn load_oscillator_reset_osc(
     if let Some(oscillator_reset_node) = xml::parse_opt_children_element_content::<OnOff>(root, keys::OSCILLATOR_RESET)? {
         let retrig_phase = retrig_phase_from_oscillator_reset(oscillator_reset_node);
 
-        assign_retrig_phase(osc1, retrig_phase);
-        assign_retrig_phase(osc2, retrig_phase);
+        assign_retrig_phase(osc1, "generator.osc1", retrig_phase);
+        assign_retrig_phase(osc2, "generator.osc2", retrig_phase);
     }
 
     Ok(())
@@ -205,6 +223,8 @@ fn load_oscillator_reset_waveform_osc(
 
         osc1.retrig_phase = retrig_phase;
         osc2.retrig_phase = retrig_phase;
+        add_reinterpreted("generator.osc1.retrig_phase", keys::OSCILLATOR_RESET);
+        add_reinterpreted("generator.osc2.retrig_phase", keys::OSCILLATOR_RESET);
     }
 
     Ok(())
@@ -218,11 +238,8 @@ pub(crate) fn load_fm_sound(root: &Element) -> Result<SynthEngine, Serialization
     let default_params_node = xml::get_children_element(root, keys::DEFAULT_PARAMS)?;
     let params_a = &DefaultParams::new(TwinSelector::A, default_params_node);
     let params_b = &DefaultParams::new(TwinSelector::B, default_params_node);
-    let mut osc1 = load_carrier(osc1_node, params_a)?;
-    let mut osc2 = load_carrier(osc2_node, params_b)?;
-
-    load_oscillator_reset_carrier(root, &mut osc1, &mut osc2)?;
 
+    // The oscillator reset is not applied to the carriers, their retrig phase is loaded as is
     Ok(SynthEngine::from(FmSynth {
         osc1: load_carrier(osc1_node, params_a)?,
         osc2: load_carrier(osc2_node, params_b)?,
@@ -234,21 +251,6 @@ pub(crate) fn load_fm_sound(root: &Element) -> Result<SynthEngine, Serialization
     }))
 }
 
-fn load_oscillator_reset_carrier(
-    root: &Element,
-    mut osc1: &mut FmCarrier,
-    mut osc2: &mut FmCarrier,
-) -> Result<(), SerializationError> {
-    if let Some(oscillator_reset_node) = xml::parse_opt_children_element_content::<OnOff>(root, keys::OSCILLATOR_RESET)? {
-        let retrig_phase = retrig_phase_from_oscillator_reset(oscillator_reset_node);
-
-        osc1.retrig_phase = retrig_phase;
-        osc2.retrig_phase = retrig_phase;
-    }
-
-    Ok(())
-}
-
 fn retrig_phase_from_oscillator_reset(oscillator_reset_node: OnOff) -> RetrigPhase {
     match oscillator_reset_node {
         OnOff::On => RetrigPhase::Degrees(0),
@@ -297,7 +299,7 @@ fn load_sample_oscillator(root: &Element) -> Result<SubtractiveOscillator, Seria
         mode: xml::parse_children_element_content(root, keys::LOOP_MODE)?,
         pitch_speed: xml::parse_children_element_content(root, keys::TIME_STRETCH_ENABLE)?,
         time_stretch_amount: xml::parse_children_element_content(root, keys::TIME_STRETCH_AMOUNT)?,
-        sample: load_sample(root)?,
+        sample: in_field("sample", || load_sample(root))?,
         linear_interpolation: xml::parse_opt_children_element_content(root, keys::LINEAR_INTERPOLATION)?.unwrap_or_default(),
     }))
 }
@@ -308,14 +310,17 @@ fn load_sample(root: &Element) -> Result<Sample, SerializationError> {
             let mut ranges: Vec<SampleRange> = Vec::new();
             let sample_range_nodes = xml::get_all_children_element_with_name(sample_ranges_node, keys::SAMPLE_RANGE);
 
-            for sample_range_node in sample_range_nodes {
+            for (index, sample_range_node) in sample_range_nodes
+                .into_iter()
+                .enumerate()
+            {
                 let zone_node = xml::get_children_element(sample_range_node, keys::ZONE)?;
                 let range = SampleRange {
                     range_top_note: xml::parse_opt_children_element_content(sample_range_node, keys::SAMPLE_RANGE_TOP_NOTE)?,
                     file_path: xml::parse_children_element_content(sample_range_node, keys::FILE_NAME)?,
                     transpose: xml::parse_opt_children_element_content(sample_range_node, keys::TRANSPOSE)?.unwrap_or_default(),
                     fine_transpose: xml::parse_opt_children_element_content(sample_range_node, keys::CENTS)?.unwrap_or_default(),
-                    zone: parse_sample_zone(zone_node)?,
+                    zone: in_field(&format!("ranges[{}].zone", index), || parse_sample_zone(zone_node))?,
                 };
 
                 ranges.push(range);
@@ -325,7 +330,7 @@ fn load_sample(root: &Element) -> Result<Sample, SerializationError> {
         } else if let Some(sample_zone_node) = xml::get_opt_children_element(root, "zone") {
             Sample::OneZone(SampleOneZone {
                 file_path: xml::parse_opt_children_element_content(root, keys::FILE_NAME)?.unwrap_or_default(),
-                zone: Some(parse_sample_zone(sample_zone_node)?),
+                zone: Some(in_field("zone", || parse_sample_zone(sample_zone_node))?),
             })
         } else {
             Sample::OneZone(SampleOneZone {
@@ -342,24 +347,8 @@ fn load_sample(root: &Element) -> Result<Sample, SerializationError> {
 /// We try to get start and end positions as samples if possible, and as milliseconds if forced.
 /// If both are missing then SamplePosition(0) is assigned.
 fn parse_sample_zone(root: &Element) -> Result<SampleZone, SerializationError> {
-    let start = SamplePosition::new(
-        match xml::parse_opt_children_element_content::<u64>(root, keys::START_SAMPLES_POS)? {
-            Some(samples) => samples,
-            None => xml::parse_opt_children_element_content::<u64>(root, keys::START_MILLISECONDS_POS)?
-                .map(convert_milliseconds_to_samples)
-                .unwrap_or_default(),
-        },
-    );
-
-    let end = SamplePosition::new(
-        match xml::parse_opt_children_element_content::<u64>(root, keys::END_SAMPLES_POS)? {
-            Some(samples) => samples,
-            None => xml::parse_opt_children_element_content::<u64>(root, keys::END_MILLISECONDS_POS)?
-                .map(convert_milliseconds_to_samples)
-                .unwrap_or_default(),
-        },
-    );
-
+    let start = parse_sample_zone_position(root, "start", keys::START_SAMPLES_POS, keys::START_MILLISECONDS_POS)?;
+    let end = parse_sample_zone_position(root, "end", keys::END_SAMPLES_POS, keys::END_MILLISECONDS_POS)?;
     let start_loop = xml::parse_opt_children_element_content::<u64>(root, keys::START_LOOP_SAMPLES_POS)?.map(SamplePosition::new);
     let end_loop = xml::parse_opt_children_element_content::<u64>(root, keys::END_LOOP_SAMPLES_POS)?.map(SamplePosition::new);
 
@@ -371,6 +360,31 @@ fn parse_sample_zone(root: &Element) -> Result<SampleZone, SerializationError> {
     })
 }
 
+/// Parse a position of a sample zone, the position in milliseconds is used only if the position in samples is missing
+fn parse_sample_zone_position(
+    root: &Element,
+    name: &str,
+    samples_key: &str,
+    milliseconds_key: &str,
+) -> Result<SamplePosition, SerializationError> {
+    if let Some(samples) = xml::parse_opt_children_element_content::<u64>(root, samples_key)? {
+        return Ok(SamplePosition::new(samples));
+    }
+
+    Ok(
+        match xml::parse_opt_children_element_content::<u64>(root, milliseconds_key)? {
+            Some(milliseconds) => {
+                add_reinterpreted(name, milliseconds_key);
+                SamplePosition::from_millis(milliseconds)
+            }
+            None => {
+                add_default(name);
+                SamplePosition::default()
+            }
+        },
+    )
+}
+
 fn load_waveform_oscillator(
     osc_type: OscType,
     root: &Element,
@@ -397,7 +411,7 @@ fn load_waveform_oscillator_imp(
 
 fn load_midi_output(root: &Element) -> Result<MidiRow, SerializationError> {
     let channel: MidiChannel = xml::parse_children_element_content(root, keys::CHANNEL)?;
-    let note = xml::parse_children_element_content(root, keys::NOTE)?;
+    let note: MidiNote = xml::parse_children_element_content(root, keys::NOTE)?;
 
     Ok(MidiRow { channel, note })
 }
@@ -408,7 +422,7 @@ fn load_gate_output(root: &Element) -> Result<CvGateRow, SerializationError> {
 
 fn load_sound_output(root: &Element) -> Result<SoundRow, SerializationError> {
     Ok(SoundRow {
-        sound: Box::new(load_sound(root)?),
+        sound: Box::new(in_field("sound", || load_sound(root))?),
         name: xml::parse_children_element_content(root, keys::NAME)?,
     })
 }
@@ -463,10 +477,20 @@ pub(crate) fn load_delay(root: &Element, default_params_node: &Element) -> Resul
     })
 }
 
+/// Load the global delay of a kit
+///
+/// The switches are read from the node "delay" of the kit when it's present. The kits of the format 1 usually
+/// don't have this node, the switches missing are the ones written by the firmware 3.1.5 when it re-saves
+/// these kits: ping pong on, analog off and a sync level of 16th.
 fn load_global_delay(kit_node: &Element) -> Result<Delay, SerializationError> {
     let default_params_node = xml::get_children_element(kit_node, keys::DEFAULT_PARAMS)?;
     let default_delay_node = xml::get_children_element(default_params_node, keys::DELAY)?;
 
+    // The format 1 doesn't store the switches of the kit delay
+    add_default("delay.ping_pong");
+    add_default("delay.analog");
+    add_default("delay.sync_level");
+
     Ok(Delay {
         ping_pong: OnOff::On,
         analog: OnOff::Off,
@@ -484,6 +508,10 @@ pub(crate) fn load_distorsion(root: &Element, default_params_node: &Element) ->
     })
 }
 
+/// Load an equalizer, the values use the same scale as the format 3
+///
+/// The neutral value is 0x00000000 in every format, the Deluge re-saves it unchanged with the firmware 3.1.5
+/// so no offset is applied. It's the value 25 of [Equalizer::default].
 pub(crate) fn load_equalizer(root: &Element) -> Result<Equalizer, SerializationError> {
     Ok(Equalizer {
         bass_level: xml::parse_children_element_content(root, keys::EQ_BASS)?,
@@ -588,21 +616,31 @@ pub(crate) fn load_global_hpf(kit_node: &Element) -> Result<Hpf, SerializationEr
 pub(crate) fn load_global_equalizer(kit_node: &Element) -> Result<Equalizer, SerializationError> {
     Ok(match xml::get_opt_children_element(kit_node, keys::DEFAULT_PARAMS) {
         Some(default_params_node) => load_equalizer(xml::get_children_element(default_params_node, keys::EQUALIZER)?)?,
-        None => Equalizer::default(),
+        None => {
+            add_default("equalizer");
+            Equalizer::default()
+        }
     })
 }
 
-pub(crate) fn load_global_hexu(kit_node: &Element, key: &str) -> Result<HexU50, SerializationError> {
+/// Load a global parameter of a kit, the parameter is 0 if the kit has no parameters node
+pub(crate) fn load_global_hexu(kit_node: &Element, key: &str, name: &str) -> Result<HexU50, SerializationError> {
     Ok(match xml::get_opt_children_element(kit_node, keys::DEFAULT_PARAMS) {
         Some(default_params_node) => xml::parse_children_element_content(default_params_node, key)?,
-        None => 0.into(),
+        None => {
+            add_default(name);
+            0.into()
+        }
     })
 }
 
 pub(crate) fn load_global_pan(kit_node: &Element) -> Result<Pan, SerializationError> {
     Ok(match xml::get_opt_children_element(kit_node, keys::DEFAULT_PARAMS) {
         Some(default_params_node) => xml::parse_children_element_content(default_params_node, keys::PAN)?,
-        None => Pan::default(),
+        None => {
+            add_default("pan");
+            Pan::default()
+        }
     })
 }
 
@@ -611,8 +649,8 @@ mod tests {
     use crate::{
         deserialize_synth, serialize_synth,
         values::{
-            AttackSidechain, ClippingAmount, FineTranspose, LfoShape, LpfMode, Pan, Polyphony, ReleaseSidechain, RetrigPhase,
-            Transpose, UnisonDetune, UnisonVoiceCount, VoicePriority,
+            AttackSidechain, ClippingAmount, FineTranspose, LfoShape, LpfMode, Pan, PatchDestination, PatchSource, Polyphony,
+            ReleaseSidechain, RetrigPhase, Transpose, UnisonDetune, UnisonVoiceCount, VoicePriority,
         },
     };
 
@@ -717,7 +755,7 @@ mod tests {
         assert_eq!(waveform.osc_type, OscType::Square);
         assert_eq!(waveform.transpose, Transpose::new(0));
         assert_eq!(waveform.fine_transpose, FineTranspose::new(0));
-        assert_eq!(waveform.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(waveform.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(waveform.pulse_width, HexU50::parse("0x00000000").unwrap());
 
         let waveform = generator.osc2.as_waveform().unwrap();
@@ -725,21 +763,21 @@ mod tests {
         assert_eq!(waveform.osc_type, OscType::Saw);
         assert_eq!(waveform.transpose, Transpose::new(0));
         assert_eq!(waveform.fine_transpose, FineTranspose::new(8));
-        assert_eq!(waveform.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(waveform.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(waveform.pulse_width, HexU50::parse("0x00000000").unwrap());
 
         assert_eq!(3, sound.cables.len());
 
-        assert_eq!("velocity", sound.cables[0].source);
-        assert_eq!("volume", sound.cables[0].destination);
+        assert_eq!(PatchSource::Velocity, sound.cables[0].source);
+        assert_eq!(PatchDestination::Volume, sound.cables[0].destination);
         assert_eq!(HexU50::parse("0x3FFFFFE8").unwrap(), sound.cables[0].amount);
 
-        assert_eq!("lfo1", sound.cables[1].source);
-        assert_eq!("pitch", sound.cables[1].destination);
+        assert_eq!(PatchSource::Lfo1, sound.cables[1].source);
+        assert_eq!(PatchDestination::Pitch, sound.cables[1].destination);
         assert_eq!(HexU50::parse("0x03000000").unwrap(), sound.cables[1].amount);
 
-        assert_eq!("envelope2", sound.cables[2].source);
-        assert_eq!("lpfFrequency", sound.cables[2].destination);
+        assert_eq!(PatchSource::Envelope2, sound.cables[2].source);
+        assert_eq!(PatchDestination::LpfFrequency, sound.cables[2].destination);
         assert_eq!(HexU50::parse("0x251EB844").unwrap(), sound.cables[2].amount);
     }
 
@@ -755,21 +793,21 @@ mod tests {
 
         assert_eq!(generator.osc1.transpose, Transpose::new(0));
         assert_eq!(generator.osc1.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.osc1.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(generator.osc1.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(generator.osc1.feedback, HexU50::parse("0x80000000").unwrap());
 
         assert_eq!(generator.osc2.transpose, Transpose::new(0));
         assert_eq!(generator.osc2.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.osc2.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(generator.osc2.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(generator.osc2.feedback, HexU50::parse("0x80000000").unwrap());
 
         assert_eq!(generator.modulator1.transpose, Transpose::new(-15));
         assert_eq!(generator.modulator1.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.modulator1.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(generator.modulator1.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(generator.modulator2_to_modulator1, OnOff::Off);
 
         assert_eq!(generator.modulator2.transpose, Transpose::new(-12));
         assert_eq!(generator.modulator2.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.modulator2.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(generator.modulator2.retrig_phase, RetrigPhase::Degrees(0));
     }
 }
@@ -1,12 +1,18 @@
+mod writing;
+
+pub use writing::{write_kit, write_synth};
+
 use crate::{
     values::{AttackSidechain, OnOff, ReleaseSidechain, SynthMode, TableIndex},
-    Arpeggiator, Delay, Kit, RowKit, SerializationError, Sidechain, Sound, SubtractiveSynth, Synth, SynthEngine,
+    Arpeggiator, Delay, Kit, RowError, SerializationError, Sidechain, Sound, SubtractiveSynth, Synth, SynthEngine,
 };
 use xmltree::Element;
 
 use super::{
     default_params::{DefaultParams, TwinSelector},
-    keys,
+    keys, load_metadata,
+    migration_report::{add_default, in_field},
+    serialization_common::{into_strict_kit, load_kit_rows, remap_selected_row_index},
     serialization_v1::{
         load_distorsion, load_envelope, load_equalizer, load_fm_sound, load_global_equalizer, load_global_hexu, load_global_hpf,
         load_global_lpf, load_global_pan, load_lfo1, load_lfo2, load_mod_knobs, load_modulation_fx, load_oscillator,
@@ -20,64 +26,60 @@ pub fn load_synth_nodes(root_nodes: &[Element]) -> Result<Synth, SerializationEr
     let sound_node = xml::get_element(root_nodes, keys::SOUND)?;
 
     Ok(Synth {
-        sound: load_sound(sound_node)?,
+        sound: in_field("sound", || load_sound(sound_node))?,
+        metadata: load_metadata(sound_node),
     })
 }
 
 pub fn load_kit_nodes(roots: &[Element]) -> Result<Kit, SerializationError> {
+    into_strict_kit(load_kit_nodes_lenient(roots))
+}
+
+/// Load a deluge kit XML file, skipping the rows that can't be loaded
+pub fn load_kit_nodes_lenient(roots: &[Element]) -> Result<(Kit, Vec<RowError>), SerializationError> {
     let kit_node = xml::get_element(roots, keys::KIT)?;
     let sound_sources_node = xml::get_children_element(kit_node, keys::SOUND_SOURCES)?;
-    let sources: Vec<Result<RowKit, SerializationError>> = sound_sources_node
-        .children
-        .iter()
-        .filter_map(xml::keep_element_only)
-        .map(load_sound_source)
-        .collect();
-
-    if let Some(result_with_error) = sources.iter().find(|s| s.is_err()) {
-        return Err(result_with_error
-            .as_ref()
-            .unwrap_err()
-            .clone());
-    }
-
-    return Ok(Kit {
-        rows: sources
-            .iter()
-            .flatten()
-            .cloned()
-            .collect::<Vec<RowKit>>(),
-        lpf_mode: xml::parse_children_element_content(kit_node, keys::LPF_MODE)?,
+    let (rows, row_errors) = load_kit_rows(sound_sources_node, load_sound_source);
+    let kit = Kit {
+        rows,
+        lpf_mode: xml::parse_enum_children_element_content(kit_node, keys::LPF_MODE)?,
         modulation_fx: load_modulation_fx(kit_node)?,
         current_filter_type: xml::parse_children_element_content(kit_node, keys::CURRENT_FILTER_TYPE)?,
-        selected_row_index: xml::parse_children_element_content(kit_node, keys::SELECTED_DRUM_INDEX)?,
-        volume: load_global_hexu(kit_node, keys::VOLUME)?,
-        reverb_amount: load_global_hexu(kit_node, keys::REVERB_AMOUNT)?,
+        selected_row_index: remap_selected_row_index(
+            xml::parse_opt_children_element_content(kit_node, keys::SELECTED_DRUM_INDEX)?,
+            &row_errors,
+        ),
+        volume: load_global_hexu(kit_node, keys::VOLUME, "volume")?,
+        reverb_amount: load_global_hexu(kit_node, keys::REVERB_AMOUNT, "reverb_amount")?,
         pan: load_global_pan(kit_node)?,
-        bit_crush: load_global_hexu(kit_node, keys::BIT_CRUSH)?,
-        decimation: load_global_hexu(kit_node, keys::DECIMATION)?,
-        stutter_rate: load_global_hexu(kit_node, keys::STUTTER_RATE)?,
+        bit_crush: load_global_hexu(kit_node, keys::BIT_CRUSH, "bit_crush")?,
+        decimation: load_global_hexu(kit_node, keys::DECIMATION, "decimation")?,
+        stutter_rate: load_global_hexu(kit_node, keys::STUTTER_RATE, "stutter_rate")?,
         delay: load_global_delay(kit_node)?,
         sidechain: load_global_sidechain(kit_node)?,
         lpf: load_global_lpf(kit_node)?,
         hpf: load_global_hpf(kit_node)?,
         equalizer: load_global_equalizer(kit_node)?,
-    });
+        metadata: load_metadata(kit_node),
+    };
+
+    Ok((kit, row_errors))
 }
 
 fn load_sound(root: &Element) -> Result<Sound, SerializationError> {
-    let sound_type = xml::parse_children_element_content::<SynthMode>(root, keys::MODE)?;
+    let mode = xml::get_children_element_content(root, keys::MODE)?;
     let default_params_node = xml::get_children_element(root, keys::DEFAULT_PARAMS)?;
 
-    let generator = match sound_type {
-        SynthMode::Subtractive => load_subtractive_sound(root)?,
-        SynthMode::Fm => load_fm_sound(root)?,
-        SynthMode::RingMod => load_ringmode_sound(root)?,
-        _ => return Err(SerializationError::UnsupportedSoundType),
+    let generator = match serde_plain::from_str::<SynthMode>(&mode) {
+        Ok(SynthMode::Subtractive) => load_subtractive_sound(root)?,
+        Ok(SynthMode::Fm) => load_fm_sound(root)?,
+        Ok(SynthMode::RingMod) => load_ringmode_sound(root)?,
+        _ => return Err(SerializationError::UnsupportedSoundType(mode)),
     };
 
     Ok(Sound {
         polyphonic: xml::parse_children_element_content(root, keys::POLYPHONIC)?,
+        max_voices: xml::parse_opt_children_element_content(root, keys::MAX_VOICES)?,
         voice_priority: xml::parse_children_element_content(root, keys::VOICE_PRIORITY)?,
         volume: xml::parse_children_element_content(default_params_node, keys::VOLUME)?,
         reverb_amount: xml::parse_children_element_content(default_params_node, keys::REVERB_AMOUNT)?,
@@ -108,11 +110,15 @@ fn load_subtractive_sound(root: &Element) -> Result<SynthEngine, SerializationEr
     let default_params_node = xml::get_children_element(root, keys::DEFAULT_PARAMS)?;
 
     Ok(SynthEngine::from(SubtractiveSynth {
-        osc1: load_oscillator(osc1_node, &DefaultParams::new(TwinSelector::A, default_params_node))?,
-        osc2: load_oscillator(osc2_node, &DefaultParams::new(TwinSelector::B, default_params_node))?,
+        osc1: in_field("generator.osc1", || {
+            load_oscillator(osc1_node, &DefaultParams::new(TwinSelector::A, default_params_node))
+        })?,
+        osc2: in_field("generator.osc2", || {
+            load_oscillator(osc2_node, &DefaultParams::new(TwinSelector::B, default_params_node))
+        })?,
         osc2_sync: xml::parse_opt_children_element_content(osc2_node, keys::OSCILLATOR_SYNC)?.unwrap_or(OnOff::Off),
         noise: xml::parse_children_element_content(default_params_node, keys::NOISE_VOLUME)?,
-        lpf_mode: xml::parse_children_element_content(root, keys::LPF_MODE)?,
+        lpf_mode: xml::parse_enum_children_element_content(root, keys::LPF_MODE)?,
         lpf_frequency: xml::parse_children_element_content(default_params_node, keys::LPF_FREQUENCY)?,
         lpf_resonance: xml::parse_children_element_content(default_params_node, keys::LPF_RESONANCE)?,
         hpf_frequency: xml::parse_children_element_content(default_params_node, keys::HPF_FREQUENCY)?,
@@ -157,7 +163,10 @@ fn load_arpeggiator(root: &Element, default_params_node: &Element) -> Result<Arp
             rate: xml::parse_children_element_content(default_params_node, keys::ARPEGGIATOR_RATE)?,
             gate: xml::parse_children_element_content(default_params_node, keys::ARPEGGIATOR_GATE)?,
         },
-        None => Arpeggiator::default(),
+        None => {
+            add_default("arpeggiator");
+            Arpeggiator::default()
+        }
     })
 }
 
@@ -170,15 +179,25 @@ fn load_sidechain(root: &Element, default_params_node: &Element) -> Result<Sidec
     })
 }
 
+/// The version 2 only stores the sync level of the sidechain of the kits
 fn load_global_sidechain(kit_node: &Element) -> Result<Sidechain, SerializationError> {
     Ok(match xml::get_opt_children_element(kit_node, keys::COMPRESSOR) {
-        Some(compressor_node) => Sidechain {
-            attack: AttackSidechain::new(TableIndex::new(7)),
-            release: ReleaseSidechain::new(TableIndex::new(28)),
-            shape: 18.into(),
-            sync: xml::parse_children_element_content(compressor_node, keys::COMPRESSOR_SYNCLEVEL)?,
-        },
-        None => Sidechain::default(),
+        Some(compressor_node) => {
+            for name in ["attack", "release", "shape"] {
+                add_default(&format!("sidechain.{}", name));
+            }
+
+            Sidechain {
+                attack: AttackSidechain::new(TableIndex::new(7)),
+                release: ReleaseSidechain::new(TableIndex::new(28)),
+                shape: 18.into(),
+                sync: xml::parse_children_element_content(compressor_node, keys::COMPRESSOR_SYNCLEVEL)?,
+            }
+        }
+        None => {
+            add_default("sidechain");
+            Sidechain::default()
+        }
     })
 }
 
@@ -187,8 +206,9 @@ mod tests {
     use crate::{
         deserialize_synth, serialize_synth,
         values::{
-            ArpeggiatorMode, AttackSidechain, ClippingAmount, FineTranspose, HexU50, LfoShape, LpfMode, OscType, Pan, Polyphony,
-            ReleaseSidechain, RetrigPhase, SyncLevel, Transpose, UnisonDetune, UnisonVoiceCount, VoicePriority,
+            ArpeggiatorMode, AttackSidechain, ClippingAmount, FineTranspose, HexU50, LfoShape, LpfMode, OscType, Pan,
+            PatchDestination, PatchSource, Polyphony, ReleaseSidechain, RetrigPhase, SyncLevel, Transpose, UnisonDetune,
+            UnisonVoiceCount, VoicePriority,
         },
         ModulationFx,
     };
@@ -293,7 +313,7 @@ mod tests {
         assert_eq!(waveform.osc_type, OscType::Square);
         assert_eq!(waveform.transpose, Transpose::new(0));
         assert_eq!(waveform.fine_transpose, FineTranspose::new(0));
-        assert_eq!(waveform.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(waveform.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(waveform.pulse_width, HexU50::parse("0x00000000").unwrap());
 
         let waveform = generator.osc2.as_waveform().unwrap();
@@ -301,21 +321,21 @@ mod tests {
         assert_eq!(waveform.osc_type, OscType::Saw);
         assert_eq!(waveform.transpose, Transpose::new(0));
         assert_eq!(waveform.fine_transpose, FineTranspose::new(8));
-        assert_eq!(waveform.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(waveform.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(waveform.pulse_width, HexU50::parse("0x00000000").unwrap());
 
         assert_eq!(3, sound.cables.len());
 
-        assert_eq!("velocity", sound.cables[0].source);
-        assert_eq!("volume", sound.cables[0].destination);
+        assert_eq!(PatchSource::Velocity, sound.cables[0].source);
+        assert_eq!(PatchDestination::Volume, sound.cables[0].destination);
         assert_eq!(HexU50::parse("0x3FFFFFE8").unwrap(), sound.cables[0].amount);
 
-        assert_eq!("lfo1", sound.cables[1].source);
-        assert_eq!("pitch", sound.cables[1].destination);
+        assert_eq!(PatchSource::Lfo1, sound.cables[1].source);
+        assert_eq!(PatchDestination::Pitch, sound.cables[1].destination);
         assert_eq!(HexU50::parse("0x03000000").unwrap(), sound.cables[1].amount);
 
-        assert_eq!("envelope2", sound.cables[2].source);
-        assert_eq!("lpfFrequency", sound.cables[2].destination);
+        assert_eq!(PatchSource::Envelope2, sound.cables[2].source);
+        assert_eq!(PatchDestination::LpfFrequency, sound.cables[2].destination);
         assert_eq!(HexU50::parse("0x251EB844").unwrap(), sound.cables[2].amount);
     }
 
@@ -331,21 +351,21 @@ mod tests {
 
         assert_eq!(generator.osc1.transpose, Transpose::new(0));
         assert_eq!(generator.osc1.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.osc1.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(generator.osc1.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(generator.osc1.feedback, HexU50::parse("0x80000000").unwrap());
 
         assert_eq!(generator.osc2.transpose, Transpose::new(0));
         assert_eq!(generator.osc2.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.osc2.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(generator.osc2.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(generator.osc2.feedback, HexU50::parse("0x80000000").unwrap());
 
         assert_eq!(generator.modulator1.transpose, Transpose::new(-15));
         assert_eq!(generator.modulator1.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.modulator1.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(generator.modulator1.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(generator.modulator2_to_modulator1, OnOff::Off);
 
         assert_eq!(generator.modulator2.transpose, Transpose::new(-12));
         assert_eq!(generator.modulator2.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.modulator2.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(generator.modulator2.retrig_phase, RetrigPhase::Degrees(0));
     }
 }
@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    serialization::{keys, serialization_v3, FirmwareVersion},
+    Kit, SerializationError, Synth,
+};
+
+use xmltree::{Element, XMLNode};
+
+/// Write a synth using the format version 2
+///
+/// The version 2 stores the values as children instead of attributes.
+/// The version information are stored in root nodes placed before the sound node.
+pub fn write_synth(
+    synth: &Synth,
+    firmware_version: &FirmwareVersion,
+    earliest_compatible_firmware: &FirmwareVersion,
+) -> Result<Vec<Element>, SerializationError> {
+    let patch_node = serialization_v3::write_synth(synth, firmware_version, earliest_compatible_firmware)?;
+
+    Ok(write_roots(patch_node, firmware_version, earliest_compatible_firmware))
+}
+
+/// Write a kit using the format version 2
+pub fn write_kit(
+    kit: &Kit,
+    firmware_version: &FirmwareVersion,
+    earliest_compatible_firmware: &FirmwareVersion,
+) -> Result<Vec<Element>, SerializationError> {
+    let patch_node = serialization_v3::write_kit(kit, firmware_version, earliest_compatible_firmware)?;
+
+    Ok(write_roots(patch_node, firmware_version, earliest_compatible_firmware))
+}
+
+fn write_roots(
+    mut patch_node: Element,
+    firmware_version: &FirmwareVersion,
+    earliest_compatible_firmware: &FirmwareVersion,
+) -> Vec<Element> {
+    patch_node
+        .attributes
+        .remove(keys::FIRMWARE_VERSION);
+    patch_node
+        .attributes
+        .remove(keys::EARLIEST_COMPATIBLE_FIRMWARE);
+
+    vec![
+        write_text_node(keys::FIRMWARE_VERSION, &firmware_version.to_string()),
+        write_text_node(keys::EARLIEST_COMPATIBLE_FIRMWARE, &earliest_compatible_firmware.to_string()),
+        convert_attributes_to_children(patch_node),
+    ]
+}
+
+fn write_text_node(name: &str, text: &str) -> Element {
+    let mut node = Element::new(name);
+
+    if !text.is_empty() {
+        node.children
+            .push(XMLNode::Text(text.to_string()));
+    }
+
+    node
+}
+
+/// Recursively transform each attribute into a child node containing the attribute value.
+/// The attributes are sorted by name so the output does not depend on the attributes storage order.
+fn convert_attributes_to_children(mut element: Element) -> Element {
+    let attributes: BTreeMap<String, String> = std::mem::take(&mut element.attributes)
+        .into_iter()
+        .collect();
+    let mut children: Vec<XMLNode> = attributes
+        .iter()
+        .map(|(name, value)| XMLNode::Element(write_text_node(name, value)))
+        .collect();
+
+    for child in element.children.drain(..) {
+        children.push(match child {
+            XMLNode::Element(child_element) => XMLNode::Element(convert_attributes_to_children(child_element)),
+            other => other,
+        });
+    }
+
+    element.children = children;
+    element
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialization::{serialization_common::VERSION_2_FIRMWARE_VERSION, serialization_v2, xml};
+    use pretty_assertions::assert_eq;
+
+    /// Collect the text of each leaf node, the path contains the index of the node among the siblings with the same name.
+    fn collect_leaves(element: &Element, path: &str, leaves: &mut BTreeMap<String, String>) {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+
+        for child in element
+            .children
+            .iter()
+            .filter_map(XMLNode::as_element)
+        {
+            let count = counts
+                .entry(child.name.as_str())
+                .or_insert(0);
+            let child_path = format!("{}/{}[{}]", path, child.name, count);
+            let text = xml::get_text(child);
+
+            *count += 1;
+
+            if !text.trim().is_empty() {
+                leaves.insert(child_path.clone(), text.trim().to_string());
+            }
+
+            collect_leaves(child, &child_path, leaves);
+        }
+    }
+
+    #[test]
+    fn test_write_synth_version_2_layout() {
+        let roots = write_synth(&Synth::default(), &VERSION_2_FIRMWARE_VERSION, &VERSION_2_FIRMWARE_VERSION).unwrap();
+
+        assert_eq!(roots.len(), 3);
+        assert_eq!(xml::get_text(&roots[0]), VERSION_2_FIRMWARE_VERSION.to_string());
+        assert_eq!(xml::get_text(&roots[1]), VERSION_2_FIRMWARE_VERSION.to_string());
+        assert!(roots[2].attributes.is_empty());
+        assert!(xml::get_opt_children_element(&roots[2], keys::MODE).is_some());
+    }
+
+    #[test]
+    fn test_write_load_synth_version_2() {
+        let synth = Synth::default();
+        let roots = write_synth(&synth, &VERSION_2_FIRMWARE_VERSION, &VERSION_2_FIRMWARE_VERSION).unwrap();
+        let reloaded_synth = serialization_v2::load_synth_nodes(&roots).unwrap();
+
+        assert_eq!(reloaded_synth, synth);
+    }
+
+    /// SYNT061 was saved by the firmware 2.0.0-beta, the output must contain the same nodes at the same places
+    /// excepted the parameters not stored by the model: the FM parameters of a subtractive sound and the
+    /// parameters of a disabled modulation FX.
+    /// The hexadecimal values are rounded to the 0-50 range when loaded so only their format is compared.
+    #[test]
+    fn test_write_synth_version_2_same_as_device() {
+        let device_roots = xml::load_xml(include_str!("../../data_tests/SYNTHS/SYNT061.XML")).unwrap();
+        let synth = serialization_v2::load_synth_nodes(&device_roots).unwrap();
+        let firmware_version: FirmwareVersion = xml::get_text(&device_roots[0])
+            .parse()
+            .unwrap();
+        let earliest_compatible_firmware: FirmwareVersion = xml::get_text(&device_roots[1])
+            .parse()
+            .unwrap();
+        let roots = write_synth(&synth, &firmware_version, &earliest_compatible_firmware).unwrap();
+        let root_names = |roots: &[Element]| {
+            roots
+                .iter()
+                .map(|root| root.name.clone())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(root_names(&roots), root_names(&device_roots));
+        assert_eq!(xml::get_text(&roots[0]), "2.0.0-beta");
+        assert_eq!(xml::get_text(&roots[1]), "2.0.0-beta");
+
+        let mut device_leaves = BTreeMap::new();
+        let mut written_leaves = BTreeMap::new();
+
+        collect_leaves(&device_roots[2], "", &mut device_leaves);
+        collect_leaves(&roots[2], "", &mut written_leaves);
+
+        let not_stored = [
+            "carrier1Feedback",
+            "carrier2Feedback",
+            "modulator1Amount",
+            "modulator1Feedback",
+            "modulator2Amount",
+            "modulator2Feedback",
+            "modFXDepth",
+            "modFXOffset",
+        ]
+        .map(|name| format!("/{}[0]/{}[0]", keys::DEFAULT_PARAMS, name));
+
+        for (path, text) in device_leaves
+            .iter()
+            .filter(|(path, _)| !not_stored.contains(path))
+        {
+            let written_text = written_leaves
+                .get(path)
+                .unwrap_or_else(|| panic!("{} is missing", path));
+
+            if text.starts_with("0x") {
+                assert_eq!(written_text.len(), text.len(), "{}", path);
+                assert!(written_text.starts_with("0x"), "{}", path);
+                assert!(
+                    written_text[2..]
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || ('A'..='F').contains(&c)),
+                    "{}",
+                    path
+                );
+            } else {
+                assert_eq!(written_text, text, "{}", path);
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! The order of the attributes and the elements in the patches saved by the firmware 3.1
+use xmltree::{Element, XMLNode};
+
+use crate::serialization::keys;
+
+const SOUND_ATTRIBUTES: &[&str] = &[
+    keys::NAME,
+    keys::FIRMWARE_VERSION,
+    keys::EARLIEST_COMPATIBLE_FIRMWARE,
+    keys::POLYPHONIC,
+    keys::VOICE_PRIORITY,
+    keys::SIDECHAIN_SEND,
+    keys::MODE,
+    keys::LPF_MODE,
+    keys::MOD_FX_TYPE,
+    keys::CLIPPING_AMOUNT,
+];
+
+const SOUND_CHILDREN: &[&str] = &[
+    keys::OSC1,
+    keys::OSC2,
+    keys::LFO1,
+    keys::LFO2,
+    keys::FM_MODULATOR1,
+    keys::FM_MODULATOR2,
+    keys::UNISON,
+    keys::DELAY,
+    keys::COMPRESSOR,
+    keys::DEFAULT_PARAMS,
+    keys::ARPEGGIATOR,
+    keys::MOD_KNOBS,
+];
+
+const SOUND_PARAMS_ATTRIBUTES: &[&str] = &[
+    keys::ARPEGGIATOR_GATE,
+    keys::PORTAMENTO,
+    keys::COMPRESSOR_SHAPE,
+    keys::VOLUME_OSC_A,
+    keys::PULSE_WIDTH_OSC_A,
+    keys::VOLUME_OSC_B,
+    keys::PULSE_WIDTH_OSC_B,
+    keys::NOISE_VOLUME,
+    keys::VOLUME,
+    keys::PAN,
+    keys::LPF_FREQUENCY,
+    keys::LPF_RESONANCE,
+    keys::HPF_FREQUENCY,
+    keys::HPF_RESONANCE,
+    keys::LFO1_RATE,
+    keys::LFO2_RATE,
+    keys::AMOUNT_MODULATOR1,
+    keys::FEEDBACK_MODULATOR1,
+    keys::AMOUNT_MODULATOR2,
+    keys::FEEDBACK_MODULATOR2,
+    keys::FEEDBACK_CARRIER1,
+    keys::FEEDBACK_CARRIER2,
+    keys::MODULATION_FX_RATE,
+    keys::MODULATION_FX_DEPTH,
+    keys::DELAY_RATE,
+    keys::DELAY_FEEDBACK,
+    keys::REVERB_AMOUNT,
+    keys::ARPEGGIATOR_RATE,
+    keys::STUTTER_RATE,
+    keys::DECIMATION,
+    keys::BIT_CRUSH,
+    keys::MODULATION_FX_OFFSET,
+    keys::MODULATION_FX_FEEDBACK,
+];
+
+const SOUND_PARAMS_CHILDREN: &[&str] = &[keys::ENVELOPE1, keys::ENVELOPE2, keys::PATCH_CABLES, keys::EQUALIZER];
+
+const KIT_ATTRIBUTES: &[&str] = &[
+    keys::FIRMWARE_VERSION,
+    keys::EARLIEST_COMPATIBLE_FIRMWARE,
+    keys::LPF_MODE,
+    keys::MOD_FX_TYPE,
+    keys::CURRENT_FILTER_TYPE,
+];
+
+const KIT_CHILDREN: &[&str] = &[
+    keys::DELAY,
+    keys::COMPRESSOR,
+    keys::DEFAULT_PARAMS,
+    keys::SOUND_SOURCES,
+    keys::SELECTED_DRUM_INDEX,
+];
+
+const KIT_PARAMS_ATTRIBUTES: &[&str] = &[
+    keys::REVERB_AMOUNT,
+    keys::VOLUME,
+    keys::PAN,
+    keys::SIDECHAIN_COMPRESSOR_SHAPE,
+    keys::MODULATION_FX_DEPTH,
+    keys::MODULATION_FX_RATE,
+    keys::STUTTER_RATE,
+    keys::DECIMATION,
+    keys::BIT_CRUSH,
+    keys::MODULATION_FX_OFFSET,
+    keys::MODULATION_FX_FEEDBACK,
+];
+
+const KIT_PARAMS_CHILDREN: &[&str] = &[keys::DELAY, keys::LPF, keys::HPF, keys::EQUALIZER];
+
+const OSCILLATOR_ATTRIBUTES: &[&str] = &[
+    keys::TYPE,
+    keys::LOOP_MODE,
+    keys::REVERSED,
+    keys::TIME_STRETCH_ENABLE,
+    keys::TIME_STRETCH_AMOUNT,
+    keys::LINEAR_INTERPOLATION,
+    keys::FILE_NAME,
+    keys::TRANSPOSE,
+    keys::CENTS,
+    keys::OSCILLATOR_SYNC,
+    keys::RETRIG_PHASE,
+    keys::FM_MOD1_TO_MOD2,
+];
+
+const SAMPLE_RANGE_ATTRIBUTES: &[&str] = &[keys::SAMPLE_RANGE_TOP_NOTE, keys::FILE_NAME, keys::TRANSPOSE, keys::CENTS];
+
+const ZONE_ATTRIBUTES: &[&str] = &[
+    keys::START_SAMPLES_POS,
+    keys::END_SAMPLES_POS,
+    keys::START_LOOP_SAMPLES_POS,
+    keys::END_LOOP_SAMPLES_POS,
+];
+
+const DELAY_ATTRIBUTES: &[&str] = &[keys::PING_PONG, keys::ANALOG, keys::SYNC_LEVEL, keys::RATE, keys::FEEDBACK];
+
+const COMPRESSOR_ATTRIBUTES: &[&str] = &[keys::SYNC_LEVEL, keys::COMPRESSOR_ATTACK, keys::COMPRESSOR_RELEASE];
+
+const ARPEGGIATOR_ATTRIBUTES: &[&str] = &[keys::ARPEGGIATOR_MODE, keys::ARPEGGIATOR_OCTAVE_COUNT, keys::SYNC_LEVEL];
+
+const LFO_ATTRIBUTES: &[&str] = &[keys::TYPE, keys::SYNC_LEVEL];
+
+const ENVELOPE_ATTRIBUTES: &[&str] = &[keys::ENV_ATTACK, keys::ENV_DECAY, keys::ENV_SUSTAIN, keys::ENV_RELEASE];
+
+const EQUALIZER_ATTRIBUTES: &[&str] = &[
+    keys::EQ_BASS,
+    keys::EQ_TREBLE,
+    keys::EQ_BASS_FREQUENCY,
+    keys::EQ_TREBLE_FREQUENCY,
+];
+
+const PATCH_CABLE_ATTRIBUTES: &[&str] = &[
+    keys::PATCH_CABLE_SOURCE,
+    keys::PATCH_CABLE_DESTINATION,
+    keys::PATCH_CABLE_AMOUNT,
+];
+
+/// Sort the attributes and the children of a sound or a kit like the firmware writes them
+///
+/// The attributes and the elements the firmware 3.1 doesn't write are kept after the others, in their order.
+pub fn sort_like_the_firmware(element: &mut Element) {
+    sort_element(element, "");
+}
+
+fn sort_element(element: &mut Element, parent_name: &str) {
+    let (attributes_order, children_order) = firmware_order(parent_name, &element.name);
+
+    element
+        .attributes
+        .sort_by(|name1, _, name2, _| position(attributes_order, name1).cmp(&position(attributes_order, name2)));
+    element
+        .children
+        .sort_by_key(|child| match child {
+            XMLNode::Element(child) => position(children_order, &child.name),
+            _ => 0,
+        });
+
+    for child in element.children.iter_mut() {
+        if let XMLNode::Element(child) = child {
+            sort_element(child, &element.name);
+        }
+    }
+}
+
+fn firmware_order(parent_name: &str, name: &str) -> (&'static [&'static str], &'static [&'static str]) {
+    match (parent_name, name) {
+        (_, keys::SOUND) => (SOUND_ATTRIBUTES, SOUND_CHILDREN),
+        (keys::SOUND, keys::DEFAULT_PARAMS) => (SOUND_PARAMS_ATTRIBUTES, SOUND_PARAMS_CHILDREN),
+        (_, keys::KIT) => (KIT_ATTRIBUTES, KIT_CHILDREN),
+        (keys::KIT, keys::DEFAULT_PARAMS) => (KIT_PARAMS_ATTRIBUTES, KIT_PARAMS_CHILDREN),
+        (_, keys::OSC1 | keys::OSC2 | keys::FM_MODULATOR1 | keys::FM_MODULATOR2) => (OSCILLATOR_ATTRIBUTES, &[]),
+        (_, keys::SAMPLE_RANGE) => (SAMPLE_RANGE_ATTRIBUTES, &[]),
+        (_, keys::ZONE) => (ZONE_ATTRIBUTES, &[]),
+        (_, keys::DELAY) => (DELAY_ATTRIBUTES, &[]),
+        (_, keys::COMPRESSOR) => (COMPRESSOR_ATTRIBUTES, &[]),
+        (_, keys::ARPEGGIATOR) => (ARPEGGIATOR_ATTRIBUTES, &[]),
+        (_, keys::LFO1 | keys::LFO2) => (LFO_ATTRIBUTES, &[]),
+        (_, keys::ENVELOPE1 | keys::ENVELOPE2) => (ENVELOPE_ATTRIBUTES, &[]),
+        (_, keys::EQUALIZER) => (EQUALIZER_ATTRIBUTES, &[]),
+        (_, keys::PATCH_CABLE) => (PATCH_CABLE_ATTRIBUTES, &[]),
+        _ => (&[], &[]),
+    }
+}
+
+/// The position of a name in the order, the unknown names are after the known ones
+fn position(order: &[&str], name: &str) -> usize {
+    order
+        .iter()
+        .position(|ordered_name| *ordered_name == name)
+        .unwrap_or(order.len())
+}
@@ -2,13 +2,13 @@ use crate::{
     kit::SoundRow,
     serialization::{
         default_params::{DefaultParams, TwinSelector},
-        keys,
-        serialization_common::convert_milliseconds_to_samples,
+        keys, load_metadata,
+        serialization_common::{into_strict_kit, load_kit_rows, remap_selected_row_index},
         xml,
     },
-    values::{HexU50, MidiChannel, ModulationFxType, OnOff, OscType, Pan, SamplePosition, SynthMode},
+    values::{HexU50, MidiChannel, MidiNote, ModulationFxType, OnOff, OscType, Pan, SamplePosition, SynthMode},
     Arpeggiator, Chorus, CvGateRow, Delay, Distorsion, Envelope, Equalizer, Flanger, FmCarrier, FmModulator, FmSynth, Hpf, Kit,
-    Lfo1, Lfo2, Lpf, MidiRow, ModKnob, ModulationFx, PatchCable, Phaser, RingModSynth, RowKit, Sample, SampleOneZone,
+    Lfo1, Lfo2, Lpf, MidiRow, ModKnob, ModulationFx, PatchCable, Phaser, RingModSynth, RowError, RowKit, Sample, SampleOneZone,
     SampleOscillator, SampleRange, SampleZone, SerializationError, Sidechain, Sound, SubtractiveOscillator, SubtractiveSynth,
     Synth, SynthEngine, Unison, WaveformOscillator,
 };
@@ -21,36 +21,28 @@ pub fn load_synth_nodes(root_nodes: &[Element]) -> Result<Synth, SerializationEr
 
     Ok(Synth {
         sound: load_sound(sound_node)?,
+        metadata: load_metadata(sound_node),
     })
 }
 
 pub fn load_kit_nodes(root_nodes: &[Element]) -> Result<Kit, SerializationError> {
+    into_strict_kit(load_kit_nodes_lenient(root_nodes))
+}
+
+/// Load a deluge kit XML file, skipping the rows that can't be loaded
+pub fn load_kit_nodes_lenient(root_nodes: &[Element]) -> Result<(Kit, Vec<RowError>), SerializationError> {
     let kit_node = xml::get_element(root_nodes, keys::KIT)?;
     let sound_sources_node = xml::get_children_element(kit_node, keys::SOUND_SOURCES)?;
-    let sources: Vec<Result<RowKit, SerializationError>> = sound_sources_node
-        .children
-        .iter()
-        .filter_map(xml::keep_element_only)
-        .map(load_sound_source)
-        .collect();
-
-    if let Some(result_with_error) = sources.iter().find(|s| s.is_err()) {
-        return Err(result_with_error
-            .as_ref()
-            .unwrap_err()
-            .clone());
-    }
-
-    return Ok(Kit {
-        rows: sources
-            .iter()
-            .flatten()
-            .cloned()
-            .collect::<Vec<RowKit>>(),
-        lpf_mode: xml::parse_attribute(kit_node, keys::LPF_MODE)?,
+    let (rows, row_errors) = load_kit_rows(sound_sources_node, load_sound_source);
+    let kit = Kit {
+        rows,
+        lpf_mode: xml::parse_enum_attribute(kit_node, keys::LPF_MODE)?,
         modulation_fx: load_modulation_fx(kit_node)?,
         current_filter_type: xml::parse_attribute(kit_node, keys::CURRENT_FILTER_TYPE)?,
-        selected_row_index: xml::parse_opt_children_element_content(kit_node, keys::SELECTED_DRUM_INDEX)?,
+        selected_row_index: remap_selected_row_index(
+            xml::parse_opt_children_element_content(kit_node, keys::SELECTED_DRUM_INDEX)?,
+            &row_errors,
+        ),
         volume: load_global_hexu(kit_node, keys::VOLUME)?,
         reverb_amount: load_global_hexu(kit_node, keys::REVERB_AMOUNT)?,
         pan: load_global_pan(kit_node)?,
@@ -62,7 +54,10 @@ pub fn load_kit_nodes(root_nodes: &[Element]) -> Result<Kit, SerializationError>
         lpf: load_global_lpf(kit_node)?,
         hpf: load_global_hpf(kit_node)?,
         equalizer: load_global_equalizer(kit_node)?,
-    });
+        metadata: load_metadata(kit_node),
+    };
+
+    Ok((kit, row_errors))
 }
 
 /// Load a "sound" node.
@@ -73,19 +68,20 @@ pub fn load_kit_nodes(root_nodes: &[Element]) -> Result<Kit, SerializationError>
 /// I think the class structure in the deluge implementation looks like:
 /// class Sound
 /// class RowKit(Sound, Name, OtherAdditionalInfosByRow)
-fn load_sound(root: &Element) -> Result<Sound, SerializationError> {
-    let sound_type = xml::parse_attribute::<SynthMode>(root, keys::MODE)?;
+pub fn load_sound(root: &Element) -> Result<Sound, SerializationError> {
+    let mode = xml::get_attribute(root, keys::MODE)?.clone();
     let default_params_node = xml::get_children_element(root, keys::DEFAULT_PARAMS)?;
 
-    let generator = match sound_type {
-        SynthMode::Subtractive => load_subtractive_sound(root)?,
-        SynthMode::Fm => load_fm_sound(root)?,
-        SynthMode::RingMod => load_ringmode_sound(root)?,
-        _ => return Err(SerializationError::UnsupportedSoundType),
+    let generator = match serde_plain::from_str::<SynthMode>(&mode) {
+        Ok(SynthMode::Subtractive) => load_subtractive_sound(root)?,
+        Ok(SynthMode::Fm) => load_fm_sound(root)?,
+        Ok(SynthMode::RingMod) => load_ringmode_sound(root)?,
+        _ => return Err(SerializationError::UnsupportedSoundType(mode)),
     };
 
     Ok(Sound {
         polyphonic: xml::parse_attribute(root, keys::POLYPHONIC)?,
+        max_voices: xml::parse_opt_attribute(root, keys::MAX_VOICES)?,
         voice_priority: xml::parse_attribute(root, keys::VOICE_PRIORITY)?,
         volume: xml::parse_attribute(default_params_node, keys::VOLUME)?,
         reverb_amount: xml::parse_attribute(default_params_node, keys::REVERB_AMOUNT)?,
@@ -120,7 +116,7 @@ fn load_subtractive_sound(root: &Element) -> Result<SynthEngine, SerializationEr
         osc2: load_oscillator(osc2_node, &DefaultParams::new(TwinSelector::B, default_params_node))?,
         osc2_sync: xml::parse_opt_attribute(osc2_node, keys::OSCILLATOR_SYNC)?.unwrap_or(OnOff::Off),
         noise: xml::parse_attribute(default_params_node, keys::NOISE_VOLUME)?,
-        lpf_mode: xml::parse_attribute(root, keys::LPF_MODE)?,
+        lpf_mode: xml::parse_enum_attribute(root, keys::LPF_MODE)?,
         lpf_frequency: xml::parse_attribute(default_params_node, keys::LPF_FREQUENCY)?,
         lpf_resonance: xml::parse_attribute(default_params_node, keys::LPF_RESONANCE)?,
         hpf_frequency: xml::parse_attribute(default_params_node, keys::HPF_FREQUENCY)?,
@@ -259,19 +255,19 @@ fn load_sample(root: &Element) -> Result<Sample, SerializationError> {
 /// We try to get start and end positions as samples if possible, and as milliseconds if forced.
 /// If both are missing then SamplePosition(0) is assigned.
 fn parse_sample_zone(root: &Element) -> Result<SampleZone, SerializationError> {
-    let start = SamplePosition::new(match xml::parse_opt_attribute::<u64>(root, keys::START_SAMPLES_POS)? {
-        Some(samples) => samples,
+    let start = match xml::parse_opt_attribute::<u64>(root, keys::START_SAMPLES_POS)? {
+        Some(samples) => SamplePosition::new(samples),
         None => xml::parse_opt_attribute::<u64>(root, keys::START_MILLISECONDS_POS)?
-            .map(convert_milliseconds_to_samples)
+            .map(SamplePosition::from_millis)
             .unwrap_or_default(),
-    });
+    };
 
-    let end = SamplePosition::new(match xml::parse_opt_attribute::<u64>(root, keys::END_SAMPLES_POS)? {
-        Some(samples) => samples,
+    let end = match xml::parse_opt_attribute::<u64>(root, keys::END_SAMPLES_POS)? {
+        Some(samples) => SamplePosition::new(samples),
         None => xml::parse_opt_attribute::<u64>(root, keys::END_MILLISECONDS_POS)?
-            .map(convert_milliseconds_to_samples)
+            .map(SamplePosition::from_millis)
             .unwrap_or_default(),
-    });
+    };
 
     let start_loop = xml::parse_opt_attribute::<u64>(root, keys::START_LOOP_SAMPLES_POS)?.map(SamplePosition::new);
 
@@ -311,7 +307,7 @@ fn load_waveform_oscillator_imp(
 
 fn load_midi_output(root: &Element) -> Result<MidiRow, SerializationError> {
     let channel: MidiChannel = xml::parse_attribute(root, keys::CHANNEL)?;
-    let note = xml::parse_attribute(root, keys::NOTE)?;
+    let note: MidiNote = xml::parse_attribute(root, keys::NOTE)?;
 
     Ok(MidiRow { channel, note })
 }
@@ -332,7 +328,8 @@ fn load_sound_source(root: &Element) -> Result<RowKit, SerializationError> {
 fn load_sound_output(root: &Element) -> Result<SoundRow, SerializationError> {
     Ok(SoundRow {
         sound: Box::new(load_sound(root)?),
-        name: xml::parse_attribute(root, keys::NAME)?,
+        // The name is not written when it's empty
+        name: xml::parse_opt_attribute(root, keys::NAME)?.unwrap_or_default(),
     })
 }
 
@@ -596,6 +593,15 @@ mod tests {
         assert_eq!(kit.rows.len(), 7);
     }
 
+    #[test]
+    fn test_parse_sample_zone_milliseconds() {
+        let roots = xml::load_xml(r#"<zone startMilliseconds="500" endMilliseconds="1000" />"#).unwrap();
+        let zone = parse_sample_zone(&roots[0]).unwrap();
+
+        assert_eq!(zone.start, SamplePosition::new(22050));
+        assert_eq!(zone.end, SamplePosition::new(44100));
+    }
+
     #[test]
     fn load_valid_kit_xml_and_check_sounds_midi_and_gate() {
         let roots = xml::load_xml(include_str!("../../data_tests/KITS/KIT_TEST_SOUNDS_MIDI_GATE.XML")).unwrap();
@@ -606,7 +612,7 @@ mod tests {
             kit.rows[0],
             RowKit::Midi(MidiRow {
                 channel: 1.into(),
-                note: 63
+                note: 63.into()
             })
         );
         assert_eq!(kit.rows[1], RowKit::CvGate(CvGateRow { channel: 3.into() }));
@@ -710,7 +716,7 @@ mod tests {
         assert_eq!(waveform.osc_type, OscType::Square);
         assert_eq!(waveform.transpose, Transpose::new(0));
         assert_eq!(waveform.fine_transpose, FineTranspose::new(0));
-        assert_eq!(waveform.retrig_phase, RetrigPhase::default());
+        assert_eq!(waveform.retrig_phase, RetrigPhase::Off);
 
         assert_eq!(waveform.pulse_width, HexU50::parse("0x00000000").unwrap());
 
@@ -766,23 +772,23 @@ mod tests {
 
         assert_eq!(generator.osc1.transpose, Transpose::new(0));
         assert_eq!(generator.osc1.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.osc1.retrig_phase, RetrigPhase::default());
+        assert_eq!(generator.osc1.retrig_phase, RetrigPhase::Off);
         assert_eq!(generator.osc1.feedback, HexU50::parse("0xCCCCCCBF").unwrap());
 
         assert_eq!(generator.osc2.transpose, Transpose::new(32));
         assert_eq!(generator.osc2.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.osc2.retrig_phase, RetrigPhase::default());
+        assert_eq!(generator.osc2.retrig_phase, RetrigPhase::Off);
         assert_eq!(generator.osc2.feedback, HexU50::parse("0x80000000").unwrap());
 
         assert_eq!(generator.modulator1.transpose, Transpose::new(0));
         assert_eq!(generator.modulator1.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.modulator1.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(generator.modulator1.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(generator.modulator1.amount, HexU50::parse("0xB333332A").unwrap());
         assert_eq!(generator.modulator1.feedback, HexU50::parse("0x80000000").unwrap());
 
         assert_eq!(generator.modulator2.transpose, Transpose::new(-12));
         assert_eq!(generator.modulator2.fine_transpose, FineTranspose::new(0));
-        assert_eq!(generator.modulator2.retrig_phase, RetrigPhase::new(0));
+        assert_eq!(generator.modulator2.retrig_phase, RetrigPhase::Degrees(0));
         assert_eq!(generator.modulator2.amount, HexU50::parse("0xB851EB7B").unwrap());
         assert_eq!(generator.modulator2.feedback, HexU50::parse("0x80000000").unwrap());
 
@@ -866,7 +872,7 @@ mod tests {
         assert_eq!(sample.fine_transpose, FineTranspose::new(1));
         assert_eq!(sample.mode, SamplePlayMode::Cut);
         assert_eq!(sample.reversed, OnOff::Off);
-        assert_eq!(sample.pitch_speed, PitchSpeed::Independent);
+        assert_eq!(sample.pitch_speed, PitchSpeed::Linked);
         assert_eq!(sample.time_stretch_amount, TimeStretchAmount::new(0));
 
         let sample_one_zone = sample.sample.as_one_zone().unwrap();
@@ -897,7 +903,7 @@ mod tests {
         assert_eq!(waveform.osc_type, OscType::Square);
         assert_eq!(waveform.transpose, Transpose::new(0));
         assert_eq!(waveform.fine_transpose, FineTranspose::new(0));
-        assert_eq!(waveform.retrig_phase, RetrigPhase::default());
+        assert_eq!(waveform.retrig_phase, RetrigPhase::Off);
     }
 
     #[test]
@@ -976,7 +982,7 @@ mod tests {
         assert_eq!(sample.fine_transpose, FineTranspose::default());
         assert_eq!(sample.mode, SamplePlayMode::Cut);
         assert_eq!(sample.reversed, OnOff::Off);
-        assert_eq!(sample.pitch_speed, PitchSpeed::Independent);
+        assert_eq!(sample.pitch_speed, PitchSpeed::Linked);
         assert_eq!(sample.time_stretch_amount, TimeStretchAmount::new(0));
 
         let sample_ranges = sample
@@ -1017,7 +1023,7 @@ mod tests {
         assert_eq!(waveform.osc_type, OscType::Square);
         assert_eq!(waveform.transpose, Transpose::new(0));
         assert_eq!(waveform.fine_transpose, FineTranspose::new(0));
-        assert_eq!(waveform.retrig_phase, RetrigPhase::default());
+        assert_eq!(waveform.retrig_phase, RetrigPhase::Off);
 
         assert_eq!(generator.osc2_volume, HexU50::parse("0x80000000").unwrap());
     }
@@ -1,5 +1,7 @@
+mod firmware_order;
 mod loading;
 mod writing;
 
-pub use loading::{load_kit_nodes, load_synth_nodes};
-pub use writing::{write_kit, write_synth};
+pub use firmware_order::sort_like_the_firmware;
+pub use loading::{load_kit_nodes, load_kit_nodes_lenient, load_sound, load_synth_nodes};
+pub use writing::{write_kit, write_sound, write_synth};
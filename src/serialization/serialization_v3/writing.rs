@@ -4,40 +4,50 @@ use std::rc::Rc;
 use crate::{
     serialization::{
         default_params::{DefaultParamsMut, TwinSelector},
-        keys,
-        serialization_common::LATEST_SUPPORTED_FIRMWARE_VERSION,
-        xml,
+        keys, write_metadata, xml, FirmwareVersion,
     },
     values::*,
     Arpeggiator, Chorus, CvGateRow, Delay, Distorsion, Envelope, Equalizer, Flanger, FmCarrier, FmModulator, FmSynth, Hpf, Kit,
     Lfo1, Lfo2, Lpf, MidiRow, ModKnob, ModulationFx, PatchCable, Phaser, RingModSynth, RowKit, Sample, SampleOneZone,
-    SampleOscillator, SampleRange, SampleZone, SerializationError, Sidechain, Sound, SubtractiveOscillator, SubtractiveSynth,
-    Synth, SynthEngine, Unison, WaveformOscillator,
+    SampleOscillator, SampleRange, SampleZone, SampleZoneError, SerializationError, Sidechain, Sound, SubtractiveOscillator,
+    SubtractiveSynth, Synth, SynthEngine, Unison, WaveformOscillator,
 };
 
 use xmltree::Element;
 
-pub fn write_synth(synth: &Synth) -> Result<Element, SerializationError> {
+use super::firmware_order::sort_like_the_firmware;
+
+pub fn write_synth(
+    synth: &Synth,
+    firmware_version: &FirmwareVersion,
+    earliest_compatible_firmware: &FirmwareVersion,
+) -> Result<Element, SerializationError> {
     let mut sound_node = write_sound(&synth.sound, None)?;
 
-    xml::insert_attribute(&mut sound_node, keys::FIRMWARE_VERSION, &LATEST_SUPPORTED_FIRMWARE_VERSION)?;
+    xml::insert_attribute(&mut sound_node, keys::FIRMWARE_VERSION, firmware_version)?;
     xml::insert_attribute(
         &mut sound_node,
         keys::EARLIEST_COMPATIBLE_FIRMWARE,
-        &LATEST_SUPPORTED_FIRMWARE_VERSION,
+        earliest_compatible_firmware,
     )?;
+    sort_like_the_firmware(&mut sound_node);
+    write_metadata(&mut sound_node, &synth.metadata);
 
     Ok(sound_node)
 }
 
-pub fn write_kit(kit: &Kit) -> Result<Element, SerializationError> {
+pub fn write_kit(
+    kit: &Kit,
+    firmware_version: &FirmwareVersion,
+    earliest_compatible_firmware: &FirmwareVersion,
+) -> Result<Element, SerializationError> {
     let mut kit_node = Element::new(keys::KIT);
 
-    xml::insert_attribute(&mut kit_node, keys::FIRMWARE_VERSION, &LATEST_SUPPORTED_FIRMWARE_VERSION)?;
+    xml::insert_attribute(&mut kit_node, keys::FIRMWARE_VERSION, firmware_version)?;
     xml::insert_attribute(
         &mut kit_node,
         keys::EARLIEST_COMPATIBLE_FIRMWARE,
-        &LATEST_SUPPORTED_FIRMWARE_VERSION,
+        earliest_compatible_firmware,
     )?;
 
     xml::insert_attribute(&mut kit_node, keys::LPF_MODE, &kit.lpf_mode)?;
@@ -52,7 +62,8 @@ pub fn write_kit(kit: &Kit) -> Result<Element, SerializationError> {
 
     xml::insert_child(&mut kit_node, write_sound_sources(&kit.rows)?)?;
 
-    if let Some(index) = kit.selected_row_index {
+    // The firmware expects the index of an existing row
+    if let Some(index) = kit.clamped_selected_row_index() {
         xml::insert_child(&mut kit_node, write_selected_drum_index(index)?)?;
     }
 
@@ -68,6 +79,8 @@ pub fn write_kit(kit: &Kit) -> Result<Element, SerializationError> {
     xml::insert_child_rc(&default_params_node, write_equalizer(&kit.equalizer)?);
     xml::insert_child(&mut default_params_node.borrow_mut(), default_delay_node.borrow().clone())?;
     xml::insert_child(&mut kit_node, default_params_node.borrow().clone())?;
+    sort_like_the_firmware(&mut kit_node);
+    write_metadata(&mut kit_node, &kit.metadata);
 
     Ok(kit_node)
 }
@@ -77,7 +90,7 @@ fn write_sound_sources(rows: &[RowKit]) -> Result<Element, SerializationError> {
 
     for row in rows {
         let node = match row {
-            RowKit::Sound(sound) => write_sound(&sound.sound, Some(&sound.name))?,
+            RowKit::Sound(sound) => write_sound(&sound.sound, Some(sound.name.as_str()))?,
             RowKit::CvGate(gate) => write_gate_output(gate)?,
             RowKit::Midi(midi) => write_midi_output(midi)?,
         };
@@ -114,18 +127,20 @@ fn write_midi_output(midi_output: &MidiRow) -> Result<Element, SerializationErro
     Ok(midi_output_node)
 }
 
-fn write_sound(sound: &Sound, name: Option<&String>) -> Result<Element, SerializationError> {
+/// Write a "sound" node without the firmware attributes, the name is written only if it's not empty.
+pub fn write_sound(sound: &Sound, name: Option<&str>) -> Result<Element, SerializationError> {
     let mut sound_node = Element::new(keys::SOUND);
     let default_params_node = Rc::new(RefCell::new(Element::new(keys::DEFAULT_PARAMS)));
 
     if let Some(name) = name {
         if !name.is_empty() {
-            xml::insert_attribute(&mut sound_node, keys::NAME, name)?;
+            xml::insert_attribute(&mut sound_node, keys::NAME, &name)?;
         }
     }
 
     xml::insert_attribute(&mut sound_node, keys::MODE, &sound.generator.to_sound_type())?;
     xml::insert_attribute(&mut sound_node, keys::POLYPHONIC, &sound.polyphonic)?;
+    xml::insert_opt_attribute(&mut sound_node, keys::MAX_VOICES, &sound.max_voices)?;
     xml::insert_opt_attribute(&mut sound_node, keys::SIDECHAIN_SEND, &sound.sidechain_send)?;
     xml::insert_attribute(&mut sound_node, keys::VOICE_PRIORITY, &sound.voice_priority)?;
     xml::insert_attribute_rc(&default_params_node, keys::VOLUME, &sound.volume)?;
@@ -262,7 +277,7 @@ fn write_subtractive_sound(
 
     let mut osc2_node = write_oscillator(&generator.osc2, &default_params_b)?;
 
-    xml::insert_attribute(&mut osc2_node, keys::OSCILLATOR_SYNC, &generator.osc2_sync)?;
+    xml::insert_opt_attribute_if_not_default(&mut osc2_node, keys::OSCILLATOR_SYNC, &generator.osc2_sync)?;
 
     xml::insert_child(sound_node, write_oscillator(&generator.osc1, &default_params_a)?)?;
     xml::insert_child(sound_node, osc2_node)?;
@@ -314,13 +329,13 @@ fn write_sample_oscillator(sample: &SampleOscillator, default_params: &DefaultPa
     let mut node = default_params.create_element(keys::OSC1, keys::OSC2);
 
     xml::insert_attribute(&mut node, keys::TYPE, &OscType::Sample)?;
-    xml::insert_attribute(&mut node, keys::TRANSPOSE, &sample.transpose)?;
-    xml::insert_attribute(&mut node, keys::CENTS, &sample.fine_transpose)?;
+    xml::insert_opt_attribute_if_not_default(&mut node, keys::TRANSPOSE, &sample.transpose)?;
+    xml::insert_opt_attribute_if_not_default(&mut node, keys::CENTS, &sample.fine_transpose)?;
     xml::insert_attribute(&mut node, keys::REVERSED, &sample.reversed)?;
     xml::insert_attribute(&mut node, keys::LOOP_MODE, &sample.mode)?;
     xml::insert_attribute(&mut node, keys::TIME_STRETCH_ENABLE, &sample.pitch_speed)?;
     xml::insert_attribute(&mut node, keys::TIME_STRETCH_AMOUNT, &sample.time_stretch_amount)?;
-    xml::insert_attribute(&mut node, keys::LINEAR_INTERPOLATION, &sample.linear_interpolation)?;
+    xml::insert_opt_attribute_if_not_default(&mut node, keys::LINEAR_INTERPOLATION, &sample.linear_interpolation)?;
 
     write_sample(&mut node, &sample.sample)?;
 
@@ -360,7 +375,7 @@ fn write_sample_ranges(node: &mut Element, ranges: &[SampleRange]) -> Result<(),
 }
 
 fn write_sample_one_zone(node: &mut Element, sample: &SampleOneZone) -> Result<(), SerializationError> {
-    xml::insert_attribute(node, keys::FILE_NAME, &sample.file_path)?;
+    xml::insert_opt_attribute_if_not_default(node, keys::FILE_NAME, &sample.file_path)?;
 
     if let Some(zone) = &sample.zone {
         xml::insert_child(node, write_sample_zone(zone)?)?;
@@ -370,6 +385,12 @@ fn write_sample_one_zone(node: &mut Element, sample: &SampleOneZone) -> Result<(
 }
 
 fn write_sample_zone(zone: &SampleZone) -> Result<Element, SerializationError> {
+    if let (Some(start), Some(end)) = (zone.start_loop, zone.end_loop) {
+        if end < start {
+            return Err(SampleZoneError::LoopEndBeforeLoopStart { start, end }.into());
+        }
+    }
+
     let mut sample_zone_node = Element::new(keys::ZONE);
 
     xml::insert_attribute(&mut sample_zone_node, keys::START_SAMPLES_POS, &zone.start)?;
@@ -424,7 +445,7 @@ fn write_ringmod_sound(
     let default_params_b = DefaultParamsMut::new(TwinSelector::B, default_params_node.clone());
     let mut osc2_node = write_waveform_oscillator(&generator.osc2, &default_params_b)?;
 
-    xml::insert_attribute(&mut osc2_node, keys::OSCILLATOR_SYNC, &generator.osc2_sync)?;
+    xml::insert_opt_attribute_if_not_default(&mut osc2_node, keys::OSCILLATOR_SYNC, &generator.osc2_sync)?;
     xml::insert_child(sound_node, write_waveform_oscillator(&generator.osc1, &default_params_a)?)?;
     xml::insert_child(sound_node, osc2_node)?;
     xml::insert_attribute_rc(default_params_node, keys::NOISE_VOLUME, &generator.noise)?;
@@ -468,7 +489,7 @@ fn write_distorsion(
     sound_node: &mut Element,
     default_params_node: &Rc<RefCell<Element>>,
 ) -> Result<(), SerializationError> {
-    xml::insert_attribute(sound_node, keys::CLIPPING_AMOUNT, &distorsion.saturation)?;
+    xml::insert_opt_attribute_if_not_default(sound_node, keys::CLIPPING_AMOUNT, &distorsion.saturation)?;
     xml::insert_attribute_rc(default_params_node, keys::BIT_CRUSH, &distorsion.bit_crush)?;
     xml::insert_attribute_rc(default_params_node, keys::DECIMATION, &distorsion.decimation)?;
 
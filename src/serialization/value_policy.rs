@@ -0,0 +1,325 @@
+//! The [OutOfRange] policy consulted by the readers of the integer values when loading a patch.
+//!
+//! The values stored as hexadecimal like [HexU50](crate::HexU50) or [Pan](crate::Pan), and the values stored as
+//! signed 32-bits decimal like [DecU50](crate::DecU50), can't be out of range: each 32-bits value is mapped
+//! in their range. Only the values stored as is, such as a transpose, a count of unison voices, a MIDI note or
+//! a sidechain time, are checked by their reader.
+//!
+//! The readers are called by serde without any context, so the policy is kept for the current thread while a patch
+//! is loaded, see [load_with_policy]. The XML parsing functions report the values out of range found by the readers
+//! with the key and the element they were read from.
+use std::cell::RefCell;
+
+use xmltree::{Element, XMLNode};
+
+use super::SerializationError;
+
+/// What to do with an integer value out of its range when loading a patch
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutOfRange {
+    /// Load the value as is, the loading only fails if the type of the value can't hold it.
+    /// The value kept out of its range is saved unchanged.
+    #[default]
+    Keep,
+    /// Fail with [SerializationError::ValueOutOfRange] naming the field and the text of the value.
+    Error,
+    /// Replace the value by the nearest bound, each value replaced is reported with a [ClampedValue].
+    Clamp,
+}
+
+/// A value replaced by the nearest bound of its range when loading a patch with [OutOfRange::Clamp]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClampedValue {
+    /// The path of the XML node containing the value, for example "sound/osc1".
+    pub path: String,
+    /// The key of the value, for example "transpose".
+    pub field: String,
+    /// The text of the value in the file
+    pub value: String,
+    /// The value loaded instead
+    pub clamped: i64,
+}
+
+thread_local! {
+    static CURRENT_LOADING: RefCell<Option<Loading>> = const { RefCell::new(None) };
+}
+
+/// The patch being loaded on the current thread
+struct Loading {
+    policy: OutOfRange,
+    /// The value out of range found by the last reader, not yet reported by the XML parsing function
+    pending: Option<Pending>,
+    clamped_values: Vec<FoundValue>,
+    error_element: Option<*const Element>,
+}
+
+enum Pending {
+    Rejected,
+    Clamped(i64),
+}
+
+/// A value clamped, the path of its element is found once the patch is loaded
+struct FoundValue {
+    element: *const Element,
+    field: String,
+    value: String,
+    clamped: i64,
+}
+
+/// Restore the loading of the caller, even if the loading panics
+struct LoadingGuard(Option<Loading>);
+
+impl Drop for LoadingGuard {
+    fn drop(&mut self) {
+        CURRENT_LOADING.set(self.0.take());
+    }
+}
+
+/// Load a patch from its XML nodes, the readers of the integer values use the policy while the patch is loaded.
+///
+/// Returns the patch loaded and the values clamped. The error of a value out of range is annotated with the path
+/// of the XML node of the value.
+pub(crate) fn load_with_policy<T>(
+    roots: &[Element],
+    policy: OutOfRange,
+    load: impl FnOnce() -> Result<T, SerializationError>,
+) -> Result<(T, Vec<ClampedValue>), SerializationError> {
+    let loading = Loading {
+        policy,
+        pending: None,
+        clamped_values: Vec::new(),
+        error_element: None,
+    };
+    let guard = LoadingGuard(CURRENT_LOADING.replace(Some(loading)));
+    let result = load();
+    let loading = CURRENT_LOADING
+        .take()
+        .expect("the loading is only removed by its guard");
+
+    drop(guard);
+
+    match result {
+        Ok(patch) => {
+            let clamped_values = loading
+                .clamped_values
+                .into_iter()
+                .map(|found| ClampedValue {
+                    path: find_path(roots, found.element).unwrap_or_default(),
+                    field: found.field,
+                    value: found.value,
+                    clamped: found.clamped,
+                })
+                .collect();
+
+            Ok((patch, clamped_values))
+        }
+        Err(error) => match (
+            error.inner(),
+            loading
+                .error_element
+                .and_then(|element| find_path(roots, element)),
+        ) {
+            (SerializationError::ValueOutOfRange { .. }, Some(path)) => Err(SerializationError::Located {
+                path,
+                location: None,
+                error: Box::new(error.inner().clone()),
+            }),
+            _ => Err(error),
+        },
+    }
+}
+
+/// Check a value read for a type of range [min; max], returns the value to load or None if the reader must fail.
+///
+/// Outside of [load_with_policy] the values out of range are rejected, like the readers always did.
+/// With [OutOfRange::Keep] the value is returned as is, the reader still fails if its type can't hold it.
+pub(crate) fn check_range(value: i64, min: i64, max: i64) -> Option<i64> {
+    if (min..=max).contains(&value) {
+        return Some(value);
+    }
+
+    CURRENT_LOADING.with_borrow_mut(|loading| {
+        let loading = loading.as_mut()?;
+
+        match loading.policy {
+            OutOfRange::Keep => Some(value),
+            OutOfRange::Error => {
+                loading.pending = Some(Pending::Rejected);
+                None
+            }
+            OutOfRange::Clamp => {
+                let clamped = value.clamp(min, max);
+
+                loading.pending = Some(Pending::Clamped(clamped));
+                Some(clamped)
+            }
+        }
+    })
+}
+
+/// Report the value out of range found while parsing the value of a field of an element
+///
+/// Called by the XML parsing functions with the result of the reader.
+pub(crate) fn report<T>(
+    element: &Element,
+    field: &str,
+    value: &str,
+    result: Result<T, SerializationError>,
+) -> Result<T, SerializationError> {
+    CURRENT_LOADING.with_borrow_mut(|loading| {
+        let Some(loading) = loading.as_mut() else {
+            return result;
+        };
+
+        match loading.pending.take() {
+            None => result,
+            Some(Pending::Rejected) => {
+                loading.error_element = Some(element);
+
+                Err(SerializationError::ValueOutOfRange {
+                    field: field.to_string(),
+                    value: value.to_string(),
+                })
+            }
+            Some(Pending::Clamped(clamped)) => {
+                loading.clamped_values.push(FoundValue {
+                    element,
+                    field: field.to_string(),
+                    value: value.to_string(),
+                    clamped,
+                });
+
+                result
+            }
+        }
+    })
+}
+
+/// Find the path of an element like "kit/soundSources/sound[3]"
+fn find_path(roots: &[Element], element: *const Element) -> Option<String> {
+    find_path_in(roots.iter(), "", element)
+}
+
+fn find_path_in<'a>(elements: impl Iterator<Item = &'a Element>, parent_path: &str, element: *const Element) -> Option<String> {
+    indexed_paths(elements, parent_path)
+        .into_iter()
+        .find_map(|(path, candidate)| match std::ptr::eq(candidate, element) {
+            true => Some(path),
+            false => find_path_in(child_elements(candidate), &path, element),
+        })
+}
+
+fn child_elements(element: &Element) -> impl Iterator<Item = &Element> {
+    element
+        .children
+        .iter()
+        .filter_map(|node| match node {
+            XMLNode::Element(child) => Some(child),
+            _ => None,
+        })
+}
+
+/// Give to each element its path like "kit/soundSources/sound[3]", the index counts the previous siblings with the same name.
+fn indexed_paths<'a>(elements: impl Iterator<Item = &'a Element>, parent_path: &str) -> Vec<(String, &'a Element)> {
+    let mut paths: Vec<(String, &'a Element)> = Vec::new();
+
+    for element in elements {
+        let index = paths
+            .iter()
+            .filter(|(_, sibling)| sibling.name == element.name)
+            .count();
+        let name = match index {
+            0 => element.name.clone(),
+            _ => format!("{}[{}]", element.name, index),
+        };
+        let path = match parent_path.is_empty() {
+            true => name,
+            false => format!("{}/{}", parent_path, name),
+        };
+
+        paths.push((path, element));
+    }
+
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialization::xml;
+    use crate::values::{CvGateChannel, MidiChannel};
+    use crate::MidiNote;
+    use pretty_assertions::assert_eq;
+
+    const ROWS: &str =
+        r#"<kit><soundSources><midiOutput channel="12" note="200" /><gateOutput channel="12" /></soundSources></kit>"#;
+
+    /// Load the channels and the note of the rows
+    fn load_rows(roots: &[Element]) -> Result<(MidiChannel, MidiNote, CvGateChannel), SerializationError> {
+        let sources = xml::get_children_element(&roots[0], "soundSources")?;
+        let midi_output = xml::get_children_element(sources, "midiOutput")?;
+        let gate_output = xml::get_children_element(sources, "gateOutput")?;
+
+        Ok((
+            xml::parse_attribute(midi_output, "channel")?,
+            xml::parse_attribute(midi_output, "note")?,
+            xml::parse_attribute(gate_output, "channel")?,
+        ))
+    }
+
+    #[test]
+    fn test_values_out_of_range_are_rejected_outside_of_a_loading() {
+        let roots = xml::load_xml(ROWS).unwrap();
+
+        assert_eq!(check_range(12, 1, 4), None);
+        assert!(load_rows(&roots).is_err());
+    }
+
+    #[test]
+    fn test_keep_values() {
+        let roots = xml::load_xml(ROWS).unwrap();
+        let (rows, clamped_values) = load_with_policy(&roots, OutOfRange::Keep, || load_rows(&roots)).unwrap();
+
+        assert_eq!(rows, (MidiChannel::new(12), MidiNote::new(200), CvGateChannel::new(12)));
+        assert!(!rows.1.is_valid());
+        assert!(!rows.2.is_valid());
+        assert_eq!(clamped_values, vec![]);
+    }
+
+    #[test]
+    fn test_error_is_located() {
+        let roots = xml::load_xml(ROWS).unwrap();
+        let error = load_with_policy(&roots, OutOfRange::Error, || load_rows(&roots)).unwrap_err();
+
+        assert_eq!(error.path(), Some("kit/soundSources/midiOutput"));
+        assert!(matches!(
+            error.inner(),
+            SerializationError::ValueOutOfRange { field, value } if field == "note" && value == "200"
+        ));
+    }
+
+    #[test]
+    fn test_clamp_values() {
+        let roots = xml::load_xml(ROWS).unwrap();
+        let (rows, clamped_values) = load_with_policy(&roots, OutOfRange::Clamp, || load_rows(&roots)).unwrap();
+
+        assert_eq!(rows, (MidiChannel::new(12), MidiNote::new(127), CvGateChannel::new(4)));
+        assert_eq!(
+            clamped_values,
+            vec![
+                ClampedValue {
+                    path: "kit/soundSources/midiOutput".to_string(),
+                    field: "note".to_string(),
+                    value: "200".to_string(),
+                    clamped: 127,
+                },
+                ClampedValue {
+                    path: "kit/soundSources/gateOutput".to_string(),
+                    field: "channel".to_string(),
+                    value: "12".to_string(),
+                    clamped: 4,
+                },
+            ]
+        );
+    }
+}
@@ -1,30 +1,87 @@
+use std::str::FromStr;
+
 use xmltree::Element;
 
-use super::{keys, patch_type::PatchType, xml};
+use super::{
+    firmware_version::FirmwareVersion, keys, patch_info::peek_patch_info, patch_type::PatchType, xml, SerializationError,
+};
 
+/// The versions read from a patch, see [deserialize_synth_with_version](crate::deserialize_synth_with_version)
 #[derive(PartialEq, Eq, Debug)]
 pub struct VersionInfo {
+    /// The version of the firmware that saved the patch, None for the format version 1
     pub firmware_version: Option<String>,
+    /// The oldest firmware able to load the patch, None for the format version 1
     pub earliest_compatible_firmware: Option<String>,
+    /// The format of the XML, deduced from the versions above
     pub format_version: FormatVersion,
 }
 
+impl VersionInfo {
+    /// Parse the firmware version, None if it's missing or if it can't be parsed.
+    pub fn parsed_firmware_version(&self) -> Option<FirmwareVersion> {
+        self.firmware_version
+            .as_deref()
+            .and_then(|version| version.parse().ok())
+    }
+
+    /// Parse the earliest compatible firmware version, None if it's missing or if it can't be parsed.
+    pub fn parsed_earliest_compatible_firmware(&self) -> Option<FirmwareVersion> {
+        self.earliest_compatible_firmware
+            .as_deref()
+            .and_then(|version| version.parse().ok())
+    }
+
+    /// Returns true if the patch was saved by a firmware more recent than [LATEST_SUPPORTED_FIRMWARE_VERSION](crate::LATEST_SUPPORTED_FIRMWARE_VERSION).
+    ///
+    /// Such a patch is loaded but it might contain data that this crate ignores.
+    /// The patches saved by the community firmware are not concerned.
+    pub fn is_newer_than_supported(&self) -> bool {
+        self.parsed_firmware_version()
+            .map(|version| version.is_newer_than_supported())
+            .unwrap_or(false)
+    }
+}
+
 pub fn load_version_info(roots: &[Element], patch_type: PatchType) -> VersionInfo {
-    let earliest_compatible_firmware = load_version(roots, patch_type, keys::EARLIEST_COMPATIBLE_FIRMWARE);
+    load_root_version_info(roots, patch_type.get_key())
+}
+
+/// Load the version information of any root node, for example the node "song".
+pub fn load_root_version_info(roots: &[Element], root_key: &str) -> VersionInfo {
+    let firmware_version = load_version(roots, root_key, keys::FIRMWARE_VERSION);
+    let earliest_compatible_firmware = load_version(roots, root_key, keys::EARLIEST_COMPATIBLE_FIRMWARE);
+
+    make_version_info(firmware_version, earliest_compatible_firmware)
+}
+
+/// Deduce the format version from the firmware versions
+pub fn make_version_info(firmware_version: Option<String>, earliest_compatible_firmware: Option<String>) -> VersionInfo {
+    let format_version = match &firmware_version {
+        Some(version) if is_community_version(version) => FormatVersion::Community,
+        _ => earliest_compatible_firmware
+            .clone()
+            .into(),
+    };
 
     VersionInfo {
-        firmware_version: load_version(roots, patch_type, keys::FIRMWARE_VERSION),
-        earliest_compatible_firmware: earliest_compatible_firmware.clone(),
-        format_version: earliest_compatible_firmware.into(),
+        firmware_version,
+        earliest_compatible_firmware,
+        format_version,
     }
 }
 
-fn load_version(roots: &[Element], patch_type: PatchType, key: &str) -> Option<String> {
+/// The community firmware prefixes its version with a 'c', for example "c1.0.1".
+fn is_community_version(version: &str) -> bool {
+    version.starts_with('c')
+}
+
+fn load_version(roots: &[Element], root_key: &str, key: &str) -> Option<String> {
     if let Some(version) = xml::get_opt_element(roots, key).map(xml::get_text) {
         return Some(version);
     }
 
-    if let Some(node) = xml::get_opt_element(roots, patch_type.get_key()) {
+    if let Some(node) = xml::get_opt_element(roots, root_key) {
         if let Some(version) = xml::get_opt_attribute(node, key).cloned() {
             return Some(version);
         }
@@ -34,6 +91,8 @@ fn load_version(roots: &[Element], patch_type: PatchType, key: &str) -> Option<S
 }
 
 /// Deluge format version
+///
+/// Displayed and parsed as "none", "unsupported", "v1", "v2", "v3" or "community".
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum FormatVersion {
     /// No version specified
@@ -46,6 +105,56 @@ pub enum FormatVersion {
     Version2,
     /// This version uses more attributes instead of children.
     Version3,
+    /// The format written by the Deluge Community Firmware. It's based on the version 3.
+    Community,
+}
+
+impl FormatVersion {
+    /// Detect the format version of a kit or a synth without deserializing it, see [peek_patch_info](crate::peek_patch_info)
+    /// ```
+    /// # use deluge::FormatVersion;
+    /// let xml = include_str!("../data_tests/SYNTHS/SYNT184.XML");
+    ///
+    /// assert_eq!(FormatVersion::detect(xml).unwrap(), FormatVersion::Version3);
+    /// ```
+    pub fn detect(xml: &str) -> Result<Self, SerializationError> {
+        Ok(peek_patch_info(xml)?.format_version)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            FormatVersion::None => "none",
+            FormatVersion::Unsupported => "unsupported",
+            FormatVersion::Version1 => "v1",
+            FormatVersion::Version2 => "v2",
+            FormatVersion::Version3 => "v3",
+            FormatVersion::Community => "community",
+        }
+    }
+}
+
+impl std::fmt::Display for FormatVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FormatVersion {
+    type Err = SerializationError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        [
+            FormatVersion::None,
+            FormatVersion::Unsupported,
+            FormatVersion::Version1,
+            FormatVersion::Version2,
+            FormatVersion::Version3,
+            FormatVersion::Community,
+        ]
+        .into_iter()
+        .find(|version| version.as_str() == text)
+        .ok_or(SerializationError::InvalidVersionFormat)
+    }
 }
 
 fn parse_version(version_string: String) -> FormatVersion {
@@ -75,6 +184,7 @@ impl From<Option<String>> for FormatVersion {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::serialization::community_synth_xml;
     use test_case::test_case;
 
     #[test]
@@ -131,6 +241,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_detect_format_version_community_sound() {
+        assert_eq!(
+            VersionInfo {
+                firmware_version: Some("c1.0.1".to_string()),
+                earliest_compatible_firmware: Some("4.1.0-alpha".to_string()),
+                format_version: FormatVersion::Community,
+            },
+            load_version_info(&xml::load_xml(&community_synth_xml()).unwrap(), PatchType::Synth)
+        );
+    }
+
+    #[test]
+    fn test_parsed_versions() {
+        let version_info = load_version_info(
+            &xml::load_xml(include_str!("../data_tests/SYNTHS/SYNT184.XML")).unwrap(),
+            PatchType::Synth,
+        );
+
+        assert_eq!(version_info.parsed_firmware_version(), Some(FirmwareVersion::new(3, 1, 5)));
+        assert_eq!(
+            version_info.parsed_earliest_compatible_firmware(),
+            Some(FirmwareVersion::new(3, 1, 0).with_suffix("beta"))
+        );
+        assert!(!version_info.is_newer_than_supported());
+    }
+
+    #[test_case(Some("4.0.0"), true)]
+    #[test_case(Some("3.1.5"), false)]
+    #[test_case(Some("c1.0.1"), false)]
+    #[test_case(None, false)]
+    fn test_is_newer_than_supported(firmware_version: Option<&str>, expected: bool) {
+        let version_info = VersionInfo {
+            firmware_version: firmware_version.map(str::to_string),
+            earliest_compatible_firmware: None,
+            format_version: FormatVersion::Version3,
+        };
+
+        assert_eq!(version_info.is_newer_than_supported(), expected);
+    }
+
+    #[test_case("c1.0.1", true)]
+    #[test_case("c1.1.0-beta", true)]
+    #[test_case("3.1.5", false)]
+    #[test_case("", false)]
+    fn test_is_community_version(input: &str, expected: bool) {
+        assert_eq!(is_community_version(input), expected);
+    }
+
+    #[test_case(include_str!("../data_tests/KITS/KIT000.XML"), FormatVersion::Version1 ; "kit version 1")]
+    #[test_case(include_str!("../data_tests/SYNTHS/SYNT028.XML"), FormatVersion::Version1 ; "synth version 1")]
+    #[test_case(include_str!("../data_tests/KITS/KIT026.XML"), FormatVersion::Version2 ; "kit version 2")]
+    #[test_case(include_str!("../data_tests/KITS/KIT057.XML"), FormatVersion::Version3 ; "kit version 3")]
+    #[test_case(include_str!("../data_tests/SYNTHS/SYNT184.XML"), FormatVersion::Version3 ; "synth version 3")]
+    #[test_case(&community_synth_xml(), FormatVersion::Community ; "synth community")]
+    fn test_detect(xml: &str, expected: FormatVersion) {
+        assert_eq!(FormatVersion::detect(xml).unwrap(), expected);
+    }
+
+    #[test_case(FormatVersion::None, "none")]
+    #[test_case(FormatVersion::Unsupported, "unsupported")]
+    #[test_case(FormatVersion::Version1, "v1")]
+    #[test_case(FormatVersion::Version2, "v2")]
+    #[test_case(FormatVersion::Version3, "v3")]
+    #[test_case(FormatVersion::Community, "community")]
+    fn test_format_version_display_from_str(version: FormatVersion, text: &str) {
+        assert_eq!(version.to_string(), text);
+        assert_eq!(text.parse::<FormatVersion>().unwrap(), version);
+    }
+
+    #[test]
+    fn test_format_version_from_str_invalid() {
+        assert!(matches!(
+            "version 3".parse::<FormatVersion>(),
+            Err(SerializationError::InvalidVersionFormat)
+        ));
+    }
+
     #[test_case("1", FormatVersion::Version1)]
     #[test_case("2", FormatVersion::Version2)]
     #[test_case("3", FormatVersion::Version3)]
@@ -1,31 +1,301 @@
+use super::value_policy;
 use crate::SerializationError;
 
+use quick_xml::{
+    events::{BytesCData, BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+    reader::Reader,
+    Writer,
+};
 use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
 use std::sync::Arc;
 use std::{cell::RefCell, rc::Rc};
 use xmltree::{Element, EmitterConfig, XMLNode};
 
-pub fn write_xml(elements: &[Element]) -> String {
-    let mut buffer: Vec<u8> = Vec::with_capacity(1024);
+/// Write the elements using the default formatting of xmltree
+pub fn write_xml_to<W: Write>(elements: &[Element], writer: &mut W) -> Result<(), SerializationError> {
     let mut config: EmitterConfig = EmitterConfig::new();
 
     config.perform_indent = true;
-    for element in elements {
+    for (index, element) in elements.iter().enumerate() {
+        // Only the first root element is preceded by the XML declaration.
+        if index > 0 {
+            config.write_document_declaration = false;
+            writer.write_all(b"\n")?;
+        }
+
         element
-            .write_with_config(&mut buffer, config.clone())
+            .write_with_config(&mut *writer, config.clone())
+            .map_err(std::io::Error::other)?;
+    }
+
+    Ok(())
+}
+
+/// Write the elements like [write_xml_to] excepted the attributes are sorted by name
+///
+/// The attributes keep the order they were written in, the canonical output must not depend on this order.
+pub fn write_canonical_xml(elements: &[Element]) -> String {
+    let mut writer = Writer::new_with_indent(Vec::with_capacity(1024), b' ', 2);
+
+    writer
+        .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))
+        .unwrap();
+
+    for element in elements {
+        write_canonical_element(&mut writer, element);
+    }
+
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+fn write_canonical_element(writer: &mut Writer<Vec<u8>>, element: &Element) {
+    let mut attributes = element
+        .attributes
+        .iter()
+        .collect::<Vec<_>>();
+
+    attributes.sort();
+
+    let start = BytesStart::new(element.name.as_str()).with_attributes(
+        attributes
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str())),
+    );
+
+    if element.children.is_empty() {
+        writer
+            .write_event(Event::Empty(start))
             .unwrap();
+
+        return;
+    }
+
+    writer
+        .write_event(Event::Start(start))
+        .unwrap();
+
+    for child in &element.children {
+        match child {
+            XMLNode::Element(child) => write_canonical_element(writer, child),
+            XMLNode::Text(text) => writer
+                .write_event(Event::Text(BytesText::new(text)))
+                .unwrap(),
+            XMLNode::CData(text) => writer
+                .write_event(Event::CData(BytesCData::new(text)))
+                .unwrap(),
+            XMLNode::Comment(text) => writer
+                .write_event(Event::Comment(BytesText::from_escaped(text.as_str())))
+                .unwrap(),
+            XMLNode::ProcessingInstruction(..) => {}
+        }
     }
 
-    String::from_utf8(buffer).unwrap()
+    writer
+        .write_event(Event::End(BytesEnd::new(element.name.as_str())))
+        .unwrap();
 }
 
+/// Load the XML elements of a string, see [load_xml_from_reader]
 pub fn load_xml(xml: &str) -> Result<Vec<Element>, SerializationError> {
-    Ok(Element::parse_all(xml.as_bytes())
-        .map_err(|e| SerializationError::XmlParsingFailed(Arc::new(e)))?
-        .iter()
-        .filter_map(|n| n.as_element())
-        .cloned()
-        .collect::<Vec<Element>>())
+    load_xml_from_reader(xml.as_bytes())
+}
+
+/// Load the XML elements from a reader
+///
+/// The elements are built from the events of the pull parser of quick-xml while the content is read, so the content
+/// is not copied in a string first. The tree of elements is still built entirely before being returned.
+/// The whitespace only texts are ignored, like the tree based parser does.
+pub fn load_xml_from_reader<R: BufRead>(reader: R) -> Result<Vec<Element>, SerializationError> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buffer = Vec::with_capacity(1024);
+    let mut roots = Vec::new();
+    let mut stack: Vec<Element> = Vec::new();
+
+    reader.trim_text(true);
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buffer)
+            .map_err(|e| SerializationError::XmlReadingFailed(Arc::new(e)))?;
+
+        match event {
+            Event::Start(tag) => stack.push(create_element(&reader, &tag)?),
+            Event::Empty(tag) => {
+                let element = create_element(&reader, &tag)?;
+
+                push_element(&mut stack, &mut roots, element);
+            }
+            Event::End(_) => {
+                if let Some(element) = stack.pop() {
+                    push_element(&mut stack, &mut roots, element);
+                }
+            }
+            Event::Text(text) => {
+                if let Some(parent) = stack.last_mut() {
+                    let text = text
+                        .unescape()
+                        .map_err(|e| SerializationError::XmlReadingFailed(Arc::new(e)))?;
+
+                    parent
+                        .children
+                        .push(XMLNode::Text(text.into_owned()));
+                }
+            }
+            Event::CData(data) => {
+                if let Some(parent) = stack.last_mut() {
+                    let data = reader
+                        .decoder()
+                        .decode(&data)
+                        .map_err(|e| SerializationError::XmlReadingFailed(Arc::new(e)))?;
+
+                    parent
+                        .children
+                        .push(XMLNode::CData(data.into_owned()));
+                }
+            }
+            Event::Comment(text) => {
+                // The comments are kept to load the metadata, see metadata_comment
+                if let Some(parent) = stack.last_mut() {
+                    let text = reader
+                        .decoder()
+                        .decode(&text)
+                        .map_err(|e| SerializationError::XmlReadingFailed(Arc::new(e)))?;
+
+                    parent
+                        .children
+                        .push(XMLNode::Comment(text.into_owned()));
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+
+        buffer.clear();
+    }
+
+    if let Some(element) = stack.last() {
+        return Err(SerializationError::XmlReadingFailed(Arc::new(
+            quick_xml::Error::UnexpectedEof(element.name.clone()),
+        )));
+    }
+
+    Ok(roots)
+}
+
+fn create_element<R>(reader: &Reader<R>, tag: &BytesStart) -> Result<Element, SerializationError> {
+    let to_error = |e: quick_xml::Error| SerializationError::XmlReadingFailed(Arc::new(e));
+    let tag_name = tag.name();
+    let name = reader
+        .decoder()
+        .decode(tag_name.as_ref())
+        .map_err(to_error)?;
+    let mut element = Element::new(&name);
+
+    for attribute in tag.attributes() {
+        let attribute = attribute.map_err(|e| to_error(e.into()))?;
+        let key = reader
+            .decoder()
+            .decode(attribute.key.as_ref())
+            .map_err(to_error)?;
+        let value = attribute
+            .decode_and_unescape_value(reader)
+            .map_err(to_error)?;
+
+        element
+            .attributes
+            .insert(key.into_owned(), value.into_owned());
+    }
+
+    Ok(element)
+}
+
+fn push_element(stack: &mut [Element], roots: &mut Vec<Element>, element: Element) {
+    match stack.last_mut() {
+        Some(parent) => parent
+            .children
+            .push(XMLNode::Element(element)),
+        None => roots.push(element),
+    }
+}
+
+/// Find the line and the column of the node designated by a path like "kit/soundSources/sound[3]".
+///
+/// Each segment of the path is the name of a node, optionally followed by the index of the node
+/// among its siblings with the same name. Lines and columns start at 1.
+pub fn find_location(xml: &str, path: &str) -> Option<(usize, usize)> {
+    let segments = path
+        .split('/')
+        .map(parse_path_segment)
+        .collect::<Option<Vec<(&str, usize)>>>()?;
+    let mut reader = Reader::from_str(xml);
+    let mut matched_count = 0usize;
+    let mut depth = 0usize;
+    let mut sibling_count = 0usize;
+
+    loop {
+        let position = reader.buffer_position();
+        let event = reader.read_event().ok()?;
+
+        match &event {
+            Event::Start(tag) | Event::Empty(tag) => {
+                if depth == matched_count {
+                    let (name, index) = segments[matched_count];
+
+                    if tag.name().as_ref() == name.as_bytes() {
+                        if sibling_count == index {
+                            matched_count += 1;
+                            sibling_count = 0;
+
+                            if matched_count == segments.len() {
+                                return Some(to_line_column(xml, position));
+                            }
+
+                            // The matched node is empty so it can't contain the next node of the path.
+                            if matches!(event, Event::Empty(_)) {
+                                return None;
+                            }
+                        } else {
+                            sibling_count += 1;
+                        }
+                    }
+                }
+
+                if matches!(event, Event::Start(_)) {
+                    depth += 1;
+                }
+            }
+            Event::End(_) => {
+                depth = depth.checked_sub(1)?;
+
+                // The matched node is closed without containing the next node of the path.
+                if depth < matched_count {
+                    return None;
+                }
+            }
+            Event::Eof => return None,
+            _ => (),
+        }
+    }
+}
+
+fn parse_path_segment(segment: &str) -> Option<(&str, usize)> {
+    match segment.split_once('[') {
+        Some((name, index)) => Some((name, index.strip_suffix(']')?.parse().ok()?)),
+        None => Some((segment, 0)),
+    }
+}
+
+fn to_line_column(xml: &str, position: usize) -> (usize, usize) {
+    let before = &xml[..position];
+    let line = before.matches('\n').count() + 1;
+    let column = position
+        - before
+            .rfind('\n')
+            .map_or(0, |index| index + 1)
+        + 1;
+
+    (line, column)
 }
 
 pub fn keep_element_only(node: &XMLNode) -> Option<&Element> {
@@ -85,19 +355,16 @@ pub fn parse_children_element_content<'a, T: Deserialize<'a>>(
     element: &'a Element,
     name: &'a str,
 ) -> Result<T, SerializationError> {
-    let element = get_children_element(element, name)?;
-
-    parse_content(element)
+    parse_value(element, name, get_text_impl(get_children_element(element, name)?))
 }
 
 pub fn parse_opt_children_element_content<'a, T: Deserialize<'a>>(
     element: &'a Element,
     name: &'a str,
 ) -> Result<Option<T>, SerializationError> {
-    Ok(match get_opt_children_element(element, name) {
-        Some(element) => Some(parse_content(element)?),
-        None => None,
-    })
+    get_opt_children_element(element, name)
+        .map(|child| parse_value(element, name, get_text_impl(child)))
+        .transpose()
 }
 
 pub fn get_text(element: &Element) -> String {
@@ -108,7 +375,26 @@ pub fn get_text(element: &Element) -> String {
 }
 
 pub fn parse_attribute<'a, T: Deserialize<'a>>(element: &'a Element, name: &'a str) -> Result<T, SerializationError> {
-    serde_plain::from_str::<T>(get_attribute(element, name)?).map_err(SerializationError::SerdeError)
+    parse_value(element, name, get_attribute(element, name)?)
+}
+
+/// Parse an enumeration, an unknown value is reported with [SerializationError::UnknownEnumVariant]
+pub fn parse_enum<'a, T: Deserialize<'a>>(field: &str, value: &'a str) -> Result<T, SerializationError> {
+    serde_plain::from_str::<T>(value).map_err(|_| SerializationError::UnknownEnumVariant {
+        field: field.to_string(),
+        value: value.to_string(),
+    })
+}
+
+pub fn parse_enum_attribute<'a, T: Deserialize<'a>>(element: &'a Element, name: &'a str) -> Result<T, SerializationError> {
+    parse_enum(name, get_attribute(element, name)?)
+}
+
+pub fn parse_enum_children_element_content<'a, T: Deserialize<'a>>(
+    element: &'a Element,
+    name: &'a str,
+) -> Result<T, SerializationError> {
+    parse_enum(name, get_text_impl(get_children_element(element, name)?))
 }
 
 const NULL_STRING: &str = "";
@@ -132,18 +418,19 @@ fn get_text_impl<'a>(element: &'a Element) -> &'a str {
     }
 }
 
-pub fn parse_content<'a, T: Deserialize<'a>>(element: &'a Element) -> Result<T, SerializationError> {
-    serde_plain::from_str::<T>(get_text_impl(element)).map_err(SerializationError::SerdeError)
+/// Parse the text of a field of an element, a value out of range found by the reader is reported with the field
+fn parse_value<'a, T: Deserialize<'a>>(element: &Element, field: &str, text: &'a str) -> Result<T, SerializationError> {
+    let result = serde_plain::from_str::<T>(text).map_err(SerializationError::SerdeError);
+
+    value_policy::report(element, field, text, result)
 }
 
 pub fn parse_opt_attribute<'a, T: Deserialize<'a>>(element: &'a Element, name: &'a str) -> Result<Option<T>, SerializationError> {
-    let mut result = None;
-
-    if let Some(attribute) = element.attributes.get(name) {
-        result = Some(serde_plain::from_str::<T>(attribute).map_err(SerializationError::SerdeError)?);
-    }
-
-    Ok(result)
+    element
+        .attributes
+        .get(name)
+        .map(|attribute| parse_value(element, name, attribute))
+        .transpose()
 }
 
 pub fn insert_attribute<T: Serialize>(element: &mut Element, attribute_name: &str, value: &T) -> Result<(), SerializationError> {
@@ -208,3 +495,55 @@ pub fn insert_attribute_rc<T: Serialize>(
 
     Ok(())
 }
+
+pub fn insert_opt_attribute_rc<T: Serialize>(
+    element: &Rc<RefCell<Element>>,
+    attribute_name: &str,
+    value: &Option<T>,
+) -> Result<(), SerializationError> {
+    if let Some(value) = value {
+        insert_attribute_rc(element, attribute_name, value)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const XML: &str = "<kit>\n\t<soundSources>\n\t\t<sound />\n\t\t<midiOutput />\n\t\t<sound>\n\t\t\t<osc1 />\n\t\t</sound>\n\t</soundSources>\n</kit>";
+
+    #[test_case("kit", Some((1, 1)))]
+    #[test_case("kit/soundSources", Some((2, 2)))]
+    #[test_case("kit/soundSources/sound", Some((3, 3)))]
+    #[test_case("kit/soundSources/sound[1]", Some((5, 3)))]
+    #[test_case("kit/soundSources/midiOutput[0]", Some((4, 3)))]
+    #[test_case("kit/soundSources/sound[1]/osc1", Some((6, 4)))]
+    #[test_case("kit/soundSources/sound[0]/osc1", None)]
+    #[test_case("kit/soundSources/sound[2]", None)]
+    #[test_case("kit/delay", None)]
+    #[test_case("kit/soundSources/sound[heu]", None)]
+    fn test_find_location(path: &str, expected: Option<(usize, usize)>) {
+        assert_eq!(find_location(XML, path), expected);
+    }
+
+    #[test]
+    fn test_load_xml_same_as_tree_parser() {
+        let xml = include_str!("../data_tests/KITS/KIT057.XML");
+        let expected = Element::parse_all(xml.as_bytes())
+            .unwrap()
+            .into_iter()
+            .filter_map(|node| node.as_element().cloned())
+            .collect::<Vec<_>>();
+
+        assert_eq!(load_xml(xml).unwrap(), expected);
+    }
+
+    #[test_case("<sound><osc1></sound>" ; "mismatched end")]
+    #[test_case("<sound><osc1 />" ; "unclosed element")]
+    fn test_load_xml_invalid(xml: &str) {
+        assert!(matches!(load_xml(xml), Err(SerializationError::XmlReadingFailed(_))));
+    }
+}
@@ -0,0 +1,255 @@
+use std::io::Write;
+
+use xmltree::{Element, XMLNode};
+
+use crate::{serialization::keys, SerializationError};
+
+/// The line ending written in the XML, see [XmlFormat]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Newline {
+    /// "\n", used by the Deluge
+    Lf,
+    /// "\r\n"
+    CrLf,
+}
+
+impl Newline {
+    fn as_str(self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// How the XML of a patch is formatted, see [SerializationOptions::with_xml_format](crate::SerializationOptions::with_xml_format)
+///
+/// Use [XmlFormat::deluge] to write the patches like the device, so a patch loaded then saved
+/// does not add cosmetic changes in a versioned card. The values rounded to the 50 steps displayed by the Deluge
+/// and the parameters the crate doesn't model are still different from the file saved by the device.
+/// ```
+/// # use deluge::{serialize_synth_with_options, SerializationOptions, Synth, XmlFormat};
+/// let options = SerializationOptions::default().with_xml_format(XmlFormat::deluge());
+/// let xml = serialize_synth_with_options(&Synth::default(), &options).unwrap();
+///
+/// assert!(xml.contains("\n\t<osc1\n\t\ttype=\""));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XmlFormat {
+    /// The string written once per level of indentation
+    pub indent: String,
+    pub newline: Newline,
+    /// Write the elements without content as `<name />` instead of `<name></name>`
+    pub self_closing_empty_elements: bool,
+    /// Write each attribute on its own line, except for the elements in [XmlFormat::inline_attributes_elements]
+    pub attributes_on_separate_lines: bool,
+    /// The names of the elements whose attributes are always written on the line of the element
+    pub inline_attributes_elements: Vec<String>,
+    /// Write the empty elements like the firmware: the sample oscillators, the MIDI rows and the CV gate rows
+    /// get a closing tag on its own line, and the rows are followed by an empty line.
+    pub firmware_empty_elements: bool,
+}
+
+impl XmlFormat {
+    /// The format of the files written by the Deluge: tabs, LF and self-closing elements
+    ///
+    /// The firmware writes the attributes on separate lines, except for the few elements it writes on a single line
+    /// like the LFOs, the unison and the mod knobs. This matches the synths and the kits saved by the firmware 3.1.
+    pub fn deluge() -> Self {
+        Self {
+            indent: "\t".to_string(),
+            newline: Newline::Lf,
+            self_closing_empty_elements: true,
+            attributes_on_separate_lines: true,
+            inline_attributes_elements: [
+                keys::LFO1,
+                keys::LFO2,
+                keys::UNISON,
+                keys::MOD_KNOB,
+                keys::MIDI_OUTPUT,
+                keys::GATE_OUTPUT,
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+            firmware_empty_elements: true,
+        }
+    }
+}
+
+/// Write the elements using the format, the XML declaration is written once before the first element
+pub(crate) fn write_xml_to<W: Write>(elements: &[Element], format: &XmlFormat, writer: &mut W) -> Result<(), SerializationError> {
+    let mut writer = FormattedWriter { writer, format };
+
+    writer.write_line(0, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+
+    for element in elements {
+        writer.write_element(element, 0)?;
+    }
+
+    Ok(())
+}
+
+struct FormattedWriter<'a, W: Write> {
+    writer: &'a mut W,
+    format: &'a XmlFormat,
+}
+
+impl<W: Write> FormattedWriter<'_, W> {
+    fn write_line(&mut self, depth: usize, line: &str) -> std::io::Result<()> {
+        self.write_indent(depth)?;
+        self.writer.write_all(line.as_bytes())?;
+        self.writer
+            .write_all(self.format.newline.as_str().as_bytes())
+    }
+
+    fn write_indent(&mut self, depth: usize) -> std::io::Result<()> {
+        for _ in 0..depth {
+            self.writer
+                .write_all(self.format.indent.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    fn write_element(&mut self, element: &Element, depth: usize) -> std::io::Result<()> {
+        let start_tag = self.format_start_tag(element, depth);
+        let texts = element
+            .children
+            .iter()
+            .filter_map(|child| child.as_text())
+            .collect::<Vec<_>>();
+
+        if element.children.is_empty() && self.format.firmware_empty_elements && is_written_open_by_the_firmware(element) {
+            self.write_line(depth, &format!("{}>", start_tag))?;
+            self.write_line(depth, &format!("</{}>", element.name))?;
+
+            match is_kit_output_row(element) {
+                true => self.write_line(0, ""),
+                false => Ok(()),
+            }
+        } else if element.children.is_empty() {
+            match self.format.self_closing_empty_elements {
+                true => self.write_line(depth, &format!("{} />", start_tag)),
+                false => self.write_line(depth, &format!("{}></{}>", start_tag, element.name)),
+            }
+        } else if texts.len() == element.children.len() {
+            let text = escape(&texts.concat(), false);
+
+            self.write_line(depth, &format!("{}>{}</{}>", start_tag, text, element.name))
+        } else {
+            self.write_line(depth, &format!("{}>", start_tag))?;
+
+            for child in &element.children {
+                match child {
+                    XMLNode::Element(child) => self.write_element(child, depth + 1)?,
+                    XMLNode::Text(text) => self.write_line(depth + 1, &escape(text, false))?,
+                    XMLNode::CData(text) => self.write_line(depth + 1, &format!("<![CDATA[{}]]>", text))?,
+                    XMLNode::Comment(text) => self.write_line(depth + 1, &format!("<!--{}-->", text))?,
+                    XMLNode::ProcessingInstruction(..) => {}
+                }
+            }
+
+            self.write_line(depth, &format!("</{}>", element.name))
+        }
+    }
+
+    /// Format the start tag without the closing '>', the attributes on separate lines are indented.
+    fn format_start_tag(&self, element: &Element, depth: usize) -> String {
+        let mut tag = format!("<{}", element.name);
+        let on_separate_lines = self.format.attributes_on_separate_lines
+            && !self
+                .format
+                .inline_attributes_elements
+                .contains(&element.name);
+
+        for (name, value) in element.attributes.iter() {
+            match on_separate_lines {
+                true => {
+                    tag.push_str(self.format.newline.as_str());
+                    tag.push_str(&self.format.indent.repeat(depth + 1));
+                }
+                false => tag.push(' '),
+            }
+
+            tag.push_str(&format!("{}=\"{}\"", name, escape(value, true)));
+        }
+
+        tag
+    }
+}
+
+/// The firmware opens the elements that can have children even if it writes none
+fn is_written_open_by_the_firmware(element: &Element) -> bool {
+    let is_sample_oscillator = (element.name == keys::OSC1 || element.name == keys::OSC2)
+        && element
+            .attributes
+            .get(keys::TYPE)
+            .is_some_and(|osc_type| osc_type == keys::SAMPLE_OSC_TYPE);
+
+    is_sample_oscillator || is_kit_output_row(element)
+}
+
+fn is_kit_output_row(element: &Element) -> bool {
+    element.name == keys::MIDI_OUTPUT || element.name == keys::GATE_OUTPUT
+}
+
+fn escape(text: &str, is_attribute: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if is_attribute => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialization::xml;
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    fn write_xml(elements: &[Element], format: &XmlFormat) -> String {
+        let mut buffer = Vec::new();
+
+        write_xml_to(elements, format, &mut buffer).unwrap();
+
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test_case(include_str!("../data_tests/SYNTHS/SYNT184.XML") ; "synth")]
+    #[test_case(include_str!("../data_tests/KITS/KIT057.XML") ; "kit")]
+    #[test_case(include_str!("../../tests/data_tests/KITS/KIT049.XML") ; "kit with an empty sample oscillator")]
+    #[test_case(include_str!("../data_tests/KITS/KIT_TEST_SOUNDS_MIDI_GATE.XML") ; "kit with MIDI and gate rows")]
+    fn test_deluge_format_is_identical_to_the_device(original: &str) {
+        let roots = xml::load_xml(original).unwrap();
+
+        assert_eq!(write_xml(&roots, &XmlFormat::deluge()), original);
+    }
+
+    #[test]
+    fn test_custom_format() {
+        let roots = xml::load_xml(r#"<a x="1"><b y="&quot;2&quot;" z="3"/><c>text &amp; more</c></a>"#).unwrap();
+        let format = XmlFormat {
+            indent: "  ".to_string(),
+            newline: Newline::CrLf,
+            self_closing_empty_elements: false,
+            attributes_on_separate_lines: false,
+            inline_attributes_elements: Vec::new(),
+            firmware_empty_elements: false,
+        };
+
+        assert_eq!(
+            write_xml(&roots, &format),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\r\n<a x=\"1\">\r\n  <b y=\"&quot;2&quot;\" z=\"3\"></b>\r\n  <c>text &amp; more</c>\r\n</a>\r\n"
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use xmltree::Element;
+
+use crate::{
+    serialization::{get_opt_attribute, keep_element_only, keys, load_root_version_info, load_sound_v3, load_xml},
+    FormatVersion, SerializationError, Sound, Synth,
+};
+
+/// Extract the sounds of a song
+///
+/// The sounds are the synths of the song and the sound rows of its kits, in the order of the file.
+/// Each sound comes with its name if the song specifies one: the preset name for a synth, the row name for a kit row.
+/// Only the songs saved with the format version 3 or by the community firmware are supported.
+///
+/// The song of the example and the tests is written for the tests, the extraction is not yet checked with a song
+/// saved by the device.
+/// ```
+/// # use deluge::{extract_sounds, Synth};
+/// let xml = include_str!("data_tests/SONGS/Test Assembled Song.XML");
+/// let sounds = extract_sounds(xml).unwrap();
+///
+/// assert_eq!(sounds[0].0.as_deref(), Some("Square Lead"));
+///
+/// let synth = Synth::from_extracted_sound(sounds[0].1.clone());
+/// ```
+pub fn extract_sounds(xml: &str) -> Result<Vec<(Option<String>, Sound)>, SerializationError> {
+    let roots = load_xml(xml)?;
+    let version_info = load_root_version_info(&roots, keys::SONG);
+
+    match version_info.format_version {
+        FormatVersion::Version3 | FormatVersion::Community => {}
+        format_version => return Err(SerializationError::UnsupportedVersion(format_version)),
+    }
+
+    let song_node = roots
+        .iter()
+        .find(|root| root.name == keys::SONG)
+        .ok_or_else(|| SerializationError::MissingElement(keys::SONG.to_string()))?;
+    let mut sounds = Vec::new();
+
+    find_sounds(song_node, keys::SONG, &mut sounds).map_err(|error| error.with_location(xml))?;
+
+    Ok(sounds)
+}
+
+/// Load the sounds found under a node, the path of the node is used to annotate the errors
+fn find_sounds(node: &Element, path: &str, sounds: &mut Vec<(Option<String>, Sound)>) -> Result<(), SerializationError> {
+    let mut indices: HashMap<&str, usize> = HashMap::new();
+
+    for child in node
+        .children
+        .iter()
+        .filter_map(keep_element_only)
+    {
+        let index = indices
+            .entry(child.name.as_str())
+            .or_default();
+        let child_path = format!("{}/{}[{}]", path, child.name, index);
+
+        *index += 1;
+
+        if child.name == keys::SOUND {
+            let sound = load_sound_v3(child).map_err(|error| error.with_path(child_path))?;
+
+            sounds.push((load_sound_name(child), sound));
+        } else {
+            find_sounds(child, &child_path, sounds)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn load_sound_name(node: &Element) -> Option<String> {
+    get_opt_attribute(node, keys::PRESET_NAME)
+        .or_else(|| get_opt_attribute(node, keys::NAME))
+        .cloned()
+}
+
+impl Synth {
+    /// Create a synth from a sound extracted from a song, see [extract_sounds]
+    pub fn from_extracted_sound(sound: Sound) -> Synth {
+        Synth::new(sound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deserialize_kit, deserialize_synth, serialize_synth, values::HexU50};
+    use pretty_assertions::assert_eq;
+
+    // Not a song saved by the device: the synth is written for the tests and the kit rows are the ones of KIT057
+    const SONG: &str = include_str!("data_tests/SONGS/Test Assembled Song.XML");
+
+    #[test]
+    fn test_extract_sounds() {
+        let sounds = extract_sounds(SONG).unwrap();
+        let names: Vec<Option<&str>> = sounds
+            .iter()
+            .map(|(name, _)| name.as_deref())
+            .collect();
+        let kit = deserialize_kit(include_str!("data_tests/KITS/KIT057.XML")).unwrap();
+
+        assert_eq!(sounds.len(), 3);
+        assert_eq!(
+            names,
+            vec![Some("Square Lead"), Some("halftime_goodie"), Some("halftime_goodie2")]
+        );
+        assert_eq!(
+            sounds[0]
+                .1
+                .generator
+                .as_subtractive()
+                .unwrap()
+                .lpf_frequency,
+            HexU50::parse("0x40000000").unwrap()
+        );
+        assert_eq!(
+            &sounds[1].1,
+            kit.rows[0]
+                .as_sound()
+                .unwrap()
+                .sound
+                .as_ref()
+        );
+        assert_eq!(
+            &sounds[2].1,
+            kit.rows[1]
+                .as_sound()
+                .unwrap()
+                .sound
+                .as_ref()
+        );
+    }
+
+    #[test]
+    fn test_synth_from_extracted_sound() {
+        let (_, sound) = extract_sounds(SONG).unwrap().remove(0);
+        let synth = Synth::from_extracted_sound(sound);
+
+        assert_eq!(deserialize_synth(&serialize_synth(&synth).unwrap()).unwrap(), synth);
+    }
+
+    #[test]
+    fn test_extract_sounds_error_location() {
+        let xml = SONG.replacen("polyphonic=\"auto\"", "polyphonic=\"heu\"", 1);
+        let error = extract_sounds(&xml).unwrap_err();
+
+        assert_eq!(error.path(), Some("song/instruments[0]/kit[0]/soundSources[0]/sound[0]"));
+        assert_eq!(error.location(), Some((156, 5)));
+    }
+
+    #[test]
+    fn test_extract_sounds_unsupported_version() {
+        let xml = SONG.replace(
+            "earliestCompatibleFirmware=\"3.1.0-beta\"",
+            "earliestCompatibleFirmware=\"2.0.0\"",
+        );
+
+        assert!(matches!(
+            extract_sounds(&xml),
+            Err(SerializationError::UnsupportedVersion(FormatVersion::Version2))
+        ));
+    }
+}
@@ -1,9 +1,13 @@
 use enum_as_inner::EnumAsInner;
 
-use crate::values::{AttackSidechain, ClippingAmount, HexU50, OnOff, ReleaseSidechain, SyncLevel, TableIndex};
+use crate::{
+    range_check::check_ranges,
+    values::{AttackSidechain, ClippingAmount, HexU50, ModulationFxType, OnOff, ReleaseSidechain, SyncLevel, TableIndex},
+};
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, setter(into), build_fn(validate = "Self::check_ranges"))]
 pub struct Delay {
     pub ping_pong: OnOff,
     pub analog: OnOff,
@@ -12,6 +16,44 @@ pub struct Delay {
     pub sync_level: SyncLevel,
 }
 
+check_ranges!(
+    Delay,
+    DelayBuilder {
+        ping_pong,
+        analog,
+        amount,
+        rate,
+        sync_level
+    }
+);
+
+impl Delay {
+    /// Create a delay synchronized to the tempo
+    /// ```
+    /// # use deluge::{Delay, HexU50, SyncLevel};
+    /// let delay = Delay::synced(SyncLevel::Eighth, 20);
+    ///
+    /// assert_eq!(delay.amount, HexU50::new(20));
+    /// ```
+    pub fn synced(sync_level: SyncLevel, amount: impl Into<HexU50>) -> Self {
+        Self {
+            amount: amount.into(),
+            sync_level,
+            ..Default::default()
+        }
+    }
+
+    /// Create a delay not synchronized to the tempo, its time is set by the rate
+    pub fn with_rate(rate: impl Into<HexU50>, amount: impl Into<HexU50>) -> Self {
+        Self {
+            amount: amount.into(),
+            rate: rate.into(),
+            sync_level: SyncLevel::Off,
+            ..Default::default()
+        }
+    }
+}
+
 impl Default for Delay {
     fn default() -> Self {
         Self {
@@ -25,25 +67,54 @@ impl Default for Delay {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct Distorsion {
     pub bit_crush: HexU50,
     pub saturation: ClippingAmount,
     pub decimation: HexU50,
 }
 
+check_ranges!(
+    Distorsion,
+    DistorsionBuilder {
+        bit_crush,
+        saturation,
+        decimation
+    }
+);
+
+impl Distorsion {
+    /// A distorsion only saturating the sound
+    /// ```
+    /// # use deluge::{ClippingAmount, Distorsion};
+    /// let distorsion = Distorsion::with_saturation(ClippingAmount::new(4));
+    ///
+    /// assert_eq!(distorsion.bit_crush, Distorsion::default().bit_crush);
+    /// ```
+    pub fn with_saturation(saturation: ClippingAmount) -> Self {
+        Self {
+            saturation,
+            ..Self::default()
+        }
+    }
+}
+
 impl Default for Distorsion {
+    /// The distorsion of the patches created by the device: bitCrush and sampleRateReduction are stored
+    /// as 0x80000000 which is HexU50(0), the midpoint HexU50(25) is never used by the firmware.
     fn default() -> Self {
         Self {
             bit_crush: 0.into(),
-            saturation: 0.into(),
+            saturation: ClippingAmount::none(),
             decimation: 0.into(),
         }
     }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct Equalizer {
     /// The default must be HexU50(25)!
     /// About 25 the basses are increased, below they are decreased
@@ -57,6 +128,16 @@ pub struct Equalizer {
     pub treble_frequency: HexU50,
 }
 
+check_ranges!(
+    Equalizer,
+    EqualizerBuilder {
+        bass_level,
+        bass_frequency,
+        treble_level,
+        treble_frequency
+    }
+);
+
 impl Default for Equalizer {
     fn default() -> Self {
         Self {
@@ -69,6 +150,8 @@ impl Default for Equalizer {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, EnumAsInner)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
 pub enum ModulationFx {
     Off,
     Flanger(Flanger),
@@ -76,13 +159,28 @@ pub enum ModulationFx {
     Phaser(Phaser),
 }
 
+impl ModulationFx {
+    /// Gets the type of the modulation FX, as selected in the menu "MOD-FX TYPE"
+    pub fn fx_type(&self) -> ModulationFxType {
+        match self {
+            ModulationFx::Off => ModulationFxType::Off,
+            ModulationFx::Flanger(_) => ModulationFxType::Flanger,
+            ModulationFx::Chorus(_) => ModulationFxType::Chorus,
+            ModulationFx::Phaser(_) => ModulationFxType::Phaser,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct Flanger {
     pub rate: HexU50,
     pub feedback: HexU50,
 }
 
+check_ranges!(Flanger, FlangerBuilder { rate, feedback });
+
 impl Default for Flanger {
     fn default() -> Self {
         Self {
@@ -93,25 +191,35 @@ impl Default for Flanger {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(build_fn(validate = "Self::check_ranges"))]
 pub struct Chorus {
     pub rate: HexU50,
     pub depth: HexU50,
     pub offset: HexU50,
 }
 
+check_ranges!(Chorus, ChorusBuilder { rate, depth, offset });
+
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(build_fn(validate = "Self::check_ranges"))]
 pub struct Phaser {
     pub rate: HexU50,
     pub depth: HexU50,
     pub feedback: HexU50,
 }
 
+check_ranges!(Phaser, PhaserBuilder { rate, depth, feedback });
+
 /// Sidechain
 ///
 /// Notice the "compressor" (the sidechain affecting the volume) is serialized
 /// as a specific patch cable. When you edit the value accessible using the shortcut Row+Volduck this
-/// is the amount of a patch cable.
+/// is the amount of a patch cable, see [Sound::sidechain_level](crate::Sound::sidechain_level).
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(build_fn(validate = "Self::check_ranges"))]
 pub struct Sidechain {
     pub attack: AttackSidechain,
     pub release: ReleaseSidechain,
@@ -119,6 +227,16 @@ pub struct Sidechain {
     pub sync: SyncLevel,
 }
 
+check_ranges!(
+    Sidechain,
+    SidechainBuilder {
+        attack,
+        release,
+        shape,
+        sync
+    }
+);
+
 impl Default for Sidechain {
     fn default() -> Self {
         Self {
@@ -0,0 +1,353 @@
+//! Convert the engine of a sound while keeping its oscillators.
+//!
+//! The transpose, the fine transpose and the retrig phase of the oscillators are kept by each conversion.
+//! The parameters without equivalent in the destination engine are lost, the missing ones get their default values.
+use crate::{
+    values::{OscType, SynthMode},
+    FmCarrier, FmSynth, OscillatorSlot, RingModSynth, Sound, SubtractiveOscillator, SubtractiveSynth, SynthEngine,
+    WaveformOscillator,
+};
+
+use super::ConversionError;
+
+impl TryFrom<&SubtractiveSynth> for RingModSynth {
+    type Error = ConversionError;
+
+    /// Use the waveform oscillators of a subtractive synth, fails if an oscillator plays a sample
+    ///
+    /// The sync of the oscillator 2 and the noise are kept.
+    /// The volumes of the oscillators and the filters have no equivalent in a ring mod synth.
+    fn try_from(synth: &SubtractiveSynth) -> Result<Self, Self::Error> {
+        let (osc1, osc2) = get_waveforms(synth, SynthMode::RingMod)?;
+
+        Ok(Self {
+            osc2_sync: synth.osc2_sync,
+            noise: synth.noise,
+            ..Self::new(osc1.clone(), osc2.clone())
+        })
+    }
+}
+
+impl From<&RingModSynth> for SubtractiveSynth {
+    /// Use the oscillators of a ring mod synth
+    ///
+    /// The sync of the oscillator 2 and the noise are kept. Both oscillators are played at their maximum
+    /// volume like in the ring mod synth, the filters get their default values.
+    fn from(synth: &RingModSynth) -> Self {
+        Self {
+            osc2_sync: synth.osc2_sync,
+            noise: synth.noise,
+            osc1_volume: 50.into(),
+            osc2_volume: 50.into(),
+            ..Self::new(synth.osc1.clone().into(), synth.osc2.clone().into())
+        }
+    }
+}
+
+impl FmSynth {
+    /// Create a FM synth whose carriers are tuned like two waveform oscillators
+    ///
+    /// The carriers of a FM synth are sine waves, the type and the pulse width of the oscillators have no equivalent.
+    /// The feedbacks, the modulators and the volumes get their default values.
+    pub fn from_waveforms(osc1: &WaveformOscillator, osc2: &WaveformOscillator) -> Self {
+        Self {
+            osc1: waveform_to_carrier(osc1),
+            osc2: waveform_to_carrier(osc2),
+            ..Self::default()
+        }
+    }
+}
+
+impl Sound {
+    /// Convert the engine of the sound, the sound is not changed if the conversion fails
+    ///
+    /// The oscillators keep their tuning, see [RingModSynth::try_from], [SubtractiveSynth::from] and [FmSynth::from_waveforms].
+    /// The carriers of a FM synth become sine oscillators. A subtractive synth converted to FM keeps its oscillator volumes.
+    /// ```
+    /// # use deluge::{SamplePath, Sound, SynthMode};
+    /// let mut sound = Sound::default();
+    ///
+    /// sound.convert_engine(SynthMode::RingMod).unwrap();
+    ///
+    /// assert!(sound.generator.as_ring_mod().is_some());
+    /// assert!(Sound::new_sample(SamplePath::new("SAMPLES/KICK.WAV").unwrap(), 0u64.into(), 100u64.into())
+    ///     .convert_engine(SynthMode::Fm)
+    ///     .is_err());
+    /// ```
+    pub fn convert_engine(&mut self, mode: SynthMode) -> Result<(), ConversionError> {
+        let generator: SynthEngine = match (&self.generator, &mode) {
+            (_, SynthMode::Off) => return Err(ConversionError::UnsupportedEngine(mode.clone())),
+            (SynthEngine::Subtractive(_), SynthMode::Subtractive)
+            | (SynthEngine::RingMod(_), SynthMode::RingMod)
+            | (SynthEngine::Fm(_), SynthMode::Fm) => return Ok(()),
+            (SynthEngine::Subtractive(synth), SynthMode::RingMod) => RingModSynth::try_from(synth)?.into(),
+            (SynthEngine::Subtractive(synth), SynthMode::Fm) => {
+                let (osc1, osc2) = get_waveforms(synth, SynthMode::Fm)?;
+
+                FmSynth {
+                    osc1_volume: synth.osc1_volume,
+                    osc2_volume: synth.osc2_volume,
+                    ..FmSynth::from_waveforms(osc1, osc2)
+                }
+                .into()
+            }
+            (SynthEngine::RingMod(synth), SynthMode::Subtractive) => SubtractiveSynth::from(synth).into(),
+            (SynthEngine::RingMod(synth), SynthMode::Fm) => FmSynth::from_waveforms(&synth.osc1, &synth.osc2).into(),
+            (SynthEngine::Fm(synth), SynthMode::Subtractive) => SubtractiveSynth {
+                osc1_volume: synth.osc1_volume,
+                osc2_volume: synth.osc2_volume,
+                ..SubtractiveSynth::new(
+                    carrier_to_waveform(&synth.osc1).into(),
+                    carrier_to_waveform(&synth.osc2).into(),
+                )
+            }
+            .into(),
+            (SynthEngine::Fm(synth), SynthMode::RingMod) => {
+                RingModSynth::new(carrier_to_waveform(&synth.osc1), carrier_to_waveform(&synth.osc2)).into()
+            }
+        };
+
+        self.generator = generator;
+
+        Ok(())
+    }
+}
+
+/// Get the waveform oscillators of a subtractive synth, fails if an oscillator plays a sample
+fn get_waveforms(
+    synth: &SubtractiveSynth,
+    mode: SynthMode,
+) -> Result<(&WaveformOscillator, &WaveformOscillator), ConversionError> {
+    Ok((
+        get_waveform(&synth.osc1, OscillatorSlot::Osc1, &mode)?,
+        get_waveform(&synth.osc2, OscillatorSlot::Osc2, &mode)?,
+    ))
+}
+
+fn get_waveform<'a>(
+    oscillator: &'a SubtractiveOscillator,
+    slot: OscillatorSlot,
+    mode: &SynthMode,
+) -> Result<&'a WaveformOscillator, ConversionError> {
+    match oscillator {
+        SubtractiveOscillator::Waveform(waveform) => Ok(waveform),
+        SubtractiveOscillator::Sample(_) => Err(ConversionError::SampleOscillator(slot, mode.clone())),
+    }
+}
+
+fn waveform_to_carrier(oscillator: &WaveformOscillator) -> FmCarrier {
+    FmCarrier {
+        transpose: oscillator.transpose,
+        fine_transpose: oscillator.fine_transpose,
+        retrig_phase: oscillator.retrig_phase,
+        ..FmCarrier::default()
+    }
+}
+
+fn carrier_to_waveform(carrier: &FmCarrier) -> WaveformOscillator {
+    WaveformOscillator {
+        osc_type: OscType::Sine,
+        transpose: carrier.transpose,
+        fine_transpose: carrier.fine_transpose,
+        retrig_phase: carrier.retrig_phase,
+        ..WaveformOscillator::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::values::{FineTranspose, HexU50, OnOff, RetrigPhase, SamplePath, Transpose};
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    fn tuned_waveform(osc_type: OscType, transpose: i8, fine_transpose: i8) -> WaveformOscillator {
+        WaveformOscillator {
+            osc_type,
+            transpose: Transpose::new(transpose),
+            fine_transpose: FineTranspose::new(fine_transpose),
+            retrig_phase: RetrigPhase::new(90),
+            pulse_width: 10.into(),
+        }
+    }
+
+    fn tuned_subtractive_synth() -> SubtractiveSynth {
+        SubtractiveSynth {
+            osc2_sync: OnOff::On,
+            noise: 12.into(),
+            osc2_volume: 30.into(),
+            lpf_frequency: 20.into(),
+            ..SubtractiveSynth::new(
+                tuned_waveform(OscType::Saw, 12, -5).into(),
+                tuned_waveform(OscType::Triangle, -7, 20).into(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_subtractive_to_ring_mod() {
+        let synth = RingModSynth::try_from(&tuned_subtractive_synth()).unwrap();
+
+        assert_eq!(
+            synth,
+            RingModSynth {
+                osc1: tuned_waveform(OscType::Saw, 12, -5),
+                osc2: tuned_waveform(OscType::Triangle, -7, 20),
+                osc2_sync: OnOff::On,
+                noise: 12.into(),
+            }
+        );
+    }
+
+    #[test_case(OscillatorSlot::Osc1 ; "oscillator 1")]
+    #[test_case(OscillatorSlot::Osc2 ; "oscillator 2")]
+    fn test_subtractive_with_sample_to_ring_mod(slot: OscillatorSlot) {
+        let mut synth = tuned_subtractive_synth();
+        let oscillator = match slot {
+            OscillatorSlot::Osc1 => &mut synth.osc1,
+            OscillatorSlot::Osc2 => &mut synth.osc2,
+        };
+
+        *oscillator = SubtractiveOscillator::new_sample(Default::default());
+
+        assert_eq!(
+            RingModSynth::try_from(&synth),
+            Err(ConversionError::SampleOscillator(slot, SynthMode::RingMod))
+        );
+    }
+
+    #[test]
+    fn test_ring_mod_to_subtractive() {
+        let ring_mod_synth = RingModSynth::try_from(&tuned_subtractive_synth()).unwrap();
+        let synth = SubtractiveSynth::from(&ring_mod_synth);
+
+        assert_eq!(
+            synth,
+            SubtractiveSynth {
+                osc1_volume: 50.into(),
+                osc2_volume: 50.into(),
+                lpf_frequency: SubtractiveSynth::default().lpf_frequency,
+                ..tuned_subtractive_synth()
+            }
+        );
+        assert_eq!(RingModSynth::try_from(&synth).unwrap(), ring_mod_synth);
+    }
+
+    #[test]
+    fn test_fm_from_waveforms() {
+        let synth = FmSynth::from_waveforms(
+            &tuned_waveform(OscType::Saw, 12, -5),
+            &tuned_waveform(OscType::Square, -7, 20),
+        );
+
+        assert_eq!(synth.osc1.transpose, Transpose::new(12));
+        assert_eq!(synth.osc1.fine_transpose, FineTranspose::new(-5));
+        assert_eq!(synth.osc1.retrig_phase, RetrigPhase::new(90));
+        assert_eq!(synth.osc2.transpose, Transpose::new(-7));
+        assert_eq!(synth.osc2.fine_transpose, FineTranspose::new(20));
+        assert_eq!(
+            FmSynth {
+                osc1: FmCarrier::default(),
+                osc2: FmCarrier::default(),
+                ..synth
+            },
+            FmSynth::default()
+        );
+    }
+
+    #[test]
+    fn test_convert_subtractive_to_fm_and_back() {
+        let mut sound = Sound {
+            generator: tuned_subtractive_synth().into(),
+            ..Sound::default()
+        };
+
+        sound
+            .convert_engine(SynthMode::Fm)
+            .unwrap();
+
+        let fm_synth = sound.generator.as_fm().unwrap();
+
+        assert_eq!(fm_synth.osc1.transpose, Transpose::new(12));
+        assert_eq!(fm_synth.osc2.fine_transpose, FineTranspose::new(20));
+        assert_eq!(fm_synth.osc2_volume, HexU50::new(30));
+
+        sound
+            .convert_engine(SynthMode::Subtractive)
+            .unwrap();
+
+        let synth = sound
+            .generator
+            .as_subtractive()
+            .unwrap();
+
+        assert_eq!(
+            synth.osc1,
+            SubtractiveOscillator::from(carrier_to_waveform(&fm_synth_carrier(12, -5)))
+        );
+        assert_eq!(synth.osc2.transpose(), Transpose::new(-7));
+        assert_eq!(synth.osc2.osc_type(), OscType::Sine);
+        assert_eq!(synth.osc2_volume, HexU50::new(30));
+    }
+
+    fn fm_synth_carrier(transpose: i8, fine_transpose: i8) -> FmCarrier {
+        waveform_to_carrier(&tuned_waveform(OscType::Sine, transpose, fine_transpose))
+    }
+
+    #[test]
+    fn test_convert_ring_mod_to_fm_and_back() {
+        let mut sound = Sound {
+            generator: RingModSynth::try_from(&tuned_subtractive_synth())
+                .unwrap()
+                .into(),
+            ..Sound::default()
+        };
+
+        sound
+            .convert_engine(SynthMode::Fm)
+            .unwrap();
+
+        assert_eq!(sound.generator.as_fm().unwrap().osc1, fm_synth_carrier(12, -5));
+
+        sound
+            .convert_engine(SynthMode::RingMod)
+            .unwrap();
+
+        let synth = sound.generator.as_ring_mod().unwrap();
+
+        assert_eq!(synth.osc1, carrier_to_waveform(&fm_synth_carrier(12, -5)));
+        assert_eq!(synth.osc2.transpose, Transpose::new(-7));
+        assert_eq!(synth.osc2_sync, OnOff::Off);
+    }
+
+    #[test]
+    fn test_convert_to_the_same_engine() {
+        let mut sound = Sound {
+            generator: tuned_subtractive_synth().into(),
+            ..Sound::default()
+        };
+        let expected_sound = sound.clone();
+
+        sound
+            .convert_engine(SynthMode::Subtractive)
+            .unwrap();
+
+        assert_eq!(sound, expected_sound);
+    }
+
+    #[test]
+    fn test_convert_failure_keeps_the_sound() {
+        let mut sound = Sound::new_sample(SamplePath::new("SAMPLES/KICK.WAV").unwrap(), 0u64.into(), 100u64.into());
+        let expected_sound = sound.clone();
+
+        assert_eq!(
+            sound.convert_engine(SynthMode::Fm),
+            Err(ConversionError::SampleOscillator(OscillatorSlot::Osc1, SynthMode::Fm))
+        );
+        assert_eq!(
+            sound.convert_engine(SynthMode::Off),
+            Err(ConversionError::UnsupportedEngine(SynthMode::Off))
+        );
+        assert_eq!(sound, expected_sound);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{
+    values::{SynthMode, Transpose},
+    OscillatorSlot, SamplePosition,
+};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum SampleZoneError {
+    #[error("the end {end:?} is before the start {start:?}")]
+    EndBeforeStart { start: SamplePosition, end: SamplePosition },
+
+    #[error("the loop start {0:?} is outside of the zone")]
+    LoopStartOutOfZone(SamplePosition),
+
+    #[error("the loop end {0:?} is outside of the zone")]
+    LoopEndOutOfZone(SamplePosition),
+
+    #[error("the loop end {end:?} is before the loop start {start:?}")]
+    LoopEndBeforeLoopStart { start: SamplePosition, end: SamplePosition },
+
+    #[error("the loop fractions must be in the range [0; 1]")]
+    LoopFractionOutOfRange,
+}
+
+/// An issue in the ranges of a multisample, see [Sample::validate_ranges](crate::Sample::validate_ranges)
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum RangeIssue {
+    #[error("the multisample has no range")]
+    NoRange,
+
+    #[error("the ranges {previous_index} and {index} have the same top note {top_note}")]
+    DuplicateTopNote {
+        index: usize,
+        previous_index: usize,
+        top_note: u8,
+    },
+
+    #[error("the top note {top_note} of the range {index} is lower than the previous top note {previous_top_note}")]
+    DescendingTopNote {
+        index: usize,
+        top_note: u8,
+        previous_top_note: u8,
+    },
+
+    #[error("the range {0} has no top note but it's not the last range")]
+    MissingTopNote(usize),
+
+    #[error("the notes above {0} are not covered by any range")]
+    UncoveredNotes(u8),
+}
+
+/// The errors returned by [MultisampleBuilder::build](crate::MultisampleBuilder::build)
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum MultisampleError {
+    #[error("the multisample has no sample")]
+    NoSample,
+
+    #[error("the root note {0} is not a valid MIDI note")]
+    InvalidRootNote(u8),
+
+    #[error("the root note {root_note} of the sample {index} is not above the previous root note {previous_root_note}")]
+    UnorderedRootNotes {
+        index: usize,
+        root_note: u8,
+        previous_root_note: u8,
+    },
+}
+
+/// The error returned by [Sound::transpose_by](crate::Sound::transpose_by)
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("transposing {transpose} by {semitones} semitones leaves the range [-96; 96]")]
+pub struct TransposeError {
+    pub transpose: Transpose,
+    pub semitones: i8,
+}
+
+/// The error returned when an engine does not support an operation, see [SynthEngine::set_osc_volume](crate::SynthEngine::set_osc_volume)
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum EngineError {
+    #[error("the {0} engine has no oscillator volume")]
+    NoOscillatorVolume(SynthMode),
+}
+
+/// The error returned when the engine of a sound can't be converted, see [Sound::convert_engine](crate::Sound::convert_engine)
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    #[error("the oscillator {0:?} plays a sample, the {1} engine has no sample oscillator")]
+    SampleOscillator(OscillatorSlot, SynthMode),
+
+    #[error("a sound can't use the engine {0}")]
+    UnsupportedEngine(SynthMode),
+}
+
+/// The error returned by [FmCarrier::set_ratio](crate::FmCarrier::set_ratio) and [FmModulator::set_ratio](crate::FmModulator::set_ratio)
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum RatioError {
+    #[error("the ratio {0} is not a positive number")]
+    NotPositive(f64),
+
+    #[error("the ratio {0} is out of the range of the transpose [-96; 96] semitones")]
+    OutOfRange(f64),
+}
@@ -1,7 +1,12 @@
-use crate::values::{FineTranspose, HexU50, OnOff, RetrigPhase, Transpose};
+use crate::{
+    range_check::check_ranges,
+    values::{FineTranspose, HexU50, OnOff, RetrigPhase, Transpose},
+    RatioError,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct FmSynth {
     pub osc1: FmCarrier,
     pub osc2: FmCarrier,
@@ -14,6 +19,19 @@ pub struct FmSynth {
     pub modulator2_to_modulator1: OnOff,
 }
 
+check_ranges!(
+    FmSynth,
+    FmSynthBuilder {
+        osc1,
+        osc2,
+        modulator1,
+        modulator2,
+        osc1_volume,
+        osc2_volume,
+        modulator2_to_modulator1
+    }
+);
+
 impl FmSynth {
     pub fn new(osc1: FmCarrier, osc2: FmCarrier) -> Self {
         Self {
@@ -43,7 +61,8 @@ impl Default for FmSynth {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct FmCarrier {
     pub transpose: Transpose,
     pub fine_transpose: FineTranspose,
@@ -51,6 +70,30 @@ pub struct FmCarrier {
     pub feedback: HexU50,
 }
 
+check_ranges!(
+    FmCarrier,
+    FmCarrierBuilder {
+        transpose,
+        fine_transpose,
+        retrig_phase,
+        feedback
+    }
+);
+
+impl FmCarrier {
+    /// Gets the frequency ratio of the carrier, computed from the transpose and the fine transpose
+    pub fn ratio(&self) -> f64 {
+        transposes_to_ratio(self.transpose, self.fine_transpose)
+    }
+
+    /// Sets the transpose and the fine transpose from a frequency ratio, the ratio is rounded to the nearest cent
+    pub fn set_ratio(&mut self, ratio: f64) -> Result<(), RatioError> {
+        (self.transpose, self.fine_transpose) = ratio_to_transposes(ratio)?;
+
+        Ok(())
+    }
+}
+
 impl Default for FmCarrier {
     fn default() -> Self {
         Self {
@@ -63,7 +106,8 @@ impl Default for FmCarrier {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct FmModulator {
     pub transpose: Transpose,
     pub fine_transpose: FineTranspose,
@@ -72,6 +116,40 @@ pub struct FmModulator {
     pub feedback: HexU50,
 }
 
+check_ranges!(
+    FmModulator,
+    FmModulatorBuilder {
+        transpose,
+        fine_transpose,
+        retrig_phase,
+        amount,
+        feedback
+    }
+);
+
+impl FmModulator {
+    /// Gets the frequency ratio of the modulator, computed from the transpose and the fine transpose
+    /// ```
+    /// # use deluge::FmModulator;
+    /// let mut modulator = FmModulator::default();
+    ///
+    /// modulator.set_ratio(2.0).unwrap();
+    ///
+    /// assert_eq!(modulator.transpose.as_i8(), 12);
+    /// assert_eq!(modulator.ratio(), 2.0);
+    /// ```
+    pub fn ratio(&self) -> f64 {
+        transposes_to_ratio(self.transpose, self.fine_transpose)
+    }
+
+    /// Sets the transpose and the fine transpose from a frequency ratio, the ratio is rounded to the nearest cent
+    pub fn set_ratio(&mut self, ratio: f64) -> Result<(), RatioError> {
+        (self.transpose, self.fine_transpose) = ratio_to_transposes(ratio)?;
+
+        Ok(())
+    }
+}
+
 impl Default for FmModulator {
     fn default() -> Self {
         Self {
@@ -83,3 +161,79 @@ impl Default for FmModulator {
         }
     }
 }
+
+fn transposes_to_ratio(transpose: Transpose, fine_transpose: FineTranspose) -> f64 {
+    let cents = f64::from(transpose.as_i8()) * 100.0 + f64::from(fine_transpose.as_i8());
+
+    (cents / 1200.0).exp2()
+}
+
+/// Converts a frequency ratio to a transpose in semitones and a fine transpose in cents
+///
+/// The fine transpose is kept in [-50; 50] except at the limits of the transpose.
+fn ratio_to_transposes(ratio: f64) -> Result<(Transpose, FineTranspose), RatioError> {
+    if !ratio.is_finite() || ratio <= 0.0 {
+        return Err(RatioError::NotPositive(ratio));
+    }
+
+    let cents = (ratio.log2() * 1200.0).round();
+    let semitones = (cents / 100.0)
+        .round()
+        .clamp(f64::from(Transpose::MIN), f64::from(Transpose::MAX));
+    let fine_cents = cents - semitones * 100.0;
+
+    if !(f64::from(FineTranspose::MIN)..=f64::from(FineTranspose::MAX)).contains(&fine_cents) {
+        return Err(RatioError::OutOfRange(ratio));
+    }
+
+    Ok((Transpose::new(semitones as i8), FineTranspose::new(fine_cents as i8)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    #[test_case(0.5, -12, 0)]
+    #[test_case(1.0, 0, 0)]
+    #[test_case(2.0, 12, 0)]
+    #[test_case(3.0, 19, 2)]
+    #[test_case(7.0, 34, -31)]
+    fn test_set_ratio(ratio: f64, transpose: i8, fine_transpose: i8) {
+        let mut modulator = FmModulator::default();
+        let mut carrier = FmCarrier::default();
+
+        modulator.set_ratio(ratio).unwrap();
+        carrier.set_ratio(ratio).unwrap();
+
+        assert_eq!(modulator.transpose, Transpose::new(transpose));
+        assert_eq!(modulator.fine_transpose, FineTranspose::new(fine_transpose));
+        assert_eq!(carrier.transpose, modulator.transpose);
+        assert_eq!(carrier.fine_transpose, modulator.fine_transpose);
+        assert!((modulator.ratio() - ratio).abs() / ratio < 0.0003);
+    }
+
+    #[test_case(1.234)]
+    #[test_case(0.01)]
+    #[test_case(150.0)]
+    fn test_set_ratio_odd_ratios(ratio: f64) {
+        let mut modulator = FmModulator::default();
+
+        modulator.set_ratio(ratio).unwrap();
+
+        // Rounding to the nearest cent changes the ratio by 0.03% at most
+        assert!((modulator.ratio() - ratio).abs() / ratio < 0.0003);
+    }
+
+    #[test_case(0.0, RatioError::NotPositive(0.0))]
+    #[test_case(-2.0, RatioError::NotPositive(-2.0))]
+    #[test_case(400.0, RatioError::OutOfRange(400.0))]
+    #[test_case(0.002, RatioError::OutOfRange(0.002))]
+    fn test_set_ratio_error(ratio: f64, expected: RatioError) {
+        let mut carrier = FmCarrier::default();
+
+        assert_eq!(carrier.set_ratio(ratio), Err(expected));
+        assert_eq!(carrier, FmCarrier::default());
+    }
+}
@@ -1,35 +1,45 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::{
+    diff::{diff_sounds, PatchCompareOptions},
+    range_check::check_ranges,
     values::{
-        ArpeggiatorMode, DecU50, FineTranspose, HexU50, OctavesCount, OscType, Pan, Polyphony, RetrigPhase, SamplePath,
-        SyncLevel, SynthMode, Transpose, UnisonDetune, UnisonVoiceCount, VoicePriority,
+        ArpeggiatorMode, DecU50, FineTranspose, HexU50, OctavesCount, OscType, Pan, PatchDestination, PatchSource, Polyphony,
+        RetrigPhase, SamplePath, SyncLevel, SynthMode, Transpose, UnisonDetune, UnisonVoiceCount, VoicePriority,
     },
-    SamplePosition,
+    OscillatorSlot, SamplePosition, SerializationError,
 };
 
 use enum_as_inner::EnumAsInner;
 
 mod effects;
+mod engine_conversion;
+mod error;
 mod fm;
 mod modulators;
+mod multisample;
 mod ring_mod;
 mod subtractive;
+mod validation;
 
 pub use effects::{
     Chorus, ChorusBuilder, Delay, DelayBuilder, Distorsion, DistorsionBuilder, Equalizer, EqualizerBuilder, Flanger,
     FlangerBuilder, ModulationFx, Phaser, PhaserBuilder, Sidechain, SidechainBuilder,
 };
 
+pub use error::{ConversionError, EngineError, MultisampleError, RangeIssue, RatioError, SampleZoneError, TransposeError};
 pub use fm::{FmCarrier, FmCarrierBuilder, FmModulator, FmModulatorBuilder, FmSynth, FmSynthBuilder};
 pub use modulators::{
     Envelope, EnvelopeBuilder, Lfo1, Lfo1Builder, Lfo2, Lfo2Builder, ModKnob, ModKnobBuilder, PatchCable, PatchCableBuilder,
 };
+pub use multisample::MultisampleBuilder;
 pub use ring_mod::{RingModSynth, RingModSynthBuilder};
 pub use subtractive::{
     Sample, SampleOneZone, SampleOneZoneBuilder, SampleOscillator, SampleOscillatorBuilder, SampleRange, SampleRangeBuilder,
-    SampleZone, SampleZoneBuilder, SubtractiveOscillator, SubtractiveSynth, SubtractiveSynthBuilder,
+    SampleZone, SampleZoneBuilder, Stretch, SubtractiveOscillator, SubtractiveSynth, SubtractiveSynthBuilder,
 };
+pub(crate) use validation::MOD_KNOB_COUNT;
+pub use validation::{IssueCode, IssueSeverity, PatchIssue};
 
 /// Composes Synth and Kit patches
 ///
@@ -56,16 +66,25 @@ pub use subtractive::{
 /// [Kit]: crate::Kit
 /// [SoundBuilder]: crate::SoundBuilder
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct Sound {
     pub generator: SynthEngine,
     pub polyphonic: Polyphony,
+    /// The maximum count of voices played at the same time, the firmware decides if it's not specified.
+    ///
+    /// The name of the attribute "maxVoices" is the one read and written by `Sound::readTagFromFile` and
+    /// `Sound::writeToFile` in `src/deluge/processing/sound/sound.cpp` of the community firmware.
+    /// No patch saved by the device with a voice limit is available in `data_tests`.
+    pub max_voices: Option<u8>,
     pub voice_priority: VoicePriority,
     pub volume: HexU50,
     pub pan: Pan,
     pub portamento: HexU50,
     pub reverb_amount: HexU50,
     pub stutter_rate: HexU50,
+    /// The level of the sound sent to the sidechain compressors of the other sounds, see [Sound::set_sidechain_send].
+    /// None if the patch doesn't specify it.
     pub sidechain_send: Option<DecU50>,
     pub envelope1: Envelope,
     pub envelope2: Envelope,
@@ -79,13 +98,45 @@ pub struct Sound {
     pub equalizer: Equalizer,
     pub sidechain: Sidechain,
 
+    /// The patch cables, the firmware only uses the last cable of a source and a destination.
+    /// The builder setter `add_cable` appends the cable even if a cable with the same source and destination exists,
+    /// use [Sound::add_or_update_cable] to replace it.
     #[builder(setter(each(name = "add_cable")))]
     pub cables: Vec<PatchCable>,
 
-    // This must be an array
+    /// The 16 mod knobs, 8 knobs with 2 parameters each, see [Sound::normalize_mod_knobs].
     pub mod_knobs: Vec<ModKnob>,
 }
 
+check_ranges!(
+    Sound,
+    SoundBuilder {
+        generator,
+        polyphonic,
+        max_voices,
+        voice_priority,
+        volume,
+        pan,
+        portamento,
+        reverb_amount,
+        stutter_rate,
+        sidechain_send,
+        envelope1,
+        envelope2,
+        lfo1,
+        lfo2,
+        unison,
+        arpeggiator,
+        delay,
+        distorsion,
+        modulation_fx,
+        equalizer,
+        sidechain,
+        cables,
+        mod_knobs
+    }
+);
+
 impl Sound {
     /// Factory function that creates a regular sample based sound
     pub fn new_sample(path: SamplePath, start: SamplePosition, end: SamplePosition) -> Self {
@@ -102,6 +153,20 @@ impl Sound {
         }
     }
 
+    /// Factory function that creates a sample based sound playing a whole WAV file of a card
+    ///
+    /// The path must be located on the card, the length of the sample is read from the WAV file.
+    #[cfg(feature = "wav")]
+    pub fn new_sample_from_file<FS: crate::FileSystem>(
+        card: &crate::Card<FS>,
+        path: &std::path::Path,
+    ) -> Result<Self, crate::WavError> {
+        let sample_path = card.sample_path(path)?;
+        let zone = SampleZone::full_length_of(&card.wav_info(&sample_path)?);
+
+        Ok(Self::new_sample(sample_path, zone.start, zone.end))
+    }
+
     pub fn new_subtractive(osc1: SubtractiveOscillator, osc2: SubtractiveOscillator) -> Self {
         Self {
             generator: SynthEngine::from(SubtractiveSynth::new(osc1, osc2)),
@@ -123,78 +188,309 @@ impl Sound {
         }
     }
 
+    /// The sound of the row created by the Deluge in a new kit
+    ///
+    /// Both oscillators play an empty sample. This sound is compared with the rows of a default kit saved by the device.
+    pub fn default_kit_row() -> Self {
+        let osc1 = SubtractiveOscillator::new_sample(Sample::OneZone(SampleOneZone {
+            file_path: SamplePath::default(),
+            zone: Some(SampleZone {
+                start: 0u64.into(),
+                end: 9999999u64.into(),
+                start_loop: None,
+                end_loop: None,
+            }),
+        }));
+        let osc2 = SubtractiveOscillator::new_sample(Sample::OneZone(SampleOneZone {
+            file_path: SamplePath::default(),
+            zone: None,
+        }));
+
+        let mut sound = Sound::new_subtractive(osc1, osc2);
+
+        sound.polyphonic = Polyphony::Auto;
+        sound.mod_knobs[12].control_param = "pitch".to_string();
+
+        sound
+    }
+
     /// Gets all the sample paths used by this sound.
+    ///
+    /// The empty paths are ignored.
     pub fn get_sample_paths(&self) -> BTreeSet<SamplePath> {
-        let mut paths = BTreeSet::new();
-
-        if let SynthEngine::Subtractive(generator) = &self.generator {
-            if let SubtractiveOscillator::Sample(generator) = &generator.osc1 {
-                paths.extend(
-                    generator
-                        .sample
-                        .get_sample_paths()
-                        .into_iter(),
-                );
+        let oscillators = match &self.generator {
+            SynthEngine::Subtractive(generator) => vec![&generator.osc1, &generator.osc2],
+            // The oscillators of these engines can't play samples.
+            SynthEngine::RingMod(_) | SynthEngine::Fm(_) => Vec::new(),
+        };
+
+        oscillators
+            .into_iter()
+            .filter_map(SubtractiveOscillator::as_sample)
+            .flat_map(|oscillator| oscillator.sample.get_sample_paths())
+            .filter(|path| !path.is_empty())
+            .collect()
+    }
+
+    /// Replace a sample path by another one
+    ///
+    /// Returns the count of references replaced.
+    pub fn replace_sample_path(&mut self, from: &SamplePath, to: &SamplePath) -> usize {
+        self.replace_sample_paths(&BTreeMap::from([(from.clone(), to.clone())]))
+    }
+
+    /// Replace the sample paths using a map where the keys are the paths to replace
+    ///
+    /// Returns the count of references replaced.
+    pub fn replace_sample_paths(&mut self, replacements: &BTreeMap<SamplePath, SamplePath>) -> usize {
+        let oscillators = match &mut self.generator {
+            SynthEngine::Subtractive(generator) => vec![&mut generator.osc1, &mut generator.osc2],
+            SynthEngine::RingMod(_) | SynthEngine::Fm(_) => Vec::new(),
+        };
+        let mut replaced_count = 0;
+
+        for path in oscillators
+            .into_iter()
+            .filter_map(SubtractiveOscillator::as_sample_mut)
+            .flat_map(|oscillator| oscillator.sample.get_sample_paths_mut())
+        {
+            if let Some(replacement) = replacements.get(path) {
+                *path = replacement.clone();
+                replaced_count += 1;
             }
+        }
+
+        replaced_count
+    }
 
-            if let SubtractiveOscillator::Sample(generator) = &generator.osc2 {
-                paths.extend(
-                    generator
-                        .sample
-                        .get_sample_paths()
-                        .into_iter(),
-                );
+    /// Pad or truncate the mod knobs to the 16 knobs expected by the Deluge
+    ///
+    /// The missing knobs get the parameters of the knobs of a default sound, the extra knobs are removed.
+    /// ```
+    /// # use deluge::Sound;
+    /// let mut sound = Sound::default();
+    ///
+    /// sound.mod_knobs.truncate(14);
+    /// sound.normalize_mod_knobs();
+    ///
+    /// assert_eq!(sound.mod_knobs, Sound::default().mod_knobs);
+    /// ```
+    pub fn normalize_mod_knobs(&mut self) {
+        let count = self.mod_knobs.len();
+
+        self.mod_knobs.truncate(MOD_KNOB_COUNT);
+        self.mod_knobs.extend(
+            default_mod_knobs()
+                .into_iter()
+                .skip(count),
+        );
+    }
+
+    /// Check the sound and report the contradictory or out of range data
+    ///
+    /// The Deluge loads these sounds without complaining but they will probably not behave as expected.
+    pub fn validate(&self) -> Vec<PatchIssue> {
+        validation::validate_sound(self)
+    }
+
+    /// Compare two sounds ignoring the differences that are not audible
+    ///
+    /// The mod knobs assignments are ignored, see [PatchCompareOptions].
+    /// The patch cables are compared whatever their order.
+    pub fn sonically_equals(&self, other: &Sound) -> bool {
+        self.sonically_equals_with(other, &PatchCompareOptions::default())
+    }
+
+    /// Compare two sounds ignoring the differences selected by the options
+    pub fn sonically_equals_with(&self, other: &Sound, options: &PatchCompareOptions) -> bool {
+        options.ignores_all(&diff_sounds(self, other))
+    }
+
+    /// Get the patch cable from the source to the destination
+    ///
+    /// If several cables have the same source and destination, the last one is returned as it's the one used by the firmware.
+    pub fn cable(&self, source: PatchSource, destination: PatchDestination) -> Option<&PatchCable> {
+        self.cables
+            .iter()
+            .rev()
+            .find(|cable| cable.source == source && cable.destination == destination)
+    }
+
+    /// Add a patch cable, or update the amount of the cable with the same source and destination
+    ///
+    /// The other cables with the same source and destination are removed.
+    /// ```
+    /// # use deluge::{HexU50, PatchDestination, PatchSource, Sound};
+    /// let mut sound = Sound::default();
+    /// let cables_count = sound.cables.len();
+    ///
+    /// sound.add_or_update_cable(PatchSource::Lfo1, PatchDestination::Pitch, 10.into());
+    /// sound.add_or_update_cable(PatchSource::Lfo1, PatchDestination::Pitch, 20.into());
+    ///
+    /// assert_eq!(sound.cables.len(), cables_count + 1);
+    /// assert_eq!(sound.cable(PatchSource::Lfo1, PatchDestination::Pitch).unwrap().amount, HexU50::new(20));
+    /// ```
+    pub fn add_or_update_cable(&mut self, source: PatchSource, destination: PatchDestination, amount: HexU50) {
+        let is_same_cable = |cable: &PatchCable| cable.source == source && cable.destination == destination;
+
+        match self
+            .cables
+            .iter()
+            .rposition(is_same_cable)
+        {
+            Some(last_index) => {
+                // The cable keeps the position of the cable used by the firmware
+                let duplicates_count = self.cables[..last_index]
+                    .iter()
+                    .filter(|cable| is_same_cable(cable))
+                    .count();
+
+                self.cables
+                    .retain(|cable| !is_same_cable(cable));
+                self.cables
+                    .insert(last_index - duplicates_count, PatchCable::new(source, destination, amount));
             }
+            None => self
+                .cables
+                .push(PatchCable::new(source, destination, amount)),
+        }
+    }
+
+    /// Remove the patch cables from the source to the destination, returns true if a cable was removed
+    pub fn remove_cable(&mut self, source: PatchSource, destination: PatchDestination) -> bool {
+        let cables_count = self.cables.len();
+
+        self.cables
+            .retain(|cable| cable.source != source || cable.destination != destination);
+
+        self.cables.len() != cables_count
+    }
+
+    /// Get the amount of the sidechain compressor applied to the volume
+    ///
+    /// The Deluge stores this amount, edited with the shortcut Row+Volduck, as a patch cable from the source `compressor`
+    /// to the destination `volumePostReverbSend`. This is the cable the default mod knob controlling the sidechain expects.
+    /// None means the sidechain doesn't affect the volume, whatever the values of [Sidechain].
+    /// The shape, the attack and the release of [Sidechain] only define how the compressor reacts when this cable exists.
+    /// Like [Sound::cable], the last cable is used if there are several.
+    pub fn sidechain_level(&self) -> Option<HexU50> {
+        self.cable(PatchSource::Compressor, PatchDestination::VolumePostReverbSend)
+            .map(|cable| cable.amount)
+    }
+
+    /// Set the amount of the sidechain compressor applied to the volume, the patch cable is created if needed
+    ///
+    /// See [Sound::sidechain_level].
+    pub fn set_sidechain_level(&mut self, amount: HexU50) {
+        self.add_or_update_cable(PatchSource::Compressor, PatchDestination::VolumePostReverbSend, amount);
+    }
+
+    /// Remove the patch cables of the sidechain compressor, the sidechain no longer affects the volume
+    ///
+    /// Returns the amount of the last removed cable, the one used by the firmware. See [Sound::sidechain_level].
+    pub fn remove_sidechain_level(&mut self) -> Option<HexU50> {
+        let amount = self.sidechain_level();
+
+        self.remove_cable(PatchSource::Compressor, PatchDestination::VolumePostReverbSend);
+
+        amount
+    }
+
+    /// Set the level of the sound sent to the sidechain compressors, None removes it from the patch
+    ///
+    /// The level is in the range [0; 50], an error is returned and the sound is not modified if it's greater.
+    /// ```
+    /// # use deluge::{DecU50, Sound};
+    /// let mut sound = Sound::default();
+    ///
+    /// sound.set_sidechain_send(Some(50))?;
+    /// assert_eq!(sound.sidechain_send, Some(DecU50::new(50)));
+    /// assert!(sound.set_sidechain_send(Some(51)).is_err());
+    /// # Ok::<(), deluge::SerializationError>(())
+    /// ```
+    pub fn set_sidechain_send(&mut self, level: Option<u8>) -> Result<(), SerializationError> {
+        self.sidechain_send = level.map(DecU50::try_new).transpose()?;
+
+        Ok(())
+    }
+
+    /// Transpose all the oscillators by a count of semitones
+    ///
+    /// The oscillators, the FM modulators and the ranges of the multisamples are transposed,
+    /// see [SynthEngine::oscillator_transposes]. If a transpose would leave the range [-96; 96], an error is returned
+    /// and the sound is not modified.
+    /// ```
+    /// # use deluge::Sound;
+    /// let mut sound = Sound::default();
+    ///
+    /// sound.transpose_by(12).unwrap();
+    ///
+    /// assert!(sound.transpose_by(90).is_err());
+    /// assert_eq!(sound.generator.oscillator_transposes(), vec![12.into(), 12.into()]);
+    /// ```
+    pub fn transpose_by(&mut self, semitones: i8) -> Result<(), TransposeError> {
+        let mut transposes = self
+            .generator
+            .oscillator_transposes_mut();
+        let transposed = transposes
+            .iter()
+            .map(|transpose| {
+                transpose
+                    .as_i8()
+                    .checked_add(semitones)
+                    .and_then(|value| Transpose::try_new(value).ok())
+                    .ok_or(TransposeError {
+                        transpose: **transpose,
+                        semitones,
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (transpose, value) in transposes.iter_mut().zip(transposed) {
+            **transpose = value;
         }
 
-        paths
+        Ok(())
     }
 }
 
+/// The mod knobs of a default sound, in the order of the knobs
+fn default_mod_knobs() -> Vec<ModKnob> {
+    vec![
+        ModKnob::new("pan"),
+        ModKnob::new("volumePostFX"),
+        ModKnob::new("lpfResonance"),
+        ModKnob::new("lpfFrequency"),
+        ModKnob::new("env1Release"),
+        ModKnob::new("env1Attack"),
+        ModKnob::new("delayFeedback"),
+        ModKnob::new("delayRate"),
+        ModKnob::new("reverbAmount"),
+        ModKnob::new_with_patch_amount("volumePostReverbSend", PatchSource::Compressor),
+        ModKnob::new_with_patch_amount("pitch", PatchSource::Lfo1),
+        ModKnob::new("lfo1Rate"),
+        ModKnob::new("portamento"),
+        ModKnob::new("stutterRate"),
+        ModKnob::new("bitcrushAmount"),
+        ModKnob::new("sampleRateReduction"),
+    ]
+}
+
 /// Default implementation for Sound
 ///
 /// This implementation returns a Sound exactly like the
 /// Deluge would create it for a default synth patch.
 impl Default for Sound {
     fn default() -> Self {
-        let envelope1 = Envelope {
-            attack: 0.into(),
-            decay: 20.into(),
-            sustain: 50.into(),
-            release: 0.into(),
-        };
-
-        let envelope2 = Envelope {
-            attack: 20.into(),
-            decay: 20.into(),
-            sustain: 25.into(),
-            release: 20.into(),
-        };
+        let envelope1 = Envelope::adsr(0, 20, 50, 0);
+        let envelope2 = Envelope::adsr(20, 20, 25, 20);
 
-        let mod_knobs = vec![
-            ModKnob::new("pan"),
-            ModKnob::new("volumePostFX"),
-            ModKnob::new("lpfResonance"),
-            ModKnob::new("lpfFrequency"),
-            ModKnob::new("env1Release"),
-            ModKnob::new("env1Attack"),
-            ModKnob::new("delayFeedback"),
-            ModKnob::new("delayRate"),
-            ModKnob::new("reverbAmount"),
-            ModKnob::new_with_patch_amount("volumePostReverbSend", "compressor"),
-            ModKnob::new_with_patch_amount("pitch", "lfo1"),
-            ModKnob::new("lfo1Rate"),
-            ModKnob::new("portamento"),
-            ModKnob::new("stutterRate"),
-            ModKnob::new("bitcrushAmount"),
-            ModKnob::new("sampleRateReduction"),
-        ];
-
-        let cables = vec![PatchCable::new("velocity", "volume", 37.into())];
+        let cables = vec![PatchCable::new(PatchSource::Velocity, PatchDestination::Volume, 37.into())];
 
         Self {
             generator: Default::default(),
             polyphonic: Polyphony::Poly,
+            max_voices: None,
             voice_priority: Default::default(),
             volume: 40.into(),
             pan: Default::default(),
@@ -214,7 +510,7 @@ impl Default for Sound {
             equalizer: Equalizer::default(),
             sidechain: Sidechain::default(),
             cables,
-            mod_knobs,
+            mod_knobs: default_mod_knobs(),
         }
     }
 }
@@ -231,6 +527,8 @@ impl Default for Sound {
 /// let fm_synth_mode = SynthEngine::from(FmSynth::default());
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq, EnumAsInner)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
 pub enum SynthEngine {
     Subtractive(SubtractiveSynth),
     RingMod(RingModSynth),
@@ -263,6 +561,79 @@ impl SynthEngine {
             SynthEngine::RingMod(_) => SynthMode::RingMod,
         }
     }
+
+    /// Gets the transpose of each oscillator
+    ///
+    /// The oscillators are listed in order, followed by the modulators of a FM synth.
+    /// The transpose of a multisample oscillator is followed by the transpose of each of its ranges.
+    pub fn oscillator_transposes(&self) -> Vec<Transpose> {
+        match self {
+            SynthEngine::Subtractive(synth) => [&synth.osc1, &synth.osc2]
+                .into_iter()
+                .flat_map(SubtractiveOscillator::transposes)
+                .collect(),
+            SynthEngine::RingMod(synth) => vec![synth.osc1.transpose, synth.osc2.transpose],
+            SynthEngine::Fm(synth) => vec![
+                synth.osc1.transpose,
+                synth.osc2.transpose,
+                synth.modulator1.transpose,
+                synth.modulator2.transpose,
+            ],
+        }
+    }
+
+    /// Gets the volume of an oscillator, None for a ring mod synth which has no oscillator volume
+    pub fn osc_volume(&self, slot: OscillatorSlot) -> Option<HexU50> {
+        match (self, slot) {
+            (SynthEngine::Subtractive(synth), OscillatorSlot::Osc1) => Some(synth.osc1_volume),
+            (SynthEngine::Subtractive(synth), OscillatorSlot::Osc2) => Some(synth.osc2_volume),
+            (SynthEngine::Fm(synth), OscillatorSlot::Osc1) => Some(synth.osc1_volume),
+            (SynthEngine::Fm(synth), OscillatorSlot::Osc2) => Some(synth.osc2_volume),
+            (SynthEngine::RingMod(_), _) => None,
+        }
+    }
+
+    /// Sets the volume of an oscillator, returns an error for a ring mod synth which has no oscillator volume
+    /// ```
+    /// # use deluge::{OscillatorSlot, RingModSynth, SynthEngine};
+    /// let mut engine = SynthEngine::default();
+    ///
+    /// engine.set_osc_volume(OscillatorSlot::Osc2, 25.into()).unwrap();
+    ///
+    /// assert_eq!(engine.osc_volume(OscillatorSlot::Osc2), Some(25.into()));
+    /// assert!(SynthEngine::from(RingModSynth::default())
+    ///     .set_osc_volume(OscillatorSlot::Osc1, 25.into())
+    ///     .is_err());
+    /// ```
+    pub fn set_osc_volume(&mut self, slot: OscillatorSlot, volume: HexU50) -> Result<(), EngineError> {
+        let osc_volume = match (self, slot) {
+            (SynthEngine::Subtractive(synth), OscillatorSlot::Osc1) => &mut synth.osc1_volume,
+            (SynthEngine::Subtractive(synth), OscillatorSlot::Osc2) => &mut synth.osc2_volume,
+            (SynthEngine::Fm(synth), OscillatorSlot::Osc1) => &mut synth.osc1_volume,
+            (SynthEngine::Fm(synth), OscillatorSlot::Osc2) => &mut synth.osc2_volume,
+            (SynthEngine::RingMod(_), _) => return Err(EngineError::NoOscillatorVolume(SynthMode::RingMod)),
+        };
+
+        *osc_volume = volume;
+
+        Ok(())
+    }
+
+    fn oscillator_transposes_mut(&mut self) -> Vec<&mut Transpose> {
+        match self {
+            SynthEngine::Subtractive(synth) => [&mut synth.osc1, &mut synth.osc2]
+                .into_iter()
+                .flat_map(SubtractiveOscillator::transposes_mut)
+                .collect(),
+            SynthEngine::RingMod(synth) => vec![&mut synth.osc1.transpose, &mut synth.osc2.transpose],
+            SynthEngine::Fm(synth) => vec![
+                &mut synth.osc1.transpose,
+                &mut synth.osc2.transpose,
+                &mut synth.modulator1.transpose,
+                &mut synth.modulator2.transpose,
+            ],
+        }
+    }
 }
 
 /// Implementation by default is the default [SubtractiveSynth]
@@ -273,7 +644,8 @@ impl Default for SynthEngine {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct WaveformOscillator {
     pub osc_type: OscType,
     pub transpose: Transpose,
@@ -282,6 +654,17 @@ pub struct WaveformOscillator {
     pub pulse_width: HexU50,
 }
 
+check_ranges!(
+    WaveformOscillator,
+    WaveformOscillatorBuilder {
+        osc_type,
+        transpose,
+        fine_transpose,
+        retrig_phase,
+        pulse_width
+    }
+);
+
 impl WaveformOscillator {
     pub fn new_sine() -> Self {
         Self {
@@ -322,12 +705,15 @@ impl Default for WaveformOscillator {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct Unison {
     pub voice_count: UnisonVoiceCount,
     pub detune: UnisonDetune,
 }
 
+check_ranges!(Unison, UnisonBuilder { voice_count, detune });
+
 impl Default for Unison {
     fn default() -> Self {
         Self {
@@ -338,7 +724,8 @@ impl Default for Unison {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct Arpeggiator {
     pub mode: ArpeggiatorMode,
     pub gate: HexU50,
@@ -347,6 +734,17 @@ pub struct Arpeggiator {
     pub octaves_count: OctavesCount,
 }
 
+check_ranges!(
+    Arpeggiator,
+    ArpeggiatorBuilder {
+        mode,
+        gate,
+        rate,
+        sync_level,
+        octaves_count
+    }
+);
+
 impl Default for Arpeggiator {
     fn default() -> Self {
         Self {
@@ -358,3 +756,294 @@ impl Default for Arpeggiator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deserialize_synth, serialize_synth, ClippingAmount, Synth};
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    #[test]
+    fn test_default_distorsion_matches_the_device() {
+        let synth = deserialize_synth(include_str!("../data_tests/default/SYNT Default Test.XML")).unwrap();
+        let kit = crate::deserialize_kit(include_str!("../data_tests/default/KIT Default Test.XML")).unwrap();
+
+        assert_eq!(synth.sound.distorsion, Distorsion::default());
+        assert!(kit
+            .sounds()
+            .all(|sound| sound.distorsion == Distorsion::default()));
+        assert_eq!(Distorsion::default().saturation, ClippingAmount::none());
+    }
+
+    #[test]
+    fn test_distorsion_with_saturation() {
+        let distorsion = Distorsion::with_saturation(ClippingAmount::new(12));
+
+        assert_eq!(distorsion.saturation, ClippingAmount::new(12));
+        assert_eq!(distorsion.bit_crush, HexU50::new(0));
+        assert_eq!(distorsion.decimation, HexU50::new(0));
+    }
+
+    #[test]
+    fn test_default_sound_has_no_sidechain_level() {
+        assert_eq!(Sound::default().sidechain_level(), None);
+        assert_eq!(Sound::default().remove_sidechain_level(), None);
+    }
+
+    #[test]
+    fn test_set_sidechain_level() {
+        let mut sound = Sound::default();
+        let cables_count = sound.cables.len();
+
+        sound.set_sidechain_level(30.into());
+        sound.set_sidechain_level(35.into());
+
+        assert_eq!(sound.sidechain_level(), Some(35.into()));
+        assert_eq!(sound.cables.len(), cables_count + 1);
+
+        let synth = Synth::new(sound);
+        let reloaded_synth = deserialize_synth(&serialize_synth(&synth).unwrap()).unwrap();
+
+        assert_eq!(reloaded_synth.sound.sidechain_level(), Some(35.into()));
+    }
+
+    #[test]
+    fn test_add_or_update_cable_replaces_the_duplicates() {
+        let lfo_cable = |amount: u8| PatchCable::new(PatchSource::Lfo1, PatchDestination::Pitch, amount.into());
+        let velocity_cable = PatchCable::new(PatchSource::Velocity, PatchDestination::Volume, 37.into());
+        // The builder appends the cables, the firmware would use the second LFO cable
+        let mut sound = SoundBuilder::default()
+            .add_cable(lfo_cable(50))
+            .add_cable(velocity_cable.clone())
+            .add_cable(lfo_cable(10))
+            .build()
+            .unwrap();
+
+        assert_eq!(sound.cable(PatchSource::Lfo1, PatchDestination::Pitch), Some(&lfo_cable(10)));
+
+        sound.add_or_update_cable(PatchSource::Lfo1, PatchDestination::Pitch, 20.into());
+
+        assert_eq!(sound.cables, vec![velocity_cable.clone(), lfo_cable(20)]);
+
+        sound.add_or_update_cable(PatchSource::Envelope2, PatchDestination::Pitch, 5.into());
+
+        assert_eq!(sound.cables.len(), 3);
+        assert!(sound.remove_cable(PatchSource::Lfo1, PatchDestination::Pitch));
+        assert!(!sound.remove_cable(PatchSource::Lfo1, PatchDestination::Pitch));
+        assert_eq!(sound.cable(PatchSource::Lfo1, PatchDestination::Pitch), None);
+        assert_eq!(
+            sound.cables,
+            vec![
+                velocity_cable,
+                PatchCable::new(PatchSource::Envelope2, PatchDestination::Pitch, 5.into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remove_sidechain_level() {
+        let mut sound = Sound::default();
+
+        sound.set_sidechain_level(30.into());
+
+        assert_eq!(sound.remove_sidechain_level(), Some(30.into()));
+        assert_eq!(sound.sidechain_level(), None);
+        assert_eq!(sound, Sound::default());
+    }
+
+    #[test]
+    fn test_sidechain_level_duplicated_cables() {
+        let sidechain_cable =
+            |amount: u8| PatchCable::new(PatchSource::Compressor, PatchDestination::VolumePostReverbSend, amount.into());
+        // The builder appends the cables, the firmware would use the second sidechain cable
+        let mut sound = SoundBuilder::default()
+            .add_cable(sidechain_cable(50))
+            .add_cable(sidechain_cable(10))
+            .build()
+            .unwrap();
+
+        assert_eq!(sound.sidechain_level(), Some(10.into()));
+        assert_eq!(sound.remove_sidechain_level(), Some(10.into()));
+        assert_eq!(sound.sidechain_level(), None);
+        assert!(sound.cables.is_empty());
+    }
+
+    fn fm_sound() -> Sound {
+        Sound {
+            generator: SynthEngine::from(FmSynth::default()),
+            ..Default::default()
+        }
+    }
+
+    fn ring_mod_sound() -> Sound {
+        Sound {
+            generator: SynthEngine::from(RingModSynth::default()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_transpose_subtractive_sound() {
+        let mut sound = Sound::default();
+
+        sound.transpose_by(-12).unwrap();
+
+        assert_eq!(sound.generator.oscillator_transposes(), vec![(-12).into(), (-12).into()]);
+    }
+
+    #[test]
+    fn test_transpose_multisample_sound() {
+        let mut sound = deserialize_synth(include_str!("../data_tests/SYNTHS/SYNT168A.XML"))
+            .unwrap()
+            .sound;
+        let transposes = sound.generator.oscillator_transposes();
+
+        sound.transpose_by(7).unwrap();
+
+        assert!(transposes.len() > 2);
+        assert_eq!(
+            sound.generator.oscillator_transposes(),
+            transposes
+                .iter()
+                .map(|transpose| Transpose::new(transpose.as_i8() + 7))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_transpose_fm_sound() {
+        let mut sound = fm_sound();
+
+        sound.transpose_by(96).unwrap();
+
+        assert_eq!(sound.generator.oscillator_transposes(), vec![96.into(); 4]);
+        assert_eq!(
+            sound.transpose_by(1),
+            Err(TransposeError {
+                transpose: 96.into(),
+                semitones: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_transpose_ring_mod_sound() {
+        let mut sound = ring_mod_sound();
+
+        sound.transpose_by(-96).unwrap();
+
+        assert_eq!(sound.generator.oscillator_transposes(), vec![(-96).into(); 2]);
+        assert!(sound.transpose_by(-1).is_err());
+        assert!(sound.transpose_by(i8::MIN).is_err());
+    }
+
+    #[test_case(Sound::default() ; "subtractive")]
+    #[test_case(fm_sound() ; "fm")]
+    fn test_set_osc_volume(mut sound: Sound) {
+        sound
+            .generator
+            .set_osc_volume(OscillatorSlot::Osc1, 12.into())
+            .unwrap();
+        sound
+            .generator
+            .set_osc_volume(OscillatorSlot::Osc2, 34.into())
+            .unwrap();
+
+        let reloaded_synth = deserialize_synth(&serialize_synth(&Synth::new(sound)).unwrap()).unwrap();
+
+        assert_eq!(
+            reloaded_synth
+                .sound
+                .generator
+                .osc_volume(OscillatorSlot::Osc1),
+            Some(12.into())
+        );
+        assert_eq!(
+            reloaded_synth
+                .sound
+                .generator
+                .osc_volume(OscillatorSlot::Osc2),
+            Some(34.into())
+        );
+    }
+
+    #[test]
+    fn test_set_osc_volume_ring_mod() {
+        let mut sound = ring_mod_sound();
+
+        assert_eq!(
+            sound
+                .generator
+                .osc_volume(OscillatorSlot::Osc1),
+            None
+        );
+        assert_eq!(
+            sound
+                .generator
+                .set_osc_volume(OscillatorSlot::Osc2, 12.into()),
+            Err(EngineError::NoOscillatorVolume(SynthMode::RingMod))
+        );
+        assert_eq!(sound, ring_mod_sound());
+    }
+
+    #[test]
+    fn test_failed_transpose_does_not_modify_the_sound() {
+        let mut sound = fm_sound();
+
+        sound
+            .generator
+            .as_fm_mut()
+            .unwrap()
+            .modulator2
+            .transpose = 90.into();
+
+        let expected = sound.clone();
+
+        assert!(sound.transpose_by(10).is_err());
+        assert_eq!(sound, expected);
+    }
+
+    #[test_case(Some(20), "sideChainSend=\"-429496748\"" ; "some")]
+    #[test_case(Some(50), "sideChainSend=\"2147483647\"" ; "max")]
+    fn test_save_load_sidechain_send(level: Option<u8>, expected_attribute: &str) {
+        let mut sound = Sound::default();
+
+        sound.set_sidechain_send(level).unwrap();
+
+        let xml = serialize_synth(&Synth::new(sound.clone())).unwrap();
+        let reloaded_synth = deserialize_synth(&xml).unwrap();
+
+        assert!(xml.contains(expected_attribute));
+        assert_eq!(reloaded_synth.sound.sidechain_send, level.map(DecU50::new));
+        assert_eq!(reloaded_synth.sound, sound);
+    }
+
+    #[test]
+    fn test_remove_sidechain_send() {
+        let mut sound = Sound::default();
+
+        sound
+            .set_sidechain_send(Some(10))
+            .unwrap();
+        sound.set_sidechain_send(None).unwrap();
+
+        let xml = serialize_synth(&Synth::new(sound)).unwrap();
+
+        assert!(!xml.contains("sideChainSend"));
+    }
+
+    #[test]
+    fn test_set_sidechain_send_out_of_range() {
+        let mut sound = Sound::default();
+
+        sound
+            .set_sidechain_send(Some(30))
+            .unwrap();
+
+        assert!(sound
+            .set_sidechain_send(Some(51))
+            .is_err());
+        assert_eq!(sound.sidechain_send, Some(DecU50::new(30)));
+    }
+}
@@ -1,6 +1,26 @@
-use crate::values::{HexU50, LfoShape, SyncLevel};
+use crate::{
+    range_check::check_ranges,
+    values::{HexU50, LfoShape, PatchDestination, PatchSource, SyncLevel},
+};
 
+/// Envelope
+///
+/// The values are in the range [0; 50] like on the device, the times they represent grow faster than the values.
+/// ```
+/// # use deluge::{Envelope, EnvelopeBuilder};
+/// let envelope = EnvelopeBuilder::default()
+///     .attack(0)
+///     .decay(20)
+///     .sustain(50)
+///     .release(0)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(envelope, Envelope::adsr(0, 20, 50, 0));
+/// ```
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(setter(into), build_fn(validate = "Self::check_ranges"))]
 pub struct Envelope {
     pub attack: HexU50,
     pub decay: HexU50,
@@ -8,14 +28,86 @@ pub struct Envelope {
     pub release: HexU50,
 }
 
+check_ranges!(
+    Envelope,
+    EnvelopeBuilder {
+        attack,
+        decay,
+        sustain,
+        release
+    }
+);
+
+impl Envelope {
+    /// Create an envelope from its attack, decay, sustain and release, each value in the range [0; 50]
+    ///
+    /// The values are not checked, use [EnvelopeBuilder] to get an error for an out of range value.
+    pub fn adsr(
+        attack: impl Into<HexU50>,
+        decay: impl Into<HexU50>,
+        sustain: impl Into<HexU50>,
+        release: impl Into<HexU50>,
+    ) -> Self {
+        Self {
+            attack: attack.into(),
+            decay: decay.into(),
+            sustain: sustain.into(),
+            release: release.into(),
+        }
+    }
+
+    /// A short percussive envelope: instant attack, short decay and no sustain
+    ///
+    /// This is a preset of this library, the values are not taken from a device preset.
+    pub fn pluck() -> Self {
+        Self::adsr(0, 15, 0, 12)
+    }
+
+    /// A slow envelope: long attack and long release
+    ///
+    /// This is a preset of this library, the values are not taken from a device preset.
+    pub fn pad() -> Self {
+        Self::adsr(30, 30, 40, 35)
+    }
+
+    /// The envelope of an organ: the note is held at full level while the key is pressed
+    ///
+    /// This is a preset of this library, the values are not taken from a device preset.
+    pub fn organ() -> Self {
+        Self::adsr(0, 0, 50, 3)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, setter(into), build_fn(validate = "Self::check_ranges"))]
 pub struct Lfo1 {
     pub shape: LfoShape,
     pub sync_level: SyncLevel,
     pub rate: HexU50,
 }
 
+check_ranges!(Lfo1, Lfo1Builder { shape, sync_level, rate });
+
+impl Lfo1 {
+    pub fn new(shape: LfoShape, rate: impl Into<HexU50>) -> Self {
+        Self {
+            shape,
+            sync_level: SyncLevel::Off,
+            rate: rate.into(),
+        }
+    }
+
+    /// Create a LFO synchronized to the tempo, the rate is ignored by the device
+    pub fn synced(shape: LfoShape, sync_level: SyncLevel) -> Self {
+        Self {
+            shape,
+            sync_level,
+            ..Default::default()
+        }
+    }
+}
+
 impl Default for Lfo1 {
     fn default() -> Self {
         Self {
@@ -27,12 +119,24 @@ impl Default for Lfo1 {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, setter(into), build_fn(validate = "Self::check_ranges"))]
 pub struct Lfo2 {
     pub shape: LfoShape,
     pub rate: HexU50,
 }
 
+check_ranges!(Lfo2, Lfo2Builder { shape, rate });
+
+impl Lfo2 {
+    pub fn new(shape: LfoShape, rate: impl Into<HexU50>) -> Self {
+        Self {
+            shape,
+            rate: rate.into(),
+        }
+    }
+}
+
 impl Default for Lfo2 {
     fn default() -> Self {
         Self {
@@ -43,28 +147,49 @@ impl Default for Lfo2 {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(build_fn(validate = "Self::check_ranges"))]
 pub struct PatchCable {
-    pub source: String,
-    pub destination: String,
+    pub source: PatchSource,
+    pub destination: PatchDestination,
     pub amount: HexU50,
 }
 
+check_ranges!(
+    PatchCable,
+    PatchCableBuilder {
+        source,
+        destination,
+        amount
+    }
+);
+
 impl PatchCable {
-    pub fn new(source: &str, destination: &str, amount: HexU50) -> Self {
+    pub fn new(source: PatchSource, destination: PatchDestination, amount: HexU50) -> Self {
         Self {
-            source: source.to_string(),
-            destination: destination.to_string(),
+            source,
+            destination,
             amount,
         }
     }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(build_fn(validate = "Self::check_ranges"))]
 pub struct ModKnob {
     pub control_param: String,
-    pub patch_amount_from_source: Option<String>,
+    pub patch_amount_from_source: Option<PatchSource>,
 }
 
+check_ranges!(
+    ModKnob,
+    ModKnobBuilder {
+        control_param,
+        patch_amount_from_source
+    }
+);
+
 impl ModKnob {
     pub fn new(control_param: &str) -> Self {
         Self {
@@ -73,10 +198,54 @@ impl ModKnob {
         }
     }
 
-    pub fn new_with_patch_amount(control_param: &str, patch_amount_from_source: &str) -> Self {
+    pub fn new_with_patch_amount(control_param: &str, patch_amount_from_source: PatchSource) -> Self {
         Self {
             control_param: control_param.to_string(),
-            patch_amount_from_source: Some(patch_amount_from_source.to_string()),
+            patch_amount_from_source: Some(patch_amount_from_source),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range_check::CheckRange;
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    #[test_case(Envelope::pluck() ; "pluck")]
+    #[test_case(Envelope::pad() ; "pad")]
+    #[test_case(Envelope::organ() ; "organ")]
+    fn test_envelope_presets_are_in_range(envelope: Envelope) {
+        assert_eq!(envelope.check_range(""), Ok(()));
+    }
+
+    #[test]
+    fn test_envelope_builder_accepts_u8() {
+        let envelope = EnvelopeBuilder::default()
+            .attack(10)
+            .decay(HexU50::new(20))
+            .sustain(30)
+            .release(40)
+            .build()
+            .unwrap();
+
+        assert_eq!(envelope, Envelope::adsr(10, 20, 30, 40));
+        assert_eq!(envelope.decay, HexU50::new(20));
+    }
+
+    #[test]
+    fn test_lfo_constructors() {
+        assert_eq!(Lfo1::new(LfoShape::Triangle, 30), Lfo1::default());
+        assert_eq!(Lfo1::synced(LfoShape::Saw, SyncLevel::Eighth).sync_level, SyncLevel::Eighth);
+        assert_eq!(Lfo2::new(LfoShape::Triangle, 25), Lfo2::default());
+        assert_eq!(
+            Lfo2Builder::default()
+                .rate(40)
+                .build()
+                .unwrap()
+                .rate,
+            HexU50::new(40)
+        );
+    }
+}
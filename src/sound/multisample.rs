@@ -0,0 +1,201 @@
+use crate::{
+    values::{MidiNote, SamplePath, Transpose},
+    MultisampleError, Sample, SampleOscillator, SampleRange, SampleZone,
+};
+
+/// The root note played without transposition
+const UNTRANSPOSED_ROOT_NOTE: u8 = 60;
+
+/// Build a multisample oscillator from samples and their root notes
+///
+/// The top note of each range is placed halfway between the root notes of two neighbor samples,
+/// the last range covers all the notes above. Each range is transposed so the sample plays at its pitch
+/// when its root note is played.
+/// ```
+/// # use deluge::{MultisampleBuilder, SamplePath, SampleZone};
+/// let zone = SampleZone {
+///     start: 0u64.into(),
+///     end: 44100u64.into(),
+///     start_loop: None,
+///     end_loop: None,
+/// };
+/// let oscillator = MultisampleBuilder::new()
+///     .add_sample(SamplePath::new("SAMPLES/C4.wav").unwrap(), zone.clone(), 60)
+///     .add_sample(SamplePath::new("SAMPLES/C5.wav").unwrap(), zone, 72)
+///     .build()
+///     .unwrap();
+/// let ranges = oscillator.sample.as_sample_ranges().unwrap();
+///
+/// assert_eq!(ranges[0].range_top_note, Some(66));
+/// assert_eq!(ranges[1].range_top_note, None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MultisampleBuilder {
+    samples: Vec<(SamplePath, SampleZone, u8)>,
+}
+
+impl MultisampleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sample, the samples must be added by ascending root note
+    pub fn add_sample(mut self, file_path: SamplePath, zone: SampleZone, root_note: u8) -> Self {
+        self.samples
+            .push((file_path, zone, root_note));
+        self
+    }
+
+    pub fn build(self) -> Result<SampleOscillator, MultisampleError> {
+        if self.samples.is_empty() {
+            return Err(MultisampleError::NoSample);
+        }
+
+        let root_notes: Vec<u8> = self
+            .samples
+            .iter()
+            .map(|(_, _, root_note)| *root_note)
+            .collect();
+
+        for (index, root_note) in root_notes.iter().enumerate() {
+            if *root_note > MidiNote::MAX {
+                return Err(MultisampleError::InvalidRootNote(*root_note));
+            }
+
+            if index > 0 && root_notes[index - 1] >= *root_note {
+                return Err(MultisampleError::UnorderedRootNotes {
+                    index,
+                    root_note: *root_note,
+                    previous_root_note: root_notes[index - 1],
+                });
+            }
+        }
+
+        let ranges = self
+            .samples
+            .into_iter()
+            .enumerate()
+            .map(|(index, (file_path, zone, root_note))| SampleRange {
+                range_top_note: root_notes
+                    .get(index + 1)
+                    .map(|next_root_note| root_note + (next_root_note - root_note) / 2),
+                transpose: Transpose::new(UNTRANSPOSED_ROOT_NOTE as i8 - root_note as i8),
+                fine_transpose: Default::default(),
+                file_path,
+                zone,
+            })
+            .collect();
+
+        Ok(SampleOscillator::new(Sample::SampleRanges(ranges)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deserialize_synth;
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    fn make_zone(end: u64, start_loop: u64, end_loop: u64) -> SampleZone {
+        SampleZone {
+            start: 0u64.into(),
+            end: end.into(),
+            start_loop: Some(start_loop.into()),
+            end_loop: Some(end_loop.into()),
+        }
+    }
+
+    fn build(root_notes: &[u8]) -> Result<SampleOscillator, MultisampleError> {
+        root_notes
+            .iter()
+            .fold(MultisampleBuilder::new(), |builder, root_note| {
+                builder.add_sample(
+                    SamplePath::new(format!("SAMPLES/{}.wav", root_note)).unwrap(),
+                    make_zone(100, 0, 100),
+                    *root_note,
+                )
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_build_two_samples_of_a_device_patch() {
+        let xml = include_str!("../data_tests/SYNTHS/SYNT168A.XML");
+        let oscillator = MultisampleBuilder::new()
+            .add_sample(
+                SamplePath::new("SAMPLES/Artists/Leonard Ludvigsen/Hangdrum/1.wav").unwrap(),
+                make_zone(146506, 19101, 19603),
+                60,
+            )
+            .add_sample(
+                SamplePath::new("SAMPLES/Artists/Leonard Ludvigsen/Hangdrum/2.wav").unwrap(),
+                make_zone(137227, 8089, 8256),
+                72,
+            )
+            .build()
+            .unwrap();
+        let expected = deserialize_synth(xml).unwrap();
+        let expected_ranges = expected
+            .sound
+            .generator
+            .as_subtractive()
+            .unwrap()
+            .osc1
+            .as_sample()
+            .unwrap()
+            .sample
+            .as_sample_ranges()
+            .unwrap();
+        let ranges = oscillator
+            .sample
+            .as_sample_ranges()
+            .unwrap();
+
+        // The ranges of the patch were split at 72 on the device, the builder splits halfway between the root notes
+        assert_eq!(expected_ranges[0].range_top_note, Some(72));
+        assert_eq!(ranges[0].range_top_note, Some(66));
+        assert_eq!(ranges.len(), expected_ranges.len());
+
+        for (range, expected_range) in ranges.iter().zip(expected_ranges) {
+            let range = SampleRange {
+                range_top_note: expected_range.range_top_note,
+                ..range.clone()
+            };
+
+            assert_eq!(&range, expected_range);
+        }
+    }
+
+    #[test]
+    fn test_build_splits_halfway() {
+        let oscillator = build(&[36, 48, 55, 127]).unwrap();
+        let ranges = oscillator
+            .sample
+            .as_sample_ranges()
+            .unwrap();
+        let top_notes: Vec<Option<u8>> = ranges
+            .iter()
+            .map(|range| range.range_top_note)
+            .collect();
+        let transposes: Vec<Transpose> = ranges
+            .iter()
+            .map(|range| range.transpose)
+            .collect();
+
+        assert_eq!(top_notes, vec![Some(42), Some(51), Some(91), None]);
+        assert_eq!(
+            transposes,
+            vec![Transpose::new(24), Transpose::new(12), Transpose::new(5), Transpose::new(-67)]
+        );
+        assert_eq!(oscillator.sample.validate_ranges(), Vec::new());
+    }
+
+    #[test_case(&[], MultisampleError::NoSample ; "empty")]
+    #[test_case(&[60, 48], MultisampleError::UnorderedRootNotes { index: 1, root_note: 48, previous_root_note: 60 } ; "descending")]
+    #[test_case(&[60, 60], MultisampleError::UnorderedRootNotes { index: 1, root_note: 60, previous_root_note: 60 } ; "duplicate")]
+    #[test_case(&[60, 128], MultisampleError::InvalidRootNote(128) ; "invalid root note")]
+    fn test_build_errors(root_notes: &[u8], expected: MultisampleError) {
+        assert_eq!(build(root_notes).unwrap_err(), expected);
+    }
+}
@@ -1,10 +1,12 @@
 use crate::{
+    range_check::check_ranges,
     values::{FineTranspose, HexU50, OnOff, OscType, RetrigPhase, Transpose},
     WaveformOscillator,
 };
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct RingModSynth {
     pub osc1: WaveformOscillator,
     pub osc2: WaveformOscillator,
@@ -12,6 +14,16 @@ pub struct RingModSynth {
     pub noise: HexU50,
 }
 
+check_ranges!(
+    RingModSynth,
+    RingModSynthBuilder {
+        osc1,
+        osc2,
+        osc2_sync,
+        noise
+    }
+);
+
 impl RingModSynth {
     pub fn new(osc1: WaveformOscillator, osc2: WaveformOscillator) -> Self {
         Self {
@@ -1,11 +1,12 @@
 use enum_as_inner::EnumAsInner;
 
 use crate::{
+    range_check::check_ranges,
     values::{
-        FineTranspose, HexU50, LpfMode, OnOff, OscType, PitchSpeed, RetrigPhase, SamplePath, SamplePlayMode, SamplePosition,
-        TimeStretchAmount, Transpose,
+        FineTranspose, HexU50, LpfMode, MidiNote, OnOff, OscType, PitchSpeed, RetrigPhase, SamplePath, SamplePlayMode,
+        SamplePosition, TimeStretchAmount, Transpose,
     },
-    WaveformOscillator,
+    RangeIssue, SampleZoneError, WaveformOscillator,
 };
 
 /// Subtractive oscillator
@@ -21,6 +22,8 @@ use crate::{
 ///     .build().unwrap());
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq, EnumAsInner)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
 pub enum SubtractiveOscillator {
     Waveform(WaveformOscillator),
     Sample(SampleOscillator),
@@ -34,6 +37,62 @@ impl SubtractiveOscillator {
     pub fn new_sample(sample: Sample) -> Self {
         SubtractiveOscillator::Sample(SampleOscillator::new(sample))
     }
+
+    /// Gets the type of the oscillator, [OscType::Sample] for a sample oscillator
+    pub fn osc_type(&self) -> OscType {
+        match self {
+            SubtractiveOscillator::Waveform(oscillator) => oscillator.osc_type.clone(),
+            SubtractiveOscillator::Sample(_) => OscType::Sample,
+        }
+    }
+
+    pub fn transpose(&self) -> Transpose {
+        match self {
+            SubtractiveOscillator::Waveform(oscillator) => oscillator.transpose,
+            SubtractiveOscillator::Sample(oscillator) => oscillator.transpose,
+        }
+    }
+
+    pub fn fine_transpose(&self) -> FineTranspose {
+        match self {
+            SubtractiveOscillator::Waveform(oscillator) => oscillator.fine_transpose,
+            SubtractiveOscillator::Sample(oscillator) => oscillator.fine_transpose,
+        }
+    }
+
+    /// Gets the transpose of the oscillator followed by the transpose of each range of a multisample
+    pub(crate) fn transposes(&self) -> Vec<Transpose> {
+        match self {
+            SubtractiveOscillator::Waveform(oscillator) => vec![oscillator.transpose],
+            SubtractiveOscillator::Sample(oscillator) => std::iter::once(oscillator.transpose)
+                .chain(
+                    oscillator
+                        .sample
+                        .as_sample_ranges()
+                        .into_iter()
+                        .flatten()
+                        .map(|range| range.transpose),
+                )
+                .collect(),
+        }
+    }
+
+    /// Gets a mutable reference to each transpose, in the same order than [SubtractiveOscillator::transposes]
+    pub(crate) fn transposes_mut(&mut self) -> Vec<&mut Transpose> {
+        match self {
+            SubtractiveOscillator::Waveform(oscillator) => vec![&mut oscillator.transpose],
+            SubtractiveOscillator::Sample(oscillator) => std::iter::once(&mut oscillator.transpose)
+                .chain(
+                    oscillator
+                        .sample
+                        .as_sample_ranges_mut()
+                        .into_iter()
+                        .flatten()
+                        .map(|range| &mut range.transpose),
+                )
+                .collect(),
+        }
+    }
 }
 
 impl From<WaveformOscillator> for SubtractiveOscillator {
@@ -59,7 +118,8 @@ impl From<SampleOscillator> for SubtractiveOscillator {
 ///     .unwrap();
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct SubtractiveSynth {
     pub osc1: SubtractiveOscillator,
     pub osc2: SubtractiveOscillator,
@@ -74,6 +134,23 @@ pub struct SubtractiveSynth {
     pub hpf_resonance: HexU50,
 }
 
+check_ranges!(
+    SubtractiveSynth,
+    SubtractiveSynthBuilder {
+        osc1,
+        osc2,
+        osc2_sync,
+        osc1_volume,
+        osc2_volume,
+        noise,
+        lpf_mode,
+        lpf_frequency,
+        lpf_resonance,
+        hpf_frequency,
+        hpf_resonance
+    }
+);
+
 impl SubtractiveSynth {
     pub fn new(osc1: SubtractiveOscillator, osc2: SubtractiveOscillator) -> Self {
         Self {
@@ -119,7 +196,8 @@ impl Default for SubtractiveSynth {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
-#[builder(default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::check_ranges"))]
 pub struct SampleOscillator {
     pub transpose: Transpose,
     pub fine_transpose: FineTranspose,
@@ -133,6 +211,35 @@ pub struct SampleOscillator {
     pub sample: Sample,
 }
 
+check_ranges!(
+    SampleOscillator,
+    SampleOscillatorBuilder {
+        transpose,
+        fine_transpose,
+        mode,
+        reversed,
+        pitch_speed,
+        time_stretch_amount,
+        linear_interpolation,
+        sample
+    }
+);
+
+/// How the pitch and the speed of a sample are related, the menu "PITCH/SPEED" of the device
+///
+/// The raw values are stored in [SampleOscillator::pitch_speed] and [SampleOscillator::time_stretch_amount],
+/// use [SampleOscillator::stretch] and [SampleOscillator::set_stretch] to edit them coherently.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stretch {
+    /// "LINKED": transposing the sample changes its speed, like a tape.
+    /// The time stretch amount is ignored by the firmware.
+    Linked,
+    /// "INDEPENDENT": the sample is time stretched, the pitch and the speed are changed separately.
+    /// The amount is the speed change, edited with the menu "SPEED".
+    Independent { amount: TimeStretchAmount },
+}
+
 impl SampleOscillator {
     pub fn new(sample: Sample) -> Self {
         Self {
@@ -140,6 +247,80 @@ impl SampleOscillator {
             ..Default::default()
         }
     }
+
+    /// Get the relation between the pitch and the speed, see [Stretch]
+    pub fn stretch(&self) -> Stretch {
+        match self.pitch_speed {
+            PitchSpeed::Linked => Stretch::Linked,
+            PitchSpeed::Independent => Stretch::Independent {
+                amount: self.time_stretch_amount,
+            },
+        }
+    }
+
+    /// Set the play mode, the loop points are cleared when switching to [SamplePlayMode::Cut] like the firmware does
+    /// ```
+    /// # use deluge::{SampleOscillator, SamplePlayMode};
+    /// let mut oscillator = SampleOscillator::default();
+    /// let zone = oscillator.sample.as_one_zone_mut().unwrap().zone.as_mut().unwrap();
+    ///
+    /// zone.set_loop_fraction(0.25, 0.75).unwrap();
+    /// oscillator.set_play_mode(SamplePlayMode::Cut);
+    ///
+    /// assert_eq!(oscillator.sample.as_one_zone().unwrap().zone.as_ref().unwrap().start_loop, None);
+    /// ```
+    pub fn set_play_mode(&mut self, mode: SamplePlayMode) {
+        if mode == SamplePlayMode::Cut {
+            self.sample.clear_loops();
+        }
+
+        self.mode = mode;
+    }
+
+    /// Set the relation between the pitch and the speed, see [Stretch]
+    ///
+    /// The time stretch amount is reset when the pitch and the speed are linked, like the firmware writes it.
+    /// ```
+    /// # use deluge::{PitchSpeed, SampleOscillator, Stretch};
+    /// let mut oscillator = SampleOscillator::default();
+    ///
+    /// oscillator.set_stretch(Stretch::Independent { amount: 12.into() });
+    /// oscillator.set_stretch(Stretch::Linked);
+    ///
+    /// assert_eq!(oscillator.pitch_speed, PitchSpeed::Linked);
+    /// assert_eq!(oscillator.time_stretch_amount, 0.into());
+    /// ```
+    pub fn set_stretch(&mut self, stretch: Stretch) {
+        match stretch {
+            Stretch::Linked => {
+                self.pitch_speed = PitchSpeed::Linked;
+                self.time_stretch_amount = TimeStretchAmount::default();
+            }
+            Stretch::Independent { amount } => {
+                self.pitch_speed = PitchSpeed::Independent;
+                self.time_stretch_amount = amount;
+            }
+        }
+    }
+
+    /// Create a multisample oscillator from the path, the zone and the top note of each range
+    ///
+    /// The ranges are not transposed, see [MultisampleBuilder](crate::MultisampleBuilder) to transpose
+    /// each sample according to its root note.
+    pub fn from_ranges(ranges: Vec<(SamplePath, SampleZone, Option<u8>)>) -> Self {
+        Self::new(Sample::SampleRanges(
+            ranges
+                .into_iter()
+                .map(|(file_path, zone, range_top_note)| SampleRange {
+                    range_top_note,
+                    transpose: Default::default(),
+                    fine_transpose: Default::default(),
+                    file_path,
+                    zone,
+                })
+                .collect(),
+        ))
+    }
 }
 impl Default for SampleOscillator {
     fn default() -> Self {
@@ -148,7 +329,7 @@ impl Default for SampleOscillator {
             fine_transpose: Default::default(),
             mode: SamplePlayMode::Cut,
             reversed: OnOff::Off,
-            pitch_speed: PitchSpeed::Independent,
+            pitch_speed: PitchSpeed::Linked,
             time_stretch_amount: Default::default(),
             linear_interpolation: OnOff::Off,
             sample: Default::default(),
@@ -157,6 +338,8 @@ impl Default for SampleOscillator {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, enum_as_inner::EnumAsInner)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Sample {
     OneZone(SampleOneZone),
     SampleRanges(Vec<SampleRange>),
@@ -185,6 +368,93 @@ impl Sample {
             ),
         }
     }
+
+    /// Gets a mutable reference to each sample path
+    pub fn get_sample_paths_mut(&mut self) -> Vec<&mut SamplePath> {
+        match self {
+            Sample::OneZone(zone) => Vec::from([&mut zone.file_path]),
+            Sample::SampleRanges(ranges) => ranges
+                .iter_mut()
+                .map(|range| &mut range.file_path)
+                .collect(),
+        }
+    }
+
+    /// Remove the loop points of each zone
+    pub fn clear_loops(&mut self) {
+        match self {
+            Sample::OneZone(sample) => sample
+                .zone
+                .iter_mut()
+                .for_each(SampleZone::clear_loop),
+            Sample::SampleRanges(ranges) => ranges
+                .iter_mut()
+                .for_each(|range| range.zone.clear_loop()),
+        }
+    }
+
+    /// Find the range playing a note
+    ///
+    /// Like the firmware, the first range whose top note is greater or equal to the note is used,
+    /// a range without top note covers all the notes.
+    /// Returns None if no range covers the note or if the sample is not a multisample.
+    pub fn range_for_note(&self, note: u8) -> Option<&SampleRange> {
+        self.as_sample_ranges()?
+            .iter()
+            .find(|range| {
+                range
+                    .range_top_note
+                    .is_none_or(|top_note| top_note >= note)
+            })
+    }
+
+    /// Check the ranges of a multisample
+    ///
+    /// The top notes must be ascending and only the last range can have no top note, otherwise
+    /// some ranges are never played. The last range must cover the notes up to the highest note.
+    /// Returns no issue if the sample is not a multisample.
+    pub fn validate_ranges(&self) -> Vec<RangeIssue> {
+        let ranges = match self {
+            Sample::SampleRanges(ranges) => ranges,
+            Sample::OneZone(_) => return Vec::new(),
+        };
+        let mut issues = Vec::new();
+        // The index and the top note of the highest range found so far
+        let mut previous: Option<(usize, u8)> = None;
+
+        for (index, range) in ranges.iter().enumerate() {
+            match (range.range_top_note, previous) {
+                (Some(top_note), Some((previous_index, previous_top_note))) if top_note == previous_top_note => {
+                    issues.push(RangeIssue::DuplicateTopNote {
+                        index,
+                        previous_index,
+                        top_note,
+                    })
+                }
+                (Some(top_note), Some((_, previous_top_note))) if top_note < previous_top_note => {
+                    issues.push(RangeIssue::DescendingTopNote {
+                        index,
+                        top_note,
+                        previous_top_note,
+                    })
+                }
+                (Some(top_note), _) => previous = Some((index, top_note)),
+                (None, _) if index + 1 < ranges.len() => issues.push(RangeIssue::MissingTopNote(index)),
+                (None, _) => {}
+            }
+        }
+
+        match ranges.last() {
+            None => issues.push(RangeIssue::NoRange),
+            Some(SampleRange {
+                range_top_note: Some(top_note),
+                ..
+            }) if *top_note < MidiNote::MAX => issues.push(RangeIssue::UncoveredNotes(*top_note)),
+            Some(_) => {}
+        }
+
+        issues
+    }
 }
 
 impl Default for Sample {
@@ -194,12 +464,18 @@ impl Default for Sample {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Default, derive_builder::Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(build_fn(validate = "Self::check_ranges"))]
 pub struct SampleOneZone {
     pub file_path: SamplePath,
     pub zone: Option<SampleZone>,
 }
 
+check_ranges!(SampleOneZone, SampleOneZoneBuilder { file_path, zone });
+
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(build_fn(validate = "Self::check_ranges"))]
 pub struct SampleRange {
     pub range_top_note: Option<u8>,
     pub transpose: Transpose,
@@ -208,10 +484,298 @@ pub struct SampleRange {
     pub zone: SampleZone,
 }
 
+check_ranges!(
+    SampleRange,
+    SampleRangeBuilder {
+        range_top_note,
+        transpose,
+        fine_transpose,
+        file_path,
+        zone
+    }
+);
+
 #[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(build_fn(validate = "Self::check_ranges"))]
 pub struct SampleZone {
     pub start: SamplePosition,
     pub end: SamplePosition,
     pub start_loop: Option<SamplePosition>,
     pub end_loop: Option<SamplePosition>,
 }
+
+check_ranges!(
+    SampleZone,
+    SampleZoneBuilder {
+        start,
+        end,
+        start_loop,
+        end_loop
+    }
+);
+
+impl SampleZone {
+    /// Create a zone covering a whole WAV file, without loop
+    #[cfg(feature = "wav")]
+    pub fn full_length_of(info: &crate::WavInfo) -> Self {
+        Self {
+            start: 0u64.into(),
+            end: info.frame_count.into(),
+            start_loop: None,
+            end_loop: None,
+        }
+    }
+
+    /// The length of the zone, 0 if the end is before the start.
+    pub fn duration(&self) -> SamplePosition {
+        self.end - self.start
+    }
+
+    /// Set the loop points at fractions of the zone, 0.0 is the start of the zone and 1.0 is its end.
+    ///
+    /// The positions are rounded to the nearest sample. The zone is not modified if a fraction is outside of [0; 1],
+    /// if the loop end is before the loop start or if the zone is not valid, see [SampleZone::validate].
+    /// ```
+    /// # use deluge::{SamplePosition, SampleZone};
+    /// let mut zone = SampleZone {
+    ///     start: 100u64.into(),
+    ///     end: 300u64.into(),
+    ///     start_loop: None,
+    ///     end_loop: None,
+    /// };
+    ///
+    /// zone.set_loop_fraction(0.5, 1.0).unwrap();
+    ///
+    /// assert_eq!(zone.start_loop, Some(SamplePosition::new(200)));
+    /// assert_eq!(zone.end_loop, Some(SamplePosition::new(300)));
+    /// ```
+    pub fn set_loop_fraction(&mut self, start: f32, end: f32) -> Result<(), SampleZoneError> {
+        if !(0.0..=1.0).contains(&start) || !(0.0..=1.0).contains(&end) {
+            return Err(SampleZoneError::LoopFractionOutOfRange);
+        }
+
+        let zone = SampleZone {
+            start_loop: Some(self.position_at(start)),
+            end_loop: Some(self.position_at(end)),
+            ..self.clone()
+        };
+
+        zone.validate()?;
+        *self = zone;
+
+        Ok(())
+    }
+
+    /// Remove the loop points
+    pub fn clear_loop(&mut self) {
+        self.start_loop = None;
+        self.end_loop = None;
+    }
+
+    fn position_at(&self, fraction: f32) -> SamplePosition {
+        let offset = (self.duration().as_u64() as f64 * f64::from(fraction)).round() as u64;
+
+        self.start + SamplePosition::new(offset)
+    }
+
+    /// Check the end is not before the start and the loop points are inside the zone.
+    pub fn validate(&self) -> Result<(), SampleZoneError> {
+        if self.end < self.start {
+            return Err(SampleZoneError::EndBeforeStart {
+                start: self.start,
+                end: self.end,
+            });
+        }
+
+        let zone = self.start..=self.end;
+
+        if let Some(start_loop) = self.start_loop {
+            if !zone.contains(&start_loop) {
+                return Err(SampleZoneError::LoopStartOutOfZone(start_loop));
+            }
+        }
+
+        if let Some(end_loop) = self.end_loop {
+            if !zone.contains(&end_loop) {
+                return Err(SampleZoneError::LoopEndOutOfZone(end_loop));
+            }
+        }
+
+        if let (Some(start), Some(end)) = (self.start_loop, self.end_loop) {
+            if end < start {
+                return Err(SampleZoneError::LoopEndBeforeLoopStart { start, end });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn make_zone(start: u64, end: u64, start_loop: Option<u64>, end_loop: Option<u64>) -> SampleZone {
+        SampleZone {
+            start: start.into(),
+            end: end.into(),
+            start_loop: start_loop.map(SamplePosition::new),
+            end_loop: end_loop.map(SamplePosition::new),
+        }
+    }
+
+    #[test_case(make_zone(10, 100, None, None), Ok(()) ; "valid")]
+    #[test_case(make_zone(10, 100, Some(10), Some(100)), Ok(()) ; "valid loop")]
+    #[test_case(make_zone(100, 10, None, None), Err(SampleZoneError::EndBeforeStart { start: 100u64.into(), end: 10u64.into() }) ; "end before start")]
+    #[test_case(make_zone(10, 100, Some(5), None), Err(SampleZoneError::LoopStartOutOfZone(5u64.into())) ; "loop start out of zone")]
+    #[test_case(make_zone(10, 100, None, Some(101)), Err(SampleZoneError::LoopEndOutOfZone(101u64.into())) ; "loop end out of zone")]
+    #[test_case(make_zone(10, 100, Some(50), Some(40)), Err(SampleZoneError::LoopEndBeforeLoopStart { start: 50u64.into(), end: 40u64.into() }) ; "loop end before loop start")]
+    fn test_validate_zone(zone: SampleZone, expected: Result<(), SampleZoneError>) {
+        assert_eq!(expected, zone.validate());
+    }
+
+    fn make_range(range_top_note: Option<u8>) -> SampleRange {
+        SampleRange {
+            range_top_note,
+            transpose: Default::default(),
+            fine_transpose: Default::default(),
+            file_path: SamplePath::default(),
+            zone: make_zone(0, 100, None, None),
+        }
+    }
+
+    fn load_osc1_sample(xml: &str) -> Sample {
+        crate::deserialize_synth(xml)
+            .unwrap()
+            .sound
+            .generator
+            .as_subtractive()
+            .unwrap()
+            .osc1
+            .as_sample()
+            .unwrap()
+            .sample
+            .clone()
+    }
+
+    #[test_case(0, Some(53) ; "lowest note")]
+    #[test_case(53, Some(53) ; "top note of the first range")]
+    #[test_case(54, Some(54) ; "second range")]
+    #[test_case(67, Some(68) ; "inside a range")]
+    #[test_case(79, None ; "last range")]
+    #[test_case(127, None ; "highest note")]
+    fn test_range_for_note(note: u8, expected_top_note: Option<u8>) {
+        let sample = load_osc1_sample(include_str!("../data_tests/SYNTHS/SYNT170.XML"));
+        let range = sample.range_for_note(note).unwrap();
+
+        assert_eq!(range.range_top_note, expected_top_note);
+    }
+
+    #[test]
+    fn test_range_for_note_two_ranges() {
+        let sample = load_osc1_sample(include_str!("../data_tests/SYNTHS/SYNT168A.XML"));
+        let ranges = sample.as_sample_ranges().unwrap();
+
+        assert_eq!(sample.range_for_note(72), Some(&ranges[0]));
+        assert_eq!(sample.range_for_note(73), Some(&ranges[1]));
+        assert_eq!(Sample::default().range_for_note(60), None);
+        assert_eq!(Sample::SampleRanges(vec![make_range(Some(60))]).range_for_note(61), None);
+    }
+
+    #[test]
+    fn test_validate_ranges_fixtures() {
+        for xml in [
+            include_str!("../data_tests/SYNTHS/SYNT170.XML"),
+            include_str!("../data_tests/SYNTHS/SYNT168A.XML"),
+        ] {
+            assert_eq!(load_osc1_sample(xml).validate_ranges(), Vec::new());
+        }
+
+        assert_eq!(Sample::default().validate_ranges(), Vec::new());
+    }
+
+    #[test_case(vec![], vec![RangeIssue::NoRange] ; "no range")]
+    #[test_case(vec![Some(60), Some(127)], vec![] ; "highest top note")]
+    #[test_case(vec![Some(60), Some(72)], vec![RangeIssue::UncoveredNotes(72)] ; "uncovered notes")]
+    #[test_case(vec![Some(60), Some(60), None], vec![RangeIssue::DuplicateTopNote { index: 1, previous_index: 0, top_note: 60 }] ; "duplicate")]
+    #[test_case(vec![Some(60), Some(50), None], vec![RangeIssue::DescendingTopNote { index: 1, top_note: 50, previous_top_note: 60 }] ; "descending")]
+    #[test_case(vec![Some(60), None, Some(72), None], vec![RangeIssue::MissingTopNote(1)] ; "missing top note")]
+    fn test_validate_ranges(top_notes: Vec<Option<u8>>, expected: Vec<RangeIssue>) {
+        let sample = Sample::SampleRanges(
+            top_notes
+                .into_iter()
+                .map(make_range)
+                .collect(),
+        );
+
+        assert_eq!(sample.validate_ranges(), expected);
+    }
+
+    #[test_case(0.0, 1.0, Some(10), Some(100) ; "whole zone")]
+    #[test_case(0.0, 0.0, Some(10), Some(10) ; "start")]
+    #[test_case(1.0, 1.0, Some(100), Some(100) ; "end")]
+    #[test_case(0.5, 0.75, Some(55), Some(78) ; "rounded")]
+    fn test_set_loop_fraction(start: f32, end: f32, expected_start_loop: Option<u64>, expected_end_loop: Option<u64>) {
+        let mut zone = make_zone(10, 100, None, None);
+
+        zone.set_loop_fraction(start, end)
+            .unwrap();
+
+        assert_eq!(zone, make_zone(10, 100, expected_start_loop, expected_end_loop));
+    }
+
+    #[test_case(make_zone(10, 100, None, None), -0.1, 1.0, SampleZoneError::LoopFractionOutOfRange ; "negative")]
+    #[test_case(make_zone(10, 100, None, None), 0.0, 1.1, SampleZoneError::LoopFractionOutOfRange ; "greater than 1")]
+    #[test_case(make_zone(10, 100, None, None), f32::NAN, 1.0, SampleZoneError::LoopFractionOutOfRange ; "nan")]
+    #[test_case(make_zone(10, 100, None, None), 0.5, 0.25, SampleZoneError::LoopEndBeforeLoopStart { start: 55u64.into(), end: 33u64.into() } ; "end before start")]
+    #[test_case(make_zone(100, 10, None, None), 0.0, 1.0, SampleZoneError::EndBeforeStart { start: 100u64.into(), end: 10u64.into() } ; "invalid zone")]
+    fn test_set_loop_fraction_error(zone: SampleZone, start: f32, end: f32, expected: SampleZoneError) {
+        let mut edited_zone = zone.clone();
+
+        assert_eq!(edited_zone.set_loop_fraction(start, end), Err(expected));
+        assert_eq!(edited_zone, zone);
+    }
+
+    #[test]
+    fn test_clear_loop() {
+        let mut zone = make_zone(10, 100, Some(20), Some(30));
+
+        zone.clear_loop();
+
+        assert_eq!(zone, make_zone(10, 100, None, None));
+    }
+
+    #[test_case(SamplePlayMode::Cut, None ; "cut")]
+    #[test_case(SamplePlayMode::Once, Some(20) ; "once")]
+    #[test_case(SamplePlayMode::Loop, Some(20) ; "loop")]
+    #[test_case(SamplePlayMode::Stretch, Some(20) ; "stretch")]
+    fn test_set_play_mode(mode: SamplePlayMode, expected_start_loop: Option<u64>) {
+        let mut range = make_range(None);
+
+        range.zone = make_zone(0, 100, Some(20), Some(30));
+
+        let mut oscillator = SampleOscillator {
+            mode: SamplePlayMode::Loop,
+            ..SampleOscillator::new(Sample::SampleRanges(vec![range]))
+        };
+
+        oscillator.set_play_mode(mode);
+
+        let zone = &oscillator
+            .sample
+            .as_sample_ranges()
+            .unwrap()[0]
+            .zone;
+
+        assert_eq!(oscillator.mode, mode);
+        assert_eq!(zone.start_loop, expected_start_loop.map(SamplePosition::new));
+    }
+
+    #[test]
+    fn test_zone_duration() {
+        assert_eq!(SamplePosition::new(90), make_zone(10, 100, None, None).duration());
+        assert_eq!(SamplePosition::new(0), make_zone(100, 10, None, None).duration());
+    }
+}
@@ -0,0 +1,465 @@
+//! Validation of the sounds
+//!
+//! The Deluge does not complain when a patch contains contradictory data, it just behaves strangely.
+//! The checks implemented here report these cases as [PatchIssue].
+
+use crate::{
+    values::{HexU50, OnOff, PatchDestination, PatchSource},
+    Sample, SampleZone, Sound, SubtractiveOscillator, SynthEngine,
+};
+
+/// How bad an issue is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IssueSeverity {
+    /// The patch can be loaded but it will probably not sound as expected.
+    Warning,
+    /// The patch contains data the Deluge can't handle.
+    Error,
+}
+
+/// Identify the kind of issue
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, strum::Display)]
+pub enum IssueCode {
+    /// The zone of a sample is invalid, see [SampleZone::validate].
+    InvalidSampleZone,
+    /// An oscillator playing a sample has no sample file but it's audible.
+    MissingSamplePath,
+    /// The sample ranges are not sorted by top note, or a range without top note is not the last.
+    UnsortedSampleRanges,
+    /// The source of a patch cable is unknown.
+    UnknownPatchSource,
+    /// The destination of a patch cable is unknown.
+    UnknownPatchDestination,
+    /// The destination of a patch cable does not exist for the synth engine.
+    UnavailablePatchDestination,
+    /// Two patch cables have the same source and the same destination.
+    DuplicatePatchCable,
+    /// The parameter controlled by a mod knob is unknown.
+    UnknownModKnobParam,
+    /// The source of the patch amount controlled by a mod knob is unknown.
+    UnknownModKnobSource,
+    /// The count of mod knobs is not the expected one.
+    InvalidModKnobCount,
+    /// The oscillator 2 is synced but its volume is 0.
+    SyncedSilentOscillator,
+    /// The sound can't be heard.
+    SilentSound,
+    /// The selected row of a kit does not exist.
+    SelectedRowOutOfBounds,
+    /// Several rows of a kit have the same name.
+    DuplicateRowName,
+}
+
+/// An issue found in a patch
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatchIssue {
+    pub severity: IssueSeverity,
+    pub code: IssueCode,
+    /// Identify the field, for example "generator.osc1.sample.zone"
+    pub path: String,
+    /// A human readable description
+    pub message: String,
+}
+
+impl PatchIssue {
+    pub fn new(severity: IssueSeverity, code: IssueCode, path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            code,
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(code: IssueCode, path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(IssueSeverity::Warning, code, path, message)
+    }
+
+    pub fn error(code: IssueCode, path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(IssueSeverity::Error, code, path, message)
+    }
+
+    /// Prefix the path, used to report the issues of a sound in a kit row.
+    pub(crate) fn with_path_prefix(mut self, prefix: &str) -> Self {
+        self.path = format!("{}.{}", prefix, self.path);
+        self
+    }
+}
+
+/// The count of mod knobs of a sound, 8 knobs with 2 parameters each.
+pub(crate) const MOD_KNOB_COUNT: usize = 16;
+
+pub(crate) fn validate_sound(sound: &Sound) -> Vec<PatchIssue> {
+    let mut issues = Vec::new();
+
+    validate_generator(sound, &mut issues);
+    validate_cables(sound, &mut issues);
+    validate_mod_knobs(sound, &mut issues);
+
+    if sound.volume == 0u8 {
+        issues.push(PatchIssue::warning(IssueCode::SilentSound, "volume", "the volume is 0"));
+    }
+
+    issues
+}
+
+fn validate_generator(sound: &Sound, issues: &mut Vec<PatchIssue>) {
+    let generator = match &sound.generator {
+        SynthEngine::Subtractive(generator) => generator,
+        SynthEngine::RingMod(_) | SynthEngine::Fm(_) => return,
+    };
+
+    let oscillators = [
+        ("generator.osc1", &generator.osc1, generator.osc1_volume),
+        ("generator.osc2", &generator.osc2, generator.osc2_volume),
+    ];
+
+    for (path, oscillator, volume) in oscillators {
+        if let SubtractiveOscillator::Sample(oscillator) = oscillator {
+            validate_sample(&oscillator.sample, volume, &format!("{}.sample", path), issues);
+        }
+    }
+
+    if generator.osc2_sync == OnOff::On && generator.osc2_volume == 0u8 {
+        issues.push(PatchIssue::warning(
+            IssueCode::SyncedSilentOscillator,
+            "generator.osc2_sync",
+            "the oscillator 2 is synced but its volume is 0",
+        ));
+    }
+
+    if generator.osc1_volume == 0u8 && generator.osc2_volume == 0u8 && generator.noise == 0u8 {
+        issues.push(PatchIssue::warning(
+            IssueCode::SilentSound,
+            "generator",
+            "the volumes of the oscillators and the noise are 0",
+        ));
+    }
+}
+
+fn validate_sample(sample: &Sample, volume: HexU50, path: &str, issues: &mut Vec<PatchIssue>) {
+    match sample {
+        Sample::OneZone(one_zone) => {
+            if one_zone.file_path.is_empty() && volume > 0u8 {
+                issues.push(PatchIssue::warning(
+                    IssueCode::MissingSamplePath,
+                    format!("{}.file_path", path),
+                    "the oscillator is audible but has no sample",
+                ));
+            }
+
+            if let Some(zone) = &one_zone.zone {
+                validate_zone(zone, &format!("{}.zone", path), issues);
+            }
+        }
+        Sample::SampleRanges(ranges) => {
+            let mut previous_top_note: Option<u8> = None;
+
+            for (index, range) in ranges.iter().enumerate() {
+                let range_path = format!("{}.ranges[{}]", path, index);
+                let is_last = index + 1 == ranges.len();
+                let is_sorted = match range.range_top_note {
+                    Some(top_note) => previous_top_note.is_none_or(|previous| previous < top_note),
+                    None => is_last,
+                };
+
+                if !is_sorted {
+                    issues.push(PatchIssue::warning(
+                        IssueCode::UnsortedSampleRanges,
+                        format!("{}.range_top_note", range_path),
+                        "the ranges must be sorted by top note and only the last range has no top note",
+                    ));
+                }
+
+                previous_top_note = range
+                    .range_top_note
+                    .or(previous_top_note);
+                validate_zone(&range.zone, &format!("{}.zone", range_path), issues);
+            }
+        }
+    }
+}
+
+fn validate_zone(zone: &SampleZone, path: &str, issues: &mut Vec<PatchIssue>) {
+    if let Err(error) = zone.validate() {
+        issues.push(PatchIssue::error(IssueCode::InvalidSampleZone, path, error.to_string()));
+    }
+}
+
+fn validate_cables(sound: &Sound, issues: &mut Vec<PatchIssue>) {
+    for (index, cable) in sound.cables.iter().enumerate() {
+        let path = format!("cables[{}]", index);
+
+        if let PatchSource::Custom(source) = &cable.source {
+            issues.push(PatchIssue::warning(
+                IssueCode::UnknownPatchSource,
+                format!("{}.source", path),
+                format!("unknown source '{}'", source),
+            ));
+        }
+
+        if let PatchDestination::Custom(destination) = &cable.destination {
+            issues.push(PatchIssue::warning(
+                IssueCode::UnknownPatchDestination,
+                format!("{}.destination", path),
+                format!("unknown destination '{}'", destination),
+            ));
+        } else if !is_destination_available(&sound.generator, &cable.destination) {
+            issues.push(PatchIssue::warning(
+                IssueCode::UnavailablePatchDestination,
+                format!("{}.destination", path),
+                format!("the destination '{}' does not exist for this synth engine", cable.destination),
+            ));
+        }
+
+        if sound.cables[..index]
+            .iter()
+            .any(|other| other.source == cable.source && other.destination == cable.destination)
+        {
+            issues.push(PatchIssue::warning(
+                IssueCode::DuplicatePatchCable,
+                path,
+                format!("another cable connects '{}' to '{}'", cable.source, cable.destination),
+            ));
+        }
+    }
+}
+
+fn is_destination_available(generator: &SynthEngine, destination: &PatchDestination) -> bool {
+    use PatchDestination::*;
+
+    match destination {
+        Modulator1Volume | Modulator2Volume | Modulator1Pitch | Modulator2Pitch | Modulator1Feedback | Modulator2Feedback
+        | Carrier1Feedback | Carrier2Feedback => generator.is_fm(),
+        LpfFrequency | LpfResonance | HpfFrequency | HpfResonance | OscAPhaseWidth | OscBPhaseWidth | NoiseVolume => {
+            !generator.is_fm()
+        }
+        _ => true,
+    }
+}
+
+fn validate_mod_knobs(sound: &Sound, issues: &mut Vec<PatchIssue>) {
+    if sound.mod_knobs.len() != MOD_KNOB_COUNT {
+        issues.push(PatchIssue::warning(
+            IssueCode::InvalidModKnobCount,
+            "mod_knobs",
+            format!("{} mod knobs instead of {}", sound.mod_knobs.len(), MOD_KNOB_COUNT),
+        ));
+    }
+
+    for (index, mod_knob) in sound.mod_knobs.iter().enumerate() {
+        let path = format!("mod_knobs[{}]", index);
+
+        if let PatchDestination::Custom(param) = PatchDestination::from(mod_knob.control_param.as_str()) {
+            issues.push(PatchIssue::warning(
+                IssueCode::UnknownModKnobParam,
+                format!("{}.control_param", path),
+                format!("unknown parameter '{}'", param),
+            ));
+        }
+
+        if let Some(PatchSource::Custom(source)) = &mod_knob.patch_amount_from_source {
+            issues.push(PatchIssue::warning(
+                IssueCode::UnknownModKnobSource,
+                format!("{}.patch_amount_from_source", path),
+                format!("unknown source '{}'", source),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deserialize_kit, deserialize_synth, values::SamplePath, PatchCable, SampleRange, SubtractiveSynth};
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    fn codes(issues: &[PatchIssue]) -> Vec<(IssueCode, &str)> {
+        issues
+            .iter()
+            .map(|issue| (issue.code, issue.path.as_str()))
+            .collect()
+    }
+
+    fn make_zone(start: u64, end: u64) -> SampleZone {
+        SampleZone {
+            start: start.into(),
+            end: end.into(),
+            start_loop: None,
+            end_loop: None,
+        }
+    }
+
+    #[test]
+    fn test_default_sound_is_valid() {
+        assert_eq!(Vec::<PatchIssue>::new(), Sound::default().validate());
+    }
+
+    #[test]
+    fn test_invalid_zone() {
+        let sound = Sound::new_sample(SamplePath::new("a.wav").unwrap(), 100u64.into(), 10u64.into());
+
+        assert_eq!(
+            vec![(IssueCode::InvalidSampleZone, "generator.osc1.sample.zone")],
+            codes(&sound.validate())
+        );
+        assert_eq!(IssueSeverity::Error, sound.validate()[0].severity);
+    }
+
+    #[test]
+    fn test_missing_sample_path() {
+        let sound = Sound::new_sample(SamplePath::default(), 0u64.into(), 10u64.into());
+
+        assert_eq!(
+            vec![(IssueCode::MissingSamplePath, "generator.osc1.sample.file_path")],
+            codes(&sound.validate())
+        );
+    }
+
+    #[test]
+    fn test_unsorted_sample_ranges() {
+        let make_range = |range_top_note: Option<u8>| SampleRange {
+            range_top_note,
+            transpose: Default::default(),
+            fine_transpose: Default::default(),
+            file_path: SamplePath::new("a.wav").unwrap(),
+            zone: make_zone(0, 10),
+        };
+        let sound = Sound::new_subtractive(
+            SubtractiveOscillator::new_sample(Sample::SampleRanges(vec![
+                make_range(Some(60)),
+                make_range(Some(48)),
+                make_range(None),
+                make_range(None),
+            ])),
+            SubtractiveOscillator::new_sample(Sample::default()),
+        );
+
+        assert_eq!(
+            vec![
+                (
+                    IssueCode::UnsortedSampleRanges,
+                    "generator.osc1.sample.ranges[1].range_top_note"
+                ),
+                (
+                    IssueCode::UnsortedSampleRanges,
+                    "generator.osc1.sample.ranges[2].range_top_note"
+                ),
+            ],
+            codes(&sound.validate())
+        );
+    }
+
+    #[test]
+    fn test_cables() {
+        let mut sound = Sound::new_fm(Default::default(), Default::default());
+
+        sound.cables = vec![
+            PatchCable::new("nowhere".into(), PatchDestination::Volume, 10.into()),
+            PatchCable::new(PatchSource::Lfo1, "lpfMorph".into(), 10.into()),
+            PatchCable::new(PatchSource::Lfo1, PatchDestination::LpfFrequency, 10.into()),
+            PatchCable::new(PatchSource::Lfo1, PatchDestination::Modulator1Volume, 10.into()),
+            PatchCable::new(PatchSource::Lfo1, PatchDestination::Modulator1Volume, 20.into()),
+        ];
+
+        assert_eq!(
+            vec![
+                (IssueCode::UnknownPatchSource, "cables[0].source"),
+                (IssueCode::UnknownPatchDestination, "cables[1].destination"),
+                (IssueCode::UnavailablePatchDestination, "cables[2].destination"),
+                (IssueCode::DuplicatePatchCable, "cables[4]"),
+            ],
+            codes(&sound.validate())
+        );
+    }
+
+    #[test]
+    fn test_mod_knobs() {
+        let mut sound = Sound::default();
+
+        sound.mod_knobs[2].control_param = "removedParam".to_string();
+        sound.mod_knobs[3].patch_amount_from_source = Some("nothing".into());
+        sound.mod_knobs.pop();
+
+        assert_eq!(
+            vec![
+                (IssueCode::InvalidModKnobCount, "mod_knobs"),
+                (IssueCode::UnknownModKnobParam, "mod_knobs[2].control_param"),
+                (IssueCode::UnknownModKnobSource, "mod_knobs[3].patch_amount_from_source"),
+            ],
+            codes(&sound.validate())
+        );
+    }
+
+    #[test_case(0, 15 ; "missing knob")]
+    #[test_case(2, 17 ; "extra knob")]
+    fn test_mod_knob_count(pan_knobs_count: usize, expected_count: usize) {
+        let pan_knob = "\t\t<modKnob controlsParam=\"pan\" />\n";
+        let xml = include_str!("../data_tests/SYNTHS/SYNT184.XML").replace(pan_knob, &pan_knob.repeat(pan_knobs_count));
+        let mut sound = deserialize_synth(&xml).unwrap().sound;
+        let has_count_issue = |sound: &Sound| {
+            sound
+                .validate()
+                .iter()
+                .any(|issue| issue.code == IssueCode::InvalidModKnobCount)
+        };
+
+        assert_eq!(sound.mod_knobs.len(), expected_count);
+        assert!(has_count_issue(&sound));
+
+        sound.normalize_mod_knobs();
+
+        assert_eq!(sound.mod_knobs.len(), MOD_KNOB_COUNT);
+        assert!(!has_count_issue(&sound));
+    }
+
+    #[test]
+    fn test_silent_sound() {
+        let mut sound = Sound::new_subtractive(SubtractiveSynth::default().osc1, SubtractiveSynth::default().osc2);
+        let generator = sound
+            .generator
+            .as_subtractive_mut()
+            .unwrap();
+
+        generator.osc1_volume = 0.into();
+        generator.osc2_sync = OnOff::On;
+        sound.volume = 0.into();
+
+        assert_eq!(
+            vec![
+                (IssueCode::SyncedSilentOscillator, "generator.osc2_sync"),
+                (IssueCode::SilentSound, "generator"),
+                (IssueCode::SilentSound, "volume"),
+            ],
+            codes(&sound.validate())
+        );
+    }
+
+    #[test_case(include_str!("../data_tests/SYNTHS/SYNT184.XML") ; "SYNT184")]
+    #[test_case(include_str!("../data_tests/SYNTHS/SYNT168.XML") ; "SYNT168")]
+    #[test_case(include_str!("../data_tests/SYNTHS/SYNT061.XML") ; "SYNT061")]
+    #[test_case(include_str!("../data_tests/SYNTHS/SYNT008.XML") ; "SYNT008")]
+    #[test_case(include_str!("../data_tests/SYNTHS/Default Test.XML") ; "Default Test")]
+    fn test_data_synths_have_no_error(xml: &str) {
+        let synth = deserialize_synth(xml).unwrap();
+
+        assert!(synth
+            .sound
+            .validate()
+            .iter()
+            .all(|issue| issue.severity != IssueSeverity::Error));
+    }
+
+    #[test_case(include_str!("../data_tests/KITS/KIT030.XML") ; "KIT030")]
+    #[test_case(include_str!("../data_tests/KITS/KIT057.XML") ; "KIT057")]
+    #[test_case(include_str!("../data_tests/KITS/KIT_TEST_SOUNDS_MIDI_GATE.XML") ; "KIT_TEST_SOUNDS_MIDI_GATE")]
+    fn test_data_kits_have_no_error(xml: &str) {
+        let kit = deserialize_kit(xml).unwrap();
+
+        assert!(kit
+            .validate()
+            .iter()
+            .all(|issue| issue.severity != IssueSeverity::Error));
+    }
+}
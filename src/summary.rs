@@ -0,0 +1,288 @@
+//! Short text descriptions of the patches, to inspect them quickly in a terminal.
+//!
+//! The formatting is stable: a change of the text of a summary is a breaking change.
+use crate::{
+    values::{FineTranspose, LpfMode, ModulationFxType, OnOff, SyncLevel, Transpose},
+    Delay, Kit, ModulationFx, RowKit, Sound, SubtractiveOscillator, SynthEngine, WaveformOscillator,
+};
+
+const SEPARATOR: &str = " | ";
+
+impl Sound {
+    /// Describes the sound on a single line
+    ///
+    /// The engine is described by [SynthEngine::summary], followed by the modulation FX if any,
+    /// the delay and the count of patch cables.
+    /// ```
+    /// # use deluge::Sound;
+    /// assert_eq!(
+    ///     Sound::default().summary(),
+    ///     "Subtractive | osc1 Square +0 | osc2 Square +0 | LPF24 50/0 | delay off | 1 cable"
+    /// );
+    /// ```
+    pub fn summary(&self) -> String {
+        let mut parts = vec![self.generator.summary()];
+
+        if let Some(modulation_fx) = modulation_fx_summary(&self.modulation_fx) {
+            parts.push(modulation_fx.to_string());
+        }
+
+        parts.push(delay_summary(&self.delay));
+        parts.push(count_summary(self.cables.len(), "cable"));
+
+        parts.join(SEPARATOR)
+    }
+}
+
+impl SynthEngine {
+    /// Describes the engine and its oscillators on a single line
+    ///
+    /// Each oscillator is described by its type and its transpose, the fine transpose in cents is added when it's not 0.
+    /// The low pass filter of a subtractive synth is described by its mode, its frequency and its resonance.
+    pub fn summary(&self) -> String {
+        let parts = match self {
+            SynthEngine::Subtractive(synth) => vec![
+                "Subtractive".to_string(),
+                subtractive_oscillator_summary("osc1", &synth.osc1),
+                subtractive_oscillator_summary("osc2", &synth.osc2),
+                format!(
+                    "{} {}/{}",
+                    lpf_mode_summary(&synth.lpf_mode),
+                    synth.lpf_frequency,
+                    synth.lpf_resonance
+                ),
+            ],
+            SynthEngine::RingMod(synth) => vec![
+                "Ring mod".to_string(),
+                waveform_oscillator_summary("osc1", &synth.osc1),
+                waveform_oscillator_summary("osc2", &synth.osc2),
+            ],
+            SynthEngine::Fm(synth) => vec![
+                "FM".to_string(),
+                pitch_summary("osc1", synth.osc1.transpose, synth.osc1.fine_transpose),
+                pitch_summary("osc2", synth.osc2.transpose, synth.osc2.fine_transpose),
+                format!(
+                    "{} amount {}",
+                    pitch_summary("mod1", synth.modulator1.transpose, synth.modulator1.fine_transpose),
+                    synth.modulator1.amount
+                ),
+                format!(
+                    "{} amount {}",
+                    pitch_summary("mod2", synth.modulator2.transpose, synth.modulator2.fine_transpose),
+                    synth.modulator2.amount
+                ),
+            ],
+        };
+
+        parts.join(SEPARATOR)
+    }
+}
+
+impl RowKit {
+    /// Describes the row on a single line, the summary of a sound row starts with its name
+    pub fn summary(&self) -> String {
+        match self {
+            RowKit::Sound(row) => format!("{}{}{}", row.name, SEPARATOR, row.sound.summary()),
+            RowKit::Midi(row) => format!("MIDI channel {} note {}", row.channel, row.note),
+            RowKit::CvGate(row) => format!("CV gate {}", row.channel),
+        }
+    }
+}
+
+impl Kit {
+    /// Describes the kit, the first line gives the count of rows followed by one line per row
+    ///
+    /// Each row is described by [RowKit::summary].
+    pub fn summary(&self) -> String {
+        std::iter::once(format!("Kit{}{}", SEPARATOR, count_summary(self.rows.len(), "row")))
+            .chain(self.rows.iter().map(RowKit::summary))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+fn subtractive_oscillator_summary(name: &str, oscillator: &SubtractiveOscillator) -> String {
+    pitch_summary(
+        &format!("{} {}", name, oscillator.osc_type()),
+        oscillator.transpose(),
+        oscillator.fine_transpose(),
+    )
+}
+
+fn waveform_oscillator_summary(name: &str, oscillator: &WaveformOscillator) -> String {
+    pitch_summary(
+        &format!("{} {}", name, oscillator.osc_type),
+        oscillator.transpose,
+        oscillator.fine_transpose,
+    )
+}
+
+fn pitch_summary(name: &str, transpose: Transpose, fine_transpose: FineTranspose) -> String {
+    match fine_transpose.as_i8() {
+        0 => format!("{} {:+}", name, transpose.as_i8()),
+        cents => format!("{} {:+} {:+}c", name, transpose.as_i8(), cents),
+    }
+}
+
+fn lpf_mode_summary(lpf_mode: &LpfMode) -> &'static str {
+    match lpf_mode {
+        LpfMode::Lpf24 => "LPF24",
+        LpfMode::Lpf12 => "LPF12",
+        LpfMode::Lpf24Drive => "LPF24 drive",
+    }
+}
+
+fn modulation_fx_summary(modulation_fx: &ModulationFx) -> Option<&'static str> {
+    match modulation_fx.fx_type() {
+        ModulationFxType::Off => None,
+        ModulationFxType::Flanger => Some("flanger"),
+        ModulationFxType::Chorus => Some("chorus"),
+        ModulationFxType::Phaser => Some("phaser"),
+    }
+}
+
+/// The delay is off when its amount is 0, its time is the sync level or the rate if it's not synchronized
+fn delay_summary(delay: &Delay) -> String {
+    if delay.amount.as_u8() == 0 {
+        return "delay off".to_string();
+    }
+
+    let mut words = vec![
+        "delay".to_string(),
+        match sync_level_summary(delay.sync_level) {
+            Some(sync_level) => sync_level,
+            None => format!("rate {}", delay.rate),
+        },
+    ];
+
+    if delay.ping_pong == OnOff::On {
+        words.push("pingpong".to_string());
+    }
+
+    if delay.analog == OnOff::On {
+        words.push("analog".to_string());
+    }
+
+    words.join(" ")
+}
+
+fn sync_level_summary(sync_level: SyncLevel) -> Option<String> {
+    match sync_level {
+        SyncLevel::Off => None,
+        sync_level => Some(sync_level.to_string()),
+    }
+}
+
+fn count_summary(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        count => format!("{} {}s", count, noun),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        deserialize_kit, deserialize_synth, Delay, Kit, RingModSynth, RowKit, Sound, SyncLevel, SynthEngine, WaveformOscillator,
+    };
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    #[test_case(
+        include_str!("data_tests/SYNTHS/Default Test.XML"),
+        "Subtractive | osc1 Square +0 | osc2 Square +0 | LPF24 50/0 | delay off | 1 cable" ;
+        "default"
+    )]
+    #[test_case(
+        include_str!("data_tests/SYNTHS/SYNT061A.XML"),
+        "Subtractive | osc1 Square +0 | osc2 Saw +0 +8c | LPF24 32/0 | delay off | 3 cables" ;
+        "fine transpose"
+    )]
+    #[test_case(
+        include_str!("data_tests/SYNTHS/SYNT002A.XML"),
+        "Subtractive | osc1 AnalogSquare +0 | osc2 Saw -12 -2c | LPF24 9/17 | delay 1/16 pingpong analog | 3 cables" ;
+        "analog delay"
+    )]
+    #[test_case(
+        include_str!("data_tests/SYNTHS/SYNT004A.XML"),
+        "Subtractive | osc1 Square +0 | osc2 AnalogSquare -12 -2c | LPF24 20/23 | chorus | delay off | 5 cables" ;
+        "chorus"
+    )]
+    #[test_case(
+        include_str!("data_tests/SYNTHS/SYNT168A.XML"),
+        "Subtractive | osc1 Sample +0 | osc2 Square +0 | LPF24 41/0 | delay off | 3 cables" ;
+        "sample"
+    )]
+    #[test_case(
+        include_str!("data_tests/SYNTHS/SYNT177.XML"),
+        "Ring mod | osc1 Triangle +0 | osc2 Sine +17 | delay 1/16 pingpong | 2 cables" ;
+        "ring mod"
+    )]
+    #[test_case(
+        include_str!("data_tests/SYNTHS/SYNT176.XML"),
+        "FM | osc1 +0 | osc2 +32 | mod1 +0 amount 10 | mod2 -12 amount 11 | delay off | 5 cables" ;
+        "fm"
+    )]
+    fn test_synth_summary(xml: &str, expected_summary: &str) {
+        let synth = deserialize_synth(xml).unwrap();
+
+        assert_eq!(synth.sound.summary(), expected_summary);
+    }
+
+    #[test]
+    fn test_kit_summary() {
+        let kit = deserialize_kit(include_str!("data_tests/KITS/KIT_TEST_SOUNDS_MIDI_GATE.XML")).unwrap();
+        let sound_rows =
+            ["halftime_goodie | Subtractive | osc1 Sample +0 | osc2 Sample +0 | LPF24 50/0 | delay off | 1 cable".to_string()]
+                .into_iter()
+                .chain((2..=7).map(|index| {
+                    format!(
+                        "halftime_goodie{} | Subtractive | osc1 Sample +0 | osc2 Square +0 | LPF24 50/0 | delay off | 1 cable",
+                        index
+                    )
+                }));
+        let expected_summary = ["Kit | 9 rows", "MIDI channel 1 note 63 (D#3)", "CV gate 3"]
+            .map(String::from)
+            .into_iter()
+            .chain(sound_rows)
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        assert_eq!(kit.summary(), expected_summary);
+    }
+
+    #[test]
+    fn test_empty_kit_summary() {
+        let kit = Kit::new(Vec::new());
+
+        assert_eq!(kit.summary(), "Kit | 0 rows");
+    }
+
+    #[test_case(Delay::synced(SyncLevel::Eighth, 20), "delay 1/8 pingpong" ; "synced")]
+    #[test_case(Delay::with_rate(30, 20), "delay rate 30 pingpong" ; "rate")]
+    #[test_case(Delay::synced(SyncLevel::OneBar, 0), "delay off" ; "off")]
+    fn test_delay_summary(delay: Delay, expected_summary: &str) {
+        let sound = Sound {
+            generator: SynthEngine::from(RingModSynth::new(
+                WaveformOscillator::new_saw(),
+                WaveformOscillator::new_sine(),
+            )),
+            delay,
+            ..Sound::default()
+        };
+
+        assert_eq!(
+            sound.summary(),
+            format!("Ring mod | osc1 Saw +0 | osc2 Sine +0 | {} | 1 cable", expected_summary)
+        );
+    }
+
+    #[test]
+    fn test_row_summary() {
+        assert_eq!(
+            RowKit::new_midi(10.into(), 60.into()).summary(),
+            "MIDI channel 10 note 60 (C3)"
+        );
+        assert_eq!(RowKit::new_cv_gate(2.into()).summary(), "CV gate 2");
+    }
+}
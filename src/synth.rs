@@ -1,23 +1,153 @@
-use crate::Sound;
+use std::collections::BTreeSet;
 
-/// Default implementation for Kit
+use crate::{range_check::check_ranges, sound::MOD_KNOB_COUNT, Metadata, SamplePath, SerializationError, Sound, SoundRow};
+
+/// A synth patch
 ///
 /// The default Synth is exactly like the Deluge would create it for a default synth patch without any user changes.
-#[derive(Clone, Debug, PartialEq, Eq, Default)]
+/// It's checked against the file "data_tests/default/SYNT Default Test.XML" saved by a Deluge running the firmware 3.1.5.
+///
+/// To build a Synth, you can use [SynthBuilder]:
+/// ```
+/// # use deluge::{FmCarrier, Sound, SynthBuilder};
+/// let synth = SynthBuilder::default()
+///     .sound(Sound::new_fm(FmCarrier::default(), FmCarrier::default()))
+///     .build()
+///     .unwrap();
+///
+/// assert!(synth.sound.generator.as_fm().is_some());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Default, derive_builder::Builder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[builder(default, build_fn(validate = "Self::validate"))]
 pub struct Synth {
     pub sound: Sound,
+    /// The librarian information, see [Metadata]
+    pub metadata: Option<Metadata>,
+}
+
+check_ranges!(Synth, SynthBuilder { sound });
+
+// SynthBuilder is generated by derive_builder::Builder.
+impl SynthBuilder {
+    /// Check the ranges and the count of mod knobs, a synth that can't be serialized can't be built
+    fn validate(&self) -> Result<(), String> {
+        self.check_ranges()?;
+
+        match self
+            .sound
+            .as_ref()
+            .map(|sound| sound.mod_knobs.len())
+        {
+            Some(count) if count != MOD_KNOB_COUNT => Err(SerializationError::InvalidModKnobCount(count).to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Synth {
+    /// Create a synth without metadata
+    pub fn new(sound: Sound) -> Self {
+        Self { sound, metadata: None }
+    }
+
+    /// Create a synth without metadata playing the sound of a kit row, see [RowKit::from_synth](crate::RowKit::from_synth)
+    pub fn from_sound_row(row: &SoundRow) -> Self {
+        Self::new(row.sound.as_ref().clone())
+    }
+
+    /// Gets all the sample paths used by this synth.
+    pub fn get_sample_paths(&self) -> BTreeSet<SamplePath> {
+        self.sound.get_sample_paths()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{deserialize_synth, Synth};
+    use crate::{deserialize_synth, serialize_synth, Metadata, SamplePath, Sound, Synth, SynthBuilder, WaveformOscillator};
     use pretty_assertions::assert_eq;
+    use std::collections::BTreeSet;
 
     #[test]
     fn default_synth_test() {
         let default_synth = Synth::default();
-        let expected_default_synth = deserialize_synth(include_str!("data_tests/default/SYNTh Default.XML")).unwrap();
+        let expected_default_synth = deserialize_synth(include_str!("data_tests/default/SYNT Default Test.XML")).unwrap();
 
         assert_eq!(expected_default_synth, default_synth)
     }
+
+    #[test]
+    fn test_default_synth_reloaded() {
+        let default_synth = Synth::default();
+
+        assert_eq!(
+            deserialize_synth(&serialize_synth(&default_synth).unwrap()).unwrap(),
+            default_synth
+        );
+    }
+
+    #[test]
+    fn test_synth_builder() {
+        assert_eq!(SynthBuilder::default().build().unwrap(), Synth::default());
+
+        let metadata = Metadata::default();
+        let sound = Sound::new_ringmod(WaveformOscillator::default(), WaveformOscillator::default());
+        let synth = SynthBuilder::default()
+            .sound(sound.clone())
+            .metadata(Some(metadata.clone()))
+            .build()
+            .unwrap();
+
+        assert_eq!(synth.sound, sound);
+        assert_eq!(synth.metadata, Some(metadata));
+    }
+
+    #[test]
+    fn test_synth_builder_validation() {
+        let mut sound = Sound::default();
+
+        sound.mod_knobs.pop();
+
+        assert!(SynthBuilder::default()
+            .sound(sound)
+            .build()
+            .is_err());
+
+        let sound = Sound {
+            volume: 51.into(),
+            ..Sound::default()
+        };
+
+        assert!(SynthBuilder::default()
+            .sound(sound)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_get_sample_paths_sample_ranges() {
+        let synth = deserialize_synth(include_str!("data_tests/SYNTHS/SYNT168A.XML")).unwrap();
+        let expected_paths = synth
+            .sound
+            .generator
+            .as_subtractive()
+            .unwrap()
+            .osc1
+            .as_sample()
+            .unwrap()
+            .sample
+            .get_sample_paths()
+            .into_iter()
+            .collect::<BTreeSet<SamplePath>>();
+
+        assert!(expected_paths.len() > 1);
+        assert_eq!(synth.get_sample_paths(), expected_paths);
+    }
+
+    #[test]
+    fn test_get_sample_paths_default_synth() {
+        assert!(Synth::default()
+            .get_sample_paths()
+            .is_empty());
+    }
 }
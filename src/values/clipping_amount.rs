@@ -0,0 +1,134 @@
+//! The amount of saturation of a sound, in the range [0; 16].
+//! This type is formatted as an unsigned decimal integer.
+use crate::values::Uint8;
+use crate::SerializationError;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+type ClippingAmountValue = Uint8<0, 16, 0>;
+
+/// The saturation of a sound
+///
+/// The higher the amount, the more the signal is amplified before being clipped.
+/// The value is displayed as the amount stored in the files, the labels shown by the device are not verified.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ClippingAmount(ClippingAmountValue);
+
+impl ClippingAmount {
+    pub const MIN: u8 = ClippingAmountValue::MIN;
+    pub const MAX: u8 = ClippingAmountValue::MAX;
+
+    /// Create a value, the range [0; 16] is only checked by the debug builds, see [ClippingAmount::try_new].
+    pub fn new(value: u8) -> Self {
+        Self(ClippingAmountValue::new(value))
+    }
+
+    /// Create a value, returns an error if it's not in the range [0; 16].
+    pub fn try_new(value: u8) -> Result<Self, SerializationError> {
+        Ok(Self(ClippingAmountValue::try_new(value)?))
+    }
+
+    /// No saturation, the default of the device
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+
+    /// Returns true if the value is in the range [0; 16].
+    pub fn is_valid(&self) -> bool {
+        self.0.is_valid()
+    }
+
+    pub fn as_u8(self) -> u8 {
+        self.0.as_u8()
+    }
+
+    /// Iterate over all the values in the range [0; 16]
+    pub fn iter() -> impl Iterator<Item = Self> {
+        ClippingAmountValue::iter().map(Self)
+    }
+}
+
+impl From<u8> for ClippingAmount {
+    fn from(value: u8) -> Self {
+        Self::new(value)
+    }
+}
+
+impl std::fmt::Display for ClippingAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_u8())
+    }
+}
+
+impl FromStr for ClippingAmount {
+    type Err = SerializationError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(Self(ClippingAmountValue::from_str(text.trim())?))
+    }
+}
+
+impl Serialize for ClippingAmount {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ClippingAmount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        ClippingAmountValue::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deserialize_synth_with_clamped_values, SerializationOptions, Synth};
+    use test_case::test_case;
+
+    /// Load a synth keeping the saturation even if it's out of range
+    fn synth_with_saturation(saturation: &str) -> Synth {
+        let xml = include_str!("../data_tests/SYNTHS/SYNT184.XML")
+            .replace("clippingAmount=\"4\"", &format!("clippingAmount=\"{}\"", saturation));
+
+        deserialize_synth_with_clamped_values(&xml, &SerializationOptions::default())
+            .unwrap()
+            .0
+    }
+
+    #[test_case(ClippingAmount::none(), "0" ; "none")]
+    #[test_case(ClippingAmount::new(4), "4" ; "4")]
+    #[test_case(ClippingAmount::new(16), "16" ; "max")]
+    fn test_display(value: ClippingAmount, expected: &str) {
+        assert_eq!(value.to_string(), expected);
+        assert_eq!(ClippingAmount::from_str(expected).unwrap(), value);
+    }
+
+    #[test_case(ClippingAmount::none(), "0" ; "none")]
+    #[test_case(ClippingAmount::new(12), "12" ; "12")]
+    fn test_serialization(value: ClippingAmount, expected: &str) {
+        assert_eq!(serde_plain::to_string(&value).unwrap(), expected);
+        assert_eq!(serde_plain::from_str::<ClippingAmount>(expected).unwrap(), value);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        assert!(ClippingAmount::try_new(17).is_err());
+        let synth = synth_with_saturation("17");
+
+        assert!(!synth
+            .sound
+            .distorsion
+            .saturation
+            .is_valid());
+        assert!(serde_plain::from_str::<ClippingAmount>("17").is_err());
+        assert_eq!(ClippingAmount::iter().count(), 17);
+    }
+}
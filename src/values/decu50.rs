@@ -1,14 +1,33 @@
-use crate::values::{map_50_i32, map_i32_50, read_i32, SerializationError};
+//! Store an unsigned integer in the range [0; 50].
+//! Unlike [HexU50](crate::HexU50), this type of value is formatted as a signed 32-bits decimal integer:
+//! 0 is stored as "-2147483648", 25 as "0" and 50 as "2147483647".
+use crate::values::{
+    map_50_i32, map_i32_50, read_i32,
+    u50::{u50_value_helpers, U50_MAX},
+    SerializationError,
+};
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
-
+use std::str::FromStr;
+
+/// A value in the range [0; 50] stored as a decimal number, used by [Sound::sidechain_send](crate::Sound::sidechain_send)
+/// ```
+/// # use deluge::DecU50;
+/// let value = DecU50::from(50);
+///
+/// assert_eq!(value.to_string(), "50");
+/// assert_eq!(DecU50::parse("2147483647").unwrap(), value);
+/// assert_eq!("50".parse::<DecU50>().unwrap(), value);
+/// ```
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct DecU50(u8);
 
 impl DecU50 {
+    /// Create a value without checking it's in the range [0; 50], see [DecU50::try_new].
     pub fn new(value: u8) -> Self {
         Self(value)
     }
 
+    /// Parse the decimal value stored in the files, see [DecU50::from_str] to parse a value in the range [0; 50].
     pub fn parse(text: &str) -> Result<Self, SerializationError> {
         read_decu50(text)
     }
@@ -18,6 +37,14 @@ impl DecU50 {
     }
 }
 
+u50_value_helpers!(DecU50);
+
+impl From<u8> for DecU50 {
+    fn from(value: u8) -> Self {
+        DecU50::new(value)
+    }
+}
+
 impl Serialize for DecU50 {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -35,7 +62,7 @@ impl<'de> Visitor<'de> for DecU50Visitor {
     type Value = DecU50;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
-        formatter.write_str("a string with unsigned hexadecimal number")
+        formatter.write_str("a signed decimal number")
     }
 
     fn visit_i32<E>(self, v: i32) -> Result<Self::Value, E>
@@ -44,6 +71,24 @@ impl<'de> Visitor<'de> for DecU50Visitor {
     {
         Ok(map_i32_decu50(v))
     }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let v = i32::try_from(v).map_err(|_| E::custom(format!("value '{}' is out of range", v)))?;
+
+        self.visit_i32(v)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let v = i32::try_from(v).map_err(|_| E::custom(format!("value '{}' is out of range", v)))?;
+
+        self.visit_i32(v)
+    }
 }
 
 impl<'de> Deserialize<'de> for DecU50 {
@@ -61,6 +106,24 @@ impl std::fmt::Display for DecU50 {
     }
 }
 
+impl FromStr for DecU50 {
+    type Err = SerializationError;
+
+    /// Parse a value in the range [0; 50]
+    ///
+    /// Unlike [HexU50](crate::HexU50), the value stored in the files can't be distinguished from the value displayed,
+    /// use [DecU50::parse] to parse it.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let value = u8::from_str(text.trim())?;
+
+        if value > U50_MAX {
+            return Err(SerializationError::Overflow(value.to_string(), U50_MAX.to_string()));
+        }
+
+        Ok(DecU50(value))
+    }
+}
+
 fn map_i32_decu50(value: i32) -> DecU50 {
     DecU50(map_i32_50(value))
 }
@@ -69,7 +132,7 @@ fn map_decu50_i32(value: DecU50) -> i32 {
     map_50_i32(value.0)
 }
 
-/// Read a 0-50 value encoded as unsigned u32 hexadecimal
+/// Read a 0-50 value encoded as signed i32 decimal
 fn read_decu50(text: &str) -> Result<DecU50, SerializationError> {
     read_i32(text).map(map_i32_decu50)
 }
@@ -79,8 +142,60 @@ mod tests {
     use super::*;
     use test_case::test_case;
 
+    #[test_case("-2147483648", DecU50(0); "0")]
+    #[test_case("-2061584303", DecU50(1); "1")]
+    #[test_case("-429496748", DecU50(20); "20")]
+    #[test_case("0", DecU50(25); "25")]
+    #[test_case("2061584257", DecU50(49); "49")]
     #[test_case("2147483647", DecU50(50); "50")]
     fn test_parse_decu50(input: &str, expected: DecU50) {
         assert_eq!(expected, DecU50::parse(input).unwrap());
+        assert_eq!(input, serde_plain::to_string(&expected).unwrap());
+    }
+
+    #[test_case("0", DecU50(0) ; "0")]
+    #[test_case("50", DecU50(50) ; "50")]
+    #[test_case(" 20 ", DecU50(20) ; "spaces")]
+    fn test_from_str(input: &str, expected: DecU50) {
+        assert_eq!(expected, DecU50::from_str(input).unwrap());
+    }
+
+    #[test_case("51" ; "overflow")]
+    #[test_case("-1" ; "negative")]
+    #[test_case("2147483647" ; "stored value")]
+    fn test_from_str_invalid(input: &str) {
+        assert!(DecU50::from_str(input).is_err());
+    }
+
+    #[test]
+    fn test_from_u8() {
+        assert_eq!(DecU50::from(20), DecU50::new(20));
+        assert_eq!(DecU50::from(20).as_u8(), 20);
+        assert_eq!(DecU50::from(20).to_string(), "20");
+    }
+
+    #[test]
+    fn test_arithmetic_stays_in_range() {
+        for value in 0..=50u8 {
+            for delta in i8::MIN..=i8::MAX {
+                let value = DecU50(value);
+
+                for result in [value + delta, value - delta] {
+                    assert!(result <= 50u8);
+                    assert_eq!(result, map_i32_decu50(map_decu50_i32(result)));
+                }
+
+                if let Some(result) = value.checked_sub(delta) {
+                    assert_eq!(result, value - delta);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_percent() {
+        assert_eq!(DecU50(25).as_percent(), 50f32);
+        assert_eq!(DecU50::from_percent(100f32), DecU50(50));
+        assert_eq!(DecU50::from_percent(-1f32), DecU50(0));
     }
 }
@@ -1,14 +1,17 @@
 //! Store an unsigned integer in the range [0; 50].
 //! This type of value is formatted as an 32-bits unsigned integer hexadecimal.
 use crate::values::{
+    u50::{u50_value_helpers, U50_MAX},
     SerializationError, {map_50_i32, map_i32_50, map_i32_u32, map_u32_i32, read_hexadecimal_u32, write_hexadecimal_u32},
 };
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct HexU50(u8);
 
 impl HexU50 {
+    /// Create a value without checking it's in the range [0; 50], see [HexU50::try_new].
     pub fn new(value: u8) -> Self {
         Self(value)
     }
@@ -22,6 +25,8 @@ impl HexU50 {
     }
 }
 
+u50_value_helpers!(HexU50);
+
 impl From<u8> for HexU50 {
     fn from(value: u8) -> Self {
         HexU50::new(value)
@@ -71,6 +76,27 @@ impl std::fmt::Display for HexU50 {
     }
 }
 
+impl FromStr for HexU50 {
+    type Err = SerializationError;
+
+    /// Parse either the value in the range [0; 50] or the hexadecimal value stored in the files, prefixed by "0x".
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        if text.starts_with("0x") {
+            return read_hexu50(text);
+        }
+
+        let value = u8::from_str(text)?;
+
+        if value > U50_MAX {
+            return Err(SerializationError::Overflow(value.to_string(), U50_MAX.to_string()));
+        }
+
+        Ok(HexU50(value))
+    }
+}
+
 fn map_i32_hexu50(value: i32) -> HexU50 {
     HexU50(map_i32_50(value))
 }
@@ -235,4 +261,62 @@ mod tests {
         assert_eq!(2147483647u32, map_i32_u32(2147483647i32).unwrap());
         assert_eq!("0x7FFFFFFF", write_hexadecimal_u32(2147483647u32));
     }
+
+    #[test]
+    fn test_arithmetic_stays_in_range() {
+        for value in 0..=50u8 {
+            for delta in i8::MIN..=i8::MAX {
+                let value = HexU50(value);
+
+                for result in [value + delta, value - delta] {
+                    assert!(result <= 50u8);
+                    assert_eq!(result, read_hexu50(&write_hexu50(result).unwrap()).unwrap());
+                }
+
+                if let Some(result) = value.checked_add(delta) {
+                    assert_eq!(result, value + delta);
+                }
+            }
+        }
+    }
+
+    #[test_case(HexU50(10), 5, HexU50(15) ; "add")]
+    #[test_case(HexU50(10), -5, HexU50(5) ; "add negative")]
+    #[test_case(HexU50(48), 5, HexU50(50) ; "saturate max")]
+    #[test_case(HexU50(2), -5, HexU50(0) ; "saturate min")]
+    fn test_add(value: HexU50, delta: i8, expected: HexU50) {
+        assert_eq!(expected, value + delta);
+        assert_eq!(expected, value - -delta);
+    }
+
+    #[test_case(HexU50(10), 5, Some(HexU50(15)) ; "in range")]
+    #[test_case(HexU50(48), 5, None ; "overflow")]
+    #[test_case(HexU50(2), -5, None ; "underflow")]
+    fn test_checked_add(value: HexU50, delta: i8, expected: Option<HexU50>) {
+        assert_eq!(expected, value.checked_add(delta));
+    }
+
+    #[test_case(0f32, HexU50(0) ; "0")]
+    #[test_case(50f32, HexU50(25) ; "50")]
+    #[test_case(100f32, HexU50(50) ; "100")]
+    #[test_case(-10f32, HexU50(0) ; "negative")]
+    #[test_case(150f32, HexU50(50) ; "more than 100")]
+    #[test_case(f32::NAN, HexU50(0) ; "nan")]
+    fn test_from_percent(percent: f32, expected: HexU50) {
+        assert_eq!(expected, HexU50::from_percent(percent));
+    }
+
+    #[test]
+    fn test_percent_round_trip() {
+        for value in 0..=50u8 {
+            assert_eq!(HexU50(value), HexU50::from_percent(HexU50(value).as_percent()));
+        }
+    }
+
+    #[test]
+    fn test_compare_with_u8() {
+        assert!(HexU50(10) < 11u8);
+        assert!(HexU50(10) > 9u8);
+        assert!(HexU50(10) == 10u8);
+    }
 }
@@ -1,6 +1,9 @@
 //! Strong 8-bit integer constrained to a range defined at compile time.
 
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+use crate::{serialization::value_policy, SerializationError};
 
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub struct Int8<const MIN: i8, const MAX: i8, const DEFAULT: i8> {
@@ -26,22 +29,31 @@ impl<const MIN: i8, const MAX: i8, const DEFAULT: i8> From<i8> for Int8<MIN, MAX
 }
 
 impl<const MIN: i8, const MAX: i8, const DEFAULT: i8> Int8<MIN, MAX, DEFAULT> {
-    const MIN: i8 = MIN;
-    const MAX: i8 = MAX;
-
-    fn check(val: i8) -> Self {
-        debug_assert!(
-            val >= Self::MIN && val <= Self::MAX,
-            "{} <= {} <= {}",
-            Self::MIN,
-            val,
-            Self::MAX
-        );
+    pub const MIN: i8 = MIN;
+    pub const MAX: i8 = MAX;
+
+    /// Create a value, the range [MIN; MAX] is only checked by the debug builds, see [Int8::try_new].
+    pub fn new(val: i8) -> Self {
+        debug_assert!((MIN..=MAX).contains(&val), "{} <= {} <= {}", MIN, val, MAX);
         Self { val }
     }
 
-    pub fn new(val: i8) -> Self {
-        Self::check(val)
+    /// Create a value, returns an error if it's not in the range [MIN; MAX].
+    pub fn try_new(val: i8) -> Result<Self, SerializationError> {
+        if val > MAX {
+            return Err(SerializationError::Overflow(val.to_string(), MAX.to_string()));
+        }
+
+        if val < MIN {
+            return Err(SerializationError::Underflow(val.to_string(), MIN.to_string()));
+        }
+
+        Ok(Self { val })
+    }
+
+    /// Returns true if the value is in the range [MIN; MAX].
+    pub fn is_valid(&self) -> bool {
+        (MIN..=MAX).contains(&self.val)
     }
 }
 
@@ -67,18 +79,25 @@ impl<'de, const MIN: i8, const MAX: i8, const DEFAULT: i8> Visitor<'de> for Uint
         )
     }
 
-    fn visit_i8<E>(self, v: i8) -> Result<Self::Value, E>
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
     {
-        if v > MAX {
-            return Err(E::custom(format!("value '{}' is too big, can't be greater than {}", v, MAX)));
-        }
-        if v < MIN {
-            return Err(E::custom(format!("value '{}' is too small, can't be lesser than {}", v, MIN)));
-        }
+        let value = value_policy::check_range(v, MIN.into(), MAX.into())
+            .ok_or_else(|| E::custom(format!("value '{}' is out of range [{}; {}]", v, MIN, MAX)))?;
+        let value = i8::try_from(value).map_err(|_| E::custom(format!("value '{}' is out of range", v)))?;
 
-        Ok(Self::Value::new(v))
+        // Not created with Int8::new, the value is out of range when it's loaded with OutOfRange::Keep.
+        Ok(Int8 { val: value })
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let v = i64::try_from(v).map_err(|_| E::custom(format!("value '{}' is out of range", v)))?;
+
+        self.visit_i64(v)
     }
 }
 
@@ -87,7 +106,7 @@ impl<'de, const MIN: i8, const MAX: i8, const DEFAULT: i8> Deserialize<'de> for
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_i8(Uint8Visitor)
+        deserializer.deserialize_i64(Uint8Visitor)
     }
 }
 
@@ -96,3 +115,11 @@ impl<const MIN: i8, const MAX: i8, const DEFAULT: i8> std::fmt::Display for Int8
         write!(f, "{}", self.val)
     }
 }
+
+impl<const MIN: i8, const MAX: i8, const DEFAULT: i8> FromStr for Int8<MIN, MAX, DEFAULT> {
+    type Err = SerializationError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::try_new(i8::from_str(text.trim())?)
+    }
+}
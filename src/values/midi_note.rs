@@ -0,0 +1,353 @@
+use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+use crate::{serialization::value_policy, SerializationError};
+
+const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// A MIDI note in the range [0; 127]
+///
+/// Like the Deluge, the note 60 is named C3 so the note 0 is C-2.
+/// The note is displayed with its number and its name, both forms can be parsed:
+/// ```
+/// # use deluge::MidiNote;
+/// let note: MidiNote = "C#3".parse().unwrap();
+///
+/// assert_eq!(note.as_u8(), 61);
+/// assert_eq!(note.to_string(), "61 (C#3)");
+/// assert_eq!("61 (C#3)".parse::<MidiNote>().unwrap(), note);
+/// assert_eq!("61".parse::<MidiNote>().unwrap(), note);
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Debug, Default)]
+pub struct MidiNote {
+    val: u8,
+}
+
+impl MidiNote {
+    pub const MAX: u8 = 127;
+
+    /// Create a note without checking it's in the range [0; 127], see [MidiNote::try_new].
+    pub fn new(val: u8) -> Self {
+        Self { val }
+    }
+
+    /// Create a note, returns an error if it's greater than 127.
+    pub fn try_new(val: u8) -> Result<Self, SerializationError> {
+        if val > Self::MAX {
+            return Err(SerializationError::Overflow(val.to_string(), Self::MAX.to_string()));
+        }
+
+        Ok(Self { val })
+    }
+
+    /// Returns true if the note is in the range [0; 127].
+    pub fn is_valid(&self) -> bool {
+        self.val <= Self::MAX
+    }
+
+    pub fn as_u8(self) -> u8 {
+        self.val
+    }
+
+    /// Get the name of the note, for example "C3" for the note 60
+    pub fn name(self) -> String {
+        format_name(self.val, OctaveConvention::Deluge)
+    }
+
+    /// Parse a note name like "C3", "C#3" or "Db3"
+    fn parse_name(text: &str) -> Result<Self, SerializationError> {
+        match note_name_to_number(text, OctaveConvention::Deluge) {
+            Ok(note) => Ok(Self::new(note)),
+            Err(NoteParseError::InvalidName(_)) => Err(SerializationError::InvalidMidiNote(text.to_string())),
+            Err(NoteParseError::OutOfRange(note)) if note < 0 => {
+                Err(SerializationError::Underflow(note.to_string(), 0.to_string()))
+            }
+            Err(NoteParseError::OutOfRange(note)) => Err(SerializationError::Overflow(note.to_string(), Self::MAX.to_string())),
+        }
+    }
+}
+
+/// The numbering of the octaves used to name the notes
+///
+/// Both conventions only differ by the octave number, the note 60 is C3 on the Deluge and C4 in most MIDI software.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OctaveConvention {
+    /// The note 60 is C3 and the note 0 is C-2, like the Deluge displays them
+    #[default]
+    Deluge,
+    /// The note 60 is C4 and the note 0 is C-1, like the scientific pitch notation
+    Midi,
+}
+
+impl OctaveConvention {
+    /// The octave of the note 0
+    fn lowest_octave(self) -> i32 {
+        match self {
+            OctaveConvention::Deluge => -2,
+            OctaveConvention::Midi => -1,
+        }
+    }
+}
+
+/// Error returned when a note name can't be converted to a MIDI note number, or the reverse
+#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone)]
+pub enum NoteParseError {
+    #[error("invalid note name '{0}', expected a letter from A to G, an optional '#' or 'b' and an octave like 'C#3'")]
+    InvalidName(String),
+
+    #[error("the note {0} is out of the MIDI range [0; 127]")]
+    OutOfRange(i32),
+}
+
+/// Convert a note name like "C3", "C#3" or "Db3" to a MIDI note number
+///
+/// The letter is case insensitive, the octave can be negative. The octave numbering is selected by the convention:
+/// ```
+/// # use deluge::{note_name_to_number, OctaveConvention};
+/// assert_eq!(note_name_to_number("C#3", OctaveConvention::Deluge).unwrap(), 61);
+/// assert_eq!(note_name_to_number("C#3", OctaveConvention::Midi).unwrap(), 49);
+/// ```
+pub fn note_name_to_number(name: &str, convention: OctaveConvention) -> Result<u8, NoteParseError> {
+    let invalid_name = || NoteParseError::InvalidName(name.to_string());
+    let mut chars = name.chars();
+    let letter = chars
+        .next()
+        .ok_or_else(invalid_name)?
+        .to_ascii_uppercase();
+    let mut semitone = match letter {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return Err(invalid_name()),
+    };
+    let mut octave_text = chars.as_str();
+
+    if let Some(rest) = octave_text.strip_prefix('#') {
+        semitone += 1;
+        octave_text = rest;
+    } else if let Some(rest) = octave_text.strip_prefix('b') {
+        semitone -= 1;
+        octave_text = rest;
+    }
+
+    let octave = i32::from_str(octave_text).map_err(|_| invalid_name())?;
+    // An octave so far from the MIDI range that the note number overflows is reported as out of range in the same direction
+    let note = octave
+        .checked_sub(convention.lowest_octave())
+        .and_then(|octave| octave.checked_mul(12))
+        .and_then(|note| note.checked_add(semitone))
+        .unwrap_or(match octave < 0 {
+            true => i32::MIN,
+            false => i32::MAX,
+        });
+
+    match u8::try_from(note) {
+        Ok(number) if number <= MidiNote::MAX => Ok(number),
+        _ => Err(NoteParseError::OutOfRange(note)),
+    }
+}
+
+/// Convert a MIDI note number to its name, the sharps are used for the black keys
+/// ```
+/// # use deluge::{note_number_to_name, OctaveConvention};
+/// assert_eq!(note_number_to_name(60, OctaveConvention::Deluge).unwrap(), "C3");
+/// assert_eq!(note_number_to_name(60, OctaveConvention::Midi).unwrap(), "C4");
+/// ```
+pub fn note_number_to_name(number: u8, convention: OctaveConvention) -> Result<String, NoteParseError> {
+    match number <= MidiNote::MAX {
+        true => Ok(format_name(number, convention)),
+        false => Err(NoteParseError::OutOfRange(i32::from(number))),
+    }
+}
+
+fn format_name(number: u8, convention: OctaveConvention) -> String {
+    let octave = i32::from(number / 12) + convention.lowest_octave();
+
+    format!("{}{}", NOTE_NAMES[usize::from(number % 12)], octave)
+}
+
+impl From<u8> for MidiNote {
+    fn from(value: u8) -> Self {
+        Self::new(value)
+    }
+}
+
+impl std::fmt::Display for MidiNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.val, self.name())
+    }
+}
+
+impl FromStr for MidiNote {
+    type Err = SerializationError;
+
+    /// Parse a note number, a note name or both like "60 (C3)"
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        if let Some((number, name)) = text.split_once('(') {
+            let note = Self::try_new(u8::from_str(number.trim())?)?;
+            let name = name
+                .strip_suffix(')')
+                .ok_or_else(|| SerializationError::InvalidMidiNote(text.to_string()))?;
+
+            return match Self::parse_name(name.trim())? == note {
+                true => Ok(note),
+                false => Err(SerializationError::InvalidMidiNote(text.to_string())),
+            };
+        }
+
+        match text.starts_with(|c: char| c.is_ascii_digit()) {
+            true => Self::try_new(u8::from_str(text)?),
+            false => Self::parse_name(text),
+        }
+    }
+}
+
+impl Serialize for MidiNote {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(self.val)
+    }
+}
+
+struct MidiNoteVisitor;
+
+impl<'de> Visitor<'de> for MidiNoteVisitor {
+    type Value = MidiNote;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::result::Result<(), std::fmt::Error> {
+        write!(formatter, "a MIDI note in range [0; {}]", MidiNote::MAX)
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let value = value_policy::check_range(v, 0, MidiNote::MAX.into())
+            .ok_or_else(|| E::custom(format!("value '{}' is out of range [0; {}]", v, MidiNote::MAX)))?;
+        let value = u8::try_from(value).map_err(|_| E::custom(format!("value '{}' is out of range", v)))?;
+
+        Ok(MidiNote::new(value))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let v = i64::try_from(v).map_err(|_| E::custom(format!("value '{}' is out of range", v)))?;
+
+        self.visit_i64(v)
+    }
+}
+
+impl<'de> Deserialize<'de> for MidiNote {
+    fn deserialize<D>(deserializer: D) -> Result<MidiNote, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_i64(MidiNoteVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    #[test_case(0, "0 (C-2)")]
+    #[test_case(60, "60 (C3)")]
+    #[test_case(61, "61 (C#3)")]
+    #[test_case(127, "127 (G8)")]
+    fn test_display(note: u8, expected: &str) {
+        assert_eq!(MidiNote::new(note).to_string(), expected);
+    }
+
+    #[test_case("60", 60)]
+    #[test_case(" 60 (C3) ", 60)]
+    #[test_case("C3", 60)]
+    #[test_case("c3", 60)]
+    #[test_case("Db3", 61)]
+    #[test_case("C-2", 0)]
+    #[test_case("G8", 127)]
+    fn test_parse(text: &str, expected: u8) {
+        assert_eq!(text.parse::<MidiNote>().unwrap(), MidiNote::new(expected));
+    }
+
+    #[test_case("128" ; "number too big")]
+    #[test_case("G#8" ; "name too high")]
+    #[test_case("Cb-2" ; "name too low")]
+    #[test_case("C2147483647" ; "huge positive octave")]
+    #[test_case("C-2147483648" ; "huge negative octave")]
+    #[test_case("60 (D3)" ; "number and name mismatch")]
+    #[test_case("H3" ; "invalid letter")]
+    #[test_case("" ; "empty")]
+    fn test_parse_invalid(text: &str) {
+        assert!(text.parse::<MidiNote>().is_err());
+    }
+
+    #[test_case("C-2", OctaveConvention::Deluge, 0)]
+    #[test_case("C3", OctaveConvention::Deluge, 60)]
+    #[test_case("C4", OctaveConvention::Deluge, 72)]
+    #[test_case("B2", OctaveConvention::Deluge, 59)]
+    #[test_case("Cb3", OctaveConvention::Deluge, 59)]
+    #[test_case("G8", OctaveConvention::Deluge, 127)]
+    #[test_case("C-1", OctaveConvention::Midi, 0)]
+    #[test_case("C3", OctaveConvention::Midi, 48)]
+    #[test_case("C4", OctaveConvention::Midi, 60)]
+    #[test_case("a4", OctaveConvention::Midi, 69)]
+    #[test_case("Db4", OctaveConvention::Midi, 61)]
+    #[test_case("G9", OctaveConvention::Midi, 127)]
+    fn test_note_name_to_number(name: &str, convention: OctaveConvention, expected: u8) {
+        assert_eq!(note_name_to_number(name, convention), Ok(expected));
+    }
+
+    #[test_case("C-3", OctaveConvention::Deluge, NoteParseError::OutOfRange(-12))]
+    #[test_case("G#8", OctaveConvention::Deluge, NoteParseError::OutOfRange(128))]
+    #[test_case("C-2", OctaveConvention::Midi, NoteParseError::OutOfRange(-12))]
+    #[test_case("G9", OctaveConvention::Deluge, NoteParseError::OutOfRange(139))]
+    #[test_case("C2147483647", OctaveConvention::Deluge, NoteParseError::OutOfRange(i32::MAX))]
+    #[test_case("Cb-2147483648", OctaveConvention::Midi, NoteParseError::OutOfRange(i32::MIN))]
+    #[test_case("C99999999999", OctaveConvention::Deluge, NoteParseError::InvalidName("C99999999999".to_string()))]
+    #[test_case("H3", OctaveConvention::Deluge, NoteParseError::InvalidName("H3".to_string()))]
+    #[test_case("C#", OctaveConvention::Midi, NoteParseError::InvalidName("C#".to_string()))]
+    #[test_case("", OctaveConvention::Midi, NoteParseError::InvalidName("".to_string()))]
+    fn test_note_name_to_number_invalid(name: &str, convention: OctaveConvention, expected: NoteParseError) {
+        assert_eq!(note_name_to_number(name, convention), Err(expected));
+    }
+
+    #[test_case(0, OctaveConvention::Deluge, "C-2")]
+    #[test_case(60, OctaveConvention::Deluge, "C3")]
+    #[test_case(61, OctaveConvention::Deluge, "C#3")]
+    #[test_case(0, OctaveConvention::Midi, "C-1")]
+    #[test_case(60, OctaveConvention::Midi, "C4")]
+    #[test_case(127, OctaveConvention::Midi, "G9")]
+    fn test_note_number_to_name(number: u8, convention: OctaveConvention, expected: &str) {
+        assert_eq!(note_number_to_name(number, convention).unwrap(), expected);
+    }
+
+    #[test_case(OctaveConvention::Deluge)]
+    #[test_case(OctaveConvention::Midi)]
+    fn test_note_name_round_trip(convention: OctaveConvention) {
+        for number in 0..=MidiNote::MAX {
+            let name = note_number_to_name(number, convention).unwrap();
+
+            assert_eq!(note_name_to_number(&name, convention), Ok(number));
+        }
+
+        assert_eq!(note_number_to_name(128, convention), Err(NoteParseError::OutOfRange(128)));
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(MidiNote::try_new(127).is_ok());
+        assert!(matches!(MidiNote::try_new(128), Err(SerializationError::Overflow(..))));
+    }
+}
@@ -5,23 +5,31 @@
 //! serialized as an 32-bits unsigned integer.
 //! As a Deluge user, you manipulate a value in the range [0; 50] without having to think how it will be stored in the XML file.
 
+mod clipping_amount;
 mod decu50;
 mod hexu50;
 mod int8;
+mod midi_note;
 mod on_off;
 mod pan;
+mod patch_cable_values;
 mod retrig_phase;
 mod sample_path;
 mod sample_position;
 mod sidechain_values;
 mod simple_enums;
+mod u50;
 mod uint8;
+mod unison_detune;
 
+pub use clipping_amount::ClippingAmount;
 pub use decu50::DecU50;
 pub use hexu50::HexU50;
 pub use int8::Int8;
+pub use midi_note::{note_name_to_number, note_number_to_name, MidiNote, NoteParseError, OctaveConvention};
 pub use on_off::OnOff;
 pub use pan::Pan;
+pub use patch_cable_values::{PatchDestination, PatchSource};
 pub use retrig_phase::RetrigPhase;
 pub use sample_path::SamplePath;
 pub use sample_position::SamplePosition;
@@ -31,12 +39,11 @@ pub use simple_enums::{
     SynthMode, VoicePriority,
 };
 pub use uint8::Uint8;
+pub use unison_detune::UnisonDetune;
 
-pub type ClippingAmount = Uint8<0, 16, 0>;
 pub type FineTranspose = Int8<-100, 100, 0>;
 pub type TimeStretchAmount = Int8<-48, 48, 0>;
 pub type Transpose = Int8<-96, 96, 0>;
-pub type UnisonDetune = Uint8<0, 50, 0>;
 pub type UnisonVoiceCount = Uint8<1, 8, 1>;
 pub type OctavesCount = Uint8<1, 8, 1>;
 pub type CvGateChannel = Uint8<1, 4, 1>;
@@ -150,4 +157,132 @@ mod tests {
     fn test_i32_u32_conversion_back_and_forth(input: i32) {
         assert_eq!(input, map_u32_i32(map_i32_u32(input).unwrap()).unwrap());
     }
+
+    mod from_str_display {
+        use crate::values::*;
+        use pretty_assertions::assert_eq;
+        use std::{fmt::Debug, fmt::Display, str::FromStr};
+        use test_case::test_case;
+
+        /// Check the value is displayed as `human`, and that both `human` and `stored` are parsed back to the same value.
+        fn check<T>(value: T, human: &str, stored: &str)
+        where
+            T: FromStr + Display + PartialEq + Debug,
+            T::Err: Debug,
+        {
+            assert_eq!(human, value.to_string());
+            assert_eq!(value, T::from_str(human).unwrap());
+            assert_eq!(value, T::from_str(stored).unwrap());
+        }
+
+        #[test_case(HexU50::new(0), "0", "0x80000000" ; "0")]
+        #[test_case(HexU50::new(25), "25", "0x00000000" ; "25")]
+        #[test_case(HexU50::new(40), "40", "0x4CCCCCA8" ; "40")]
+        #[test_case(HexU50::new(50), "50", "0x7FFFFFFF" ; "50")]
+        fn test_hexu50(value: HexU50, human: &str, stored: &str) {
+            check(value, human, stored);
+        }
+
+        #[test_case(Pan::new(-12).unwrap(), "L12", "0xD0000000" ; "left")]
+        #[test_case(Pan::new(0).unwrap(), "Center", "0x00000000" ; "center")]
+        #[test_case(Pan::new(32).unwrap(), "R32", "0x7FFFFFFF" ; "right")]
+        fn test_pan(value: Pan, human: &str, stored: &str) {
+            check(value, human, stored);
+        }
+
+        #[test_case(RetrigPhase::Off, "Off", "-1" ; "off")]
+        #[test_case(RetrigPhase::Degrees(0), "0°", "0" ; "0")]
+        #[test_case(RetrigPhase::Degrees(10), "10°", "119304640" ; "10")]
+        #[test_case(RetrigPhase::Degrees(181), "181°", "-2135553312" ; "181")]
+        fn test_retrig_phase(value: RetrigPhase, human: &str, stored: &str) {
+            check(value, human, stored);
+        }
+
+        #[test]
+        fn test_ranged_integers() {
+            check(Transpose::new(-12), "-12", "-12");
+            check(FineTranspose::new(50), "50", "50");
+            check(TimeStretchAmount::new(-48), "-48", "-48");
+            check(ClippingAmount::new(16), "16", "16");
+            check(ClippingAmount::none(), "0", "0");
+            check(UnisonDetune::new(8), "8", "8");
+            check(UnisonVoiceCount::new(3), "3", "3");
+            check(OctavesCount::new(2), "2", "2");
+        }
+
+        #[test]
+        fn test_simple_enums() {
+            check(Polyphony::Legato, "Legato", "legato");
+            check(SynthMode::RingMod, "RingMod", "ringmod");
+            check(OscType::AnalogSaw, "AnalogSaw", "analogSaw");
+            check(LfoShape::Triangle, "Triangle", "triangle");
+            check(SamplePlayMode::Stretch, "Stretch", "3");
+            check(PitchSpeed::Linked, "Linked", "0");
+            check(PitchSpeed::Independent, "Independent", "1");
+            check(SyncLevel::Sixteenth, "1/16", "7");
+            check(SyncLevel::Off, "Off", "0");
+            check(LpfMode::Lpf24, "Lpf24", "24dB");
+            check(ArpeggiatorMode::Both, "Both", "both");
+            check(VoicePriority::High, "High", "2");
+            check(ModulationFxType::Off, "Off", "none");
+            check(FilterType::Equalizer, "Equalizer", "eq");
+        }
+
+        #[test_case(SyncLevel::FourBars, "4 bars", "1" ; "4 bars")]
+        #[test_case(SyncLevel::OneBar, "1 bar", "3" ; "1 bar")]
+        #[test_case(SyncLevel::HundredTwentyEighth, "1/128", "10" ; "128th")]
+        fn test_sync_level(value: SyncLevel, human: &str, stored: &str) {
+            check(value, human, stored);
+        }
+
+        #[test_case("51" ; "hexu50 overflow")]
+        #[test_case("abc" ; "hexu50 not a number")]
+        fn test_hexu50_invalid(input: &str) {
+            assert!(HexU50::from_str(input).is_err());
+        }
+
+        #[test_case("L33" ; "left overflow")]
+        #[test_case("R128" ; "right overflow")]
+        #[test_case("X12" ; "invalid")]
+        fn test_pan_invalid(input: &str) {
+            assert!(Pan::from_str(input).is_err());
+        }
+
+        #[test]
+        fn test_ranged_integers_invalid() {
+            assert!(Transpose::from_str("97").is_err());
+            assert!(UnisonVoiceCount::from_str("0").is_err());
+            assert!(LpfMode::from_str("36dB").is_err());
+        }
+
+        #[test]
+        fn test_try_new() {
+            assert_eq!(HexU50::try_new(50).unwrap(), HexU50::new(50));
+            assert!(HexU50::try_new(51).is_err());
+            assert!(DecU50::try_new(51).is_err());
+            assert!(Transpose::try_new(-97).is_err());
+            assert!(UnisonVoiceCount::try_new(0).is_err());
+            assert!(!HexU50::new(200).is_valid());
+            assert!(TimeStretchAmount::new(48).is_valid());
+        }
+
+        #[test]
+        #[cfg(debug_assertions)]
+        #[should_panic]
+        fn test_new_unsigned_out_of_range() {
+            UnisonVoiceCount::new(0);
+        }
+
+        #[test]
+        #[cfg(debug_assertions)]
+        #[should_panic]
+        fn test_new_signed_out_of_range() {
+            Transpose::new(97);
+        }
+
+        #[test]
+        fn test_simple_enums_ignore_case() {
+            assert_eq!(LpfMode::Lpf24Drive, LpfMode::from_str("lpf24drive").unwrap());
+        }
+    }
 }
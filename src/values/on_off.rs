@@ -63,6 +63,26 @@ impl<'de> Visitor<'de> for OnOffVisitor {
             _ => OnOff::On,
         })
     }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(match v {
+            0i64 => OnOff::Off,
+            _ => OnOff::On,
+        })
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(match v {
+            0u64 => OnOff::Off,
+            _ => OnOff::On,
+        })
+    }
 }
 
 impl std::fmt::Display for OnOff {
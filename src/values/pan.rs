@@ -5,6 +5,7 @@ use crate::values::{map_i32_u32, map_u32_i32, read_hexadecimal_u32, write_hexade
 use crate::SerializationError;
 
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
 pub struct Pan(i8);
@@ -29,9 +30,49 @@ impl Pan {
         read_pan(text)
     }
 
+    /// The pan displayed "L{amount}", the amount must be in the range [0; 32]
+    pub fn left(amount: u8) -> Result<Self, SerializationError> {
+        Ok(Self(-Self::check_amount(amount)?))
+    }
+
+    /// The pan displayed "R{amount}", the amount must be in the range [0; 32]
+    pub fn right(amount: u8) -> Result<Self, SerializationError> {
+        Ok(Self(Self::check_amount(amount)?))
+    }
+
+    pub fn center() -> Self {
+        Self(0)
+    }
+
     pub fn as_i8(&self) -> i8 {
         self.0
     }
+
+    /// Get the pan in the range [-1.0; 1.0], -1.0 being fully left
+    pub fn as_f32(&self) -> f32 {
+        f32::from(self.0) / f32::from(Self::MAX_PAN)
+    }
+
+    /// Move the pan by `delta` steps, the result is clamped in the range [-32L; 32R]
+    pub fn offset(&self, delta: i8) -> Self {
+        Self(
+            self.0
+                .saturating_add(delta)
+                .clamp(Self::MIN_PAN, Self::MAX_PAN),
+        )
+    }
+
+    /// Parse the amount following "L" or "R"
+    fn parse_amount(text: &str) -> Result<i8, SerializationError> {
+        Self::check_amount(u8::from_str(text)?)
+    }
+
+    fn check_amount(amount: u8) -> Result<i8, SerializationError> {
+        match amount > Self::MAX_PAN as u8 {
+            true => Err(SerializationError::Overflow(amount.to_string(), Self::MAX_PAN.to_string())),
+            false => Ok(amount as i8),
+        }
+    }
 }
 
 impl std::fmt::Display for Pan {
@@ -44,6 +85,35 @@ impl std::fmt::Display for Pan {
     }
 }
 
+impl FromStr for Pan {
+    type Err = SerializationError;
+
+    /// Parse the value displayed ("L12", "R12" or "Center"), a signed integer
+    /// or the hexadecimal value stored in the files, prefixed by "0x".
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        if text.eq_ignore_ascii_case("center") {
+            return Ok(Pan::center());
+        }
+
+        if text.starts_with("0x") {
+            return read_pan(text);
+        }
+
+        match text.split_at(
+            text.chars()
+                .next()
+                .map(char::len_utf8)
+                .unwrap_or(0),
+        ) {
+            ("L" | "l", value) => Pan::new(-Self::parse_amount(value)?),
+            ("R" | "r", value) => Pan::new(Self::parse_amount(value)?),
+            _ => Pan::new(i8::from_str(text)?),
+        }
+    }
+}
+
 const PAN_FACTOR: f64 = 67108864f64;
 
 fn write_pan(pan: Pan) -> Result<String, SerializationError> {
@@ -244,4 +314,53 @@ mod tests {
     fn test_read_pan_32() {
         assert_eq!(Pan::new(32).unwrap(), read_pan("0x7FFFFFFF").unwrap());
     }
+
+    #[test_case(Pan::left(32).unwrap(), -32, "L32" ; "left 32")]
+    #[test_case(Pan::left(20).unwrap(), -20, "L20" ; "left 20")]
+    #[test_case(Pan::left(1).unwrap(), -1, "L1" ; "left 1")]
+    #[test_case(Pan::left(0).unwrap(), 0, "Center" ; "left 0")]
+    #[test_case(Pan::center(), 0, "Center" ; "center")]
+    #[test_case(Pan::right(0).unwrap(), 0, "Center" ; "right 0")]
+    #[test_case(Pan::right(5).unwrap(), 5, "R5" ; "right 5")]
+    #[test_case(Pan::right(32).unwrap(), 32, "R32" ; "right 32")]
+    fn test_constructors(pan: Pan, expected_value: i8, expected_text: &str) {
+        assert_eq!(pan.as_i8(), expected_value);
+        assert_eq!(pan.to_string(), expected_text);
+        assert_eq!(Pan::from_str(expected_text).unwrap(), pan);
+    }
+
+    #[test]
+    fn test_constructors_overflow() {
+        assert!(matches!(Pan::left(33), Err(SerializationError::Overflow(..))));
+        assert!(matches!(Pan::right(33), Err(SerializationError::Overflow(..))));
+        assert!(matches!(Pan::from_str("L33"), Err(SerializationError::Overflow(..))));
+    }
+
+    #[test]
+    fn test_display_from_str_round_trip() {
+        for value in Pan::MIN_PAN..=Pan::MAX_PAN {
+            let pan = Pan::new(value).unwrap();
+
+            assert_eq!(Pan::from_str(&pan.to_string()).unwrap(), pan);
+        }
+    }
+
+    #[test_case(Pan::left(32).unwrap(), -1.0 ; "left 32")]
+    #[test_case(Pan::left(16).unwrap(), -0.5 ; "left 16")]
+    #[test_case(Pan::center(), 0.0 ; "center")]
+    #[test_case(Pan::right(8).unwrap(), 0.25 ; "right 8")]
+    #[test_case(Pan::right(32).unwrap(), 1.0 ; "right 32")]
+    fn test_as_f32(pan: Pan, expected: f32) {
+        assert_eq!(pan.as_f32(), expected);
+    }
+
+    #[test_case(Pan::center(), 5, Pan::right(5).unwrap() ; "center plus 5")]
+    #[test_case(Pan::right(5).unwrap(), -10, Pan::left(5).unwrap() ; "right 5 minus 10")]
+    #[test_case(Pan::right(30).unwrap(), 5, Pan::right(32).unwrap() ; "saturates right")]
+    #[test_case(Pan::left(30).unwrap(), -5, Pan::left(32).unwrap() ; "saturates left")]
+    #[test_case(Pan::left(32).unwrap(), i8::MIN, Pan::left(32).unwrap() ; "i8 min")]
+    #[test_case(Pan::right(32).unwrap(), i8::MAX, Pan::right(32).unwrap() ; "i8 max")]
+    fn test_offset(pan: Pan, delta: i8, expected: Pan) {
+        assert_eq!(pan.offset(delta), expected);
+    }
 }
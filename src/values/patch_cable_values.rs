@@ -0,0 +1,164 @@
+//! Sources and destinations of the patch cables.
+//!
+//! Each value is serialized using the exact string expected by the Deluge.
+//! The values unknown by this library are kept as they are using the variant Custom.
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+macro_rules! patch_cable_value {
+    ($(#[$meta:meta])* $name:ident { $($(#[$variant_meta:meta])* $variant:ident => $key:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// A value unknown by this library, stored as it is
+            Custom(String),
+        }
+
+        impl $name {
+            /// Get the string used by the Deluge for this value
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $key,)*
+                    Self::Custom(value) => value,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($key => Self::$variant,)*
+                    _ => Self::Custom(value.to_string()),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Ok(Self::from(String::deserialize(deserializer)?.as_str()))
+            }
+        }
+    };
+}
+
+patch_cable_value! {
+    /// The source of a patch cable
+    PatchSource {
+        Velocity => "velocity",
+        Note => "note",
+        Lfo1 => "lfo1",
+        Lfo2 => "lfo2",
+        Envelope1 => "envelope1",
+        Envelope2 => "envelope2",
+        Aftertouch => "aftertouch",
+        /// The sidechain compressor
+        Compressor => "compressor",
+        Random => "random",
+        /// MPE X axis
+        X => "x",
+        /// MPE Y axis
+        Y => "y",
+    }
+}
+
+patch_cable_value! {
+    /// The destination of a patch cable
+    PatchDestination {
+        Volume => "volume",
+        VolumePostFx => "volumePostFX",
+        VolumePostReverbSend => "volumePostReverbSend",
+        Pan => "pan",
+        Pitch => "pitch",
+        OscAVolume => "oscAVolume",
+        OscBVolume => "oscBVolume",
+        OscAPitch => "oscAPitch",
+        OscBPitch => "oscBPitch",
+        OscAPhaseWidth => "oscAPhaseWidth",
+        OscBPhaseWidth => "oscBPhaseWidth",
+        NoiseVolume => "noiseVolume",
+        Modulator1Volume => "modulator1Volume",
+        Modulator2Volume => "modulator2Volume",
+        Modulator1Pitch => "modulator1Pitch",
+        Modulator2Pitch => "modulator2Pitch",
+        Modulator1Feedback => "modulator1Feedback",
+        Modulator2Feedback => "modulator2Feedback",
+        Carrier1Feedback => "carrier1Feedback",
+        Carrier2Feedback => "carrier2Feedback",
+        LpfFrequency => "lpfFrequency",
+        LpfResonance => "lpfResonance",
+        HpfFrequency => "hpfFrequency",
+        HpfResonance => "hpfResonance",
+        Env1Attack => "env1Attack",
+        Env1Decay => "env1Decay",
+        Env1Sustain => "env1Sustain",
+        Env1Release => "env1Release",
+        Env2Attack => "env2Attack",
+        Env2Decay => "env2Decay",
+        Env2Sustain => "env2Sustain",
+        Env2Release => "env2Release",
+        Lfo1Rate => "lfo1Rate",
+        Lfo2Rate => "lfo2Rate",
+        ArpeggiatorRate => "arpeggiatorRate",
+        ArpeggiatorGate => "arpeggiatorGate",
+        DelayRate => "delayRate",
+        DelayFeedback => "delayFeedback",
+        ReverbAmount => "reverbAmount",
+        ModFxRate => "modFXRate",
+        ModFxDepth => "modFXDepth",
+        ModFxOffset => "modFXOffset",
+        ModFxFeedback => "modFXFeedback",
+        Portamento => "portamento",
+        StutterRate => "stutterRate",
+        SampleRateReduction => "sampleRateReduction",
+        BitcrushAmount => "bitcrushAmount",
+        CompressorShape => "compressorShape",
+        /// The amount of another patch cable
+        Range => "range",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("velocity", PatchSource::Velocity)]
+    #[test_case("envelope2", PatchSource::Envelope2)]
+    #[test_case("x", PatchSource::X)]
+    #[test_case("lfo1 ", PatchSource::Custom("lfo1 ".to_string()))]
+    fn test_patch_source_round_trip(input: &str, expected: PatchSource) {
+        let source: PatchSource = serde_plain::from_str(input).unwrap();
+
+        assert_eq!(source, expected);
+        assert_eq!(serde_plain::to_string(&source).unwrap(), input);
+    }
+
+    #[test_case("volume", PatchDestination::Volume)]
+    #[test_case("lpfFrequency", PatchDestination::LpfFrequency)]
+    #[test_case("modFXRate", PatchDestination::ModFxRate)]
+    #[test_case("Lpffrequency", PatchDestination::Custom("Lpffrequency".to_string()))]
+    fn test_patch_destination_round_trip(input: &str, expected: PatchDestination) {
+        let destination: PatchDestination = serde_plain::from_str(input).unwrap();
+
+        assert_eq!(destination, expected);
+        assert_eq!(serde_plain::to_string(&destination).unwrap(), input);
+    }
+}
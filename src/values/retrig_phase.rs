@@ -1,38 +1,50 @@
 //! Specify the phase in degrees.
 //! This type is formatted as 32-bits unsigned integer hexadecimal.
-//! Notice RetrigPhase(0) is different than RetrigPhase::Off!
+//! Notice RetrigPhase::Degrees(0) is different than RetrigPhase::Off!
 use crate::values::{map_i32_u32, map_u32_i32, read_i32, SerializationError};
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
 use std::num::Wrapping;
+use std::str::FromStr;
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
 pub enum RetrigPhase {
     /// The phase is never reset
     Off,
-    /// Degrees that the phase will be reset on note-on
+    /// Degrees that the phase will be reset on note-on, in the range [0; 360[
     Degrees(u16),
 }
 
 impl RetrigPhase {
     const MAX_DEGREES: u16 = 360;
 
+    /// Create a phase in degrees, the degrees are wrapped in the range [0; 360[ so 360 is 0.
     pub fn new(degrees: u16) -> Self {
         Self::Degrees(degrees).normalise()
     }
 
-    pub fn normalise(self) -> Self {
-        match self {
-            Self::Degrees(value) => {
-                let mut value = value;
+    /// Create a phase in degrees, returns [SerializationError::Overflow] if the degrees are not in the range [0; 360[
+    pub fn try_new(degrees: u16) -> Result<Self, SerializationError> {
+        match degrees < Self::MAX_DEGREES {
+            true => Ok(Self::Degrees(degrees)),
+            false => Err(SerializationError::Overflow(
+                degrees.to_string(),
+                (Self::MAX_DEGREES - 1).to_string(),
+            )),
+        }
+    }
 
-                if value > Self::MAX_DEGREES {
-                    while value >= Self::MAX_DEGREES {
-                        value -= Self::MAX_DEGREES;
-                    }
-                }
+    /// Get the degrees, None if the phase is Off
+    pub fn degrees(&self) -> Option<u16> {
+        match self {
+            Self::Off => None,
+            Self::Degrees(degrees) => Some(*degrees),
+        }
+    }
 
-                Self::Degrees(value)
-            }
+    /// Wrap the degrees in the range [0; 360[
+    pub fn normalise(self) -> Self {
+        match self {
+            Self::Degrees(value) => Self::Degrees(value % Self::MAX_DEGREES),
             Self::Off => Self::Off,
         }
     }
@@ -91,6 +103,32 @@ impl<'de> Deserialize<'de> for RetrigPhase {
     }
 }
 
+impl FromStr for RetrigPhase {
+    type Err = SerializationError;
+
+    /// Parse the value displayed ("Off" or degrees like "90°") or the value stored in the files.
+    ///
+    /// An integer in the range [0; 360[ without the degree sign is read as degrees because
+    /// the values stored in the files are multiples of 11930464 excepted 0 which means 0 degrees.
+    /// The degrees out of the range [0; 360[ are reported with [SerializationError::Overflow].
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        if text.eq_ignore_ascii_case("off") {
+            return Ok(RetrigPhase::Off);
+        }
+
+        if let Some(degrees) = text.strip_suffix('°') {
+            return RetrigPhase::try_new(u16::from_str(degrees.trim_end())?);
+        }
+
+        match read_i32(text)? {
+            degrees @ 0..=359 => RetrigPhase::try_new(degrees as u16),
+            _ => read_phase(text),
+        }
+    }
+}
+
 const PHASE_FACTOR: i32 = 11930464i32;
 const PHASE_OFF_VALUE: &str = "-1";
 
@@ -110,9 +148,10 @@ fn read_phase(text: &str) -> Result<RetrigPhase, SerializationError> {
     let number = read_i32(text)?;
     let u32_value = map_i32_u32(number)?;
 
+    // The largest values stored are rounded to 360 degrees which is 0 degrees
     Ok(match number {
         -1 => RetrigPhase::Off,
-        _ => RetrigPhase::Degrees((u32_value / PHASE_FACTOR as u32) as u16),
+        _ => RetrigPhase::new((u32_value / PHASE_FACTOR as u32) as u16),
     })
 }
 
@@ -123,7 +162,8 @@ mod tests {
 
     #[test_case(RetrigPhase::Off, RetrigPhase::Off ; "Off")]
     #[test_case(RetrigPhase::Degrees(0) , RetrigPhase::Degrees(0) ; "0")]
-    #[test_case(RetrigPhase::Degrees(360) , RetrigPhase::Degrees(360) ; "360")]
+    #[test_case(RetrigPhase::Degrees(359) , RetrigPhase::Degrees(359) ; "359")]
+    #[test_case(RetrigPhase::Degrees(360) , RetrigPhase::Degrees(0) ; "360")]
     #[test_case(RetrigPhase::Degrees(361) , RetrigPhase::Degrees(1) ; "361")]
     #[test_case(RetrigPhase::Degrees(720) , RetrigPhase::Degrees(0) ; "720")]
     fn test_normalise_phase(input: RetrigPhase, expected: RetrigPhase) {
@@ -155,7 +195,7 @@ mod tests {
     #[test_case(RetrigPhase::Degrees(180), "2147483520"; "180")]
     #[test_case(RetrigPhase::Degrees(181), "-2135553312"; "181")]
     #[test_case(RetrigPhase::Degrees(359), "-11930720"; "359")]
-    #[test_case(RetrigPhase::Degrees(360), "-256"; "360")]
+    #[test_case(RetrigPhase::Degrees(0), "-256"; "360")]
     fn test_read_phase(expected: RetrigPhase, input: &str) {
         assert_eq!(expected, read_phase(input).unwrap());
     }
@@ -174,6 +214,35 @@ mod tests {
     fn test_write_read(input: RetrigPhase) {
         let string_representation = write_phase(input).unwrap();
 
-        assert_eq!(input, read_phase(&string_representation).unwrap());
+        assert_eq!(input.normalise(), read_phase(&string_representation).unwrap());
+    }
+
+    #[test_case(0, Ok(RetrigPhase::Degrees(0)) ; "0")]
+    #[test_case(359, Ok(RetrigPhase::Degrees(359)) ; "359")]
+    #[test_case(360, Err(()) ; "360")]
+    #[test_case(720, Err(()) ; "720")]
+    fn test_try_new(degrees: u16, expected: Result<RetrigPhase, ()>) {
+        assert_eq!(RetrigPhase::try_new(degrees).map_err(|_| ()), expected);
+    }
+
+    #[test_case(RetrigPhase::Off, "Off" ; "Off")]
+    #[test_case(RetrigPhase::Degrees(0), "0°" ; "0")]
+    #[test_case(RetrigPhase::Degrees(90), "90°" ; "90")]
+    fn test_display(phase: RetrigPhase, expected: &str) {
+        assert_eq!(phase.to_string(), expected);
+        assert_eq!(RetrigPhase::from_str(expected).unwrap(), phase);
+    }
+
+    #[test_case("360°" ; "360 degrees")]
+    #[test_case("1000°" ; "1000 degrees")]
+    fn test_from_str_out_of_range(text: &str) {
+        assert!(matches!(RetrigPhase::from_str(text), Err(SerializationError::Overflow(..))));
+    }
+
+    #[test]
+    fn test_default_is_off() {
+        assert_eq!(RetrigPhase::default(), RetrigPhase::Off);
+        assert_eq!(RetrigPhase::default().degrees(), None);
+        assert_eq!(RetrigPhase::new(90).degrees(), Some(90));
     }
 }
@@ -11,15 +11,65 @@ pub struct SamplePath(PathBuf);
 impl SamplePath {
     /// Create a new sample path.
     ///
-    /// This function returns an error if the path is not a relative one.
+    /// The path is normalized: the separators `\` are replaced by `/` like in the files saved by the Deluge,
+    /// the leading "/" and "./" and the duplicated separators are removed.
+    /// This function returns an error if the path is not a relative one or if it contains "..".
+    /// ```
+    /// # use deluge::SamplePath;
+    /// let path = SamplePath::new("/SAMPLES\\DRUMS//Kick.wav").unwrap();
+    ///
+    /// assert_eq!(path.to_string_lossy(), "SAMPLES/DRUMS/Kick.wav");
+    /// assert!(SamplePath::new("SAMPLES/../Kick.wav").is_err());
+    /// ```
     pub fn new(path: impl AsRef<str>) -> Result<Self, CardError> {
-        let path = Path::new(path.as_ref());
+        let text = path.as_ref().replace('\\', "/");
+        let mut segments = Vec::new();
+
+        for segment in text.split('/') {
+            match segment {
+                "" | "." => continue,
+                ".." => return Err(CardError::PathNotRelative(PathBuf::from(path.as_ref()))),
+                segment => segments.push(segment),
+            }
+        }
+
+        let normalized_path = PathBuf::from(segments.join("/"));
 
-        if !path.is_relative() {
-            return Err(CardError::PathNotRelative(path.to_path_buf()));
+        // For example a path starting by a drive letter on Windows
+        if !normalized_path.is_relative() {
+            return Err(CardError::PathNotRelative(PathBuf::from(path.as_ref())));
         }
 
-        Ok(SamplePath(path.to_path_buf()))
+        Ok(SamplePath(normalized_path))
+    }
+
+    /// Get the name of the file, None if the path is empty
+    pub fn file_name(&self) -> Option<&str> {
+        self.0
+            .file_name()
+            .and_then(|name| name.to_str())
+    }
+
+    /// Get the path without its last component, None if the path is empty
+    ///
+    /// The parent of a file at the root of the card is an empty path.
+    pub fn parent(&self) -> Option<SamplePath> {
+        self.0
+            .parent()
+            .map(|parent| SamplePath(parent.to_path_buf()))
+    }
+
+    /// Append a path, the appended path is normalized like with [SamplePath::new]
+    /// ```
+    /// # use deluge::SamplePath;
+    /// let path = SamplePath::new("SAMPLES").unwrap().join("DRUMS\\Kick.wav").unwrap();
+    ///
+    /// assert_eq!(path.to_string_lossy(), "SAMPLES/DRUMS/Kick.wav");
+    /// assert_eq!(path.file_name(), Some("Kick.wav"));
+    /// assert_eq!(path.parent().unwrap().to_string_lossy(), "SAMPLES/DRUMS");
+    /// ```
+    pub fn join(&self, path: impl AsRef<str>) -> Result<SamplePath, CardError> {
+        SamplePath::new(format!("{}/{}", self.to_string_lossy(), path.as_ref()))
     }
 
     /// Print the path formatted for the Deluge.
@@ -34,6 +84,13 @@ impl SamplePath {
             .join("/")
     }
 
+    /// Returns true if the path is empty.
+    ///
+    /// The Deluge stores an empty path for an oscillator without sample.
+    pub fn is_empty(&self) -> bool {
+        self.0.as_os_str().is_empty()
+    }
+
     pub(crate) fn to_path(&self) -> &Path {
         self.0.as_path()
     }
@@ -48,6 +105,12 @@ impl Serialize for SamplePath {
     }
 }
 
+impl std::fmt::Display for SamplePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_string_lossy())
+    }
+}
+
 struct PathVisitor;
 
 impl<'de> Visitor<'de> for PathVisitor {
@@ -73,3 +136,52 @@ impl<'de> Deserialize<'de> for SamplePath {
         deserializer.deserialize_str(PathVisitor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    // The paths are written for the test, none of the files of data_tests contains a backslash
+    #[test_case("SAMPLES/DRUMS/Kick.wav", "SAMPLES/DRUMS/Kick.wav" ; "normalized")]
+    #[test_case("SAMPLES\\DRUMS\\Kick.wav", "SAMPLES/DRUMS/Kick.wav" ; "backslashes")]
+    #[test_case("SAMPLES/DRUMS\\Kick/Kick 1.wav", "SAMPLES/DRUMS/Kick/Kick 1.wav" ; "mixed separators")]
+    #[test_case("/SAMPLES/Kick.wav", "SAMPLES/Kick.wav" ; "leading slash")]
+    #[test_case("./SAMPLES/Kick.wav", "SAMPLES/Kick.wav" ; "leading dot")]
+    #[test_case("SAMPLES//DRUMS///Kick.wav", "SAMPLES/DRUMS/Kick.wav" ; "duplicated separators")]
+    #[test_case("", "" ; "empty")]
+    fn test_new_normalizes(path: &str, expected: &str) {
+        assert_eq!(
+            SamplePath::new(path)
+                .unwrap()
+                .to_string_lossy(),
+            expected
+        );
+    }
+
+    #[test_case("../Kick.wav" ; "leading")]
+    #[test_case("SAMPLES\\..\\Kick.wav" ; "backslashes")]
+    fn test_new_rejects_parent_segments(path: &str) {
+        assert_eq!(SamplePath::new(path), Err(CardError::PathNotRelative(PathBuf::from(path))));
+    }
+
+    #[test]
+    fn test_equality_after_normalization() {
+        assert_eq!(
+            SamplePath::new("SAMPLES\\Kick.wav").unwrap(),
+            SamplePath::new("SAMPLES/Kick.wav").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_file_name_and_parent() {
+        let path = SamplePath::new("Kick.wav").unwrap();
+
+        assert_eq!(path.file_name(), Some("Kick.wav"));
+        assert_eq!(path.parent(), Some(SamplePath::default()));
+        assert_eq!(SamplePath::default().file_name(), None);
+        assert_eq!(SamplePath::default().parent(), None);
+        assert!(path.join("../Snare.wav").is_err());
+    }
+}
@@ -1,9 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// A position in a sample, in number of samples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
 pub struct SamplePosition(u64);
 
 impl SamplePosition {
+    /// The sample rate used by the Deluge to convert times to sample positions.
+    pub const SAMPLE_RATE: u64 = 44100u64;
+
     pub fn new(value: u64) -> Self {
         Self(value)
     }
@@ -11,6 +15,28 @@ impl SamplePosition {
     pub fn as_u64(&self) -> u64 {
         self.0
     }
+
+    /// Create a position from a time in milliseconds, assuming a sample rate of 44.1 kHz.
+    pub fn from_millis(milliseconds: u64) -> Self {
+        Self((u128::from(milliseconds) * u128::from(Self::SAMPLE_RATE) / 1000u128).min(u128::from(u64::MAX)) as u64)
+    }
+
+    /// Get the position as a time in milliseconds, assuming a sample rate of 44.1 kHz.
+    ///
+    /// The result is rounded down.
+    pub fn as_millis(&self) -> u64 {
+        (u128::from(self.0) * 1000u128 / u128::from(Self::SAMPLE_RATE)) as u64
+    }
+
+    /// Get the position as a time in seconds, assuming a sample rate of 44.1 kHz.
+    pub fn as_seconds_f64(&self) -> f64 {
+        self.0 as f64 / Self::SAMPLE_RATE as f64
+    }
+
+    /// Subtract another position, returns None if the result would be negative.
+    pub fn checked_sub(self, other: SamplePosition) -> Option<SamplePosition> {
+        self.0.checked_sub(other.0).map(Self)
+    }
 }
 
 impl From<u32> for SamplePosition {
@@ -24,3 +50,66 @@ impl From<u64> for SamplePosition {
         Self::new(position)
     }
 }
+
+impl std::fmt::Display for SamplePosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::ops::Add for SamplePosition {
+    type Output = Self;
+
+    /// The result saturates at u64::MAX.
+    fn add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+}
+
+impl std::ops::Sub for SamplePosition {
+    type Output = Self;
+
+    /// The result saturates at 0.
+    fn sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(0, 0 ; "0")]
+    #[test_case(1000, 44100 ; "1 second")]
+    #[test_case(500, 22050 ; "half second")]
+    #[test_case(1, 44 ; "1 millisecond")]
+    fn test_from_millis(milliseconds: u64, expected: u64) {
+        assert_eq!(SamplePosition::new(expected), SamplePosition::from_millis(milliseconds));
+    }
+
+    #[test_case(0 ; "0")]
+    #[test_case(1000 ; "1 second")]
+    #[test_case(123450 ; "123450")]
+    fn test_millis_round_trip(milliseconds: u64) {
+        assert_eq!(milliseconds, SamplePosition::from_millis(milliseconds).as_millis());
+    }
+
+    #[test]
+    fn test_as_seconds() {
+        assert_eq!(1.5f64, SamplePosition::new(66150).as_seconds_f64());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = SamplePosition::new(10);
+        let b = SamplePosition::new(4);
+
+        assert_eq!(SamplePosition::new(14), a + b);
+        assert_eq!(SamplePosition::new(6), a - b);
+        assert_eq!(SamplePosition::new(0), b - a);
+        assert_eq!(Some(SamplePosition::new(6)), a.checked_sub(b));
+        assert_eq!(None, b.checked_sub(a));
+        assert_eq!(SamplePosition::new(u64::MAX), SamplePosition::new(u64::MAX) + a);
+    }
+}
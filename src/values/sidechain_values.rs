@@ -3,6 +3,7 @@
 //! For now, I resolve that by having a little bit of code duplicated (AttackSidechain and ReleaseSidechain only have differents numbers in their tables).
 //!
 use super::SerializationError;
+use crate::serialization::value_policy;
 use crate::values::Uint8;
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
 use std::convert::From;
@@ -42,6 +43,12 @@ impl From<TableIndex> for AttackSidechain {
     }
 }
 
+impl std::fmt::Display for AttackSidechain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.index)
+    }
+}
+
 impl TryFrom<u32> for AttackSidechain {
     type Error = SerializationError;
 
@@ -75,7 +82,27 @@ impl<'de> Visitor<'de> for AttackSidechainVisitor {
     where
         E: serde::de::Error,
     {
-        AttackSidechain::try_from(v).map_err(|e| E::custom(e))
+        // The values of the table are sorted from the longest time to the shortest.
+        let table = &AttackSidechain::SIDECHAIN_ATTACK_VALUES;
+        let value = value_policy::check_range(v.into(), table[table.len() - 1].into(), table[0].into()).ok_or_else(|| {
+            E::custom(format!(
+                "value '{}' is out of range [{}; {}]",
+                v,
+                table[table.len() - 1],
+                table[0]
+            ))
+        })?;
+
+        AttackSidechain::try_from(value as u32).map_err(|e| E::custom(e))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let v = u32::try_from(v).map_err(|_| E::custom(format!("value '{}' is out of range", v)))?;
+
+        self.visit_u32(v)
     }
 }
 
@@ -110,6 +137,12 @@ impl From<TableIndex> for ReleaseSidechain {
     }
 }
 
+impl std::fmt::Display for ReleaseSidechain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.index)
+    }
+}
+
 impl TryFrom<u32> for ReleaseSidechain {
     type Error = SerializationError;
 
@@ -143,7 +176,27 @@ impl<'de> Visitor<'de> for ReleaseSidechainVisitor {
     where
         E: serde::de::Error,
     {
-        ReleaseSidechain::try_from(v).map_err(|e| E::custom(e))
+        // The values of the table are sorted from the longest time to the shortest.
+        let table = &ReleaseSidechain::SIDECHAIN_RELEASE_VALUES;
+        let value = value_policy::check_range(v.into(), table[table.len() - 1].into(), table[0].into()).ok_or_else(|| {
+            E::custom(format!(
+                "value '{}' is out of range [{}; {}]",
+                v,
+                table[table.len() - 1],
+                table[0]
+            ))
+        })?;
+
+        ReleaseSidechain::try_from(value as u32).map_err(|e| E::custom(e))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let v = u32::try_from(v).map_err(|_| E::custom(format!("value '{}' is out of range", v)))?;
+
+        self.visit_u32(v)
     }
 }
 
@@ -1,13 +1,52 @@
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize};
 use serde_repr::*;
 
+use crate::SerializationError;
+
+/// Implements FromStr for enumerations displayed with strum.
+///
+/// Both the name displayed, ignoring the case, and the value stored in the files are accepted.
+macro_rules! enum_from_str {
+    ($($type_name:ident),+) => {
+        $(
+            impl std::str::FromStr for $type_name {
+                type Err = SerializationError;
+
+                fn from_str(text: &str) -> Result<Self, Self::Err> {
+                    use strum::IntoEnumIterator;
+
+                    match Self::iter().find(|value| value.to_string().eq_ignore_ascii_case(text)) {
+                        Some(value) => Ok(value),
+                        None => Ok(serde_plain::from_str(text)?),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+enum_from_str!(
+    Polyphony,
+    SynthMode,
+    OscType,
+    LfoShape,
+    SamplePlayMode,
+    PitchSpeed,
+    SyncLevel,
+    LpfMode,
+    ArpeggiatorMode,
+    VoicePriority,
+    ModulationFxType,
+    FilterType
+);
+
 /// Polyphony
 /// I noticed there are few patches have "0" or "1" as value.
 /// SYNT184.XML and SYNT095.XML for example. I will have to implement an alternative serialization but
 /// I will keep the attributes for the "latest" supported version.
 ///
 /// Each times, it's for a FM patch. I'm quite sure internaly Subtractive synth and Fm synth are different structure.
-#[derive(Clone, Serialize, PartialEq, Eq, Debug)]
+#[derive(Clone, Serialize, PartialEq, Eq, Debug, strum::Display, strum::EnumIter)]
 pub enum Polyphony {
     #[serde(rename = "poly")]
     Poly,
@@ -73,7 +112,7 @@ impl<'de> Deserialize<'de> for Polyphony {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, strum::Display, strum::EnumIter)]
 pub enum SynthMode {
     #[serde(rename = "off")]
     Off,
@@ -85,7 +124,7 @@ pub enum SynthMode {
     Fm,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, strum::Display, strum::EnumIter)]
 pub enum OscType {
     #[serde(rename = "square")]
     Square,
@@ -103,7 +142,7 @@ pub enum OscType {
     Sample,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, strum::Display, strum::EnumIter)]
 pub enum LfoShape {
     #[serde(rename = "square")]
     Square,
@@ -115,7 +154,7 @@ pub enum LfoShape {
     Triangle,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr, strum::Display, strum::EnumIter)]
 #[repr(u8)]
 pub enum SamplePlayMode {
     Cut = 0,
@@ -124,14 +163,18 @@ pub enum SamplePlayMode {
     Stretch = 3,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+/// The firmware stores whether the pitch and the speed are independent, "timeStretchEnable" in the XML
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr, strum::Display, strum::EnumIter)]
 #[repr(u8)]
 pub enum PitchSpeed {
-    Linked = 1,
-    Independent = 0,
+    Linked = 0,
+    Independent = 1,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+/// The synchronization of a delay, an LFO, an arpeggiator or a sidechain to the tempo
+///
+/// The level is displayed like the Deluge displays it, for example "1/16".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr, strum::EnumIter)]
 #[repr(u8)]
 pub enum SyncLevel {
     Off = 0,
@@ -157,7 +200,25 @@ pub enum SyncLevel {
     HundredTwentyEighth = 10,
 }
 
-#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug)]
+impl std::fmt::Display for SyncLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SyncLevel::Off => "Off",
+            SyncLevel::FourBars => "4 bars",
+            SyncLevel::TwoBars => "2 bars",
+            SyncLevel::OneBar => "1 bar",
+            SyncLevel::Second => "1/2",
+            SyncLevel::Fourth => "1/4",
+            SyncLevel::Eighth => "1/8",
+            SyncLevel::Sixteenth => "1/16",
+            SyncLevel::ThirtySecond => "1/32",
+            SyncLevel::SixtyFourth => "1/64",
+            SyncLevel::HundredTwentyEighth => "1/128",
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Debug, strum::Display, strum::EnumIter)]
 pub enum LpfMode {
     #[serde(rename = "24dB")]
     Lpf24,
@@ -167,7 +228,7 @@ pub enum LpfMode {
     Lpf24Drive,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, strum::Display, strum::EnumIter)]
 pub enum ArpeggiatorMode {
     #[serde(rename = "off")]
     Off,
@@ -181,7 +242,7 @@ pub enum ArpeggiatorMode {
     Random,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize_repr, Deserialize_repr, strum::Display, strum::EnumIter)]
 #[repr(u8)]
 pub enum VoicePriority {
     Low = 0,
@@ -195,7 +256,7 @@ impl Default for VoicePriority {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, strum::Display, strum::EnumIter)]
 pub enum ModulationFxType {
     #[serde(rename = "none")]
     Off,
@@ -207,7 +268,7 @@ pub enum ModulationFxType {
     Phaser,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, strum::Display, strum::EnumIter)]
 pub enum FilterType {
     #[serde(rename = "lpf")]
     Lpf,
@@ -0,0 +1,118 @@
+//! Helpers shared by the values in the range [0; 50].
+
+/// The maximum value of a value in the range [0; 50]
+pub(crate) const U50_MAX: u8 = 50;
+
+/// Implements arithmetic, percentage conversions and comparisons against u8
+/// for a tuple struct wrapping an u8 in the range [0; 50].
+macro_rules! u50_value_helpers {
+    ($type_name:ident) => {
+        impl $type_name {
+            /// Create a value, returns an error if it's greater than 50.
+            pub fn try_new(value: u8) -> Result<Self, $crate::SerializationError> {
+                if value > $crate::values::u50::U50_MAX {
+                    return Err($crate::SerializationError::Overflow(
+                        value.to_string(),
+                        $crate::values::u50::U50_MAX.to_string(),
+                    ));
+                }
+
+                Ok(Self(value))
+            }
+
+            /// Returns true if the value is in the range [0; 50].
+            pub fn is_valid(&self) -> bool {
+                self.0 <= $crate::values::u50::U50_MAX
+            }
+
+            /// Add a signed delta, returns None if the result is not in the range [0; 50].
+            pub fn checked_add(self, delta: i8) -> Option<Self> {
+                let result = i16::from(self.0) + i16::from(delta);
+
+                match (0..=i16::from($crate::values::u50::U50_MAX)).contains(&result) {
+                    true => Some(Self(result as u8)),
+                    false => None,
+                }
+            }
+
+            /// Subtract a signed delta, returns None if the result is not in the range [0; 50].
+            pub fn checked_sub(self, delta: i8) -> Option<Self> {
+                self.checked_add(delta.checked_neg()?)
+            }
+
+            /// Add a signed delta, the result is clamped in the range [0; 50].
+            pub fn saturating_add(self, delta: i8) -> Self {
+                let result = i16::from(self.0) + i16::from(delta);
+
+                Self(result.clamp(0, i16::from($crate::values::u50::U50_MAX)) as u8)
+            }
+
+            /// Subtract a signed delta, the result is clamped in the range [0; 50].
+            pub fn saturating_sub(self, delta: i8) -> Self {
+                let result = i16::from(self.0) - i16::from(delta);
+
+                Self(result.clamp(0, i16::from($crate::values::u50::U50_MAX)) as u8)
+            }
+
+            /// Get the value as a percentage, 0 is 0% and 50 is 100%.
+            pub fn as_percent(&self) -> f32 {
+                f32::from(self.0) * 100f32 / f32::from($crate::values::u50::U50_MAX)
+            }
+
+            /// Create a value from a percentage, the value is rounded to the nearest step
+            /// and clamped in the range [0; 50]. NaN gives 0.
+            pub fn from_percent(percent: f32) -> Self {
+                let value = (percent * f32::from($crate::values::u50::U50_MAX) / 100f32).round();
+
+                match value.is_nan() {
+                    true => Self(0),
+                    false => Self(value.clamp(0f32, f32::from($crate::values::u50::U50_MAX)) as u8),
+                }
+            }
+        }
+
+        impl std::ops::Add<i8> for $type_name {
+            type Output = Self;
+
+            /// The result is clamped in the range [0; 50].
+            fn add(self, delta: i8) -> Self {
+                self.saturating_add(delta)
+            }
+        }
+
+        impl std::ops::Sub<i8> for $type_name {
+            type Output = Self;
+
+            /// The result is clamped in the range [0; 50].
+            fn sub(self, delta: i8) -> Self {
+                self.saturating_sub(delta)
+            }
+        }
+
+        impl std::ops::AddAssign<i8> for $type_name {
+            fn add_assign(&mut self, delta: i8) {
+                *self = *self + delta;
+            }
+        }
+
+        impl std::ops::SubAssign<i8> for $type_name {
+            fn sub_assign(&mut self, delta: i8) {
+                *self = *self - delta;
+            }
+        }
+
+        impl PartialEq<u8> for $type_name {
+            fn eq(&self, other: &u8) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialOrd<u8> for $type_name {
+            fn partial_cmp(&self, other: &u8) -> Option<std::cmp::Ordering> {
+                self.0.partial_cmp(other)
+            }
+        }
+    };
+}
+
+pub(crate) use u50_value_helpers;
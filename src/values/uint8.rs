@@ -5,6 +5,9 @@
 //! Maybe one day this code will be useless!
 
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+use crate::{serialization::value_policy, SerializationError};
 
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub struct Uint8<const MIN: u8, const MAX: u8, const DEFAULT: u8> {
@@ -27,24 +30,43 @@ impl<const MIN: u8, const MAX: u8, const DEFAULT: u8> Uint8<MIN, MAX, DEFAULT> {
     pub const MIN: u8 = MIN;
     pub const MAX: u8 = MAX;
 
-    fn check(val: u8) -> Self {
-        debug_assert!(
-            val >= Self::MIN && val <= Self::MAX,
-            "{} <= {} <= {}",
-            Self::MIN,
-            val,
-            Self::MAX
-        );
+    /// Create a value, the range [MIN; MAX] is only checked by the debug builds, see [Uint8::try_new].
+    pub fn new(val: u8) -> Self {
+        debug_assert!((MIN..=MAX).contains(&val), "{} <= {} <= {}", MIN, val, MAX);
         Self { val }
     }
 
-    pub fn new(val: u8) -> Self {
-        Self::check(val)
+    /// Create a value, returns an error if it's not in the range [MIN; MAX].
+    pub fn try_new(val: u8) -> Result<Self, SerializationError> {
+        if val > MAX {
+            return Err(SerializationError::Overflow(val.to_string(), MAX.to_string()));
+        }
+
+        if val < MIN {
+            return Err(SerializationError::Underflow(val.to_string(), MIN.to_string()));
+        }
+
+        Ok(Self { val })
+    }
+
+    /// Returns true if the value is in the range [MIN; MAX].
+    pub fn is_valid(&self) -> bool {
+        (MIN..=MAX).contains(&self.val)
     }
 
     pub fn as_u8(self) -> u8 {
         self.val
     }
+
+    /// Iterate over all the values in the range [MIN; MAX]
+    /// ```
+    /// # use deluge::{CvGateChannel, MidiChannel};
+    /// assert_eq!(MidiChannel::iter().count(), 16);
+    /// assert_eq!(CvGateChannel::iter().map(|channel| channel.as_u8()).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    /// ```
+    pub fn iter() -> impl Iterator<Item = Self> {
+        (MIN..=MAX).map(Self::new)
+    }
 }
 
 impl<const MIN: u8, const MAX: u8, const DEFAULT: u8> Serialize for Uint8<MIN, MAX, DEFAULT> {
@@ -69,18 +91,25 @@ impl<'de, const MIN: u8, const MAX: u8, const DEFAULT: u8> Visitor<'de> for Uint
         )
     }
 
-    fn visit_u8<E>(self, v: u8) -> Result<Self::Value, E>
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
     {
-        if v > MAX {
-            return Err(E::custom(format!("value '{}' is too big, can't be greater than {}", v, MAX)));
-        }
-        if v < MIN {
-            return Err(E::custom(format!("value '{}' is too small, can't be lesser than {}", v, MIN)));
-        }
+        let value = value_policy::check_range(v, MIN.into(), MAX.into())
+            .ok_or_else(|| E::custom(format!("value '{}' is out of range [{}; {}]", v, MIN, MAX)))?;
+        let value = u8::try_from(value).map_err(|_| E::custom(format!("value '{}' is out of range", v)))?;
 
-        Ok(Self::Value::new(v))
+        // Not created with Uint8::new, the value is out of range when it's loaded with OutOfRange::Keep.
+        Ok(Uint8 { val: value })
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        let v = i64::try_from(v).map_err(|_| E::custom(format!("value '{}' is out of range", v)))?;
+
+        self.visit_i64(v)
     }
 }
 
@@ -89,7 +118,7 @@ impl<'de, const MIN: u8, const MAX: u8, const DEFAULT: u8> Deserialize<'de> for
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_u8(Uint8Visitor)
+        deserializer.deserialize_i64(Uint8Visitor)
     }
 }
 
@@ -98,3 +127,11 @@ impl<const MIN: u8, const MAX: u8, const DEFAULT: u8> std::fmt::Display for Uint
         write!(f, "{}", self.val)
     }
 }
+
+impl<const MIN: u8, const MAX: u8, const DEFAULT: u8> FromStr for Uint8<MIN, MAX, DEFAULT> {
+    type Err = SerializationError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::try_new(u8::from_str(text.trim())?)
+    }
+}
@@ -0,0 +1,95 @@
+//! The detune of the unison voices of a sound, in the range [0; 50].
+//! This type is formatted as an unsigned decimal integer.
+use crate::values::Uint8;
+use crate::SerializationError;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+type UnisonDetuneValue = Uint8<0, 50, 0>;
+
+/// The detune between the lowest and the highest unison voices, the value displayed by the device
+///
+/// The conversion of the detune in cents is not provided, the mapping used by the device is not known.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct UnisonDetune(UnisonDetuneValue);
+
+impl UnisonDetune {
+    pub const MIN: u8 = UnisonDetuneValue::MIN;
+    pub const MAX: u8 = UnisonDetuneValue::MAX;
+
+    /// Create a value, the range [0; 50] is only checked by the debug builds, see [UnisonDetune::try_new].
+    pub fn new(value: u8) -> Self {
+        Self(UnisonDetuneValue::new(value))
+    }
+
+    /// Create a value, returns an error if it's not in the range [0; 50].
+    pub fn try_new(value: u8) -> Result<Self, SerializationError> {
+        Ok(Self(UnisonDetuneValue::try_new(value)?))
+    }
+
+    /// Returns true if the value is in the range [0; 50].
+    pub fn is_valid(&self) -> bool {
+        self.0.is_valid()
+    }
+
+    pub fn as_u8(self) -> u8 {
+        self.0.as_u8()
+    }
+
+    /// Iterate over all the values in the range [0; 50]
+    pub fn iter() -> impl Iterator<Item = Self> {
+        UnisonDetuneValue::iter().map(Self)
+    }
+}
+
+impl From<u8> for UnisonDetune {
+    fn from(value: u8) -> Self {
+        Self::new(value)
+    }
+}
+
+impl std::fmt::Display for UnisonDetune {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for UnisonDetune {
+    type Err = SerializationError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(Self(UnisonDetuneValue::from_str(text)?))
+    }
+}
+
+impl Serialize for UnisonDetune {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnisonDetune {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        UnisonDetuneValue::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_out_of_range() {
+        assert!(UnisonDetune::try_new(51).is_err());
+        assert!(!UnisonDetune::new(51).is_valid());
+        assert!(serde_plain::from_str::<UnisonDetune>("51").is_err());
+        assert_eq!(UnisonDetune::iter().count(), 51);
+    }
+}
@@ -6,7 +6,10 @@
 #![cfg(test)]
 extern crate test_generator;
 
-use deluge::{deserialize_kit, deserialize_synth, serialize_kit, serialize_synth};
+use deluge::{
+    deserialize_kit, deserialize_kit_with_version, deserialize_synth, deserialize_synth_with_version, peek_patch_info,
+    read_patch_from_file, serialize_kit, serialize_synth, PatchType, VersionInfo,
+};
 use pretty_assertions::assert_eq;
 use test_generator::test_resources;
 
@@ -42,6 +45,20 @@ fn smoke_test_load_write_load_sound(resource: &str) {
     assert_eq!(reloaded_sound, sound);
 }
 
+#[test_resources("tests/data_tests/KITS/*.XML")]
+fn smoke_test_read_patch_kit(resource: &str) {
+    let (patch, _) = read_patch_from_file(resource).unwrap();
+
+    assert_eq!(patch.patch_type(), PatchType::Kit);
+}
+
+#[test_resources("tests/data_tests/SYNTHS/*.XML")]
+fn smoke_test_read_patch_synth(resource: &str) {
+    let (patch, _) = read_patch_from_file(resource).unwrap();
+
+    assert_eq!(patch.patch_type(), PatchType::Synth);
+}
+
 #[test_resources("tests/data_tests/KITS/*.XML")]
 fn smoke_test_load_write_load_kit(resource: &str) {
     assert!(std::path::Path::new(resource).exists());
@@ -77,3 +94,75 @@ fn smoke_test_load_write_load_kit_community_patches(resource: &str) {
 
     assert_eq!(reloaded_kit, kit);
 }
+
+#[test_resources("tests/data_tests/KITS/*.XML")]
+fn smoke_test_peek_kit_info(resource: &str) {
+    assert_peek_kit_info(resource);
+}
+
+#[test_resources("tests/data_tests/COMMUNITY_PATCHES/KITS/*.XML")]
+fn smoke_test_peek_kit_info_community_patches(resource: &str) {
+    assert_peek_kit_info(resource);
+}
+
+fn assert_peek_kit_info(resource: &str) {
+    let file_content = std::fs::read_to_string(resource).unwrap();
+    let (_, version_info) = deserialize_kit_with_version(&file_content).unwrap();
+
+    assert_peek_patch_info(&file_content, PatchType::Kit, version_info);
+}
+
+#[test_resources("tests/data_tests/SYNTHS/*.XML")]
+fn smoke_test_peek_synth_info(resource: &str) {
+    assert_peek_synth_info(resource);
+}
+
+#[test_resources("tests/data_tests/COMMUNITY_PATCHES/SYNTHS/*.XML")]
+fn smoke_test_peek_synth_info_community_patches(resource: &str) {
+    assert_peek_synth_info(resource);
+}
+
+fn assert_peek_synth_info(resource: &str) {
+    let file_content = std::fs::read_to_string(resource).unwrap();
+    let (_, version_info) = deserialize_synth_with_version(&file_content).unwrap();
+
+    assert_peek_patch_info(&file_content, PatchType::Synth, version_info);
+}
+
+fn assert_peek_patch_info(xml: &str, patch_type: PatchType, version_info: VersionInfo) {
+    let patch_info = peek_patch_info(xml).unwrap();
+
+    assert_eq!(patch_info.patch_type, patch_type);
+    assert_eq!(patch_info.format_version, version_info.format_version);
+    assert_eq!(patch_info.firmware_version, version_info.firmware_version);
+    assert_eq!(
+        patch_info.earliest_compatible_firmware,
+        version_info.earliest_compatible_firmware
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test_resources("tests/data_tests/KITS/*.XML")]
+fn smoke_test_kit_json_round_trip(resource: &str) {
+    assert!(std::path::Path::new(resource).exists());
+
+    let file_content = std::fs::read_to_string(resource).unwrap();
+    let kit = deserialize_kit(&file_content).unwrap();
+    let json = serde_json::to_string(&kit).unwrap();
+    let reloaded_kit: deluge::Kit = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(reloaded_kit, kit);
+}
+
+#[cfg(feature = "serde")]
+#[test_resources("tests/data_tests/SYNTHS/*.XML")]
+fn smoke_test_synth_json_round_trip(resource: &str) {
+    assert!(std::path::Path::new(resource).exists());
+
+    let file_content = std::fs::read_to_string(resource).unwrap();
+    let synth = deserialize_synth(&file_content).unwrap();
+    let json = serde_json::to_string(&synth).unwrap();
+    let reloaded_synth: deluge::Synth = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(reloaded_synth, synth);
+}